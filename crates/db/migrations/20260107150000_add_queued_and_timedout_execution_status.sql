@@ -0,0 +1,38 @@
+-- Add 'queued' and 'timedout' to execution_processes.status
+-- Following the same column-swap pattern used for run_reason
+
+-- 1. Add the replacement column with the wider CHECK
+ALTER TABLE execution_processes
+  ADD COLUMN status_new TEXT NOT NULL DEFAULT 'running'
+    CHECK (status_new IN ('queued',   -- waiting for a scheduler slot
+                          'running',
+                          'completed',
+                          'failed',
+                          'killed',
+                          'timedout'));  -- stopped by an idle or runtime limit
+
+-- 2. Copy existing values across
+UPDATE execution_processes
+  SET status_new = status;
+
+-- 3. Drop all indexes that reference the old status column
+DROP INDEX IF EXISTS idx_execution_processes_status;
+DROP INDEX IF EXISTS idx_execution_processes_session_status_run_reason;
+DROP INDEX IF EXISTS idx_execution_processes_status_run_reason_created;
+
+-- 4. Remove the old column (requires SQLite 3.35+)
+ALTER TABLE execution_processes DROP COLUMN status;
+
+-- 5. Rename the new column back to the canonical name
+ALTER TABLE execution_processes
+  RENAME COLUMN status_new TO status;
+
+-- 6. Re-create all indexes with the new column
+CREATE INDEX idx_execution_processes_status
+        ON execution_processes(status);
+
+CREATE INDEX idx_execution_processes_session_status_run_reason
+        ON execution_processes (session_id, status, run_reason);
+
+CREATE INDEX idx_execution_processes_status_run_reason_created
+        ON execution_processes (status, run_reason, created_at DESC);
@@ -0,0 +1,18 @@
+-- Persisted execution queue. Scheduling priority lives on the task so changing it
+-- reorders runs that are already waiting.
+
+ALTER TABLE tasks ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+
+-- Queue entries for execution processes waiting on a scheduler slot.
+-- Rows are removed once the process is dispatched or cancelled.
+CREATE TABLE execution_queue (
+    execution_process_id BLOB PRIMARY KEY,
+    project_id           BLOB NOT NULL,
+    executor             TEXT NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_execution_queue_created
+        ON execution_queue (created_at ASC);
@@ -0,0 +1,27 @@
+-- Record executor fallback hops: a coding agent run failed with an auth, missing
+-- executable or rate-limit error and was retried in a new session with the next
+-- profile from its fallback chain.
+CREATE TABLE executor_fallbacks (
+    id                          BLOB PRIMARY KEY,
+    workspace_id                BLOB NOT NULL,
+    failed_execution_process_id BLOB NOT NULL,
+    failed_session_id           BLOB NOT NULL,
+    failed_profile              TEXT NOT NULL,  -- ExecutorProfileId JSON
+    fallback_session_id         BLOB NOT NULL,
+    fallback_profile            TEXT NOT NULL,  -- ExecutorProfileId JSON
+    reason                      TEXT NOT NULL
+                                    CHECK (reason IN ('auth_required',
+                                                      'executable_not_found',
+                                                      'rate_limited')),
+    -- Set once the fallback session's coding agent completes successfully
+    succeeded_at                TEXT,
+    created_at                  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (failed_execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (failed_session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (fallback_session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_executor_fallbacks_workspace_id ON executor_fallbacks(workspace_id);
+CREATE UNIQUE INDEX idx_executor_fallbacks_fallback_session_id
+        ON executor_fallbacks(fallback_session_id);
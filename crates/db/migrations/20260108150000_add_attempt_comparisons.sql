@@ -0,0 +1,30 @@
+-- Best-of-N attempts: one task run in parallel across several workspaces, each
+-- with its own executor profile, so the results can be compared and a winner kept.
+CREATE TABLE attempt_comparisons (
+    id                         BLOB PRIMARY KEY,
+    task_id                    BLOB NOT NULL,
+    -- Set once a winner is picked; the other workspaces are cleaned up
+    winner_workspace_id        BLOB,
+    -- Optional judge agent run that ranks the candidates
+    judge_execution_process_id BLOB,
+    created_at                 TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at                 TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (winner_workspace_id) REFERENCES workspaces(id) ON DELETE SET NULL,
+    FOREIGN KEY (judge_execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_attempt_comparisons_task_id ON attempt_comparisons(task_id);
+
+CREATE TABLE attempt_comparison_candidates (
+    comparison_id       BLOB NOT NULL,
+    workspace_id        BLOB NOT NULL,
+    executor_profile_id TEXT NOT NULL,  -- ExecutorProfileId JSON
+    position            INTEGER NOT NULL,
+    PRIMARY KEY (comparison_id, workspace_id),
+    FOREIGN KEY (comparison_id) REFERENCES attempt_comparisons(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX idx_attempt_comparison_candidates_workspace_id
+        ON attempt_comparison_candidates(workspace_id);
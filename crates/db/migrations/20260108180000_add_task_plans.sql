@@ -0,0 +1,49 @@
+PRAGMA foreign_keys = ON;
+
+-- Plans captured from planning runs. Each task has at most one plan, which is
+-- edited in place (bumping its revision) until it is approved and executed.
+CREATE TABLE task_plans (
+    id                          BLOB PRIMARY KEY,
+    task_id                     BLOB NOT NULL UNIQUE,
+    -- Workspace of the planning run; the execution run starts here
+    workspace_id                BLOB NOT NULL,
+    content                     TEXT NOT NULL,
+    status                      TEXT NOT NULL DEFAULT 'draft'
+                                    CHECK (status IN ('draft', 'approved')),
+    revision                    INTEGER NOT NULL DEFAULT 1,
+    source_execution_process_id BLOB,
+    -- Execution run seeded with the approved plan
+    execution_process_id        BLOB,
+    approved_at                 TEXT,
+    created_at                  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at                  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (source_execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL
+);
+
+-- Add plan event types to task_history
+ALTER TABLE task_history RENAME TO task_history_old;
+
+CREATE TABLE task_history (
+    id                BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    task_id           BLOB NOT NULL,
+    event_type        TEXT NOT NULL 
+                          CHECK (event_type IN ('status_changed','description_changed','title_changed','pr_body_updated','change_requested','plan_proposed','plan_edited','plan_approved','other')),
+    old_value         TEXT,
+    new_value         TEXT,
+    metadata          TEXT,  -- JSON for additional context
+    created_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+INSERT INTO task_history (id, task_id, event_type, old_value, new_value, metadata, created_at)
+SELECT id, task_id, event_type, old_value, new_value, metadata, created_at
+FROM task_history_old;
+
+DROP TABLE task_history_old;
+
+CREATE INDEX idx_task_history_task_id ON task_history(task_id);
+CREATE INDEX idx_task_history_created_at ON task_history(created_at);
+CREATE INDEX idx_task_history_event_type ON task_history(event_type);
@@ -0,0 +1,24 @@
+-- Per-project rules consulted before a tool approval request is shown to the user.
+-- Rules are evaluated in position order and the first match decides.
+CREATE TABLE approval_policy_rules (
+    id              BLOB PRIMARY KEY,
+    project_id      BLOB NOT NULL,
+    position        INTEGER NOT NULL DEFAULT 0,
+    -- Glob on the tool name, e.g. 'Bash' or 'mcp__*'; NULL matches any tool
+    tool_name       TEXT,
+    -- Regex matched against the command of shell tools
+    command_pattern TEXT,
+    -- Glob matched against the file paths a tool touches
+    path_glob       TEXT,
+    path_scope      TEXT NOT NULL DEFAULT 'any'
+                       CHECK (path_scope IN ('any', 'inside_worktree', 'outside_worktree')),
+    decision        TEXT NOT NULL
+                       CHECK (decision IN ('allow', 'deny', 'ask')),
+    reason          TEXT NOT NULL DEFAULT '',
+    enabled         INTEGER NOT NULL DEFAULT 1,
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_approval_policy_rules_project_id ON approval_policy_rules(project_id, position);
@@ -0,0 +1,24 @@
+-- Audit trail of tool approval requests and how they were decided.
+CREATE TABLE approval_records (
+    id                   TEXT PRIMARY KEY,  -- approval id handed to the executor
+    execution_process_id BLOB NOT NULL,
+    tool_name            TEXT NOT NULL,
+    tool_input           TEXT NOT NULL,     -- JSON
+    tool_call_id         TEXT NOT NULL,
+    status               TEXT NOT NULL DEFAULT 'pending'
+                            CHECK (status IN ('pending', 'approved', 'denied', 'timed_out')),
+    denial_reason        TEXT,
+    responder            TEXT
+                            CHECK (responder IN ('user', 'policy', 'timeout', 'system')),
+    -- Set when an approval policy rule decided the request
+    policy_rule_id       BLOB,
+    created_at           TEXT NOT NULL,
+    timeout_at           TEXT NOT NULL,
+    responded_at         TEXT,
+    latency_ms           INTEGER,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (policy_rule_id) REFERENCES approval_policy_rules(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_approval_records_execution_process_id ON approval_records(execution_process_id);
+CREATE INDEX idx_approval_records_pending ON approval_records(status) WHERE status = 'pending';
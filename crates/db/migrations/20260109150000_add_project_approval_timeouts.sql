@@ -0,0 +1,5 @@
+-- Project-level override of the approval timeout. NULL falls back to the executor
+-- profile and then to the global default.
+ALTER TABLE projects ADD COLUMN approval_timeout_minutes INTEGER;
+ALTER TABLE projects ADD COLUMN approval_timeout_action TEXT
+    CHECK (approval_timeout_action IN ('deny', 'approve', 'interrupt'));
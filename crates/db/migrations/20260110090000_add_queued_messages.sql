@@ -0,0 +1,14 @@
+-- Follow-up messages waiting for the running turn of a session to finish.
+-- One message is sent per completed turn, in position order.
+CREATE TABLE queued_messages (
+    id          BLOB PRIMARY KEY,
+    session_id  BLOB NOT NULL,
+    position    INTEGER NOT NULL,
+    message     TEXT NOT NULL,
+    variant     TEXT,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_queued_messages_session_id ON queued_messages(session_id, position);
@@ -0,0 +1,13 @@
+-- Conversation transcripts imported from another session and attached to a task.
+-- The transcript column holds the JSON export format.
+CREATE TABLE task_transcripts (
+    id                BLOB PRIMARY KEY,
+    task_id           BLOB NOT NULL,
+    source_session_id BLOB,
+    title             TEXT NOT NULL,
+    transcript        TEXT NOT NULL,
+    created_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_transcripts_task_id ON task_transcripts(task_id);
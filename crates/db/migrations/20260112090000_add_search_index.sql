@@ -0,0 +1,138 @@
+-- Full-text search over task titles/descriptions, coding agent turn prompts and
+-- summaries, and normalized assistant/tool log entries.
+-- Documents live in a regular table indexed by an external-content FTS table, so they
+-- are replaced and removed through ordinary indexes. Tasks and turns are kept in sync
+-- by triggers; log entries are written by the log persistence loop as normalized
+-- entries settle.
+CREATE TABLE search_documents (
+    id                   INTEGER PRIMARY KEY,
+    kind                 TEXT NOT NULL CHECK (kind IN ('task', 'turn', 'log_entry')),
+    -- Task, coding agent turn or execution process the document was indexed from
+    source_id            BLOB NOT NULL,
+    -- Position of a log entry in the process's normalized conversation
+    entry_index          INTEGER,
+    task_id              BLOB NOT NULL,
+    workspace_id         BLOB,
+    execution_process_id BLOB,
+    title                TEXT NOT NULL,
+    body                 TEXT NOT NULL,
+    created_at           TEXT NOT NULL
+);
+
+CREATE INDEX idx_search_documents_source
+        ON search_documents (kind, source_id, entry_index);
+CREATE INDEX idx_search_documents_task_id
+        ON search_documents (task_id);
+CREATE INDEX idx_search_documents_execution_process_id
+        ON search_documents (execution_process_id);
+
+CREATE VIRTUAL TABLE search_index USING fts5(
+    title,
+    body,
+    content = 'search_documents',
+    content_rowid = 'id',
+    tokenize = 'porter unicode61'
+);
+
+CREATE TRIGGER trg_search_documents_insert
+AFTER INSERT ON search_documents
+BEGIN
+    INSERT INTO search_index (rowid, title, body) VALUES (NEW.id, NEW.title, NEW.body);
+END;
+
+CREATE TRIGGER trg_search_documents_update
+AFTER UPDATE OF title, body ON search_documents
+BEGIN
+    INSERT INTO search_index (search_index, rowid, title, body)
+    VALUES ('delete', OLD.id, OLD.title, OLD.body);
+    INSERT INTO search_index (rowid, title, body) VALUES (NEW.id, NEW.title, NEW.body);
+END;
+
+CREATE TRIGGER trg_search_documents_delete
+AFTER DELETE ON search_documents
+BEGIN
+    INSERT INTO search_index (search_index, rowid, title, body)
+    VALUES ('delete', OLD.id, OLD.title, OLD.body);
+END;
+
+INSERT INTO search_documents (kind, source_id, task_id, title, body, created_at)
+SELECT 'task', id, id, title, COALESCE(description, ''), created_at
+FROM tasks;
+
+INSERT INTO search_documents
+    (kind, source_id, task_id, workspace_id, execution_process_id, title, body, created_at)
+SELECT 'turn', cat.id, w.task_id, w.id, ep.id, '',
+       TRIM(COALESCE(cat.prompt, '') || char(10) || COALESCE(cat.summary, '')),
+       cat.created_at
+FROM coding_agent_turns cat
+JOIN execution_processes ep ON ep.id = cat.execution_process_id
+JOIN sessions s ON s.id = ep.session_id
+JOIN workspaces w ON w.id = s.workspace_id;
+
+CREATE TRIGGER trg_search_index_task_insert
+AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO search_documents (kind, source_id, task_id, title, body, created_at)
+    VALUES ('task', NEW.id, NEW.id, NEW.title, COALESCE(NEW.description, ''), NEW.created_at);
+END;
+
+CREATE TRIGGER trg_search_index_task_update
+AFTER UPDATE OF title, description ON tasks
+BEGIN
+    UPDATE search_documents
+       SET title = NEW.title, body = COALESCE(NEW.description, '')
+     WHERE kind = 'task' AND source_id = OLD.id;
+END;
+
+CREATE TRIGGER trg_search_index_task_delete
+AFTER DELETE ON tasks
+BEGIN
+    DELETE FROM search_documents WHERE task_id = OLD.id;
+END;
+
+CREATE TRIGGER trg_search_index_turn_insert
+AFTER INSERT ON coding_agent_turns
+BEGIN
+    INSERT INTO search_documents
+        (kind, source_id, task_id, workspace_id, execution_process_id, title, body, created_at)
+    SELECT 'turn', NEW.id, w.task_id, w.id, ep.id, '',
+           TRIM(COALESCE(NEW.prompt, '') || char(10) || COALESCE(NEW.summary, '')),
+           NEW.created_at
+    FROM execution_processes ep
+    JOIN sessions s ON s.id = ep.session_id
+    JOIN workspaces w ON w.id = s.workspace_id
+    WHERE ep.id = NEW.execution_process_id;
+END;
+
+CREATE TRIGGER trg_search_index_turn_update
+AFTER UPDATE OF prompt, summary ON coding_agent_turns
+BEGIN
+    UPDATE search_documents
+       SET body = TRIM(COALESCE(NEW.prompt, '') || char(10) || COALESCE(NEW.summary, ''))
+     WHERE kind = 'turn' AND source_id = OLD.id;
+END;
+
+CREATE TRIGGER trg_search_index_turn_delete
+AFTER DELETE ON coding_agent_turns
+BEGIN
+    DELETE FROM search_documents WHERE kind = 'turn' AND source_id = OLD.id;
+END;
+
+CREATE TRIGGER trg_search_index_process_delete
+AFTER DELETE ON execution_processes
+BEGIN
+    DELETE FROM search_documents WHERE execution_process_id = OLD.id;
+END;
+
+-- Logs persisted before log entries were indexed. The search backfill job indexes
+-- these processes once and removes them from the queue.
+CREATE TABLE search_backfill_queue (
+    execution_process_id BLOB PRIMARY KEY,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+INSERT INTO search_backfill_queue (execution_process_id)
+SELECT ep.id
+FROM execution_processes ep
+WHERE ep.run_reason = 'codingagent'
+  AND EXISTS (SELECT 1 FROM execution_process_logs epl WHERE epl.execution_id = ep.id);
@@ -0,0 +1,19 @@
+-- Compressed logs of finished execution processes. Once a process completes its
+-- raw JSONL rows in execution_process_logs are packed into compressed chunks here
+-- and deleted; readers decompress the chunks before any remaining raw rows.
+-- Log retention later replaces the raw stdout/stderr of old processes with their
+-- normalized conversation; `content` records which of the two a chunk holds.
+CREATE TABLE execution_process_log_chunks (
+    execution_id     BLOB NOT NULL,
+    chunk_index      INTEGER NOT NULL,
+    codec            TEXT NOT NULL DEFAULT 'gzip',
+    data             BLOB NOT NULL,
+    raw_bytes        INTEGER NOT NULL,
+    compressed_bytes INTEGER NOT NULL,
+    line_count       INTEGER NOT NULL,
+    content          TEXT NOT NULL DEFAULT 'raw'
+                     CHECK (content IN ('raw', 'normalized')),
+    created_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (execution_id, chunk_index),
+    FOREIGN KEY (execution_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
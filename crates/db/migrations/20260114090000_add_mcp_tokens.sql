@@ -0,0 +1,13 @@
+-- Bearer tokens for clients of the MCP endpoint served by the backend.
+-- Only a SHA-256 hash of each token is stored; a token bound to a workspace
+-- gives its client that workspace as context.
+CREATE TABLE mcp_tokens (
+    id           BLOB PRIMARY KEY,
+    name         TEXT NOT NULL,
+    token_hash   TEXT NOT NULL UNIQUE,
+    workspace_id BLOB REFERENCES workspaces(id) ON DELETE CASCADE,
+    last_used_at TEXT,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_mcp_tokens_workspace_id ON mcp_tokens(workspace_id);
@@ -0,0 +1,19 @@
+-- Encrypted secrets injected into the environment of execution processes.
+-- A secret belongs to either a project or a repo; repo secrets apply to every
+-- workspace that contains the repo. Values are AES-256-GCM encrypted with a
+-- key derived from the local master key and are never returned by the API.
+CREATE TABLE secrets (
+    id         BLOB PRIMARY KEY,
+    project_id BLOB REFERENCES projects(id) ON DELETE CASCADE,
+    repo_id    BLOB REFERENCES repos(id) ON DELETE CASCADE,
+    name       TEXT NOT NULL,
+    ciphertext TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    CHECK ((project_id IS NULL) != (repo_id IS NULL))
+);
+
+CREATE UNIQUE INDEX idx_secrets_project_name ON secrets(project_id, name)
+    WHERE project_id IS NOT NULL;
+CREATE UNIQUE INDEX idx_secrets_repo_name ON secrets(repo_id, name)
+    WHERE repo_id IS NOT NULL;
@@ -0,0 +1,19 @@
+-- copy_files becomes a JSON array of {"pattern", "mode"} rules instead of a
+-- comma-separated list of patterns; existing patterns keep plain copying.
+UPDATE project_repos SET copy_files = NULL WHERE trim(copy_files) = '';
+
+UPDATE project_repos
+SET copy_files = (
+    WITH RECURSIVE split(pattern, rest) AS (
+        SELECT '', project_repos.copy_files || ','
+        UNION ALL
+        SELECT trim(substr(rest, 1, instr(rest, ',') - 1)),
+               substr(rest, instr(rest, ',') + 1)
+        FROM split
+        WHERE rest <> ''
+    )
+    SELECT json_group_array(json_object('pattern', pattern, 'mode', 'copy'))
+    FROM split
+    WHERE pattern <> ''
+)
+WHERE copy_files IS NOT NULL;
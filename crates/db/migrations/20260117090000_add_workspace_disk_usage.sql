@@ -0,0 +1,9 @@
+-- Disk usage and last access of workspace worktrees, for enforcing disk budgets by
+-- evicting the least recently used ones. An evicted workspace keeps its branches and
+-- is recreated from them the next time it is used.
+CREATE TABLE workspace_disk_usage (
+    workspace_id     BLOB PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+    bytes            INTEGER NOT NULL DEFAULT 0,
+    measured_at      TEXT,
+    last_accessed_at TEXT
+);
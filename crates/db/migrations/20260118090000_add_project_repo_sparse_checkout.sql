@@ -0,0 +1,3 @@
+-- JSON array of cone-mode sparse-checkout directories for the repo's worktrees.
+-- NULL checks out everything; an empty array uses the project's working dirs.
+ALTER TABLE project_repos ADD COLUMN sparse_checkout TEXT;
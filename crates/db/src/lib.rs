@@ -7,6 +7,8 @@ use sqlx::{
 use utils::assets::asset_dir;
 
 pub mod models;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 
 #[derive(Clone)]
 pub struct DBService {
@@ -0,0 +1,236 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "approval_policy_decision", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[ts(use_ts_enum)]
+pub enum ApprovalPolicyDecision {
+    Allow,
+    Deny,
+    /// Always ask the user, even if a later rule would decide automatically
+    Ask,
+}
+
+#[derive(Debug, Clone, Copy, Default, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "approval_policy_path_scope", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(use_ts_enum)]
+pub enum ApprovalPolicyPathScope {
+    #[default]
+    Any,
+    InsideWorktree,
+    OutsideWorktree,
+}
+
+/// Rule deciding tool approval requests of a project's coding agents without
+/// asking the user. All conditions that are set must match.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ApprovalPolicyRule {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    /// Rules are evaluated in ascending position; the first match decides
+    pub position: i64,
+    /// Glob on the tool name, e.g. `Bash` or `mcp__*`. Matches any tool when unset
+    pub tool_name: Option<String>,
+    /// Regex matched against the command of shell tools
+    pub command_pattern: Option<String>,
+    /// Glob matched against the file paths the tool touches
+    pub path_glob: Option<String>,
+    pub path_scope: ApprovalPolicyPathScope,
+    pub decision: ApprovalPolicyDecision,
+    /// Shown in the conversation when the rule decides a request
+    pub reason: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateApprovalPolicyRule {
+    pub position: Option<i64>,
+    pub tool_name: Option<String>,
+    pub command_pattern: Option<String>,
+    pub path_glob: Option<String>,
+    pub path_scope: Option<ApprovalPolicyPathScope>,
+    pub decision: ApprovalPolicyDecision,
+    pub reason: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpdateApprovalPolicyRule {
+    pub position: Option<i64>,
+    pub tool_name: Option<String>,
+    pub command_pattern: Option<String>,
+    pub path_glob: Option<String>,
+    pub path_scope: Option<ApprovalPolicyPathScope>,
+    pub decision: Option<ApprovalPolicyDecision>,
+    pub reason: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+impl ApprovalPolicyRule {
+    /// Rules of a project in evaluation order
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalPolicyRule,
+            r#"SELECT id              as "id!: Uuid",
+                      project_id      as "project_id!: Uuid",
+                      position,
+                      tool_name,
+                      command_pattern,
+                      path_glob,
+                      path_scope      as "path_scope!: ApprovalPolicyPathScope",
+                      decision        as "decision!: ApprovalPolicyDecision",
+                      reason,
+                      enabled         as "enabled!: bool",
+                      created_at      as "created_at!: DateTime<Utc>",
+                      updated_at      as "updated_at!: DateTime<Utc>"
+               FROM approval_policy_rules
+               WHERE project_id = $1
+               ORDER BY position ASC, created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalPolicyRule,
+            r#"SELECT id              as "id!: Uuid",
+                      project_id      as "project_id!: Uuid",
+                      position,
+                      tool_name,
+                      command_pattern,
+                      path_glob,
+                      path_scope      as "path_scope!: ApprovalPolicyPathScope",
+                      decision        as "decision!: ApprovalPolicyDecision",
+                      reason,
+                      enabled         as "enabled!: bool",
+                      created_at      as "created_at!: DateTime<Utc>",
+                      updated_at      as "updated_at!: DateTime<Utc>"
+               FROM approval_policy_rules
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// New rules without an explicit position are appended after the existing ones
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateApprovalPolicyRule,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let path_scope = data.path_scope.unwrap_or_default();
+        let reason = data.reason.clone().unwrap_or_default();
+        let enabled = data.enabled.unwrap_or(true);
+        sqlx::query_as!(
+            ApprovalPolicyRule,
+            r#"INSERT INTO approval_policy_rules
+                   (id, project_id, position, tool_name, command_pattern, path_glob,
+                    path_scope, decision, reason, enabled)
+               VALUES ($1, $2,
+                       COALESCE($3, (SELECT COALESCE(MAX(position) + 1, 0)
+                                     FROM approval_policy_rules WHERE project_id = $2)),
+                       $4, $5, $6, $7, $8, $9, $10)
+               RETURNING id              as "id!: Uuid",
+                         project_id      as "project_id!: Uuid",
+                         position        as "position!: i64",
+                         tool_name,
+                         command_pattern,
+                         path_glob,
+                         path_scope      as "path_scope!: ApprovalPolicyPathScope",
+                         decision        as "decision!: ApprovalPolicyDecision",
+                         reason,
+                         enabled         as "enabled!: bool",
+                         created_at      as "created_at!: DateTime<Utc>",
+                         updated_at      as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.position,
+            data.tool_name,
+            data.command_pattern,
+            data.path_glob,
+            path_scope,
+            data.decision,
+            reason,
+            enabled
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// The nullable conditions are replaced as given; the other fields keep their
+    /// current value when omitted.
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateApprovalPolicyRule,
+    ) -> Result<Self, sqlx::Error> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let position = data.position.unwrap_or(existing.position);
+        let path_scope = data.path_scope.unwrap_or(existing.path_scope);
+        let decision = data.decision.unwrap_or(existing.decision);
+        let reason = data.reason.as_ref().unwrap_or(&existing.reason);
+        let enabled = data.enabled.unwrap_or(existing.enabled);
+
+        sqlx::query_as!(
+            ApprovalPolicyRule,
+            r#"UPDATE approval_policy_rules
+               SET position = $2,
+                   tool_name = $3,
+                   command_pattern = $4,
+                   path_glob = $5,
+                   path_scope = $6,
+                   decision = $7,
+                   reason = $8,
+                   enabled = $9,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id              as "id!: Uuid",
+                         project_id      as "project_id!: Uuid",
+                         position,
+                         tool_name,
+                         command_pattern,
+                         path_glob,
+                         path_scope      as "path_scope!: ApprovalPolicyPathScope",
+                         decision        as "decision!: ApprovalPolicyDecision",
+                         reason,
+                         enabled         as "enabled!: bool",
+                         created_at      as "created_at!: DateTime<Utc>",
+                         updated_at      as "updated_at!: DateTime<Utc>""#,
+            id,
+            position,
+            data.tool_name,
+            data.command_pattern,
+            data.path_glob,
+            path_scope,
+            decision,
+            reason,
+            enabled
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM approval_policy_rules WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
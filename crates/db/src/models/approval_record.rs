@@ -0,0 +1,335 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use utils::approvals::{ApprovalRequest, ApprovalStatus};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "approval_record_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(use_ts_enum)]
+pub enum ApprovalRecordStatus {
+    Pending,
+    Approved,
+    Denied,
+    TimedOut,
+}
+
+/// Who decided an approval request
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "approval_responder", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[ts(use_ts_enum)]
+pub enum ApprovalResponder {
+    User,
+    /// An approval policy rule of the project
+    Policy,
+    Timeout,
+    /// Closed by the server, e.g. because the executor exited before a response
+    System,
+}
+
+/// Persisted tool approval request together with its outcome.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ApprovalRecord {
+    pub id: String,
+    pub execution_process_id: Uuid,
+    pub tool_name: String,
+    #[ts(type = "JsonValue")]
+    pub tool_input: Json<Value>,
+    pub tool_call_id: String,
+    pub status: ApprovalRecordStatus,
+    pub denial_reason: Option<String>,
+    pub responder: Option<ApprovalResponder>,
+    pub policy_rule_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub timeout_at: DateTime<Utc>,
+    pub responded_at: Option<DateTime<Utc>>,
+    /// Time between the request and its response
+    pub latency_ms: Option<i64>,
+}
+
+impl ApprovalRecord {
+    pub async fn find_by_id(pool: &SqlitePool, id: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalRecord,
+            r#"SELECT id                   as "id!",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      tool_name,
+                      tool_input           as "tool_input!: Json<Value>",
+                      tool_call_id,
+                      status               as "status!: ApprovalRecordStatus",
+                      denial_reason,
+                      responder            as "responder: ApprovalResponder",
+                      policy_rule_id       as "policy_rule_id: Uuid",
+                      created_at           as "created_at!: DateTime<Utc>",
+                      timeout_at           as "timeout_at!: DateTime<Utc>",
+                      responded_at         as "responded_at: DateTime<Utc>",
+                      latency_ms
+               FROM approval_records
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_pending(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalRecord,
+            r#"SELECT id                   as "id!",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      tool_name,
+                      tool_input           as "tool_input!: Json<Value>",
+                      tool_call_id,
+                      status               as "status!: ApprovalRecordStatus",
+                      denial_reason,
+                      responder            as "responder: ApprovalResponder",
+                      policy_rule_id       as "policy_rule_id: Uuid",
+                      created_at           as "created_at!: DateTime<Utc>",
+                      timeout_at           as "timeout_at!: DateTime<Utc>",
+                      responded_at         as "responded_at: DateTime<Utc>",
+                      latency_ms
+               FROM approval_records
+               WHERE status = 'pending'
+               ORDER BY created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Approval history of all attempts of a task, newest first
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalRecord,
+            r#"SELECT ar.id                   as "id!",
+                      ar.execution_process_id as "execution_process_id!: Uuid",
+                      ar.tool_name,
+                      ar.tool_input           as "tool_input!: Json<Value>",
+                      ar.tool_call_id,
+                      ar.status               as "status!: ApprovalRecordStatus",
+                      ar.denial_reason,
+                      ar.responder            as "responder: ApprovalResponder",
+                      ar.policy_rule_id       as "policy_rule_id: Uuid",
+                      ar.created_at           as "created_at!: DateTime<Utc>",
+                      ar.timeout_at           as "timeout_at!: DateTime<Utc>",
+                      ar.responded_at         as "responded_at: DateTime<Utc>",
+                      ar.latency_ms
+               FROM approval_records ar
+               JOIN execution_processes ep ON ep.id = ar.execution_process_id
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               WHERE w.task_id = $1
+               ORDER BY ar.created_at DESC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Approval history of all tasks of a project, newest first
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalRecord,
+            r#"SELECT ar.id                   as "id!",
+                      ar.execution_process_id as "execution_process_id!: Uuid",
+                      ar.tool_name,
+                      ar.tool_input           as "tool_input!: Json<Value>",
+                      ar.tool_call_id,
+                      ar.status               as "status!: ApprovalRecordStatus",
+                      ar.denial_reason,
+                      ar.responder            as "responder: ApprovalResponder",
+                      ar.policy_rule_id       as "policy_rule_id: Uuid",
+                      ar.created_at           as "created_at!: DateTime<Utc>",
+                      ar.timeout_at           as "timeout_at!: DateTime<Utc>",
+                      ar.responded_at         as "responded_at: DateTime<Utc>",
+                      ar.latency_ms
+               FROM approval_records ar
+               JOIN execution_processes ep ON ep.id = ar.execution_process_id
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE t.project_id = $1
+               ORDER BY ar.created_at DESC
+               LIMIT $2"#,
+            project_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(pool: &SqlitePool, request: &ApprovalRequest) -> Result<(), sqlx::Error> {
+        let tool_input = Json(&request.tool_input);
+        sqlx::query!(
+            r#"INSERT INTO approval_records
+                   (id, execution_process_id, tool_name, tool_input, tool_call_id, created_at, timeout_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+            request.id,
+            request.execution_process_id,
+            request.tool_name,
+            tool_input,
+            request.tool_call_id,
+            request.created_at,
+            request.timeout_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record the outcome of a pending request. Requests that already have an outcome
+    /// are left untouched.
+    pub async fn record_response(
+        pool: &SqlitePool,
+        id: &str,
+        status: &ApprovalStatus,
+        responder: ApprovalResponder,
+        policy_rule_id: Option<Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        let (record_status, denial_reason) = match status {
+            ApprovalStatus::Pending => return Ok(()),
+            ApprovalStatus::Approved => (ApprovalRecordStatus::Approved, None),
+            ApprovalStatus::Denied { reason } => (ApprovalRecordStatus::Denied, reason.clone()),
+            ApprovalStatus::TimedOut => (ApprovalRecordStatus::TimedOut, None),
+        };
+        let responded_at = Utc::now();
+        sqlx::query!(
+            r#"UPDATE approval_records
+               SET status = $2,
+                   denial_reason = $3,
+                   responder = $4,
+                   policy_rule_id = $5,
+                   responded_at = $6,
+                   latency_ms = CAST(ROUND((julianday($6) - julianday(created_at)) * 86400000) AS INTEGER)
+               WHERE id = $1 AND status = 'pending'"#,
+            id,
+            record_status,
+            denial_reason,
+            responder,
+            policy_rule_id,
+            responded_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::test_utils::{
+        create_coding_agent_process, create_task, create_workspace, test_pool,
+    };
+
+    fn request(execution_process_id: Uuid, created_at: DateTime<Utc>) -> ApprovalRequest {
+        ApprovalRequest {
+            id: Uuid::new_v4().to_string(),
+            tool_name: "Bash".to_string(),
+            tool_input: serde_json::json!({ "command": "cargo test" }),
+            tool_call_id: Uuid::new_v4().to_string(),
+            execution_process_id,
+            created_at,
+            timeout_at: created_at + Duration::hours(1),
+        }
+    }
+
+    async fn setup() -> (SqlitePool, Uuid) {
+        let pool = test_pool().await;
+        let task = create_task(&pool).await;
+        let workspace = create_workspace(&pool, task.id).await;
+        let process = create_coding_agent_process(&pool, workspace.id).await;
+        (pool, process.id)
+    }
+
+    #[tokio::test]
+    async fn created_requests_are_pending_in_creation_order() {
+        let (pool, process_id) = setup().await;
+        let now = Utc::now();
+        let later = request(process_id, now);
+        let earlier = request(process_id, now - Duration::minutes(5));
+        ApprovalRecord::create(&pool, &later).await.unwrap();
+        ApprovalRecord::create(&pool, &earlier).await.unwrap();
+
+        let record = ApprovalRecord::find_by_id(&pool, &later.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.status, ApprovalRecordStatus::Pending);
+        assert_eq!(record.tool_input.0, later.tool_input);
+        assert_eq!(record.tool_call_id, later.tool_call_id);
+        assert!(record.responder.is_none());
+
+        let pending = ApprovalRecord::find_pending(&pool).await.unwrap();
+        let ids = pending.iter().map(|r| r.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, [earlier.id.as_str(), later.id.as_str()]);
+    }
+
+    #[tokio::test]
+    async fn the_first_response_is_recorded() {
+        let (pool, process_id) = setup().await;
+        let request = request(process_id, Utc::now() - Duration::seconds(2));
+        ApprovalRecord::create(&pool, &request).await.unwrap();
+
+        // Pending is not an outcome
+        ApprovalRecord::record_response(
+            &pool,
+            &request.id,
+            &ApprovalStatus::Pending,
+            ApprovalResponder::User,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ApprovalRecord::find_pending(&pool).await.unwrap().len(), 1);
+
+        ApprovalRecord::record_response(
+            &pool,
+            &request.id,
+            &ApprovalStatus::Denied {
+                reason: Some("not now".to_string()),
+            },
+            ApprovalResponder::User,
+            None,
+        )
+        .await
+        .unwrap();
+        ApprovalRecord::record_response(
+            &pool,
+            &request.id,
+            &ApprovalStatus::TimedOut,
+            ApprovalResponder::Timeout,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let record = ApprovalRecord::find_by_id(&pool, &request.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.status, ApprovalRecordStatus::Denied);
+        assert_eq!(record.denial_reason.as_deref(), Some("not now"));
+        assert_eq!(record.responder, Some(ApprovalResponder::User));
+        assert!(record.responded_at.is_some());
+        assert!(record.latency_ms.is_some_and(|ms| ms >= 2000));
+        assert!(
+            ApprovalRecord::find_pending(&pool)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
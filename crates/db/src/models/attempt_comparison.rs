@@ -0,0 +1,174 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A best-of-N run: one task attempted in parallel by several executor profiles.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct AttemptComparison {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub winner_workspace_id: Option<Uuid>,
+    pub judge_execution_process_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One workspace taking part in a comparison.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct AttemptComparisonCandidate {
+    pub comparison_id: Uuid,
+    pub workspace_id: Uuid,
+    #[ts(type = "ExecutorProfileId")]
+    pub executor_profile_id: Json<ExecutorProfileId>,
+    /// 0-based order in which the candidate was requested
+    pub position: i64,
+}
+
+impl AttemptComparison {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptComparison,
+            r#"SELECT id                         as "id!: Uuid",
+                      task_id                    as "task_id!: Uuid",
+                      winner_workspace_id        as "winner_workspace_id: Uuid",
+                      judge_execution_process_id as "judge_execution_process_id: Uuid",
+                      created_at                 as "created_at!: DateTime<Utc>",
+                      updated_at                 as "updated_at!: DateTime<Utc>"
+               FROM attempt_comparisons
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptComparison,
+            r#"SELECT id                         as "id!: Uuid",
+                      task_id                    as "task_id!: Uuid",
+                      winner_workspace_id        as "winner_workspace_id: Uuid",
+                      judge_execution_process_id as "judge_execution_process_id: Uuid",
+                      created_at                 as "created_at!: DateTime<Utc>",
+                      updated_at                 as "updated_at!: DateTime<Utc>"
+               FROM attempt_comparisons
+               WHERE task_id = $1
+               ORDER BY created_at DESC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create<'e, E>(executor: E, id: Uuid, task_id: Uuid) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            AttemptComparison,
+            r#"INSERT INTO attempt_comparisons (id, task_id)
+               VALUES ($1, $2)
+               RETURNING id                         as "id!: Uuid",
+                         task_id                    as "task_id!: Uuid",
+                         winner_workspace_id        as "winner_workspace_id: Uuid",
+                         judge_execution_process_id as "judge_execution_process_id: Uuid",
+                         created_at                 as "created_at!: DateTime<Utc>",
+                         updated_at                 as "updated_at!: DateTime<Utc>""#,
+            id,
+            task_id
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    pub async fn set_winner(
+        pool: &SqlitePool,
+        id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE attempt_comparisons
+               SET winner_workspace_id = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_judge_execution_process_id(
+        pool: &SqlitePool,
+        id: Uuid,
+        execution_process_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE attempt_comparisons
+               SET judge_execution_process_id = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+impl AttemptComparisonCandidate {
+    /// Candidates of a comparison in the order they were requested
+    pub async fn find_by_comparison_id(
+        pool: &SqlitePool,
+        comparison_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptComparisonCandidate,
+            r#"SELECT comparison_id       as "comparison_id!: Uuid",
+                      workspace_id        as "workspace_id!: Uuid",
+                      executor_profile_id as "executor_profile_id!: Json<ExecutorProfileId>",
+                      position
+               FROM attempt_comparison_candidates
+               WHERE comparison_id = $1
+               ORDER BY position ASC"#,
+            comparison_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create<'e, E>(
+        executor: E,
+        comparison_id: Uuid,
+        workspace_id: Uuid,
+        executor_profile_id: &ExecutorProfileId,
+        position: i64,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let executor_profile_id = Json(executor_profile_id);
+        sqlx::query_as!(
+            AttemptComparisonCandidate,
+            r#"INSERT INTO attempt_comparison_candidates
+                   (comparison_id, workspace_id, executor_profile_id, position)
+               VALUES ($1, $2, $3, $4)
+               RETURNING comparison_id       as "comparison_id!: Uuid",
+                         workspace_id        as "workspace_id!: Uuid",
+                         executor_profile_id as "executor_profile_id!: Json<ExecutorProfileId>",
+                         position"#,
+            comparison_id,
+            workspace_id,
+            executor_profile_id,
+            position
+        )
+        .fetch_one(executor)
+        .await
+    }
+}
@@ -41,10 +41,14 @@ pub enum ExecutionProcessError {
 #[serde(rename_all = "lowercase")]
 #[ts(use_ts_enum)]
 pub enum ExecutionProcessStatus {
+    /// Waiting in the execution queue for a scheduler slot
+    Queued,
     Running,
     Completed,
     Failed,
     Killed,
+    /// Stopped after exceeding its idle timeout or maximum runtime
+    TimedOut,
 }
 
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
@@ -374,6 +378,36 @@ impl ExecutionProcess {
         Ok(row.and_then(|r| r.agent_session_id))
     }
 
+    /// Find the agent_session_id of the latest coding agent turn that ran before the boundary
+    /// process, or up to and including it when `inclusive` is set
+    pub async fn find_coding_agent_turn_session_id_until(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        boundary_process_id: Uuid,
+        inclusive: bool,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"SELECT cat.agent_session_id
+               FROM execution_processes ep
+               JOIN coding_agent_turns cat ON ep.id = cat.execution_process_id
+               WHERE ep.session_id = $1
+                 AND ep.run_reason = 'codingagent'
+                 AND ep.dropped = FALSE
+                 AND cat.agent_session_id IS NOT NULL
+                 AND (ep.created_at < (SELECT created_at FROM execution_processes WHERE id = $2)
+                      OR ($3 AND ep.id = $2))
+               ORDER BY ep.created_at DESC
+               LIMIT 1"#,
+            session_id,
+            boundary_process_id,
+            inclusive
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.and_then(|r| r.agent_session_id))
+    }
+
     /// Find latest execution process by session and run reason
     pub async fn find_latest_by_session_and_run_reason(
         pool: &SqlitePool,
@@ -437,6 +471,9 @@ impl ExecutionProcess {
 
     /// Create a new execution process
     ///
+    /// `status` is `Running` for processes that start immediately and
+    /// `Queued` for processes waiting on the execution scheduler.
+    ///
     /// Note: We intentionally avoid using a transaction here. SQLite update
     /// hooks fire during transactions (before commit), and the hook spawns an
     /// async task that queries `find_by_rowid` on a different connection.
@@ -446,6 +483,7 @@ impl ExecutionProcess {
         pool: &SqlitePool,
         data: &CreateExecutionProcess,
         process_id: Uuid,
+        status: ExecutionProcessStatus,
         repo_states: &[CreateExecutionProcessRepoState],
     ) -> Result<Self, sqlx::Error> {
         let now = Utc::now();
@@ -460,7 +498,7 @@ impl ExecutionProcess {
             data.session_id,
             data.run_reason,
             executor_action_json,
-            ExecutionProcessStatus::Running,
+            status,
             None::<i64>,
             now,
             None::<DateTime<Utc>>,
@@ -517,6 +555,42 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// Move a queued execution process to running, resetting started_at to the dispatch time
+    pub async fn mark_started(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET status = $1, started_at = $2
+               WHERE id = $3"#,
+            ExecutionProcessStatus::Running,
+            now,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Count running coding agent processes grouped by executor
+    pub async fn count_running_coding_agents_by_executor(
+        pool: &SqlitePool,
+    ) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT
+                   COALESCE(
+                       json_extract(ep.executor_action, '$.typ.executor_profile_id.executor'),
+                       ''
+                   )                 as "executor!: String",
+                   COUNT(*)          as "count!: i64"
+               FROM execution_processes ep
+               WHERE ep.status = 'running' AND ep.run_reason = 'codingagent'
+               GROUP BY 1"#
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| (r.executor, r.count)).collect())
+    }
+
     pub fn executor_action(&self) -> Result<&ExecutorAction, anyhow::Error> {
         match &self.executor_action.0 {
             ExecutorActionField::ExecutorAction(action) => Ok(action),
@@ -1,10 +1,16 @@
+use std::io::{Read, Write};
+
 use chrono::{DateTime, Utc};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, SqliteConnection, SqlitePool, Type};
 use ts_rs::TS;
 use utils::log_msg::LogMsg;
 use uuid::Uuid;
 
+/// Raw JSONL is packed into chunks of roughly this many bytes before compression
+const CHUNK_TARGET_BYTES: usize = 1024 * 1024;
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct ExecutionProcessLogs {
     pub execution_id: Uuid,
@@ -13,26 +19,101 @@ pub struct ExecutionProcessLogs {
     pub inserted_at: DateTime<Utc>,
 }
 
+/// Compression applied to a stored log chunk
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum LogCodec {
+    Gzip,
+}
+
+/// What a stored log chunk holds. Raw chunks are the process's stdout/stderr; once the
+/// retention window has passed they are replaced by the normalized conversation.
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum LogChunkContent {
+    Raw,
+    Normalized,
+}
+
+#[derive(Debug, Clone)]
+struct LogChunk {
+    codec: LogCodec,
+    data: Vec<u8>,
+    raw_bytes: i64,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct LogCompressionStats {
+    pub chunks: i64,
+    pub raw_bytes: i64,
+    pub compressed_bytes: i64,
+}
+
+/// Bytes held by execution process logs, raw and compressed
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct LogStorageUsage {
+    /// Uncompressed JSONL rows of running or not yet compressed processes
+    pub raw_bytes: i64,
+    /// Compressed chunks of finished processes
+    pub compressed_bytes: i64,
+    /// Size of the compressed chunks once decompressed
+    pub compressed_raw_bytes: i64,
+}
+
 impl ExecutionProcessLogs {
-    /// Find logs by execution process ID
+    /// Find logs by execution process ID. Compressed chunks are decompressed and returned
+    /// ahead of any raw rows, so callers see the full JSONL in order either way.
     pub async fn find_by_execution_id(
         pool: &SqlitePool,
         execution_id: Uuid,
     ) -> Result<Vec<Self>, sqlx::Error> {
-        sqlx::query_as!(
-            ExecutionProcessLogs,
-            r#"SELECT 
-                execution_id as "execution_id!: Uuid",
-                logs,
-                byte_size,
-                inserted_at as "inserted_at!: DateTime<Utc>"
-               FROM execution_process_logs 
+        let chunks = sqlx::query_as!(
+            LogChunk,
+            r#"SELECT codec      as "codec!: LogCodec",
+                      data,
+                      raw_bytes,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM execution_process_log_chunks
                WHERE execution_id = $1
-               ORDER BY inserted_at ASC"#,
+               ORDER BY chunk_index ASC"#,
             execution_id
         )
         .fetch_all(pool)
-        .await
+        .await?;
+
+        let mut records = chunks
+            .into_iter()
+            .map(|chunk| {
+                Ok(ExecutionProcessLogs {
+                    execution_id,
+                    logs: decompress(chunk.codec, &chunk.data)
+                        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                    byte_size: chunk.raw_bytes,
+                    inserted_at: chunk.created_at,
+                })
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+        records.extend(
+            sqlx::query_as!(
+                ExecutionProcessLogs,
+                r#"SELECT 
+                    execution_id as "execution_id!: Uuid",
+                    logs,
+                    byte_size,
+                    inserted_at as "inserted_at!: DateTime<Utc>"
+                   FROM execution_process_logs 
+                   WHERE execution_id = $1
+                   ORDER BY inserted_at ASC"#,
+                execution_id
+            )
+            .fetch_all(pool)
+            .await?,
+        );
+        Ok(records)
     }
 
     /// Parse JSONL logs back into Vec<LogMsg>
@@ -66,4 +147,442 @@ impl ExecutionProcessLogs {
 
         Ok(())
     }
+
+    /// Finished execution processes that still have uncompressed log rows
+    pub async fn find_compressible(
+        pool: &SqlitePool,
+        limit: i64,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT DISTINCT epl.execution_id as "execution_id!: Uuid"
+               FROM execution_process_logs epl
+               JOIN execution_processes ep ON ep.id = epl.execution_id
+               WHERE ep.status IN ('completed', 'failed', 'killed', 'timedout')
+               LIMIT $1"#,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Pack the raw log rows of an execution process into compressed chunks appended
+    /// after any existing ones, and delete the rows
+    pub async fn compress_execution(
+        pool: &SqlitePool,
+        execution_id: Uuid,
+    ) -> Result<LogCompressionStats, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let records = sqlx::query_as!(
+            ExecutionProcessLogs,
+            r#"SELECT 
+                execution_id as "execution_id!: Uuid",
+                logs,
+                byte_size,
+                inserted_at as "inserted_at!: DateTime<Utc>"
+               FROM execution_process_logs 
+               WHERE execution_id = $1
+               ORDER BY inserted_at ASC"#,
+            execution_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let Some(last_inserted_at) = records.last().map(|record| record.inserted_at) else {
+            return Ok(LogCompressionStats::default());
+        };
+
+        let stats = insert_chunks(
+            &mut tx,
+            execution_id,
+            LogChunkContent::Raw,
+            records.iter().map(|record| record.logs.as_str()),
+        )
+        .await?;
+
+        sqlx::query!(
+            r#"DELETE FROM execution_process_logs
+               WHERE execution_id = $1 AND inserted_at <= $2"#,
+            execution_id,
+            last_inserted_at
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(stats)
+    }
+
+    /// Processes that finished before `cutoff` and still hold raw stdout/stderr, either
+    /// as rows or as raw chunks
+    pub async fn find_expired(
+        pool: &SqlitePool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT ep.id as "id!: Uuid"
+               FROM execution_processes ep
+               WHERE ep.completed_at IS NOT NULL
+                 AND datetime(ep.completed_at) < datetime($1)
+                 AND (EXISTS (SELECT 1 FROM execution_process_logs WHERE execution_id = ep.id)
+                      OR EXISTS (SELECT 1 FROM execution_process_log_chunks
+                                 WHERE execution_id = ep.id AND content = 'raw'))
+               ORDER BY ep.completed_at ASC"#,
+            cutoff
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Replace the raw stdout/stderr of a finished process with its normalized
+    /// conversation. `normalized` holds the conversation's JsonPatch messages; other
+    /// messages are dropped along with the raw output. Returns the stats of the chunks
+    /// written.
+    pub async fn expire_raw_logs(
+        pool: &SqlitePool,
+        execution_id: Uuid,
+        normalized: &[LogMsg],
+    ) -> Result<LogCompressionStats, sqlx::Error> {
+        let lines = normalized
+            .iter()
+            .filter(|msg| matches!(msg, LogMsg::JsonPatch(_)))
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM execution_process_logs WHERE execution_id = $1",
+            execution_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"DELETE FROM execution_process_log_chunks
+               WHERE execution_id = $1 AND content = 'raw'"#,
+            execution_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        let stats = insert_chunks(
+            &mut tx,
+            execution_id,
+            LogChunkContent::Normalized,
+            lines.iter().map(String::as_str),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(stats)
+    }
+
+    /// Remove log rows left behind by execution processes that no longer exist, e.g.
+    /// of tasks deleted while foreign keys were not enforced. Returns the rows removed.
+    pub async fn delete_orphaned(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+        let raw = sqlx::query!(
+            r#"DELETE FROM execution_process_logs
+               WHERE execution_id NOT IN (SELECT id FROM execution_processes)"#
+        )
+        .execute(pool)
+        .await?;
+        let chunks = sqlx::query!(
+            r#"DELETE FROM execution_process_log_chunks
+               WHERE execution_id NOT IN (SELECT id FROM execution_processes)"#
+        )
+        .execute(pool)
+        .await?;
+        Ok(raw.rows_affected() + chunks.rows_affected())
+    }
+
+    pub async fn storage_usage(pool: &SqlitePool) -> Result<LogStorageUsage, sqlx::Error> {
+        let raw_bytes = sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(byte_size), 0) as "bytes!: i64" FROM execution_process_logs"#
+        )
+        .fetch_one(pool)
+        .await?;
+        let chunks = sqlx::query!(
+            r#"SELECT COALESCE(SUM(compressed_bytes), 0) as "compressed_bytes!: i64",
+                      COALESCE(SUM(raw_bytes), 0)        as "raw_bytes!: i64"
+               FROM execution_process_log_chunks"#
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(LogStorageUsage {
+            raw_bytes,
+            compressed_bytes: chunks.compressed_bytes,
+            compressed_raw_bytes: chunks.raw_bytes,
+        })
+    }
+}
+
+/// Compress JSONL lines into chunks appended after the process's existing ones
+async fn insert_chunks<'a>(
+    conn: &mut SqliteConnection,
+    execution_id: Uuid,
+    content: LogChunkContent,
+    lines: impl IntoIterator<Item = &'a str>,
+) -> Result<LogCompressionStats, sqlx::Error> {
+    let mut chunk_index = sqlx::query_scalar!(
+        r#"SELECT COALESCE(MAX(chunk_index) + 1, 0) as "next!: i64"
+           FROM execution_process_log_chunks
+           WHERE execution_id = $1"#,
+        execution_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let mut stats = LogCompressionStats::default();
+    for (text, line_count) in pack_chunks(lines) {
+        let data = compress(LogCodec::Gzip, &text).map_err(sqlx::Error::Io)?;
+        let raw_bytes = text.len() as i64;
+        let compressed_bytes = data.len() as i64;
+        sqlx::query!(
+            r#"INSERT INTO execution_process_log_chunks
+                   (execution_id, chunk_index, codec, content, data, raw_bytes,
+                    compressed_bytes, line_count)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+            execution_id,
+            chunk_index,
+            LogCodec::Gzip,
+            content,
+            data,
+            raw_bytes,
+            compressed_bytes,
+            line_count
+        )
+        .execute(&mut *conn)
+        .await?;
+        chunk_index += 1;
+        stats.chunks += 1;
+        stats.raw_bytes += raw_bytes;
+        stats.compressed_bytes += compressed_bytes;
+    }
+    Ok(stats)
+}
+
+/// Concatenate JSONL into chunks of about `CHUNK_TARGET_BYTES`, never splitting an input
+/// string. Returns each chunk with its line count.
+fn pack_chunks<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<(String, i64)> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut line_count = 0;
+    for logs in lines {
+        current.push_str(logs);
+        if !logs.ends_with('\n') {
+            current.push('\n');
+        }
+        line_count += logs.lines().count() as i64;
+        if current.len() >= CHUNK_TARGET_BYTES {
+            chunks.push((std::mem::take(&mut current), line_count));
+            line_count = 0;
+        }
+    }
+    if !current.is_empty() {
+        chunks.push((current, line_count));
+    }
+    chunks
+}
+
+fn compress(codec: LogCodec, text: &str) -> std::io::Result<Vec<u8>> {
+    match codec {
+        LogCodec::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(text.as_bytes())?;
+            encoder.finish()
+        }
+    }
+}
+
+fn decompress(codec: LogCodec, data: &[u8]) -> std::io::Result<String> {
+    match codec {
+        LogCodec::Gzip => {
+            let mut text = String::new();
+            GzDecoder::new(data).read_to_string(&mut text)?;
+            Ok(text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        models::execution_process::{ExecutionProcess, ExecutionProcessStatus},
+        test_utils::{create_coding_agent_process, create_task, create_workspace, test_pool},
+    };
+
+    async fn append(pool: &SqlitePool, execution_id: Uuid, msg: &LogMsg) {
+        let line = format!("{}\n", serde_json::to_string(msg).unwrap());
+        ExecutionProcessLogs::append_log_line(pool, execution_id, &line)
+            .await
+            .unwrap();
+    }
+
+    async fn stored(pool: &SqlitePool, execution_id: Uuid) -> Vec<String> {
+        let records = ExecutionProcessLogs::find_by_execution_id(pool, execution_id)
+            .await
+            .unwrap();
+        ExecutionProcessLogs::parse_logs(&records)
+            .unwrap()
+            .iter()
+            .map(|msg| serde_json::to_string(msg).unwrap())
+            .collect()
+    }
+
+    async fn finish(pool: &SqlitePool, execution_id: Uuid, completed_at: DateTime<Utc>) {
+        ExecutionProcess::update_completion(
+            pool,
+            execution_id,
+            ExecutionProcessStatus::Completed,
+            Some(0),
+        )
+        .await
+        .unwrap();
+        sqlx::query("UPDATE execution_processes SET completed_at = $1 WHERE id = $2")
+            .bind(completed_at)
+            .bind(execution_id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    fn patch(index: usize) -> LogMsg {
+        LogMsg::JsonPatch(
+            serde_json::from_value(json!([{
+                "op": "add",
+                "path": format!("/entries/{index}"),
+                "value": { "type": "NORMALIZED_ENTRY", "content": format!("entry {index}") }
+            }]))
+            .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn compressed_chunks_round_trip_ahead_of_newer_rows() {
+        let pool = test_pool().await;
+        let task = create_task(&pool).await;
+        let workspace = create_workspace(&pool, task.id).await;
+        let process = create_coding_agent_process(&pool, workspace.id).await;
+        let messages = [
+            LogMsg::Stdout("first".to_string()),
+            LogMsg::Stderr("second".to_string()),
+            LogMsg::Stdout("third".to_string()),
+        ];
+        for msg in &messages {
+            append(&pool, process.id, msg).await;
+        }
+        let expected = messages
+            .iter()
+            .map(|msg| serde_json::to_string(msg).unwrap())
+            .collect::<Vec<_>>();
+
+        let stats = ExecutionProcessLogs::compress_execution(&pool, process.id)
+            .await
+            .unwrap();
+        assert_eq!(stats.chunks, 1);
+        assert_eq!(
+            stats.raw_bytes,
+            expected
+                .iter()
+                .map(|line| line.len() as i64 + 1)
+                .sum::<i64>()
+        );
+        let usage = ExecutionProcessLogs::storage_usage(&pool).await.unwrap();
+        assert_eq!(usage.raw_bytes, 0);
+        assert_eq!(usage.compressed_bytes, stats.compressed_bytes);
+        assert_eq!(stored(&pool, process.id).await, expected);
+
+        // Rows written after compression follow the chunks and land in a new chunk
+        let late = LogMsg::Stdout("fourth".to_string());
+        append(&pool, process.id, &late).await;
+        let mut expected = expected;
+        expected.push(serde_json::to_string(&late).unwrap());
+        assert_eq!(stored(&pool, process.id).await, expected);
+
+        let stats = ExecutionProcessLogs::compress_execution(&pool, process.id)
+            .await
+            .unwrap();
+        assert_eq!(stats.chunks, 1);
+        assert_eq!(stored(&pool, process.id).await, expected);
+        assert_eq!(
+            ExecutionProcessLogs::compress_execution(&pool, process.id)
+                .await
+                .unwrap()
+                .chunks,
+            0
+        );
+    }
+
+    #[test]
+    fn pack_chunks_closes_chunks_at_the_target_size_without_splitting_rows() {
+        let row = format!("{}\n", "x".repeat(CHUNK_TARGET_BYTES / 3 + 1));
+        let rows = [row.as_str(), row.as_str(), row.as_str(), "a\nb", "c\n"];
+
+        let chunks = pack_chunks(rows);
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].0, row.repeat(3));
+        assert_eq!(chunks[0].1, 3);
+        // Rows missing a trailing newline get one so lines never run together
+        assert_eq!(chunks[1], ("a\nb\nc\n".to_string(), 3));
+        assert!(pack_chunks(std::iter::empty()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn expiring_raw_logs_keeps_only_the_normalized_conversation() {
+        let pool = test_pool().await;
+        let task = create_task(&pool).await;
+        let workspace = create_workspace(&pool, task.id).await;
+        let now = Utc::now();
+        let cutoff = now - chrono::Duration::days(7);
+
+        let old = create_coding_agent_process(&pool, workspace.id).await;
+        append(&pool, old.id, &LogMsg::Stdout("compressed".to_string())).await;
+        ExecutionProcessLogs::compress_execution(&pool, old.id)
+            .await
+            .unwrap();
+        append(&pool, old.id, &LogMsg::Stderr("raw".to_string())).await;
+        finish(&pool, old.id, now - chrono::Duration::days(30)).await;
+
+        let recent = create_coding_agent_process(&pool, workspace.id).await;
+        append(&pool, recent.id, &LogMsg::Stdout("recent".to_string())).await;
+        finish(&pool, recent.id, now - chrono::Duration::days(1)).await;
+
+        // Still running, so never expired however old it is
+        let running = create_coding_agent_process(&pool, workspace.id).await;
+        append(&pool, running.id, &LogMsg::Stdout("running".to_string())).await;
+
+        assert_eq!(
+            ExecutionProcessLogs::find_expired(&pool, cutoff)
+                .await
+                .unwrap(),
+            vec![old.id]
+        );
+
+        let conversation = [patch(0), patch(1)];
+        let normalized = [
+            conversation[0].clone(),
+            LogMsg::Stdout("dropped".to_string()),
+            conversation[1].clone(),
+            LogMsg::Finished,
+        ];
+        let stats = ExecutionProcessLogs::expire_raw_logs(&pool, old.id, &normalized)
+            .await
+            .unwrap();
+        assert_eq!(stats.chunks, 1);
+
+        assert_eq!(
+            stored(&pool, old.id).await,
+            conversation
+                .iter()
+                .map(|msg| serde_json::to_string(msg).unwrap())
+                .collect::<Vec<_>>()
+        );
+        assert!(
+            ExecutionProcessLogs::find_expired(&pool, cutoff)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(stored(&pool, recent.id).await.len(), 1);
+        assert_eq!(stored(&pool, running.id).await.len(), 1);
+    }
 }
@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// An execution process waiting for a scheduler slot.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ExecutionQueueEntry {
+    pub execution_process_id: Uuid,
+    pub project_id: Uuid,
+    pub executor: String,
+    /// Priority of the task the execution belongs to
+    pub priority: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Queue entry joined with the task and workspace it belongs to, for display.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct QueuedExecution {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub entry: ExecutionQueueEntry,
+    pub session_id: Uuid,
+    pub workspace_id: Uuid,
+    pub task_id: Uuid,
+    pub task_title: String,
+    /// 1-based position in dispatch order, ignoring per-executor limits
+    pub position: i64,
+}
+
+impl ExecutionQueueEntry {
+    /// All queue entries, highest task priority first, then oldest first
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionQueueEntry,
+            r#"SELECT eq.execution_process_id as "execution_process_id!: Uuid",
+                      eq.project_id           as "project_id!: Uuid",
+                      eq.executor,
+                      t.priority              as "priority!: i64",
+                      eq.created_at           as "created_at!: DateTime<Utc>"
+               FROM execution_queue eq
+               JOIN execution_processes ep ON ep.id = eq.execution_process_id
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               ORDER BY t.priority DESC, eq.created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionQueueEntry,
+            r#"SELECT eq.execution_process_id as "execution_process_id!: Uuid",
+                      eq.project_id           as "project_id!: Uuid",
+                      eq.executor,
+                      t.priority              as "priority!: i64",
+                      eq.created_at           as "created_at!: DateTime<Utc>"
+               FROM execution_queue eq
+               JOIN execution_processes ep ON ep.id = eq.execution_process_id
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE eq.execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Queue entries with task context, in dispatch order
+    pub async fn find_all_with_context(
+        pool: &SqlitePool,
+    ) -> Result<Vec<QueuedExecution>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT eq.execution_process_id as "execution_process_id!: Uuid",
+                      eq.project_id           as "project_id!: Uuid",
+                      eq.executor,
+                      t.priority              as "priority!: i64",
+                      eq.created_at           as "created_at!: DateTime<Utc>",
+                      ep.session_id           as "session_id!: Uuid",
+                      w.id                    as "workspace_id!: Uuid",
+                      t.id                    as "task_id!: Uuid",
+                      t.title                 as "task_title!"
+               FROM execution_queue eq
+               JOIN execution_processes ep ON ep.id = eq.execution_process_id
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               ORDER BY t.priority DESC, eq.created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .enumerate()
+            .map(|(idx, r)| QueuedExecution {
+                entry: ExecutionQueueEntry {
+                    execution_process_id: r.execution_process_id,
+                    project_id: r.project_id,
+                    executor: r.executor,
+                    priority: r.priority,
+                    created_at: r.created_at,
+                },
+                session_id: r.session_id,
+                workspace_id: r.workspace_id,
+                task_id: r.task_id,
+                task_title: r.task_title,
+                position: idx as i64 + 1,
+            })
+            .collect())
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        project_id: Uuid,
+        executor: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO execution_queue (execution_process_id, project_id, executor)
+               VALUES ($1, $2, $3)"#,
+            execution_process_id,
+            project_id,
+            executor
+        )
+        .execute(pool)
+        .await?;
+
+        Self::find_by_execution_process_id(pool, execution_process_id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    pub async fn delete(pool: &SqlitePool, execution_process_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM execution_queue WHERE execution_process_id = $1",
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
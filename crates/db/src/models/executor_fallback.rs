@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use executors::{fallback::FallbackReason, profile::ExecutorProfileId};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// One hop in an executor fallback chain: the failed run and the session that
+/// retried its prompt with the next profile.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ExecutorFallback {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub failed_execution_process_id: Uuid,
+    pub failed_session_id: Uuid,
+    #[ts(type = "ExecutorProfileId")]
+    pub failed_profile: Json<ExecutorProfileId>,
+    pub fallback_session_id: Uuid,
+    #[ts(type = "ExecutorProfileId")]
+    pub fallback_profile: Json<ExecutorProfileId>,
+    pub reason: FallbackReason,
+    /// Set when the fallback session's coding agent completed successfully
+    pub succeeded_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateExecutorFallback {
+    pub workspace_id: Uuid,
+    pub failed_execution_process_id: Uuid,
+    pub failed_session_id: Uuid,
+    pub failed_profile: ExecutorProfileId,
+    pub fallback_session_id: Uuid,
+    pub fallback_profile: ExecutorProfileId,
+    pub reason: FallbackReason,
+}
+
+impl ExecutorFallback {
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutorFallback,
+            r#"SELECT id                          as "id!: Uuid",
+                      workspace_id                as "workspace_id!: Uuid",
+                      failed_execution_process_id as "failed_execution_process_id!: Uuid",
+                      failed_session_id           as "failed_session_id!: Uuid",
+                      failed_profile              as "failed_profile!: Json<ExecutorProfileId>",
+                      fallback_session_id         as "fallback_session_id!: Uuid",
+                      fallback_profile            as "fallback_profile!: Json<ExecutorProfileId>",
+                      reason                      as "reason!: FallbackReason",
+                      succeeded_at                as "succeeded_at: DateTime<Utc>",
+                      created_at                  as "created_at!: DateTime<Utc>"
+               FROM executor_fallbacks
+               WHERE workspace_id = $1
+               ORDER BY created_at ASC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find the hop that created the given fallback session, if any
+    pub async fn find_by_fallback_session_id(
+        pool: &SqlitePool,
+        session_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutorFallback,
+            r#"SELECT id                          as "id!: Uuid",
+                      workspace_id                as "workspace_id!: Uuid",
+                      failed_execution_process_id as "failed_execution_process_id!: Uuid",
+                      failed_session_id           as "failed_session_id!: Uuid",
+                      failed_profile              as "failed_profile!: Json<ExecutorProfileId>",
+                      fallback_session_id         as "fallback_session_id!: Uuid",
+                      fallback_profile            as "fallback_profile!: Json<ExecutorProfileId>",
+                      reason                      as "reason!: FallbackReason",
+                      succeeded_at                as "succeeded_at: DateTime<Utc>",
+                      created_at                  as "created_at!: DateTime<Utc>"
+               FROM executor_fallbacks
+               WHERE fallback_session_id = $1"#,
+            session_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateExecutorFallback,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let failed_profile = Json(&data.failed_profile);
+        let fallback_profile = Json(&data.fallback_profile);
+        sqlx::query_as!(
+            ExecutorFallback,
+            r#"INSERT INTO executor_fallbacks (
+                   id, workspace_id, failed_execution_process_id, failed_session_id,
+                   failed_profile, fallback_session_id, fallback_profile, reason
+               ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+               RETURNING id                          as "id!: Uuid",
+                         workspace_id                as "workspace_id!: Uuid",
+                         failed_execution_process_id as "failed_execution_process_id!: Uuid",
+                         failed_session_id           as "failed_session_id!: Uuid",
+                         failed_profile              as "failed_profile!: Json<ExecutorProfileId>",
+                         fallback_session_id         as "fallback_session_id!: Uuid",
+                         fallback_profile            as "fallback_profile!: Json<ExecutorProfileId>",
+                         reason                      as "reason!: FallbackReason",
+                         succeeded_at                as "succeeded_at: DateTime<Utc>",
+                         created_at                  as "created_at!: DateTime<Utc>""#,
+            id,
+            data.workspace_id,
+            data.failed_execution_process_id,
+            data.failed_session_id,
+            failed_profile,
+            data.fallback_session_id,
+            fallback_profile,
+            data.reason
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Mark the hop that created `session_id` as the one that succeeded
+    pub async fn mark_succeeded(pool: &SqlitePool, session_id: Uuid) -> Result<u64, sqlx::Error> {
+        let now = Utc::now();
+        let result = sqlx::query!(
+            r#"UPDATE executor_fallbacks
+               SET succeeded_at = $1
+               WHERE fallback_session_id = $2 AND succeeded_at IS NULL"#,
+            now,
+            session_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Bearer token of an MCP client. The token itself is only shown once, at creation.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct McpToken {
+    pub id: Uuid,
+    pub name: String,
+    /// Workspace the client acts in; its context is served by `get_context`
+    pub workspace_id: Option<Uuid>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateMcpToken {
+    pub name: String,
+    pub workspace_id: Option<Uuid>,
+}
+
+impl McpToken {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            McpToken,
+            r#"SELECT id as "id!: Uuid", name, workspace_id as "workspace_id: Uuid", last_used_at as "last_used_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM mcp_tokens
+               ORDER BY created_at DESC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_token_hash(
+        pool: &SqlitePool,
+        token_hash: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            McpToken,
+            r#"SELECT id as "id!: Uuid", name, workspace_id as "workspace_id: Uuid", last_used_at as "last_used_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM mcp_tokens
+               WHERE token_hash = $1"#,
+            token_hash
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateMcpToken,
+        token_hash: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            McpToken,
+            r#"INSERT INTO mcp_tokens (id, name, token_hash, workspace_id)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid", name, workspace_id as "workspace_id: Uuid", last_used_at as "last_used_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.name,
+            token_hash,
+            data.workspace_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn touch(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE mcp_tokens SET last_used_at = datetime('now', 'subsec') WHERE id = $1",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM mcp_tokens WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -1,17 +1,29 @@
+pub mod approval_policy_rule;
+pub mod approval_record;
+pub mod attempt_comparison;
 pub mod coding_agent_turn;
 pub mod execution_process;
 pub mod execution_process_logs;
 pub mod execution_process_repo_state;
+pub mod execution_queue;
+pub mod executor_fallback;
 pub mod image;
+pub mod mcp_token;
 pub mod merge;
 pub mod portfolio;
 pub mod project;
 pub mod project_repo;
+pub mod queued_message;
 pub mod repo;
 pub mod scratch;
+pub mod search;
+pub mod secret;
 pub mod session;
 pub mod tag;
 pub mod task;
 pub mod task_history;
+pub mod task_plan;
+pub mod task_transcript;
 pub mod workspace;
+pub mod workspace_disk_usage;
 pub mod workspace_repo;
@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use executors::approvals::ApprovalTimeoutAction;
 use serde::{Deserialize, Serialize};
 use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
 use thiserror::Error;
@@ -27,6 +28,9 @@ pub struct Project {
     pub remote_project_id: Option<Uuid>,
     pub portfolio_id: Option<Uuid>,
     pub include_task_id_in_commits: bool,
+    /// Overrides the executor profile's approval timeout when set
+    pub approval_timeout_minutes: Option<i64>,
+    pub approval_timeout_action: Option<ApprovalTimeoutAction>,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date")]
@@ -47,6 +51,13 @@ pub struct UpdateProject {
     pub default_agent_working_dir: Option<String>,
     pub portfolio_id: Option<Uuid>,
     pub include_task_id_in_commits: Option<bool>,
+    /// `0` removes the project's approval timeout override
+    #[serde(default)]
+    #[ts(optional)]
+    pub approval_timeout_minutes: Option<i64>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub approval_timeout_action: Option<ApprovalTimeoutAction>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -81,6 +92,8 @@ impl Project {
                       remote_project_id as "remote_project_id: Uuid",
                       portfolio_id as "portfolio_id: Uuid",
                       include_task_id_in_commits as "include_task_id_in_commits!: bool",
+                      approval_timeout_minutes,
+                      approval_timeout_action as "approval_timeout_action: ApprovalTimeoutAction",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -100,6 +113,8 @@ impl Project {
                    p.remote_project_id as "remote_project_id: Uuid",
                    p.portfolio_id as "portfolio_id: Uuid",
                    p.include_task_id_in_commits as "include_task_id_in_commits!: bool",
+                   p.approval_timeout_minutes,
+                   p.approval_timeout_action as "approval_timeout_action: ApprovalTimeoutAction",
                    p.created_at as "created_at!: DateTime<Utc>", p.updated_at as "updated_at!: DateTime<Utc>"
             FROM projects p
             WHERE p.id IN (
@@ -127,6 +142,8 @@ impl Project {
                       remote_project_id as "remote_project_id: Uuid",
                       portfolio_id as "portfolio_id: Uuid",
                       include_task_id_in_commits as "include_task_id_in_commits!: bool",
+                      approval_timeout_minutes,
+                      approval_timeout_action as "approval_timeout_action: ApprovalTimeoutAction",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -148,6 +165,8 @@ impl Project {
                       remote_project_id as "remote_project_id: Uuid",
                       portfolio_id as "portfolio_id: Uuid",
                       include_task_id_in_commits as "include_task_id_in_commits!: bool",
+                      approval_timeout_minutes,
+                      approval_timeout_action as "approval_timeout_action: ApprovalTimeoutAction",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -172,6 +191,8 @@ impl Project {
                       remote_project_id as "remote_project_id: Uuid",
                       portfolio_id as "portfolio_id: Uuid",
                       include_task_id_in_commits as "include_task_id_in_commits!: bool",
+                      approval_timeout_minutes,
+                      approval_timeout_action as "approval_timeout_action: ApprovalTimeoutAction",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
@@ -204,6 +225,8 @@ impl Project {
                           remote_project_id as "remote_project_id: Uuid",
                           portfolio_id as "portfolio_id: Uuid",
                           include_task_id_in_commits as "include_task_id_in_commits!: bool",
+                          approval_timeout_minutes,
+                          approval_timeout_action as "approval_timeout_action: ApprovalTimeoutAction",
                           created_at as "created_at!: DateTime<Utc>",
                           updated_at as "updated_at!: DateTime<Utc>""#,
             project_id,
@@ -228,11 +251,21 @@ impl Project {
         let default_agent_working_dir = payload.default_agent_working_dir.clone();
         let portfolio_id = payload.portfolio_id.or(existing.portfolio_id);
         let include_task_id_in_commits = payload.include_task_id_in_commits.unwrap_or(existing.include_task_id_in_commits);
+        let (approval_timeout_minutes, approval_timeout_action) =
+            match payload.approval_timeout_minutes {
+                Some(0) => (None, None),
+                minutes => (
+                    minutes.or(existing.approval_timeout_minutes),
+                    payload
+                        .approval_timeout_action
+                        .or(existing.approval_timeout_action),
+                ),
+            };
 
         sqlx::query_as!(
             Project,
             r#"UPDATE projects
-               SET name = $2, dev_script = $3, dev_script_working_dir = $4, default_agent_working_dir = $5, portfolio_id = $6, include_task_id_in_commits = $7
+               SET name = $2, dev_script = $3, dev_script_working_dir = $4, default_agent_working_dir = $5, portfolio_id = $6, include_task_id_in_commits = $7, approval_timeout_minutes = $8, approval_timeout_action = $9
                WHERE id = $1
                RETURNING id as "id!: Uuid",
                          name,
@@ -242,6 +275,8 @@ impl Project {
                          remote_project_id as "remote_project_id: Uuid",
                          portfolio_id as "portfolio_id: Uuid",
                          include_task_id_in_commits as "include_task_id_in_commits!: bool",
+                         approval_timeout_minutes,
+                         approval_timeout_action as "approval_timeout_action: ApprovalTimeoutAction",
                          created_at as "created_at!: DateTime<Utc>",
                          updated_at as "updated_at!: DateTime<Utc>""#,
             id,
@@ -251,6 +286,8 @@ impl Project {
             default_agent_working_dir,
             portfolio_id,
             include_task_id_in_commits,
+            approval_timeout_minutes,
+            approval_timeout_action,
         )
         .fetch_one(pool)
         .await
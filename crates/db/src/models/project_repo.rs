@@ -2,7 +2,7 @@ use std::path::Path;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, SqlitePool, types::Json};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
@@ -19,6 +19,30 @@ pub enum ProjectRepoError {
     AlreadyExists,
 }
 
+/// How files matched by a copy rule get into a new worktree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(use_ts_enum)]
+pub enum CopyMode {
+    #[default]
+    Copy,
+    /// Link to the original, so the worktree shares it (e.g. a build cache). A matched
+    /// directory is linked as a whole.
+    Symlink,
+    /// Falls back to copying across filesystems
+    Hardlink,
+    /// Copy-on-write clone where the filesystem supports it, otherwise a copy
+    Reflink,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct CopyFileRule {
+    /// File, directory or glob, relative to the repo root
+    pub pattern: String,
+    #[serde(default)]
+    pub mode: CopyMode,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct ProjectRepo {
     pub id: Uuid,
@@ -26,8 +50,13 @@ pub struct ProjectRepo {
     pub repo_id: Uuid,
     pub setup_script: Option<String>,
     pub cleanup_script: Option<String>,
-    pub copy_files: Option<String>,
+    #[ts(type = "Array<CopyFileRule> | null")]
+    pub copy_files: Option<Json<Vec<CopyFileRule>>>,
     pub parallel_setup_script: bool,
+    /// Directories to check out in cone mode; `null` checks out everything and an
+    /// empty list uses the project's agent and dev server working dirs
+    #[ts(type = "Array<string> | null")]
+    pub sparse_checkout: Option<Json<Vec<String>>>,
 }
 
 /// ProjectRepo with the associated repo name (for script execution in worktrees)
@@ -39,8 +68,9 @@ pub struct ProjectRepoWithName {
     pub repo_name: String,
     pub setup_script: Option<String>,
     pub cleanup_script: Option<String>,
-    pub copy_files: Option<String>,
+    pub copy_files: Option<Json<Vec<CopyFileRule>>>,
     pub parallel_setup_script: bool,
+    pub sparse_checkout: Option<Json<Vec<String>>>,
 }
 
 #[derive(Debug, Clone, Deserialize, TS)]
@@ -54,8 +84,9 @@ pub struct CreateProjectRepo {
 pub struct UpdateProjectRepo {
     pub setup_script: Option<String>,
     pub cleanup_script: Option<String>,
-    pub copy_files: Option<String>,
+    pub copy_files: Option<Vec<CopyFileRule>>,
     pub parallel_setup_script: Option<bool>,
+    pub sparse_checkout: Option<Vec<String>>,
 }
 
 impl ProjectRepo {
@@ -70,8 +101,9 @@ impl ProjectRepo {
                       repo_id as "repo_id!: Uuid",
                       setup_script,
                       cleanup_script,
-                      copy_files,
-                      parallel_setup_script as "parallel_setup_script!: bool"
+                      copy_files as "copy_files: Json<Vec<CopyFileRule>>",
+                      parallel_setup_script as "parallel_setup_script!: bool",
+                      sparse_checkout as "sparse_checkout: Json<Vec<String>>"
                FROM project_repos
                WHERE project_id = $1"#,
             project_id
@@ -91,8 +123,9 @@ impl ProjectRepo {
                       repo_id as "repo_id!: Uuid",
                       setup_script,
                       cleanup_script,
-                      copy_files,
-                      parallel_setup_script as "parallel_setup_script!: bool"
+                      copy_files as "copy_files: Json<Vec<CopyFileRule>>",
+                      parallel_setup_script as "parallel_setup_script!: bool",
+                      sparse_checkout as "sparse_checkout: Json<Vec<String>>"
                FROM project_repos
                WHERE repo_id = $1"#,
             repo_id
@@ -113,8 +146,9 @@ impl ProjectRepo {
                       r.name as "repo_name!",
                       pr.setup_script,
                       pr.cleanup_script,
-                      pr.copy_files,
-                      pr.parallel_setup_script as "parallel_setup_script!: bool"
+                      pr.copy_files as "copy_files: Json<Vec<CopyFileRule>>",
+                      pr.parallel_setup_script as "parallel_setup_script!: bool",
+                      pr.sparse_checkout as "sparse_checkout: Json<Vec<String>>"
                FROM project_repos pr
                JOIN repos r ON r.id = pr.repo_id
                WHERE pr.project_id = $1
@@ -159,8 +193,9 @@ impl ProjectRepo {
                       repo_id as "repo_id!: Uuid",
                       setup_script,
                       cleanup_script,
-                      copy_files,
-                      parallel_setup_script as "parallel_setup_script!: bool"
+                      copy_files as "copy_files: Json<Vec<CopyFileRule>>",
+                      parallel_setup_script as "parallel_setup_script!: bool",
+                      sparse_checkout as "sparse_checkout: Json<Vec<String>>"
                FROM project_repos
                WHERE project_id = $1 AND repo_id = $2"#,
             project_id,
@@ -234,8 +269,9 @@ impl ProjectRepo {
                          repo_id as "repo_id!: Uuid",
                          setup_script,
                          cleanup_script,
-                         copy_files,
-                         parallel_setup_script as "parallel_setup_script!: bool""#,
+                         copy_files as "copy_files: Json<Vec<CopyFileRule>>",
+                         parallel_setup_script as "parallel_setup_script!: bool",
+                         sparse_checkout as "sparse_checkout: Json<Vec<String>>""#,
             id,
             project_id,
             repo_id
@@ -255,10 +291,11 @@ impl ProjectRepo {
 
         let setup_script = payload.setup_script.clone();
         let cleanup_script = payload.cleanup_script.clone();
-        let copy_files = payload.copy_files.clone();
+        let copy_files = payload.copy_files.clone().map(Json);
         let parallel_setup_script = payload
             .parallel_setup_script
             .unwrap_or(existing.parallel_setup_script);
+        let sparse_checkout = payload.sparse_checkout.clone().map(Json);
 
         sqlx::query_as!(
             ProjectRepo,
@@ -266,19 +303,22 @@ impl ProjectRepo {
                SET setup_script = $1,
                    cleanup_script = $2,
                    copy_files = $3,
-                   parallel_setup_script = $4
-               WHERE project_id = $5 AND repo_id = $6
+                   parallel_setup_script = $4,
+                   sparse_checkout = $5
+               WHERE project_id = $6 AND repo_id = $7
                RETURNING id as "id!: Uuid",
                          project_id as "project_id!: Uuid",
                          repo_id as "repo_id!: Uuid",
                          setup_script,
                          cleanup_script,
-                         copy_files,
-                         parallel_setup_script as "parallel_setup_script!: bool""#,
+                         copy_files as "copy_files: Json<Vec<CopyFileRule>>",
+                         parallel_setup_script as "parallel_setup_script!: bool",
+                         sparse_checkout as "sparse_checkout: Json<Vec<String>>""#,
             setup_script,
             cleanup_script,
             copy_files,
             parallel_setup_script,
+            sparse_checkout,
             project_id,
             repo_id
         )
@@ -0,0 +1,352 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::scratch::DraftFollowUpData;
+
+/// Follow-up message waiting for the running turn of a session to finish
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct QueuedMessage {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    /// Messages are sent in ascending position, one per completed turn
+    pub position: i64,
+    pub message: String,
+    pub variant: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl QueuedMessage {
+    pub fn data(&self) -> DraftFollowUpData {
+        DraftFollowUpData {
+            message: self.message.clone(),
+            variant: self.variant.clone(),
+        }
+    }
+
+    /// Queue of a session in send order
+    pub async fn find_by_session_id(
+        pool: &SqlitePool,
+        session_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            QueuedMessage,
+            r#"SELECT id         as "id!: Uuid",
+                      session_id as "session_id!: Uuid",
+                      position,
+                      message,
+                      variant,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM queued_messages
+               WHERE session_id = $1
+               ORDER BY position ASC, created_at ASC"#,
+            session_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            QueuedMessage,
+            r#"SELECT id         as "id!: Uuid",
+                      session_id as "session_id!: Uuid",
+                      position,
+                      message,
+                      variant,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM queued_messages
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Append a message to the end of the session's queue
+    pub async fn create(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        data: &DraftFollowUpData,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            QueuedMessage,
+            r#"INSERT INTO queued_messages (id, session_id, position, message, variant)
+               VALUES ($1, $2,
+                       (SELECT COALESCE(MAX(position) + 1, 0)
+                        FROM queued_messages WHERE session_id = $2),
+                       $3, $4)
+               RETURNING id         as "id!: Uuid",
+                         session_id as "session_id!: Uuid",
+                         position   as "position!: i64",
+                         message,
+                         variant,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            session_id,
+            data.message,
+            data.variant
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &DraftFollowUpData,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            QueuedMessage,
+            r#"UPDATE queued_messages
+               SET message = $2,
+                   variant = $3,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id         as "id!: Uuid",
+                         session_id as "session_id!: Uuid",
+                         position,
+                         message,
+                         variant,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.message,
+            data.variant
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Give the messages consecutive positions in the order given. `ordered_ids` must
+    /// list every queued message of the session exactly once, otherwise nothing is
+    /// changed and `false` is returned.
+    pub async fn reorder(
+        pool: &SqlitePool,
+        session_id: Uuid,
+        ordered_ids: &[Uuid],
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let mut queued = sqlx::query_scalar!(
+            r#"SELECT id as "id!: Uuid" FROM queued_messages WHERE session_id = $1"#,
+            session_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut requested = ordered_ids.to_vec();
+        queued.sort();
+        requested.sort();
+        if queued != requested {
+            return Ok(false);
+        }
+
+        for (position, id) in ordered_ids.iter().enumerate() {
+            let position = position as i64;
+            sqlx::query!(
+                r#"UPDATE queued_messages
+                   SET position = $3,
+                       updated_at = datetime('now', 'subsec')
+                   WHERE id = $1 AND session_id = $2"#,
+                id,
+                session_id,
+                position
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM queued_messages WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn delete_by_session_id(
+        pool: &SqlitePool,
+        session_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM queued_messages WHERE session_id = $1",
+            session_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Remove and return the next message of the session's queue
+    pub async fn take_next(
+        pool: &SqlitePool,
+        session_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            QueuedMessage,
+            r#"DELETE FROM queued_messages
+               WHERE id = (SELECT id FROM queued_messages
+                           WHERE session_id = $1
+                           ORDER BY position ASC, created_at ASC
+                           LIMIT 1)
+               RETURNING id         as "id!: Uuid",
+                         session_id as "session_id!: Uuid",
+                         position,
+                         message,
+                         variant,
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            session_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{create_session, create_task, create_workspace, test_pool};
+
+    fn draft(message: &str) -> DraftFollowUpData {
+        DraftFollowUpData {
+            message: message.to_string(),
+            variant: None,
+        }
+    }
+
+    async fn setup() -> (SqlitePool, Uuid) {
+        let pool = test_pool().await;
+        let task = create_task(&pool).await;
+        let workspace = create_workspace(&pool, task.id).await;
+        let session = create_session(&pool, workspace.id).await;
+        (pool, session.id)
+    }
+
+    async fn queued(pool: &SqlitePool, session_id: Uuid) -> Vec<String> {
+        QueuedMessage::find_by_session_id(pool, session_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| m.message)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn messages_are_appended_in_position_order() {
+        let (pool, session_id) = setup().await;
+        let other_task = create_task(&pool).await;
+        let other_workspace = create_workspace(&pool, other_task.id).await;
+        let other_session_id = create_session(&pool, other_workspace.id).await.id;
+
+        let first = QueuedMessage::create(&pool, session_id, &draft("first"))
+            .await
+            .unwrap();
+        let other = QueuedMessage::create(&pool, other_session_id, &draft("other"))
+            .await
+            .unwrap();
+        let second = QueuedMessage::create(&pool, session_id, &draft("second"))
+            .await
+            .unwrap();
+        assert_eq!((first.position, second.position), (0, 1));
+        // Positions are per session
+        assert_eq!(other.position, 0);
+        assert_eq!(queued(&pool, session_id).await, ["first", "second"]);
+
+        // Removing a message leaves a gap; new messages still go last
+        QueuedMessage::delete(&pool, first.id).await.unwrap();
+        let third = QueuedMessage::create(&pool, session_id, &draft("third"))
+            .await
+            .unwrap();
+        assert_eq!(third.position, 2);
+        assert_eq!(queued(&pool, session_id).await, ["second", "third"]);
+    }
+
+    #[tokio::test]
+    async fn reorder_requires_every_queued_message_once() {
+        let (pool, session_id) = setup().await;
+        let mut ids = Vec::new();
+        for message in ["a", "b", "c"] {
+            ids.push(
+                QueuedMessage::create(&pool, session_id, &draft(message))
+                    .await
+                    .unwrap()
+                    .id,
+            );
+        }
+
+        for invalid in [
+            vec![ids[2], ids[0]],
+            vec![ids[2], ids[0], ids[0]],
+            vec![ids[2], ids[0], ids[1], Uuid::new_v4()],
+        ] {
+            assert!(
+                !QueuedMessage::reorder(&pool, session_id, &invalid)
+                    .await
+                    .unwrap()
+            );
+            assert_eq!(queued(&pool, session_id).await, ["a", "b", "c"]);
+        }
+
+        assert!(
+            QueuedMessage::reorder(&pool, session_id, &[ids[2], ids[0], ids[1]])
+                .await
+                .unwrap()
+        );
+        assert_eq!(queued(&pool, session_id).await, ["c", "a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn take_next_removes_the_head_of_the_queue() {
+        let (pool, session_id) = setup().await;
+        assert!(
+            QueuedMessage::take_next(&pool, session_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let a = QueuedMessage::create(&pool, session_id, &draft("a"))
+            .await
+            .unwrap();
+        let b = QueuedMessage::create(&pool, session_id, &draft("b"))
+            .await
+            .unwrap();
+        QueuedMessage::reorder(&pool, session_id, &[b.id, a.id])
+            .await
+            .unwrap();
+
+        let next = QueuedMessage::take_next(&pool, session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(next.id, b.id);
+        assert!(
+            QueuedMessage::find_by_id(&pool, b.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(queued(&pool, session_id).await, ["a"]);
+
+        let next = QueuedMessage::take_next(&pool, session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(next.id, a.id);
+        assert!(
+            QueuedMessage::take_next(&pool, session_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
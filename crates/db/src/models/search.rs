@@ -0,0 +1,296 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// What a search hit was indexed from
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SearchHitKind {
+    /// Task title and description
+    Task,
+    /// Prompt and summary of a coding agent turn
+    Turn,
+    /// Normalized assistant message or tool call from the agent logs
+    LogEntry,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    pub task_id: Uuid,
+    pub task_title: String,
+    pub project_id: Uuid,
+    pub workspace_id: Option<Uuid>,
+    pub execution_process_id: Option<Uuid>,
+    /// Position of the entry in the process's normalized conversation
+    pub entry_index: Option<i64>,
+    /// Matching text with the matched terms wrapped in `<mark>` tags
+    pub snippet: String,
+    /// BM25 score; lower is a better match
+    pub rank: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    pub project_id: Option<Uuid>,
+    pub kind: Option<SearchHitKind>,
+    pub since: Option<DateTime<Utc>>,
+}
+
+pub struct SearchIndex;
+
+impl SearchIndex {
+    /// Ranked hits for a free-text query. Returns nothing if the query has no terms.
+    pub async fn search(
+        pool: &SqlitePool,
+        query: &str,
+        filter: &SearchFilter,
+        limit: i64,
+    ) -> Result<Vec<SearchHit>, sqlx::Error> {
+        let Some(fts_query) = Self::fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        sqlx::query_as!(
+            SearchHit,
+            r#"SELECT sd.kind                 as "kind!: SearchHitKind",
+                      t.id                    as "task_id!: Uuid",
+                      t.title                 as "task_title!",
+                      t.project_id            as "project_id!: Uuid",
+                      sd.workspace_id         as "workspace_id: Uuid",
+                      sd.execution_process_id as "execution_process_id: Uuid",
+                      sd.entry_index          as "entry_index: i64",
+                      snippet(search_index, -1, '<mark>', '</mark>', '…', 16) as "snippet!: String",
+                      bm25(search_index, 4.0, 1.0) as "rank!: f64",
+                      sd.created_at           as "created_at!: DateTime<Utc>"
+               FROM search_index
+               JOIN search_documents sd ON sd.id = search_index.rowid
+               JOIN tasks t ON t.id = sd.task_id
+               WHERE search_index MATCH $1
+                 AND ($2 IS NULL OR t.project_id = $2)
+                 AND ($3 IS NULL OR sd.kind = $3)
+                 AND ($4 IS NULL OR datetime(sd.created_at) >= datetime($4))
+               ORDER BY bm25(search_index, 4.0, 1.0)
+               LIMIT $5"#,
+            fts_query,
+            filter.project_id,
+            filter.kind,
+            filter.since,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Index (or re-index) a normalized log entry of an execution process. The entry is
+    /// dated by its process, so entries backfilled from old runs keep their run's date.
+    pub async fn upsert_log_entry(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        entry_index: i64,
+        title: &str,
+        body: &str,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let updated = sqlx::query!(
+            r#"UPDATE search_documents
+               SET title = $3, body = $4
+               WHERE kind = 'log_entry'
+                 AND source_id = $1
+                 AND entry_index = $2"#,
+            execution_process_id,
+            entry_index,
+            title,
+            body
+        )
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            sqlx::query!(
+                r#"INSERT INTO search_documents
+                       (kind, source_id, entry_index, task_id, workspace_id,
+                        execution_process_id, title, body, created_at)
+                   SELECT 'log_entry', ep.id, $2, w.task_id, w.id, ep.id, $3, $4, ep.created_at
+                   FROM execution_processes ep
+                   JOIN sessions s ON s.id = ep.session_id
+                   JOIN workspaces w ON w.id = s.workspace_id
+                   WHERE ep.id = $1"#,
+                execution_process_id,
+                entry_index,
+                title,
+                body
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Execution processes whose persisted logs still need their entries indexed
+    pub async fn find_backfill_queue(pool: &SqlitePool) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT execution_process_id as "execution_process_id!: Uuid"
+               FROM search_backfill_queue"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn remove_from_backfill_queue(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM search_backfill_queue WHERE execution_process_id = $1",
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Turn free text into an FTS5 query that matches documents containing every
+    /// term (as a prefix), so user input can never be an FTS syntax error
+    pub fn fts_query(input: &str) -> Option<String> {
+        let terms: Vec<String> = input
+            .split_whitespace()
+            .map(|term| term.replace('"', ""))
+            .filter(|term| !term.is_empty())
+            .map(|term| format!("\"{term}\"*"))
+            .collect();
+        (!terms.is_empty()).then(|| terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        create_coding_agent_process, create_task, create_workspace, test_pool,
+    };
+
+    #[tokio::test]
+    async fn log_entries_are_replaced_in_place() {
+        let pool = test_pool().await;
+        let task = create_task(&pool).await;
+        let workspace = create_workspace(&pool, task.id).await;
+        let process = create_coding_agent_process(&pool, workspace.id).await;
+        let filter = SearchFilter {
+            kind: Some(SearchHitKind::LogEntry),
+            ..Default::default()
+        };
+
+        SearchIndex::upsert_log_entry(&pool, process.id, 3, "assistant", "retry the webhook")
+            .await
+            .unwrap();
+        SearchIndex::upsert_log_entry(&pool, process.id, 3, "assistant", "retry the payment")
+            .await
+            .unwrap();
+        assert!(
+            SearchIndex::search(&pool, "webhook", &filter, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let hits = SearchIndex::search(&pool, "payment", &filter, 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].task_id, task.id);
+        assert_eq!(hits[0].execution_process_id, Some(process.id));
+        assert_eq!(hits[0].entry_index, Some(3));
+        assert_eq!(hits[0].snippet, "retry the <mark>payment</mark>");
+    }
+
+    #[tokio::test]
+    async fn backfilled_log_entries_are_dated_by_their_process() {
+        let pool = test_pool().await;
+        let task = create_task(&pool).await;
+        let workspace = create_workspace(&pool, task.id).await;
+        let process = create_coding_agent_process(&pool, workspace.id).await;
+        sqlx::query!(
+            "UPDATE execution_processes SET created_at = datetime('now', '-40 days') WHERE id = $1",
+            process.id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        SearchIndex::upsert_log_entry(&pool, process.id, 0, "Edit", "src/payments/webhook.rs")
+            .await
+            .unwrap();
+
+        let last_month = SearchFilter {
+            kind: Some(SearchHitKind::LogEntry),
+            since: Some(Utc::now() - chrono::Duration::days(60)),
+            ..Default::default()
+        };
+        let hits = SearchIndex::search(&pool, "webhook", &last_month, 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].created_at < Utc::now() - chrono::Duration::days(39));
+
+        let last_week = SearchFilter {
+            since: Some(Utc::now() - chrono::Duration::days(7)),
+            ..last_month
+        };
+        assert!(
+            SearchIndex::search(&pool, "webhook", &last_week, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn task_edits_and_deletes_update_the_index() {
+        let pool = test_pool().await;
+        let task = create_task(&pool).await;
+        let filter = SearchFilter::default();
+        assert_eq!(
+            SearchIndex::search(&pool, "task", &filter, 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        sqlx::query!(
+            "UPDATE tasks SET title = 'Rotate signing keys' WHERE id = $1",
+            task.id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let hits = SearchIndex::search(&pool, "signing", &filter, 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].kind, SearchHitKind::Task);
+
+        sqlx::query!("DELETE FROM tasks WHERE id = $1", task.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(
+            SearchIndex::search(&pool, "signing", &filter, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn fts_query_quotes_terms_as_prefixes() {
+        assert_eq!(
+            SearchIndex::fts_query(r#"web"hook  retry"#).as_deref(),
+            Some(r#""webhook"* "retry"*"#)
+        );
+        assert_eq!(SearchIndex::fts_query("  "), None);
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Secret of a project or a repo. Only the encrypted value is stored, and it is never
+/// serialized.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct Secret {
+    pub id: Uuid,
+    pub project_id: Option<Uuid>,
+    pub repo_id: Option<Uuid>,
+    /// Name of the environment variable the secret is injected as
+    pub name: String,
+    #[serde(skip)]
+    #[ts(skip)]
+    pub ciphertext: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Secret {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Secret,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id: Uuid", repo_id as "repo_id: Uuid", name, ciphertext, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM secrets
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Secret,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id: Uuid", repo_id as "repo_id: Uuid", name, ciphertext, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM secrets
+               WHERE project_id = $1
+               ORDER BY name"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_repo_id(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Secret,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id: Uuid", repo_id as "repo_id: Uuid", name, ciphertext, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM secrets
+               WHERE repo_id = $1
+               ORDER BY name"#,
+            repo_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Secrets that apply to a workspace: those of its project and of its repos. Repo
+    /// secrets come first, so a project secret of the same name takes precedence.
+    pub async fn find_for_workspace(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Secret,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id: Uuid", repo_id as "repo_id: Uuid", name, ciphertext, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM secrets
+               WHERE project_id = $1
+                  OR repo_id IN (SELECT repo_id FROM workspace_repos WHERE workspace_id = $2)
+               ORDER BY project_id IS NOT NULL, created_at"#,
+            project_id,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Secrets of a project and of all its repos, with the same precedence as
+    /// `find_for_workspace`
+    pub async fn find_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Secret,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id: Uuid", repo_id as "repo_id: Uuid", name, ciphertext, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM secrets
+               WHERE project_id = $1
+                  OR repo_id IN (SELECT repo_id FROM project_repos WHERE project_id = $1)
+               ORDER BY project_id IS NOT NULL, created_at"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+        repo_id: Option<Uuid>,
+        name: &str,
+        ciphertext: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            Secret,
+            r#"INSERT INTO secrets (id, project_id, repo_id, name, ciphertext)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid", project_id as "project_id: Uuid", repo_id as "repo_id: Uuid", name, ciphertext, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            repo_id,
+            name,
+            ciphertext
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update_value(
+        pool: &SqlitePool,
+        id: Uuid,
+        ciphertext: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            Secret,
+            r#"UPDATE secrets
+               SET ciphertext = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", project_id as "project_id: Uuid", repo_id as "repo_id: Uuid", name, ciphertext, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            ciphertext
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM secrets WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -47,6 +47,8 @@ pub struct Task {
     pub description: Option<String>,
     pub status: TaskStatus,
     pub intent: TaskIntent,
+    /// Scheduling priority of the task's runs; higher priorities leave the execution queue first
+    pub priority: i64,
     pub parent_workspace_id: Option<Uuid>, // Foreign key to parent Workspace
     pub shared_task_id: Option<Uuid>,
     pub history_deleted_at: Option<DateTime<Utc>>,
@@ -60,6 +62,8 @@ pub struct TaskWithAttemptStatus {
     #[ts(flatten)]
     pub task: Task,
     pub has_in_progress_attempt: bool,
+    /// True when a coding agent run for this task is waiting in the execution queue
+    pub has_queued_attempt: bool,
     pub last_attempt_failed: bool,
     pub executor: String,
 }
@@ -91,6 +95,7 @@ pub struct CreateTask {
     pub description: Option<String>,
     pub status: Option<TaskStatus>,
     pub intent: Option<TaskIntent>,
+    pub priority: Option<i64>,
     pub parent_workspace_id: Option<Uuid>,
     pub image_ids: Option<Vec<Uuid>>,
     pub shared_task_id: Option<Uuid>,
@@ -108,6 +113,7 @@ impl CreateTask {
             description,
             status: Some(TaskStatus::Todo),
             intent: None,
+            priority: None,
             parent_workspace_id: None,
             image_ids: None,
             shared_task_id: None,
@@ -127,6 +133,7 @@ impl CreateTask {
             description,
             status: Some(status),
             intent: None,
+            priority: None,
             parent_workspace_id: None,
             image_ids: None,
             shared_task_id: Some(shared_task_id),
@@ -140,6 +147,7 @@ pub struct UpdateTask {
     pub description: Option<String>,
     pub status: Option<TaskStatus>,
     pub intent: Option<TaskIntent>,
+    pub priority: Option<i64>,
     pub parent_workspace_id: Option<Uuid>,
     pub image_ids: Option<Vec<Uuid>>,
 }
@@ -187,6 +195,7 @@ impl Task {
   t.description,
   t.status                        AS "status!: TaskStatus",
   t.intent                        AS "intent!: TaskIntent",
+  t.priority,
   t.parent_workspace_id           AS "parent_workspace_id: Uuid",
   t.shared_task_id                AS "shared_task_id: Uuid",
   t.history_deleted_at            AS "history_deleted_at: DateTime<Utc>",
@@ -204,6 +213,16 @@ impl Task {
      LIMIT 1
   ) THEN 1 ELSE 0 END            AS "has_in_progress_attempt!: i64",
 
+  CASE WHEN EXISTS (
+    SELECT 1
+      FROM workspaces w
+      JOIN sessions s ON s.workspace_id = w.id
+      JOIN execution_processes ep ON ep.session_id = s.id
+     WHERE w.task_id       = t.id
+       AND ep.status        = 'queued'
+     LIMIT 1
+  ) THEN 1 ELSE 0 END            AS "has_queued_attempt!: i64",
+
   CASE WHEN (
     SELECT ep.status
       FROM workspaces w
@@ -213,7 +232,7 @@ impl Task {
      AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
      ORDER BY ep.created_at DESC
      LIMIT 1
-  ) IN ('failed','killed') THEN 1 ELSE 0 END
+  ) IN ('failed','killed','timedout') THEN 1 ELSE 0 END
                                  AS "last_attempt_failed!: i64",
 
   ( SELECT s.executor
@@ -242,6 +261,7 @@ ORDER BY t.created_at DESC"#,
                     description: rec.description,
                     status: rec.status,
                     intent: rec.intent,
+                    priority: rec.priority,
                     parent_workspace_id: rec.parent_workspace_id,
                     shared_task_id: rec.shared_task_id,
                     history_deleted_at: rec.history_deleted_at,
@@ -249,6 +269,7 @@ ORDER BY t.created_at DESC"#,
                     updated_at: rec.updated_at,
                 },
                 has_in_progress_attempt: rec.has_in_progress_attempt != 0,
+                has_queued_attempt: rec.has_queued_attempt != 0,
                 last_attempt_failed: rec.last_attempt_failed != 0,
                 executor: rec.executor,
             })
@@ -260,7 +281,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", priority, parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE id = $1"#,
             id
@@ -272,7 +293,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", priority, parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE rowid = $1"#,
             rowid
@@ -290,7 +311,7 @@ ORDER BY t.created_at DESC"#,
     {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", priority, parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE shared_task_id = $1
                LIMIT 1"#,
@@ -303,7 +324,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_all_shared(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", priority, parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE shared_task_id IS NOT NULL"#
         )
@@ -318,17 +339,19 @@ ORDER BY t.created_at DESC"#,
     ) -> Result<Self, sqlx::Error> {
         let status = data.status.clone().unwrap_or_default();
         let intent = data.intent.clone().unwrap_or_default();
+        let priority = data.priority.unwrap_or_default();
         sqlx::query_as!(
             Task,
-            r#"INSERT INTO tasks (id, project_id, title, description, status, intent, parent_workspace_id, shared_task_id)
-               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO tasks (id, project_id, title, description, status, intent, priority, parent_workspace_id, shared_task_id)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", priority, parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             task_id,
             data.project_id,
             data.title,
             data.description,
             status,
             intent,
+            priority,
             data.parent_workspace_id,
             data.shared_task_id
         )
@@ -336,6 +359,7 @@ ORDER BY t.created_at DESC"#,
         .await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn update(
         pool: &SqlitePool,
         id: Uuid,
@@ -344,6 +368,7 @@ ORDER BY t.created_at DESC"#,
         description: Option<String>,
         status: TaskStatus,
         intent: TaskIntent,
+        priority: i64,
         parent_workspace_id: Option<Uuid>,
     ) -> Result<Self, sqlx::Error> {
         // Get the existing task for history tracking
@@ -352,15 +377,16 @@ ORDER BY t.created_at DESC"#,
         let updated_task = sqlx::query_as!(
             Task,
             r#"UPDATE tasks
-               SET title = $3, description = $4, status = $5, intent = $6, parent_workspace_id = $7
+               SET title = $3, description = $4, status = $5, intent = $6, priority = $7, parent_workspace_id = $8
                WHERE id = $1 AND project_id = $2
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", priority, parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             project_id,
             title,
             description,
             status,
             intent,
+            priority,
             parent_workspace_id
         )
         .fetch_one(pool)
@@ -578,7 +604,7 @@ ORDER BY t.created_at DESC"#,
         // Find only child tasks that have this workspace as their parent
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", intent as "intent!: TaskIntent", priority, parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", history_deleted_at as "history_deleted_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE parent_workspace_id = $1
                ORDER BY created_at DESC"#,
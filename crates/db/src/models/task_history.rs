@@ -6,7 +6,7 @@ use ts_rs::TS;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS, EnumString, Display)]
-#[sqlx(type_name = "task_history_event_type", rename_all = "lowercase")]
+#[sqlx(type_name = "task_history_event_type", rename_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
 #[strum(serialize_all = "snake_case")]
 pub enum TaskHistoryEventType {
@@ -15,6 +15,9 @@ pub enum TaskHistoryEventType {
     TitleChanged,
     PrBodyUpdated,
     ChangeRequested,
+    PlanProposed,
+    PlanEdited,
+    PlanApproved,
     Other,
 }
 
@@ -0,0 +1,357 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "task_plan_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[ts(use_ts_enum)]
+pub enum TaskPlanStatus {
+    /// Waiting for the user to review, edit or approve
+    Draft,
+    Approved,
+}
+
+/// Plan presented by a planning run, kept on the task so it can be edited and
+/// approved before an execution run implements it.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskPlan {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub workspace_id: Uuid,
+    pub content: String,
+    pub status: TaskPlanStatus,
+    /// Incremented by every new planning run and every edit
+    pub revision: i64,
+    pub source_execution_process_id: Option<Uuid>,
+    pub execution_process_id: Option<Uuid>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TaskPlan {
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskPlan,
+            r#"SELECT id                          as "id!: Uuid",
+                      task_id                     as "task_id!: Uuid",
+                      workspace_id                as "workspace_id!: Uuid",
+                      content,
+                      status                      as "status!: TaskPlanStatus",
+                      revision,
+                      source_execution_process_id as "source_execution_process_id: Uuid",
+                      execution_process_id        as "execution_process_id: Uuid",
+                      approved_at                 as "approved_at: DateTime<Utc>",
+                      created_at                  as "created_at!: DateTime<Utc>",
+                      updated_at                  as "updated_at!: DateTime<Utc>"
+               FROM task_plans
+               WHERE task_id = $1"#,
+            task_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Store the plan from a planning run. A later run replaces the content of the
+    /// existing plan and sends it back to draft.
+    pub async fn upsert_proposed(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        workspace_id: Uuid,
+        content: &str,
+        source_execution_process_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            TaskPlan,
+            r#"INSERT INTO task_plans (id, task_id, workspace_id, content, source_execution_process_id)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT(task_id) DO UPDATE SET
+                   workspace_id = excluded.workspace_id,
+                   content = excluded.content,
+                   status = 'draft',
+                   revision = task_plans.revision + 1,
+                   source_execution_process_id = excluded.source_execution_process_id,
+                   execution_process_id = NULL,
+                   approved_at = NULL,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING id                          as "id!: Uuid",
+                         task_id                     as "task_id!: Uuid",
+                         workspace_id                as "workspace_id!: Uuid",
+                         content,
+                         status                      as "status!: TaskPlanStatus",
+                         revision,
+                         source_execution_process_id as "source_execution_process_id: Uuid",
+                         execution_process_id        as "execution_process_id: Uuid",
+                         approved_at                 as "approved_at: DateTime<Utc>",
+                         created_at                  as "created_at!: DateTime<Utc>",
+                         updated_at                  as "updated_at!: DateTime<Utc>""#,
+            id,
+            task_id,
+            workspace_id,
+            content,
+            source_execution_process_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Replace the content of a draft plan. Returns None if the task has no draft plan.
+    pub async fn update_content(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        content: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskPlan,
+            r#"UPDATE task_plans
+               SET content = $2, revision = revision + 1, updated_at = datetime('now', 'subsec')
+               WHERE task_id = $1 AND status = 'draft'
+               RETURNING id                          as "id!: Uuid",
+                         task_id                     as "task_id!: Uuid",
+                         workspace_id                as "workspace_id!: Uuid",
+                         content,
+                         status                      as "status!: TaskPlanStatus",
+                         revision,
+                         source_execution_process_id as "source_execution_process_id: Uuid",
+                         execution_process_id        as "execution_process_id: Uuid",
+                         approved_at                 as "approved_at: DateTime<Utc>",
+                         created_at                  as "created_at!: DateTime<Utc>",
+                         updated_at                  as "updated_at!: DateTime<Utc>""#,
+            task_id,
+            content
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Approve the draft plan of a task. Returns None if the task has no draft plan,
+    /// so concurrent approvals start at most one execution run.
+    pub async fn mark_approved(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskPlan,
+            r#"UPDATE task_plans
+               SET status = 'approved',
+                   approved_at = datetime('now', 'subsec'),
+                   updated_at = datetime('now', 'subsec')
+               WHERE task_id = $1 AND status = 'draft'
+               RETURNING id                          as "id!: Uuid",
+                         task_id                     as "task_id!: Uuid",
+                         workspace_id                as "workspace_id!: Uuid",
+                         content,
+                         status                      as "status!: TaskPlanStatus",
+                         revision,
+                         source_execution_process_id as "source_execution_process_id: Uuid",
+                         execution_process_id        as "execution_process_id: Uuid",
+                         approved_at                 as "approved_at: DateTime<Utc>",
+                         created_at                  as "created_at!: DateTime<Utc>",
+                         updated_at                  as "updated_at!: DateTime<Utc>""#,
+            task_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Send an approved plan back to draft, e.g. when its execution run failed to start
+    pub async fn reopen(pool: &SqlitePool, task_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE task_plans
+               SET status = 'draft',
+                   execution_process_id = NULL,
+                   approved_at = NULL,
+                   updated_at = datetime('now', 'subsec')
+               WHERE task_id = $1 AND status = 'approved'"#,
+            task_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Link an approved plan to the execution run implementing it
+    pub async fn set_execution_process(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskPlan,
+            r#"UPDATE task_plans
+               SET execution_process_id = $2, updated_at = datetime('now', 'subsec')
+               WHERE task_id = $1 AND status = 'approved'
+               RETURNING id                          as "id!: Uuid",
+                         task_id                     as "task_id!: Uuid",
+                         workspace_id                as "workspace_id!: Uuid",
+                         content,
+                         status                      as "status!: TaskPlanStatus",
+                         revision,
+                         source_execution_process_id as "source_execution_process_id: Uuid",
+                         execution_process_id        as "execution_process_id: Uuid",
+                         approved_at                 as "approved_at: DateTime<Utc>",
+                         created_at                  as "created_at!: DateTime<Utc>",
+                         updated_at                  as "updated_at!: DateTime<Utc>""#,
+            task_id,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Prompt for the execution run that implements this plan
+    pub fn to_execution_prompt(&self, task_prompt: &str) -> String {
+        format!(
+            "{task_prompt}\n\n## Approved Plan\n\nThe following plan has been reviewed and approved. Implement it.\n\n{}",
+            self.content
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        create_coding_agent_process, create_task, create_workspace, test_pool,
+    };
+
+    #[tokio::test]
+    async fn planning_runs_capture_and_redraft_the_plan() {
+        let pool = test_pool().await;
+        let task = create_task(&pool).await;
+        let workspace = create_workspace(&pool, task.id).await;
+        let first_run = create_coding_agent_process(&pool, workspace.id).await;
+
+        let plan = TaskPlan::upsert_proposed(&pool, task.id, workspace.id, "1. a", first_run.id)
+            .await
+            .unwrap();
+        assert_eq!(plan.status, TaskPlanStatus::Draft);
+        assert_eq!(plan.revision, 1);
+        assert_eq!(plan.source_execution_process_id, Some(first_run.id));
+
+        TaskPlan::mark_approved(&pool, task.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let second_run = create_coding_agent_process(&pool, workspace.id).await;
+        let replanned =
+            TaskPlan::upsert_proposed(&pool, task.id, workspace.id, "1. b", second_run.id)
+                .await
+                .unwrap();
+        assert_eq!(replanned.id, plan.id);
+        assert_eq!(replanned.content, "1. b");
+        assert_eq!(replanned.status, TaskPlanStatus::Draft);
+        assert_eq!(replanned.revision, 2);
+        assert_eq!(replanned.source_execution_process_id, Some(second_run.id));
+        assert!(replanned.approved_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn only_draft_plans_can_be_edited() {
+        let pool = test_pool().await;
+        let task = create_task(&pool).await;
+        let workspace = create_workspace(&pool, task.id).await;
+        let run = create_coding_agent_process(&pool, workspace.id).await;
+
+        assert!(
+            TaskPlan::update_content(&pool, task.id, "edited")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        TaskPlan::upsert_proposed(&pool, task.id, workspace.id, "1. a", run.id)
+            .await
+            .unwrap();
+        let edited = TaskPlan::update_content(&pool, task.id, "1. a\n2. b")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(edited.content, "1. a\n2. b");
+        assert_eq!(edited.revision, 2);
+
+        TaskPlan::mark_approved(&pool, task.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            TaskPlan::update_content(&pool, task.id, "too late")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn plans_are_approved_once() {
+        let pool = test_pool().await;
+        let task = create_task(&pool).await;
+        let workspace = create_workspace(&pool, task.id).await;
+        let run = create_coding_agent_process(&pool, workspace.id).await;
+        TaskPlan::upsert_proposed(&pool, task.id, workspace.id, "1. a", run.id)
+            .await
+            .unwrap();
+
+        let approved = TaskPlan::mark_approved(&pool, task.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(approved.status, TaskPlanStatus::Approved);
+        assert!(approved.approved_at.is_some());
+        assert!(
+            TaskPlan::mark_approved(&pool, task.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let execution = create_coding_agent_process(&pool, workspace.id).await;
+        let linked = TaskPlan::set_execution_process(&pool, task.id, execution.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(linked.execution_process_id, Some(execution.id));
+
+        // A run that failed to start hands the plan back for another approval
+        TaskPlan::reopen(&pool, task.id).await.unwrap();
+        let reopened = TaskPlan::find_by_task_id(&pool, task.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reopened.status, TaskPlanStatus::Draft);
+        assert!(reopened.execution_process_id.is_none());
+        assert!(
+            TaskPlan::mark_approved(&pool, task.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn execution_prompt_appends_the_plan() {
+        let plan = TaskPlan {
+            id: Uuid::new_v4(),
+            task_id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            content: "1. a".to_string(),
+            status: TaskPlanStatus::Approved,
+            revision: 1,
+            source_execution_process_id: None,
+            execution_process_id: None,
+            approved_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let prompt = plan.to_execution_prompt("Task");
+        assert!(prompt.starts_with("Task\n\n## Approved Plan"));
+        assert!(prompt.ends_with("1. a"));
+    }
+}
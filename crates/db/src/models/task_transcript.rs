@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Conversation transcript imported from another session and attached to a task
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskTranscript {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    /// Session the transcript was exported from, possibly in another installation
+    pub source_session_id: Option<Uuid>,
+    pub title: String,
+    #[ts(type = "JsonValue")]
+    pub transcript: Json<Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskTranscript {
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskTranscript,
+            r#"SELECT id                as "id!: Uuid",
+                      task_id           as "task_id!: Uuid",
+                      source_session_id as "source_session_id: Uuid",
+                      title,
+                      transcript        as "transcript!: Json<Value>",
+                      created_at        as "created_at!: DateTime<Utc>"
+               FROM task_transcripts
+               WHERE task_id = $1
+               ORDER BY created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskTranscript,
+            r#"SELECT id                as "id!: Uuid",
+                      task_id           as "task_id!: Uuid",
+                      source_session_id as "source_session_id: Uuid",
+                      title,
+                      transcript        as "transcript!: Json<Value>",
+                      created_at        as "created_at!: DateTime<Utc>"
+               FROM task_transcripts
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        source_session_id: Option<Uuid>,
+        title: &str,
+        transcript: &Value,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let transcript = Json(transcript);
+        sqlx::query_as!(
+            TaskTranscript,
+            r#"INSERT INTO task_transcripts (id, task_id, source_session_id, title, transcript)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id                as "id!: Uuid",
+                         task_id           as "task_id!: Uuid",
+                         source_session_id as "source_session_id: Uuid",
+                         title,
+                         transcript        as "transcript!: Json<Value>",
+                         created_at        as "created_at!: DateTime<Utc>""#,
+            id,
+            task_id,
+            source_session_id,
+            title,
+            transcript
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM task_transcripts WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Disk usage of a workspace whose worktrees are on disk
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceDiskUsage {
+    pub workspace_id: Uuid,
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub container_ref: String,
+    /// Size of the worktrees at the last measurement, 0 until measured
+    pub bytes: i64,
+    pub measured_at: Option<DateTime<Utc>>,
+    /// Latest of the last access, the last execution process and the last update
+    pub last_used_at: DateTime<Utc>,
+    pub has_running_process: bool,
+}
+
+impl WorkspaceDiskUsage {
+    /// Every workspace with worktrees on disk, least recently used first
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceDiskUsage,
+            r#"SELECT w.id as "workspace_id!: Uuid",
+                      w.task_id as "task_id!: Uuid",
+                      t.project_id as "project_id!: Uuid",
+                      w.container_ref as "container_ref!",
+                      COALESCE(du.bytes, 0) as "bytes!: i64",
+                      du.measured_at as "measured_at: DateTime<Utc>",
+                      MAX(
+                          w.updated_at,
+                          COALESCE(du.last_accessed_at, w.updated_at),
+                          COALESCE(
+                              (SELECT MAX(COALESCE(ep.completed_at, ep.started_at))
+                               FROM execution_processes ep
+                               JOIN sessions s ON s.id = ep.session_id
+                               WHERE s.workspace_id = w.id),
+                              w.updated_at
+                          )
+                      ) as "last_used_at!: DateTime<Utc>",
+                      EXISTS(
+                          SELECT 1
+                          FROM execution_processes ep
+                          JOIN sessions s ON s.id = ep.session_id
+                          WHERE s.workspace_id = w.id AND ep.completed_at IS NULL
+                      ) as "has_running_process!: bool"
+               FROM workspaces w
+               JOIN tasks t ON t.id = w.task_id
+               LEFT JOIN workspace_disk_usage du ON du.workspace_id = w.id
+               WHERE w.container_ref IS NOT NULL
+               ORDER BY 7 ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn record(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        bytes: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO workspace_disk_usage (workspace_id, bytes, measured_at)
+               VALUES ($1, $2, datetime('now', 'subsec'))
+               ON CONFLICT(workspace_id) DO UPDATE
+               SET bytes = excluded.bytes, measured_at = excluded.measured_at"#,
+            workspace_id,
+            bytes
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark the workspace as used now, so it is the last to be evicted
+    pub async fn touch(pool: &SqlitePool, workspace_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO workspace_disk_usage (workspace_id, last_accessed_at)
+               VALUES ($1, datetime('now', 'subsec'))
+               ON CONFLICT(workspace_id) DO UPDATE
+               SET last_accessed_at = excluded.last_accessed_at"#,
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
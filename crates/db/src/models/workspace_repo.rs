@@ -2,11 +2,11 @@ use std::path::PathBuf;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, SqlitePool, types::Json};
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::repo::Repo;
+use super::{project_repo::CopyFileRule, repo::Repo};
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct WorkspaceRepo {
@@ -40,7 +40,8 @@ pub struct RepoWithCopyFiles {
     pub id: Uuid,
     pub path: PathBuf,
     pub name: String,
-    pub copy_files: Option<String>,
+    /// Empty when the project has no copy rules for the repo
+    pub copy_files: Vec<CopyFileRule>,
 }
 
 impl WorkspaceRepo {
@@ -242,13 +243,13 @@ impl WorkspaceRepo {
     }
 
     /// Find repos for a workspace with their copy_files configuration.
-    /// Uses LEFT JOIN so repos without project_repo entries still appear (with NULL copy_files).
+    /// Uses LEFT JOIN so repos without project_repo entries still appear (with no copy rules).
     pub async fn find_repos_with_copy_files(
         pool: &SqlitePool,
         workspace_id: Uuid,
     ) -> Result<Vec<RepoWithCopyFiles>, sqlx::Error> {
         let rows = sqlx::query!(
-            r#"SELECT r.id as "id!: Uuid", r.path, r.name, pr.copy_files
+            r#"SELECT r.id as "id!: Uuid", r.path, r.name, pr.copy_files as "copy_files: Json<Vec<CopyFileRule>>"
                FROM repos r
                JOIN workspace_repos wr ON r.id = wr.repo_id
                JOIN workspaces w ON w.id = wr.workspace_id
@@ -266,7 +267,7 @@ impl WorkspaceRepo {
                 id: row.id,
                 path: PathBuf::from(row.path),
                 name: row.name,
-                copy_files: row.copy_files,
+                copy_files: row.copy_files.map(|rules| rules.0).unwrap_or_default(),
             })
             .collect())
     }
@@ -0,0 +1,115 @@
+//! Fixtures for model tests: an in-memory database with all migrations applied and
+//! helpers for the rows most models hang off. Other crates get them through the
+//! `test-utils` feature.
+
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType, coding_agent_initial::CodingAgentInitialRequest,
+    },
+    executors::BaseCodingAgent,
+    profile::ExecutorProfileId,
+};
+use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use uuid::Uuid;
+
+use crate::models::{
+    execution_process::{
+        CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
+    },
+    project::{CreateProject, Project},
+    session::{CreateSession, Session},
+    task::{CreateTask, Task},
+    workspace::{CreateWorkspace, Workspace},
+};
+
+pub async fn test_pool() -> SqlitePool {
+    // Every connection to `sqlite::memory:` opens its own database
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    pool
+}
+
+pub async fn create_task(pool: &SqlitePool) -> Task {
+    let project = Project::create(
+        pool,
+        &CreateProject {
+            name: "project".to_string(),
+            repositories: Vec::new(),
+        },
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap();
+    Task::create(
+        pool,
+        &CreateTask::from_title_description(project.id, "task".to_string(), None),
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap()
+}
+
+pub async fn create_workspace(pool: &SqlitePool, task_id: Uuid) -> Workspace {
+    Workspace::create(
+        pool,
+        &CreateWorkspace {
+            branch: format!("vk/{}", Uuid::new_v4()),
+            agent_working_dir: None,
+        },
+        Uuid::new_v4(),
+        task_id,
+    )
+    .await
+    .unwrap()
+}
+
+pub async fn create_session(pool: &SqlitePool, workspace_id: Uuid) -> Session {
+    Session::create(
+        pool,
+        &CreateSession { executor: None },
+        Uuid::new_v4(),
+        workspace_id,
+    )
+    .await
+    .unwrap()
+}
+
+/// A running coding agent process in a new session of the workspace
+pub async fn create_coding_agent_process(
+    pool: &SqlitePool,
+    workspace_id: Uuid,
+) -> ExecutionProcess {
+    let session = create_session(pool, workspace_id).await;
+    create_coding_agent_process_in_session(pool, session.id).await
+}
+
+/// A running coding agent process as the next turn of an existing session
+pub async fn create_coding_agent_process_in_session(
+    pool: &SqlitePool,
+    session_id: Uuid,
+) -> ExecutionProcess {
+    ExecutionProcess::create(
+        pool,
+        &CreateExecutionProcess {
+            session_id,
+            executor_action: ExecutorAction::new(
+                ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                    prompt: "prompt".to_string(),
+                    executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+                    working_dir: None,
+                }),
+                None,
+            ),
+            run_reason: ExecutionProcessRunReason::CodingAgent,
+        },
+        Uuid::new_v4(),
+        ExecutionProcessStatus::Running,
+        &[],
+    )
+    .await
+    .unwrap()
+}
@@ -25,6 +25,7 @@ use services::services::{
     filesystem_watcher::FilesystemWatcherError,
     git::{GitService, GitServiceError},
     image::{ImageError, ImageService},
+    log_maintenance::LogMaintenanceService,
     pr_monitor::PrMonitorService,
     project::ProjectService,
     queued_message::QueuedMessageService,
@@ -125,6 +126,10 @@ pub trait Deployment: Clone + Send + Sync + 'static {
         PrMonitorService::spawn(db, None, publisher).await
     }
 
+    async fn spawn_log_maintenance_service(&self) -> tokio::task::JoinHandle<()> {
+        LogMaintenanceService::spawn(self.db().clone(), self.config().clone()).await
+    }
+
     /// Trigger background auto-setup of default projects for new users
     async fn trigger_auto_project_setup(&self) {
         // soft timeout to give the filesystem search a chance to complete
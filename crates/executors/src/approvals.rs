@@ -1,11 +1,28 @@
 use std::fmt;
 
 use async_trait::async_trait;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sqlx::Type;
 use thiserror::Error;
+use ts_rs::TS;
 use workspace_utils::approvals::ApprovalStatus;
 
+/// What happens to a tool approval request that nobody answered before it expired
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, Type, JsonSchema,
+)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(use_ts_enum)]
+pub enum ApprovalTimeoutAction {
+    #[default]
+    Deny,
+    Approve,
+    Interrupt,
+}
+
 /// Errors emitted by executor approval services.
 #[derive(Debug, Error)]
 pub enum ExecutorApprovalError {
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, path::PathBuf, time::Duration};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -6,7 +6,9 @@ use thiserror::Error;
 use ts_rs::TS;
 use workspace_utils::shell::resolve_executable_path;
 
-use crate::executors::ExecutorError;
+use crate::{
+    approvals::ApprovalTimeoutAction, executors::ExecutorError, profile::ExecutorProfileId,
+};
 
 #[derive(Debug, Error)]
 pub enum CommandBuildError {
@@ -58,6 +60,65 @@ pub struct CmdOverrides {
     )]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub env: Option<HashMap<String, String>>,
+    #[schemars(
+        title = "Idle Timeout (minutes)",
+        description = "Stop the executor if it produces no output for this many minutes"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout_minutes: Option<u32>,
+    #[schemars(
+        title = "Max Runtime (minutes)",
+        description = "Stop the executor once it has been running for this many minutes"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_runtime_minutes: Option<u32>,
+    #[schemars(
+        title = "Fallback Profiles",
+        description = "Profiles to retry with, in order, when this executor fails with an auth, missing executable or rate-limit error"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_profiles: Option<Vec<ExecutorProfileId>>,
+    #[schemars(
+        title = "Approval Timeout (minutes)",
+        description = "How long tool approval requests wait for a response before the timeout action applies"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_timeout_minutes: Option<u32>,
+    #[schemars(
+        title = "Approval Timeout Action",
+        description = "What to do with an unanswered tool approval request once it expires"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_timeout_action: Option<ApprovalTimeoutAction>,
+}
+
+impl CmdOverrides {
+    pub fn timeouts(&self) -> ExecutionTimeouts {
+        let minutes = |m: Option<u32>| {
+            m.filter(|m| *m > 0)
+                .map(|m| Duration::from_secs(u64::from(m) * 60))
+        };
+        ExecutionTimeouts {
+            idle: minutes(self.idle_timeout_minutes),
+            max_runtime: minutes(self.max_runtime_minutes),
+        }
+    }
+}
+
+/// Limits enforced by the container while an executor process is running.
+/// `None` disables the corresponding check.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecutionTimeouts {
+    /// Maximum time without any stdout/stderr output
+    pub idle: Option<Duration>,
+    /// Maximum wall-clock time since the process was spawned
+    pub max_runtime: Option<Duration>,
+}
+
+impl ExecutionTimeouts {
+    pub fn is_disabled(&self) -> bool {
+        self.idle.is_none() && self.max_runtime.is_none()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
@@ -160,3 +221,46 @@ pub fn apply_overrides(builder: CommandBuilder, overrides: &CmdOverrides) -> Com
         builder
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeouts_convert_minutes() {
+        let overrides = CmdOverrides {
+            idle_timeout_minutes: Some(5),
+            max_runtime_minutes: Some(90),
+            ..Default::default()
+        };
+        assert_eq!(
+            overrides.timeouts(),
+            ExecutionTimeouts {
+                idle: Some(Duration::from_secs(5 * 60)),
+                max_runtime: Some(Duration::from_secs(90 * 60)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_timeouts_unset_or_zero_are_disabled() {
+        assert!(CmdOverrides::default().timeouts().is_disabled());
+
+        let overrides = CmdOverrides {
+            idle_timeout_minutes: Some(0),
+            max_runtime_minutes: Some(0),
+            ..Default::default()
+        };
+        assert!(overrides.timeouts().is_disabled());
+
+        let overrides = CmdOverrides {
+            idle_timeout_minutes: Some(0),
+            max_runtime_minutes: Some(10),
+            ..Default::default()
+        };
+        let timeouts = overrides.timeouts();
+        assert_eq!(timeouts.idle, None);
+        assert_eq!(timeouts.max_runtime, Some(Duration::from_secs(600)));
+        assert!(!timeouts.is_disabled());
+    }
+}
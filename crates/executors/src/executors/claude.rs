@@ -2018,6 +2018,7 @@ mod tests {
                 base_command_override: None,
                 additional_params: None,
                 env: None,
+                ..Default::default()
             },
             approvals_service: None,
             disable_api_key: None,
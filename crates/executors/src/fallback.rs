@@ -0,0 +1,308 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::Type;
+use strum_macros::Display;
+use ts_rs::TS;
+use workspace_utils::log_msg::LogMsg;
+
+use crate::executors::{BaseCodingAgent, ExecutorError};
+
+/// Phrases that mark an executor's error frame as a rate or usage limit rather
+/// than an ordinary failure
+const RATE_LIMIT_MARKERS: &[&str] = &[
+    "rate_limit_error",
+    "overloaded_error",
+    "rate limit",
+    "too many requests",
+    "api error: 429",
+    "api error: 529",
+    "usage limit",
+    "usage_limit_exceeded",
+    "hit your limit",
+    "-hour limit",
+    "weekly limit",
+    "quota exceeded",
+    "exceeded your current quota",
+    "resource_exhausted",
+];
+
+/// Why an execution was retried with the next profile in its fallback chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type, Display)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[ts(use_ts_enum)]
+pub enum FallbackReason {
+    AuthRequired,
+    ExecutableNotFound,
+    RateLimited,
+}
+
+impl FallbackReason {
+    /// Classify a spawn failure
+    pub fn from_executor_error(error: &ExecutorError) -> Option<Self> {
+        match error {
+            ExecutorError::AuthRequired(_) => Some(Self::AuthRequired),
+            ExecutorError::ExecutableNotFound { .. } => Some(Self::ExecutableNotFound),
+            _ => None,
+        }
+    }
+
+    /// Classify a failed run from the error frames the executor reported. Only
+    /// the executor's own error output is inspected, so an agent that merely
+    /// talks about rate limits in its messages or tool output does not trigger a
+    /// fallback.
+    pub fn from_output(executor: BaseCodingAgent, history: &[LogMsg]) -> Option<Self> {
+        let stdout = history
+            .iter()
+            .filter_map(|msg| match msg {
+                LogMsg::Stdout(chunk) => Some(chunk.as_str()),
+                _ => None,
+            })
+            .collect::<String>();
+        let stderr = history
+            .iter()
+            .filter_map(|msg| match msg {
+                LogMsg::Stderr(chunk) => Some(chunk.as_str()),
+                _ => None,
+            })
+            .collect::<String>();
+
+        let mut errors = stdout
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line.trim()).ok())
+            .filter_map(|frame| error_frame_message(executor, &frame))
+            .collect::<Vec<_>>();
+        if !emits_json_errors(executor) {
+            errors.extend(stderr.lines().map(str::to_string));
+        }
+
+        errors
+            .iter()
+            .any(|message| is_rate_limit_message(message))
+            .then_some(Self::RateLimited)
+    }
+}
+
+/// Prompt for retrying a follow-up on a fallback executor. The fallback cannot
+/// resume the failed executor's session, so the earlier turns of the session are
+/// replayed ahead of the follow-up as `(prompt, summary)` pairs.
+pub fn follow_up_prompt(turns: &[(String, Option<String>)], follow_up: &str) -> String {
+    if turns.is_empty() {
+        return follow_up.to_string();
+    }
+    let mut prompt = String::from(
+        "You are continuing a conversation that was started with another coding agent. \
+         Its earlier requests and their outcomes were:\n\n",
+    );
+    for (i, (request, summary)) in turns.iter().enumerate() {
+        prompt.push_str(&format!(
+            "### Request {}\n{}\n\n### Outcome\n{}\n\n",
+            i + 1,
+            request.trim(),
+            summary.as_deref().map(str::trim).unwrap_or("(no summary)")
+        ));
+    }
+    prompt.push_str("---\n\n");
+    prompt.push_str(follow_up);
+    prompt
+}
+
+/// Executors that report failures as structured frames on stdout. Everything
+/// else surfaces its errors as plain text on stderr.
+fn emits_json_errors(executor: BaseCodingAgent) -> bool {
+    matches!(
+        executor,
+        BaseCodingAgent::ClaudeCode
+            | BaseCodingAgent::Amp
+            | BaseCodingAgent::CursorAgent
+            | BaseCodingAgent::Codex
+            | BaseCodingAgent::Droid
+    )
+}
+
+/// The error text carried by an executor's stdout frame, if the frame reports an
+/// error
+fn error_frame_message(executor: BaseCodingAgent, frame: &Value) -> Option<String> {
+    let field = |value: &Value, key: &str| value.get(key).map(|v| v.to_string());
+    match executor {
+        // stream-json: a failed turn ends with an erroring `result` frame, API
+        // errors are passed through as `error` frames
+        BaseCodingAgent::ClaudeCode | BaseCodingAgent::Amp | BaseCodingAgent::CursorAgent => {
+            match frame.get("type")?.as_str()? {
+                "result" if frame.get("is_error")?.as_bool()? => Some(format!(
+                    "{} {} {}",
+                    field(frame, "subtype").unwrap_or_default(),
+                    field(frame, "result").unwrap_or_default(),
+                    field(frame, "error").unwrap_or_default()
+                )),
+                "error" => Some(frame.to_string()),
+                _ => None,
+            }
+        }
+        // JSON-RPC `codex/event` notifications with a terminal `error` event;
+        // `stream_error` only announces a retry
+        BaseCodingAgent::Codex => {
+            if !frame.get("method")?.as_str()?.starts_with("codex/event") {
+                return None;
+            }
+            let msg = frame.get("params")?.get("msg")?;
+            (msg.get("type")?.as_str()? == "error").then(|| {
+                format!(
+                    "{} {}",
+                    field(msg, "message").unwrap_or_default(),
+                    field(msg, "codex_error_info").unwrap_or_default()
+                )
+            })
+        }
+        BaseCodingAgent::Droid => (frame.get("type")?.as_str()? == "error")
+            .then(|| field(frame, "message").unwrap_or_default()),
+        // ACP harness events: `{"Error": "..."}`
+        BaseCodingAgent::Gemini | BaseCodingAgent::QwenCode => {
+            frame.get("Error").map(|message| message.to_string())
+        }
+        BaseCodingAgent::Opencode | BaseCodingAgent::Copilot => None,
+    }
+}
+
+fn is_rate_limit_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    RATE_LIMIT_MARKERS
+        .iter()
+        .any(|marker| message.contains(marker))
+        || (message.contains("429") && message.contains("http_status_code"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stdout(lines: &[&str]) -> Vec<LogMsg> {
+        lines
+            .iter()
+            .map(|line| LogMsg::Stdout(format!("{line}\n")))
+            .collect()
+    }
+
+    #[test]
+    fn detects_claude_result_errors() {
+        let history = stdout(&[
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"API Error: 429"}]}}"#,
+            r#"{"type":"result","subtype":"success","is_error":true,"result":"Claude AI usage limit reached|1760000000"}"#,
+        ]);
+        assert_eq!(
+            FallbackReason::from_output(BaseCodingAgent::ClaudeCode, &history),
+            Some(FallbackReason::RateLimited)
+        );
+
+        let history = stdout(&[
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        ]);
+        assert_eq!(
+            FallbackReason::from_output(BaseCodingAgent::Amp, &history),
+            Some(FallbackReason::RateLimited)
+        );
+    }
+
+    #[test]
+    fn ignores_rate_limit_text_outside_error_frames() {
+        let history = stdout(&[
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Adding a rate limit: return 429 Too Many Requests once the usage limit is reached"}]}}"#,
+            r#"{"type":"result","subtype":"error_during_execution","is_error":true,"result":"error: could not compile"}"#,
+        ]);
+        assert_eq!(
+            FallbackReason::from_output(BaseCodingAgent::ClaudeCode, &history),
+            None
+        );
+
+        // Structured executors do not get classified from stderr noise
+        let history = vec![LogMsg::Stderr(
+            "warning: rate limit middleware disabled\n".into(),
+        )];
+        assert_eq!(
+            FallbackReason::from_output(BaseCodingAgent::ClaudeCode, &history),
+            None
+        );
+    }
+
+    #[test]
+    fn detects_codex_error_events() {
+        let history = stdout(&[
+            r#"{"method":"codex/event/stream_error","params":{"msg":{"type":"stream_error","message":"Reconnecting... 1/5","codex_error_info":{"response_stream_disconnected":{"http_status_code":429}}}}}"#,
+        ]);
+        assert_eq!(
+            FallbackReason::from_output(BaseCodingAgent::Codex, &history),
+            None
+        );
+
+        let history = stdout(&[
+            r#"{"method":"codex/event/error","params":{"msg":{"type":"error","message":"You've hit your usage limit.","codex_error_info":"usage_limit_exceeded"}}}"#,
+        ]);
+        assert_eq!(
+            FallbackReason::from_output(BaseCodingAgent::Codex, &history),
+            Some(FallbackReason::RateLimited)
+        );
+    }
+
+    #[test]
+    fn detects_droid_and_acp_errors() {
+        let history = stdout(&[r#"{"type":"error","message":"429 Too Many Requests"}"#]);
+        assert_eq!(
+            FallbackReason::from_output(BaseCodingAgent::Droid, &history),
+            Some(FallbackReason::RateLimited)
+        );
+
+        let history = stdout(&[
+            r#"{"Message":{"type":"text","text":"RESOURCE_EXHAUSTED is handled below"}}"#,
+        ]);
+        assert_eq!(
+            FallbackReason::from_output(BaseCodingAgent::Gemini, &history),
+            None
+        );
+
+        let history = stdout(&[
+            r#"{"Error":"Quota exceeded for quota metric 'Generate Content API requests per minute'"}"#,
+        ]);
+        assert_eq!(
+            FallbackReason::from_output(BaseCodingAgent::Gemini, &history),
+            Some(FallbackReason::RateLimited)
+        );
+    }
+
+    #[test]
+    fn plain_text_executors_check_stderr_only() {
+        let history = stdout(&["Error: 429 Too Many Requests"]);
+        assert_eq!(
+            FallbackReason::from_output(BaseCodingAgent::Copilot, &history),
+            None
+        );
+
+        let history = vec![LogMsg::Stderr("Error: 429 Too Many Requests\n".into())];
+        assert_eq!(
+            FallbackReason::from_output(BaseCodingAgent::Copilot, &history),
+            Some(FallbackReason::RateLimited)
+        );
+    }
+
+    #[test]
+    fn follow_up_prompt_replays_earlier_turns() {
+        assert_eq!(follow_up_prompt(&[], "Add tests"), "Add tests");
+
+        let prompt = follow_up_prompt(
+            &[
+                (
+                    "Implement the parser".into(),
+                    Some("Parser added in src/parser.rs".into()),
+                ),
+                ("Handle comments".into(), None),
+            ],
+            "Add tests",
+        );
+        let parser = prompt.find("Implement the parser").unwrap();
+        let outcome = prompt.find("Parser added in src/parser.rs").unwrap();
+        let comments = prompt.find("Handle comments").unwrap();
+        assert!(parser < outcome && outcome < comments);
+        assert!(prompt.contains("(no summary)"));
+        assert!(prompt.ends_with("---\n\nAdd tests"));
+    }
+}
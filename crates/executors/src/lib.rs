@@ -3,6 +3,7 @@ pub mod approvals;
 pub mod command;
 pub mod env;
 pub mod executors;
+pub mod fallback;
 pub mod logs;
 pub mod mcp_config;
 pub mod profile;
@@ -67,6 +67,10 @@ pub struct NormalizedConversation {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum NormalizedEntryError {
     SetupRequired,
+    /// The executor produced no output for longer than its idle timeout
+    IdleTimeout,
+    /// The executor ran longer than its maximum runtime
+    RuntimeLimitExceeded,
     Other,
 }
 
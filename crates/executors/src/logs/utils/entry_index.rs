@@ -32,34 +32,33 @@ impl EntryIndexProvider {
         self.0.store(0, Ordering::Relaxed);
     }
 
-    /// Create a provider starting from the maximum existing normalized-entry index
-    /// observed in prior JSON patches in `MsgStore`.
+    /// Create a provider for the store's shared entry index counter. The first provider
+    /// starts from the maximum existing normalized-entry index observed in prior JSON
+    /// patches in `MsgStore`; later ones continue from wherever the counter is.
     pub fn start_from(msg_store: &MsgStore) -> Self {
-        let provider = EntryIndexProvider::new();
-
-        let max_index: Option<usize> = msg_store
-            .get_history()
-            .iter()
-            .filter_map(|msg| {
-                if let LogMsg::JsonPatch(patch) = msg {
-                    patch.iter().find_map(|op| {
-                        if let PatchOperation::Add(add) = op {
-                            add.path
-                                .strip_prefix("/entries/")
-                                .and_then(|n_str| n_str.parse::<usize>().ok())
-                        } else {
-                            None
-                        }
-                    })
-                } else {
-                    None
-                }
-            })
-            .max();
-
-        let start_at = max_index.map_or(0, |n| n.saturating_add(1));
-        provider.0.store(start_at, Ordering::Relaxed);
-        provider
+        Self(msg_store.entry_index_counter(|| {
+            let max_index: Option<usize> = msg_store
+                .get_history()
+                .iter()
+                .filter_map(|msg| {
+                    if let LogMsg::JsonPatch(patch) = msg {
+                        patch.iter().find_map(|op| {
+                            if let PatchOperation::Add(add) = op {
+                                add.path
+                                    .strip_prefix("/entries/")
+                                    .and_then(|n_str| n_str.parse::<usize>().ok())
+                            } else {
+                                None
+                            }
+                        })
+                    } else {
+                        None
+                    }
+                })
+                .max();
+
+            max_index.map_or(0, |n| n.saturating_add(1))
+        }))
     }
 }
 
@@ -81,6 +80,13 @@ impl EntryIndexProvider {
 mod tests {
     use super::*;
 
+    fn add_entry_patch(index: usize) -> json_patch::Patch {
+        serde_json::from_value(serde_json::json!([
+            { "op": "add", "path": format!("/entries/{index}"), "value": {} }
+        ]))
+        .unwrap()
+    }
+
     #[test]
     fn test_entry_index_provider() {
         let provider = EntryIndexProvider::test_new();
@@ -110,4 +116,28 @@ mod tests {
         provider.next();
         assert_eq!(provider.current(), 2);
     }
+
+    #[test]
+    fn test_start_from_seeds_from_history() {
+        let store = MsgStore::new();
+        store.push_patch(add_entry_patch(0));
+        store.push_patch(add_entry_patch(4));
+
+        assert_eq!(EntryIndexProvider::start_from(&store).next(), 5);
+    }
+
+    #[test]
+    fn test_start_from_shares_counter_per_store() {
+        let store = MsgStore::new();
+        let normalizer = EntryIndexProvider::start_from(&store);
+        assert_eq!(normalizer.next(), 0);
+
+        // An index handed out before its patch is pushed is still reserved
+        let outside = EntryIndexProvider::start_from(&store);
+        assert_eq!(outside.next(), 1);
+        assert_eq!(normalizer.next(), 2);
+
+        let other_store = MsgStore::new();
+        assert_eq!(EntryIndexProvider::start_from(&other_store).next(), 0);
+    }
 }
@@ -6,6 +6,7 @@ use std::{
 };
 
 use convert_case::{Case, Casing};
+use schemars::JsonSchema;
 use serde::{Deserialize, Deserializer, Serialize, de::Error as DeError};
 use thiserror::Error;
 use ts_rs::TS;
@@ -58,10 +59,11 @@ static EXECUTOR_PROFILES_CACHE: LazyLock<RwLock<ExecutorConfigs>> =
 const DEFAULT_PROFILES_JSON: &str = include_str!("../default_profiles.json");
 
 // Executor-centric profile identifier
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, Hash, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, Hash, Eq, JsonSchema)]
 pub struct ExecutorProfileId {
     /// The executor type (e.g., "CLAUDE_CODE", "AMP")
     #[serde(alias = "profile", deserialize_with = "de_base_coding_agent_kebab")]
+    #[schemars(with = "BaseCodingAgent")]
     // Backwards compatability with ProfileVariantIds, esp stored in DB under ExecutorAction
     pub executor: BaseCodingAgent,
     /// Optional variant name (e.g., "PLAN", "ROUTER")
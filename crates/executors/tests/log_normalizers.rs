@@ -0,0 +1,186 @@
+//! Golden-file replay tests for the executor log normalizers.
+//!
+//! Each fixture under `tests/fixtures/log_normalizers/<executor>/<case>.jsonl` holds raw
+//! stdout recorded from a coding agent, one line per line. The lines are replayed through
+//! the executor's `normalize_logs` and the resulting conversation is compared against the
+//! committed `<case>.golden.json`. The directory name is the executor in snake_case.
+//!
+//! After an intentional change to a normalizer, re-bless the goldens with
+//! `BLESS_GOLDENS=1 cargo test -p executors --test log_normalizers` and review the diff.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use executors::{
+    executors::{BaseCodingAgent, StandardCodingAgentExecutor},
+    profile::{ExecutorConfigs, ExecutorProfileId},
+};
+use serde_json::{Value, json};
+use workspace_utils::{log_msg::LogMsg, msg_store::MsgStore};
+
+const BLESS_ENV: &str = "BLESS_GOLDENS";
+/// Fake worktree the fixtures were recorded in; paths below it are made relative
+const WORKTREE: &str = "/golden/worktree";
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+const STABLE_POLLS: usize = 5;
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/log_normalizers")
+}
+
+/// Every recorded fixture with the executor it belongs to, in a stable order
+fn fixtures() -> Vec<(BaseCodingAgent, PathBuf)> {
+    let mut fixtures = Vec::new();
+    for dir in fs::read_dir(fixtures_dir()).expect("fixtures directory should exist") {
+        let dir = dir.unwrap().path();
+        if !dir.is_dir() {
+            continue;
+        }
+        let name = dir.file_name().unwrap().to_string_lossy().to_uppercase();
+        let executor = BaseCodingAgent::from_str(&name)
+            .unwrap_or_else(|_| panic!("{} is not named after an executor", dir.display()));
+        for file in fs::read_dir(&dir).unwrap() {
+            let path = file.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "jsonl") {
+                fixtures.push((executor, path));
+            }
+        }
+    }
+    fixtures.sort_by(|a, b| a.1.cmp(&b.1));
+    fixtures
+}
+
+async fn replay(executor: BaseCodingAgent, fixture: &Path) -> Value {
+    let agent = ExecutorConfigs::from_defaults()
+        .get_coding_agent(&ExecutorProfileId::new(executor))
+        .unwrap_or_else(|| panic!("no default profile for {executor}"));
+
+    let msg_store = Arc::new(MsgStore::new());
+    for line in fs::read_to_string(fixture).unwrap().lines() {
+        msg_store.push_stdout(format!("{line}\n"));
+    }
+    msg_store.push_finished();
+
+    agent.normalize_logs(msg_store.clone(), Path::new(WORKTREE));
+    wait_until_settled(&msg_store).await;
+
+    conversation(&msg_store.get_history())
+}
+
+fn patch_count(msg_store: &MsgStore) -> usize {
+    msg_store
+        .get_history()
+        .iter()
+        .filter(|msg| matches!(msg, LogMsg::JsonPatch(_)))
+        .count()
+}
+
+/// Normalizers run on spawned tasks that never report completion, so wait until they
+/// stop producing patches
+async fn wait_until_settled(msg_store: &MsgStore) {
+    let deadline = Instant::now() + SETTLE_TIMEOUT;
+    let mut last_count = usize::MAX;
+    let mut stable_polls = 0;
+    while Instant::now() < deadline {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let count = patch_count(msg_store);
+        if count == last_count {
+            stable_polls += 1;
+            if stable_polls >= STABLE_POLLS {
+                return;
+            }
+        } else {
+            last_count = count;
+            stable_polls = 0;
+        }
+    }
+}
+
+/// Apply the recorded patches to an empty conversation. Entry metadata is dropped because it
+/// carries the raw executor payloads, which the fixtures already hold.
+fn conversation(history: &[LogMsg]) -> Value {
+    let mut document = json!({ "entries": [] });
+    let mut session_id = None;
+    for msg in history {
+        match msg {
+            LogMsg::JsonPatch(patch) => json_patch::patch(&mut document, patch)
+                .unwrap_or_else(|e| panic!("patch {patch:?} does not apply: {e}")),
+            LogMsg::SessionId(id) if session_id.is_none() => session_id = Some(id.clone()),
+            _ => {}
+        }
+    }
+
+    let mut entries = document["entries"].take();
+    for entry in entries.as_array_mut().into_iter().flatten() {
+        if entry["type"] == "NORMALIZED_ENTRY"
+            && let Some(content) = entry["content"].as_object_mut()
+        {
+            content.remove("metadata");
+        }
+    }
+
+    json!({ "session_id": session_id, "entries": entries })
+}
+
+#[tokio::test]
+async fn normalized_conversations_match_goldens() {
+    let bless = std::env::var_os(BLESS_ENV).is_some();
+    let fixtures = fixtures();
+    assert!(!fixtures.is_empty(), "no log normalizer fixtures found");
+
+    let mut failures = Vec::new();
+    for (executor, fixture) in fixtures {
+        let actual = replay(executor, &fixture).await;
+        let rendered = serde_json::to_string_pretty(&actual).unwrap() + "\n";
+        let golden = fixture.with_extension("golden.json");
+
+        if bless {
+            fs::write(&golden, rendered).unwrap();
+            continue;
+        }
+
+        let Ok(expected) = fs::read_to_string(&golden) else {
+            failures.push(format!("{}: missing golden file", golden.display()));
+            continue;
+        };
+        let expected: Value = serde_json::from_str(&expected)
+            .unwrap_or_else(|e| panic!("{} is not valid JSON: {e}", golden.display()));
+        if expected != actual {
+            failures.push(format!(
+                "{}: normalized conversation differs\n--- expected\n{}\n+++ actual\n{}",
+                fixture.display(),
+                serde_json::to_string_pretty(&expected).unwrap(),
+                rendered
+            ));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{}\n\nRe-run with {BLESS_ENV}=1 to accept the new output",
+        failures.join("\n\n")
+    );
+}
+
+#[test]
+fn every_executor_has_a_fixture() {
+    let covered: HashSet<BaseCodingAgent> = fixtures().into_iter().map(|(e, _)| e).collect();
+    let mut missing: Vec<String> = ExecutorConfigs::from_defaults()
+        .executors
+        .into_keys()
+        .filter(|executor| !covered.contains(executor))
+        .map(|executor| executor.to_string())
+        .collect();
+    missing.sort();
+    assert!(
+        missing.is_empty(),
+        "executors without fixtures: {missing:?}"
+    );
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io,
     path::{Path, PathBuf},
     sync::Arc,
@@ -16,12 +16,19 @@ use db::{
         execution_process::{
             ExecutionContext, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
         },
+        execution_process_logs::ExecutionProcessLogs,
         execution_process_repo_state::ExecutionProcessRepoState,
-        project_repo::ProjectRepo,
+        execution_queue::ExecutionQueueEntry,
+        executor_fallback::ExecutorFallback,
+        project::Project,
+        project_repo::{CopyFileRule, ProjectRepo},
         repo::Repo,
         scratch::{DraftFollowUpData, Scratch, ScratchType},
         task::{Task, TaskStatus},
+        task_history::{CreateTaskHistory, TaskHistory, TaskHistoryEventType},
+        task_plan::TaskPlan,
         workspace::Workspace,
+        workspace_disk_usage::WorkspaceDiskUsage,
         workspace_repo::WorkspaceRepo,
     },
 };
@@ -33,35 +40,59 @@ use executors::{
         coding_agent_initial::CodingAgentInitialRequest,
     },
     approvals::{ExecutorApprovalService, NoopExecutorApprovalService},
+    command::{CmdOverrides, ExecutionTimeouts},
     env::ExecutionEnv,
     executors::{BaseCodingAgent, ExecutorExitResult, ExecutorExitSignal, InterruptSender},
-    logs::{NormalizedEntryType, utils::patch::extract_normalized_entry_from_patch},
-    profile::ExecutorProfileId,
+    fallback::FallbackReason,
+    logs::{
+        ActionType, NormalizedEntry, NormalizedEntryError, NormalizedEntryType,
+        utils::{
+            ConversationPatch, EntryIndexProvider, patch::extract_normalized_entry_from_patch,
+        },
+    },
+    profile::{ExecutorConfigs, ExecutorProfileId},
 };
-use futures::{FutureExt, TryStreamExt, stream::select};
+use futures::{FutureExt, Stream, StreamExt, TryStreamExt, stream::select};
 use services::services::{
-    approvals::{Approvals, executor_approvals::ExecutorApprovalBridge},
-    config::Config,
+    approvals::{ApprovalTimeoutSettings, Approvals, executor_approvals::ExecutorApprovalBridge},
+    config::{Config, DevServerConfig, SchedulerConfig, WorkspacePoolConfig},
     container::{ContainerError, ContainerRef, ContainerService},
+    dev_server::{self, DevServerRegistry, DevServerStatus},
     diff_stream::{self, DiffStreamHandle},
-    git::{Commit, GitCli, GitService},
+    execution_scheduler::ExecutionScheduler,
+    git::{Commit, GitCli, GitService, GitServiceError},
     image::ImageService,
     notification::NotificationService,
     queued_message::QueuedMessageService,
+    secrets::{SecretRedactor, SecretService},
     share::SharePublisher,
+    sparse_checkout::SparseCheckout,
+    terminal::TerminalService,
+    workspace_disk::{DiskBudget, WorkspaceDiskSummary, WorkspaceGcReport, dir_size},
     workspace_manager::{RepoWorkspaceInput, WorkspaceManager},
+    workspace_pool::{PooledRepo, PooledWorkspace, WorkspacePool, delete_stale_pool_branches},
+};
+use tokio::{
+    sync::{Notify, RwLock, broadcast},
+    task::JoinHandle,
+    time::Instant,
 };
-use tokio::{sync::RwLock, task::JoinHandle};
 use tokio_util::io::ReaderStream;
 use utils::{
+    assets::asset_dir,
     log_msg::LogMsg,
     msg_store::MsgStore,
+    shell::get_shell_command,
     text::{git_branch_id, short_uuid, truncate_to_char_boundary},
 };
 use uuid::Uuid;
 
 use crate::{command, copy};
 
+/// Setup scripts of pooled workspaces run unattended, so a hanging one is given up on
+const POOLED_SETUP_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const POOLED_SETUP_ERROR_CHARS: usize = 2000;
+
 #[derive(Clone)]
 pub struct LocalContainerService {
     db: DBService,
@@ -75,6 +106,11 @@ pub struct LocalContainerService {
     queued_message_service: QueuedMessageService,
     publisher: Result<SharePublisher, RemoteClientNotConfigured>,
     notification_service: NotificationService,
+    execution_scheduler: ExecutionScheduler,
+    dev_servers: DevServerRegistry,
+    terminals: TerminalService,
+    secrets: SecretService,
+    workspace_pool: WorkspacePool,
 }
 
 impl LocalContainerService {
@@ -106,9 +142,16 @@ impl LocalContainerService {
             queued_message_service,
             publisher,
             notification_service,
+            execution_scheduler: ExecutionScheduler::new(),
+            dev_servers: DevServerRegistry::new(),
+            terminals: TerminalService::new(),
+            secrets: SecretService::new(asset_dir().join("secrets.key")),
+            workspace_pool: WorkspacePool::new(),
         };
 
         container.spawn_workspace_cleanup().await;
+        container.spawn_workspace_pool();
+        container.spawn_workspace_gc();
 
         container
     }
@@ -174,7 +217,10 @@ impl LocalContainerService {
         let _ = Workspace::clear_container_ref(&db.pool, workspace.id).await;
     }
 
-    pub async fn cleanup_expired_workspaces(db: &DBService) -> Result<(), DeploymentError> {
+    pub async fn cleanup_expired_workspaces(
+        db: &DBService,
+        terminals: &TerminalService,
+    ) -> Result<(), DeploymentError> {
         let expired_workspaces = Workspace::find_expired_for_cleanup(&db.pool).await?;
         if expired_workspaces.is_empty() {
             tracing::debug!("No expired workspaces found");
@@ -185,6 +231,7 @@ impl LocalContainerService {
             expired_workspaces.len()
         );
         for workspace in &expired_workspaces {
+            terminals.close_workspace(workspace.id);
             Self::cleanup_workspace(db, workspace).await;
         }
         Ok(())
@@ -192,13 +239,14 @@ impl LocalContainerService {
 
     pub async fn spawn_workspace_cleanup(&self) {
         let db = self.db.clone();
+        let terminals = self.terminals.clone();
         let mut cleanup_interval = tokio::time::interval(tokio::time::Duration::from_secs(1800)); // 30 minutes
         WorkspaceManager::cleanup_orphan_workspaces(&self.db.pool).await;
         tokio::spawn(async move {
             loop {
                 cleanup_interval.tick().await;
                 tracing::info!("Starting periodic workspace cleanup...");
-                Self::cleanup_expired_workspaces(&db)
+                Self::cleanup_expired_workspaces(&db, &terminals)
                     .await
                     .unwrap_or_else(|e| {
                         tracing::error!("Failed to clean up expired workspaces: {}", e)
@@ -207,6 +255,299 @@ impl LocalContainerService {
         });
     }
 
+    /// Measure workspace disk usage periodically, and enforce the disk budgets when any are
+    /// configured
+    fn spawn_workspace_gc(&self) {
+        let container = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let interval = container
+                    .config
+                    .read()
+                    .await
+                    .workspace_disk
+                    .scan_interval_secs;
+                tokio::time::sleep(Duration::from_secs(interval.max(60).into())).await;
+
+                let config = container.config.read().await.workspace_disk.clone();
+                if config.total_budget_mb.is_none() && config.project_budget_mb.is_none() {
+                    if let Err(e) = container.measure_workspace_disk_usage().await {
+                        tracing::error!("Failed to measure workspace disk usage: {}", e);
+                    }
+                    continue;
+                }
+                match container.collect_workspace_garbage(false).await {
+                    Ok(report) if !report.evicted.is_empty() => tracing::info!(
+                        "Evicted {} workspaces over the disk budget, reclaiming {} bytes",
+                        report.evicted.len(),
+                        report.reclaimed_bytes
+                    ),
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Failed to enforce workspace disk budgets: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Whether evicting the workspace would lose work: uncommitted changes, or commits
+    /// that are on neither a remote branch nor the target branch
+    async fn has_unpushed_work(&self, usage: &WorkspaceDiskUsage) -> Result<bool, ContainerError> {
+        let workspace_dir = PathBuf::from(&usage.container_ref);
+        let repos = WorkspaceRepo::find_repos_with_target_branch_for_workspace(
+            &self.db.pool,
+            usage.workspace_id,
+        )
+        .await?;
+        let git = GitCli::new();
+        for repo in &repos {
+            let worktree_path = workspace_dir.join(&repo.repo.name);
+            if !worktree_path.exists() {
+                continue;
+            }
+            if !self.git.is_worktree_clean(&worktree_path).unwrap_or(false) {
+                return Ok(true);
+            }
+            let unpushed = git.git(
+                &worktree_path,
+                [
+                    "rev-list",
+                    "--count",
+                    "HEAD",
+                    "--not",
+                    "--remotes",
+                    repo.target_branch.as_str(),
+                ],
+            );
+            if !matches!(unpushed, Ok(count) if count.trim() == "0") {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Keep ready workspaces for the most active projects, replacing those whose target
+    /// branch has moved on. Runs after the orphan cleanup, which removes pooled workspaces
+    /// left over from before a restart.
+    fn spawn_workspace_pool(&self) {
+        let container = self.clone();
+        tokio::spawn(async move {
+            let mut first_run = true;
+            loop {
+                let config = container.config.read().await.workspace_pool.clone();
+                if let Err(e) = container.refresh_workspace_pool(&config, first_run).await {
+                    tracing::error!("Failed to refresh workspace pool: {}", e);
+                }
+                first_run = false;
+
+                let interval = Duration::from_secs(config.refresh_interval_secs.max(10).into());
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = container.workspace_pool.refill_requested() => {}
+                }
+            }
+        });
+    }
+
+    async fn refresh_workspace_pool(
+        &self,
+        config: &WorkspacePoolConfig,
+        first_run: bool,
+    ) -> Result<(), ContainerError> {
+        let projects = Project::find_most_active(&self.db.pool, config.max_projects as i32).await?;
+        let pooled_projects: HashSet<Uuid> = if config.size_per_project == 0 {
+            HashSet::new()
+        } else {
+            projects.iter().map(|project| project.id).collect()
+        };
+        for pooled in self.workspace_pool.evict_except(&pooled_projects) {
+            pooled.discard().await;
+        }
+
+        for project in &projects {
+            let repos = ProjectRepo::find_repos_for_project(&self.db.pool, project.id).await?;
+            if first_run {
+                let branches = self.workspace_pool.branches();
+                for repo in &repos {
+                    delete_stale_pool_branches(&repo.path, &branches);
+                }
+            }
+            if !pooled_projects.contains(&project.id) || repos.is_empty() {
+                continue;
+            }
+
+            let targets = match self.default_target_branches(&repos) {
+                Ok(targets) => targets,
+                Err(e) => {
+                    tracing::warn!("Skipping workspace pool of project {}: {}", project.name, e);
+                    continue;
+                }
+            };
+            for pooled in self.workspace_pool.list(project.id) {
+                if !(pooled.matches(&targets) && self.is_pooled_workspace_current(&pooled))
+                    && let Some(pooled) = self.workspace_pool.remove(pooled.id)
+                {
+                    tracing::info!(
+                        "Replacing pooled workspace {} of project {}, its target branches moved",
+                        pooled.id,
+                        project.name
+                    );
+                    pooled.discard().await;
+                }
+            }
+
+            while self.workspace_pool.count(project.id) < config.size_per_project as usize {
+                match self
+                    .create_pooled_workspace(project, &repos, &targets)
+                    .await
+                {
+                    Ok(pooled) => {
+                        tracing::info!(
+                            "Added workspace {} to the pool of project {}",
+                            pooled.id,
+                            project.name
+                        );
+                        self.workspace_pool.add(pooled);
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to create pooled workspace for project {}: {}",
+                            project.name,
+                            e
+                        );
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Pooled workspaces are based on the branch each repo has checked out
+    fn default_target_branches(
+        &self,
+        repos: &[Repo],
+    ) -> Result<Vec<(Uuid, String)>, ContainerError> {
+        repos
+            .iter()
+            .map(|repo| {
+                let branch = self
+                    .git()
+                    .get_current_branch(&repo.path)
+                    .map_err(GitServiceError::from)?;
+                Ok::<_, ContainerError>((repo.id, branch))
+            })
+            .collect()
+    }
+
+    /// Whether every target branch still points at the commit the worktree was created from
+    fn is_pooled_workspace_current(&self, pooled: &PooledWorkspace) -> bool {
+        pooled.repos.iter().all(|repo| {
+            self.git()
+                .get_branch_oid(&repo.repo_path, &repo.target_branch)
+                .is_ok_and(|oid| oid == repo.base_commit)
+        })
+    }
+
+    async fn create_pooled_workspace(
+        &self,
+        project: &Project,
+        repos: &[Repo],
+        targets: &[(Uuid, String)],
+    ) -> Result<PooledWorkspace, ContainerError> {
+        let mut pooled_repos = Vec::with_capacity(repos.len());
+        for (repo, (_, target_branch)) in repos.iter().zip(targets) {
+            pooled_repos.push(PooledRepo {
+                repo_id: repo.id,
+                repo_name: repo.name.clone(),
+                repo_path: repo.path.clone(),
+                target_branch: target_branch.clone(),
+                // Read before the worktree is created, so a concurrent update of the branch
+                // makes the workspace stale instead of mislabelled
+                base_commit: self.git().get_branch_oid(&repo.path, target_branch)?,
+            });
+        }
+        let pooled = PooledWorkspace::new(project.id, pooled_repos);
+
+        let mut sparse_checkouts = SparseCheckout::for_project(&self.db.pool, project.id).await?;
+        let inputs: Vec<RepoWorkspaceInput> = repos
+            .iter()
+            .zip(targets)
+            .map(|(repo, (_, target_branch))| {
+                RepoWorkspaceInput::new(
+                    repo.clone(),
+                    target_branch.clone(),
+                    sparse_checkouts.remove(&repo.id),
+                )
+            })
+            .collect();
+        let prepared = match WorkspaceManager::create_workspace(
+            &pooled.workspace_dir,
+            &inputs,
+            &pooled.branch,
+        )
+        .await
+        {
+            Ok(_) => self.prepare_pooled_workspace(project, &pooled, repos).await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = prepared {
+            pooled.discard().await;
+            return Err(e);
+        }
+        Ok(pooled)
+    }
+
+    /// Copy project files and run the setup scripts, as starting a task attempt would
+    async fn prepare_pooled_workspace(
+        &self,
+        project: &Project,
+        pooled: &PooledWorkspace,
+        repos: &[Repo],
+    ) -> Result<(), ContainerError> {
+        let project_repos =
+            ProjectRepo::find_by_project_id_with_names(&self.db.pool, project.id).await?;
+
+        for project_repo in &project_repos {
+            if let Some(copy_files) = &project_repo.copy_files
+                && !copy_files.is_empty()
+                && let Some(repo) = repos.iter().find(|repo| repo.id == project_repo.repo_id)
+            {
+                let worktree_path = pooled.workspace_dir.join(&repo.name);
+                self.copy_project_files(&repo.path, &worktree_path, &copy_files.0)
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::warn!(
+                            "Failed to copy project files for repo '{}': {}",
+                            repo.name,
+                            e
+                        );
+                    });
+            }
+        }
+        Self::create_workspace_config_files(&pooled.workspace_dir, repos).await?;
+
+        // The task's VK_* variables don't exist yet
+        let mut env = ExecutionEnv::new();
+        env.insert("VK_PROJECT_NAME", &project.name);
+        env.insert("VK_PROJECT_ID", project.id.to_string());
+        let secrets = self.secrets.project_env(&self.db.pool, project.id).await?;
+        env.merge(&secrets);
+        let redactor = SecretRedactor::new(secrets.into_values());
+
+        for project_repo in &project_repos {
+            if let Some(script) = &project_repo.setup_script {
+                run_pooled_setup_script(
+                    &pooled.workspace_dir.join(&project_repo.repo_name),
+                    script,
+                    &env,
+                    &redactor,
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
     /// Record the current HEAD commit for each repository as the "after" state.
     /// Errors are silently ignored since this runs after the main execution completes
     /// and failure should not block process finalization.
@@ -335,11 +676,15 @@ impl LocalContainerService {
     }
 
     /// Spawn a background task that polls the child process for completion and
-    /// cleans up the execution entry when it exits.
+    /// cleans up the execution entry when it exits. When `timeouts` are set the
+    /// process is interrupted and then killed once it stalls or runs too long, and
+    /// likewise when `approval_interrupt` is signalled for an expired approval.
     pub fn spawn_exit_monitor(
         &self,
         exec_id: &Uuid,
         exit_signal: Option<ExecutorExitSignal>,
+        timeouts: ExecutionTimeouts,
+        approval_interrupt: Arc<Notify>,
     ) -> JoinHandle<()> {
         let exec_id = *exec_id;
         let child_store = self.child_store.clone();
@@ -356,7 +701,22 @@ impl LocalContainerService {
                 .map(|rx| rx.boxed()) // wait for result
                 .unwrap_or_else(|| std::future::pending().boxed()); // no signal, stall forever
 
+            // Output activity feeds the idle timer; no timeouts configured means this never resolves
+            let output_rx = msg_stores
+                .read()
+                .await
+                .get(&exec_id)
+                .map(|store| store.get_receiver());
+            let mut timeout_future = async move {
+                tokio::select! {
+                    timeout = wait_for_execution_timeout(output_rx, timeouts) => timeout,
+                    _ = approval_interrupt.notified() => ExecutionTimeout::ApprovalExpired,
+                }
+            }
+            .boxed();
+
             let status_result: std::io::Result<std::process::ExitStatus>;
+            let mut timed_out = None;
 
             // Wait for process to exit, or exit signal from executor
             tokio::select! {
@@ -383,10 +743,33 @@ impl LocalContainerService {
                 exit_status_result = &mut process_exit_rx => {
                     status_result = exit_status_result.unwrap_or_else(|e| Err(std::io::Error::other(e)));
                 }
+                // Stalled or over its runtime budget: interrupt, then kill the group
+                timeout = &mut timeout_future => {
+                    tracing::warn!("Execution process {} timed out: {}", exec_id, timeout.message());
+                    container.record_execution_timeout(exec_id, &timeout).await;
+
+                    if let Some(interrupt_sender) = container.take_interrupt_sender(&exec_id).await {
+                        let _ = interrupt_sender.send(());
+                        if tokio::time::timeout(Duration::from_secs(5), &mut process_exit_rx).await.is_err() {
+                            tracing::debug!("Graceful interrupt timed out for process {}, force killing", exec_id);
+                        }
+                    }
+                    if let Some(child_lock) = child_store.read().await.get(&exec_id).cloned() {
+                        let mut child = child_lock.write().await;
+                        if let Err(err) = command::kill_process_group(&mut child).await {
+                            tracing::error!("Failed to kill process group after timeout: {} {}", exec_id, err);
+                        }
+                    }
+
+                    status_result = Err(std::io::Error::new(std::io::ErrorKind::TimedOut, timeout.message()));
+                    timed_out = Some(timeout);
+                }
             }
+            drop(timeout_future);
 
-            let (exit_code, status) = match status_result {
-                Ok(exit_status) => {
+            let (exit_code, status) = match (status_result, timed_out) {
+                (_, Some(timeout)) => (None, timeout.process_status()),
+                (Ok(exit_status), None) => {
                     let code = exit_status.code().unwrap_or(-1) as i64;
                     let status = if exit_status.success() {
                         ExecutionProcessStatus::Completed
@@ -395,7 +778,7 @@ impl LocalContainerService {
                     };
                     (Some(code), status)
                 }
-                Err(_) => (None, ExecutionProcessStatus::Failed),
+                (Err(_), None) => (None, ExecutionProcessStatus::Failed),
             };
 
             if !ExecutionProcess::was_stopped(&db.pool, exec_id).await
@@ -405,17 +788,66 @@ impl LocalContainerService {
                 tracing::error!("Failed to update execution process completion: {}", e);
             }
 
+            // A scheduler slot may have freed up; start whatever is waiting
+            if let Err(e) = container.dispatch_queued_executions().await {
+                tracing::error!("Failed to dispatch queued executions: {}", e);
+            }
+
             if let Ok(ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await {
+                if ctx.execution_process.run_reason == ExecutionProcessRunReason::DevServer {
+                    container.handle_dev_server_exit(&ctx, exit_code).await;
+                }
+
                 // Update executor session summary if available
                 if let Err(e) = container.update_executor_session_summary(&exec_id).await {
                     tracing::warn!("Failed to update executor session summary: {}", e);
                 }
 
+                if let Err(e) = container.capture_presented_plan(&ctx).await {
+                    tracing::warn!("Failed to capture plan from execution {}: {}", exec_id, e);
+                }
+
                 let success = matches!(
                     ctx.execution_process.status,
                     ExecutionProcessStatus::Completed
                 ) && exit_code == Some(0);
 
+                let is_coding_agent = matches!(
+                    ctx.execution_process.run_reason,
+                    ExecutionProcessRunReason::CodingAgent
+                );
+                if success
+                    && is_coding_agent
+                    && let Err(e) = ExecutorFallback::mark_succeeded(&db.pool, ctx.session.id).await
+                {
+                    tracing::warn!("Failed to mark executor fallback as succeeded: {}", e);
+                }
+
+                // Rate-limited runs are retried with the next fallback profile, which
+                // takes over the task lifecycle from this process
+                let executor = ctx
+                    .execution_process
+                    .executor_action()
+                    .ok()
+                    .and_then(|action| action.base_executor());
+                let fell_back = match executor {
+                    Some(executor)
+                        if is_coding_agent
+                            && matches!(
+                                ctx.execution_process.status,
+                                ExecutionProcessStatus::Failed
+                            ) =>
+                    {
+                        match container.detect_fallback_reason(&exec_id, executor).await {
+                            Some(reason) => {
+                                container.try_start_executor_fallback(&ctx, reason).await
+                            }
+                            None => false,
+                        }
+                    }
+                    _ => false,
+                };
+
                 let cleanup_done = matches!(
                     ctx.execution_process.run_reason,
                     ExecutionProcessRunReason::CleanupScript
@@ -460,18 +892,45 @@ impl LocalContainerService {
                     }
                 }
 
-                if container.should_finalize(&ctx) {
+                if !fell_back && container.should_finalize(&ctx) {
                     // Only execute queued messages if the execution succeeded
-                    // If it failed or was killed, just clear the queue and finalize
+                    // If it failed, was killed or timed out, just clear the queue and finalize
                     let should_execute_queued = !matches!(
                         ctx.execution_process.status,
-                        ExecutionProcessStatus::Failed | ExecutionProcessStatus::Killed
+                        ExecutionProcessStatus::Failed
+                            | ExecutionProcessStatus::Killed
+                            | ExecutionProcessStatus::TimedOut
                     );
 
-                    if let Some(queued_msg) =
-                        container.queued_message_service.take_queued(ctx.session.id)
-                    {
-                        if should_execute_queued {
+                    if !should_execute_queued {
+                        // Execution failed or was killed - discard the queue and finalize
+                        match container
+                            .queued_message_service
+                            .cancel_queued(ctx.session.id)
+                            .await
+                        {
+                            Ok(0) => {}
+                            Ok(discarded) => tracing::info!(
+                                "Discarded {} queued message(s) for session {} due to execution status {:?}",
+                                discarded,
+                                ctx.session.id,
+                                ctx.execution_process.status
+                            ),
+                            Err(e) => tracing::warn!("Failed to discard queued messages: {}", e),
+                        }
+                        container.finalize_task(publisher.as_ref().ok(), &ctx).await;
+                    } else {
+                        // One queued message is sent per completed turn
+                        let next = container
+                            .queued_message_service
+                            .take_next(ctx.session.id)
+                            .await
+                            .unwrap_or_else(|e| {
+                                tracing::error!("Failed to take queued message: {}", e);
+                                None
+                            });
+
+                        if let Some(queued_msg) = next {
                             tracing::info!(
                                 "Found queued message for session {}, starting follow-up execution",
                                 ctx.session.id
@@ -493,7 +952,7 @@ impl LocalContainerService {
 
                             // Execute the queued follow-up
                             if let Err(e) = container
-                                .start_queued_follow_up(&ctx, &queued_msg.data)
+                                .start_queued_follow_up(&ctx, &queued_msg.data())
                                 .await
                             {
                                 tracing::error!("Failed to start queued follow-up: {}", e);
@@ -501,16 +960,8 @@ impl LocalContainerService {
                                 container.finalize_task(publisher.as_ref().ok(), &ctx).await;
                             }
                         } else {
-                            // Execution failed or was killed - discard the queued message and finalize
-                            tracing::info!(
-                                "Discarding queued message for session {} due to execution status {:?}",
-                                ctx.session.id,
-                                ctx.execution_process.status
-                            );
                             container.finalize_task(publisher.as_ref().ok(), &ctx).await;
                         }
-                    } else {
-                        container.finalize_task(publisher.as_ref().ok(), &ctx).await;
                     }
                 }
             }
@@ -538,6 +989,70 @@ impl LocalContainerService {
         })
     }
 
+    /// Remove an execution that is still waiting in the queue. There is no child
+    /// process yet, so only the queue entry and the process record change.
+    async fn cancel_queued_execution(
+        &self,
+        execution_process: &ExecutionProcess,
+        status: ExecutionProcessStatus,
+    ) -> Result<(), ContainerError> {
+        ExecutionQueueEntry::delete(&self.db.pool, execution_process.id).await?;
+        ExecutionProcess::update_completion(&self.db.pool, execution_process.id, status, None)
+            .await?;
+
+        if let Ok(ctx) = ExecutionProcess::load_context(&self.db.pool, execution_process.id).await
+            && !ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+                &self.db.pool,
+                ctx.workspace.id,
+            )
+            .await
+            .unwrap_or(true)
+            && let Err(e) =
+                Task::update_status(&self.db.pool, ctx.task.id, TaskStatus::InReview).await
+        {
+            tracing::error!("Failed to update task status to InReview: {e}");
+        }
+
+        tracing::debug!(
+            "Queued execution process {} cancelled",
+            execution_process.id
+        );
+        Ok(())
+    }
+
+    /// Persist the timeout reason in the raw logs and surface it as an error entry
+    /// in the live normalized conversation.
+    async fn record_execution_timeout(&self, exec_id: Uuid, timeout: &ExecutionTimeout) {
+        let message = timeout.message();
+
+        let log_message = LogMsg::Stderr(format!("{message}\n"));
+        if let Ok(json_line) = serde_json::to_string(&log_message)
+            && let Err(e) = ExecutionProcessLogs::append_log_line(
+                &self.db.pool,
+                exec_id,
+                &format!("{json_line}\n"),
+            )
+            .await
+        {
+            tracing::warn!("Failed to persist timeout message for {}: {}", exec_id, e);
+        }
+
+        if let Some(store) = self.get_msg_store_by_id(&exec_id).await {
+            let entry_index = EntryIndexProvider::start_from(&store).next();
+            store.push_patch(ConversationPatch::add_normalized_entry(
+                entry_index,
+                NormalizedEntry {
+                    timestamp: None,
+                    entry_type: NormalizedEntryType::ErrorMessage {
+                        error_type: timeout.error_type(),
+                    },
+                    content: message,
+                    metadata: None,
+                },
+            ));
+        }
+    }
+
     pub fn spawn_os_exit_watcher(
         &self,
         exec_id: Uuid,
@@ -580,19 +1095,141 @@ impl LocalContainerService {
         format!("{}-{}", short_uuid(workspace_id), task_title_id)
     }
 
-    async fn track_child_msgs_in_store(&self, id: Uuid, child: &mut AsyncGroupChild) {
+    async fn workspace_task_and_project(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<(Task, Project), ContainerError> {
+        let task = workspace
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!(
+                "Task not found for workspace"
+            )))?;
+        let project = task
+            .parent_project(&self.db.pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!("Project not found for task")))?;
+        Ok((task, project))
+    }
+
+    /// Mark the dev server ready or unhealthy once its readiness check settles
+    async fn spawn_dev_server_readiness_check(
+        &self,
+        workspace_id: Uuid,
+        exec_id: Uuid,
+        port: u16,
+        config: DevServerConfig,
+    ) {
+        let msg_store = self.get_msg_store_by_id(&exec_id).await;
+        let dev_servers = self.dev_servers.clone();
+        tokio::spawn(async move {
+            let result = dev_server::wait_until_ready(port, &config, msg_store).await;
+            if let Err(e) = &result {
+                tracing::warn!(
+                    "Dev server of workspace {} is unhealthy: {}",
+                    workspace_id,
+                    e
+                );
+            }
+            dev_servers.report_readiness(workspace_id, exec_id, result);
+        });
+    }
+
+    /// Record how a dev server ended and, if it crashed, start it again after a backoff
+    async fn handle_dev_server_exit(&self, ctx: &ExecutionContext, exit_code: Option<i64>) {
+        let workspace_id = ctx.workspace.id;
+        let exec_id = ctx.execution_process.id;
+        if ctx.execution_process.status != ExecutionProcessStatus::Failed {
+            self.dev_servers
+                .set_status(workspace_id, exec_id, DevServerStatus::Stopped, None);
+            return;
+        }
+
+        let config = self.config.read().await.dev_server.clone();
+        let error = match exit_code {
+            Some(code) => format!("Exited with code {code}"),
+            None => "Exited unexpectedly".to_string(),
+        };
+        let Some(attempt) = self
+            .dev_servers
+            .record_crash(workspace_id, exec_id, error, &config)
+        else {
+            tracing::warn!("Dev server of workspace {} crashed", workspace_id);
+            return;
+        };
+        let executor_action = match ctx.execution_process.executor_action() {
+            Ok(action) => action.clone(),
+            Err(e) => {
+                tracing::error!(
+                    "Cannot restart dev server of workspace {}: {}",
+                    workspace_id,
+                    e
+                );
+                return;
+            }
+        };
+
+        let delay = dev_server::restart_backoff(attempt);
+        tracing::info!(
+            "Dev server of workspace {} crashed, restarting in {:?} (attempt {})",
+            workspace_id,
+            delay,
+            attempt
+        );
+        let container = self.clone();
+        let workspace = ctx.workspace.clone();
+        let session = ctx.session.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            // Stopped or started again by hand in the meantime
+            if !container
+                .dev_servers
+                .awaiting_restart(workspace_id, exec_id)
+            {
+                return;
+            }
+            if let Err(e) = container
+                .start_execution(
+                    &workspace,
+                    &session,
+                    &executor_action,
+                    &ExecutionProcessRunReason::DevServer,
+                )
+                .await
+            {
+                tracing::error!(
+                    "Failed to restart dev server of workspace {}: {}",
+                    workspace_id,
+                    e
+                );
+                container.dev_servers.set_status(
+                    workspace_id,
+                    exec_id,
+                    DevServerStatus::Crashed,
+                    Some(e.to_string()),
+                );
+            }
+        });
+    }
+
+    /// Secrets are masked before output reaches the store, so neither the raw logs nor the
+    /// logs normalized from them contain them
+    async fn track_child_msgs_in_store(
+        &self,
+        id: Uuid,
+        child: &mut AsyncGroupChild,
+        redactor: SecretRedactor,
+    ) {
         let store = Arc::new(MsgStore::new());
 
         let out = child.inner().stdout.take().expect("no stdout");
         let err = child.inner().stderr.take().expect("no stderr");
 
         // Map stdout bytes -> LogMsg::Stdout
-        let out = ReaderStream::new(out)
-            .map_ok(|chunk| LogMsg::Stdout(String::from_utf8_lossy(&chunk).into_owned()));
+        let out = redacted_output(out, redactor.clone()).map_ok(LogMsg::Stdout);
 
         // Map stderr bytes -> LogMsg::Stderr
-        let err = ReaderStream::new(err)
-            .map_ok(|chunk| LogMsg::Stderr(String::from_utf8_lossy(&chunk).into_owned()));
+        let err = redacted_output(err, redactor).map_ok(LogMsg::Stderr);
 
         // If you have a JSON Patch source, map it to LogMsg::JsonPatch too, then select all three.
 
@@ -624,6 +1261,17 @@ impl LocalContainerService {
         .map_err(|e| ContainerError::Other(anyhow!("{e}")))
     }
 
+    /// Classify a failed run from the executor error frames in its MsgStore history
+    async fn detect_fallback_reason(
+        &self,
+        exec_id: &Uuid,
+        executor: BaseCodingAgent,
+    ) -> Option<FallbackReason> {
+        let msg_stores = self.msg_stores.read().await;
+        let history = msg_stores.get(exec_id)?.get_history();
+        FallbackReason::from_output(executor, &history)
+    }
+
     /// Extract the last assistant message from the MsgStore history
     fn extract_last_assistant_message(&self, exec_id: &Uuid) -> Option<String> {
         // Get the MsgStore for this execution
@@ -674,6 +1322,64 @@ impl LocalContainerService {
         Ok(())
     }
 
+    /// Store the last plan presented by a coding agent run as the task's draft plan
+    async fn capture_presented_plan(&self, ctx: &ExecutionContext) -> Result<(), anyhow::Error> {
+        if ctx.execution_process.run_reason != ExecutionProcessRunReason::CodingAgent {
+            return Ok(());
+        }
+        let Some(content) = self.extract_presented_plan(&ctx.execution_process.id) else {
+            return Ok(());
+        };
+
+        let previous = TaskPlan::find_by_task_id(&self.db.pool, ctx.task.id).await?;
+        let plan = TaskPlan::upsert_proposed(
+            &self.db.pool,
+            ctx.task.id,
+            ctx.workspace.id,
+            &content,
+            ctx.execution_process.id,
+        )
+        .await?;
+
+        TaskHistory::create(
+            &self.db.pool,
+            &CreateTaskHistory {
+                task_id: ctx.task.id,
+                event_type: TaskHistoryEventType::PlanProposed,
+                old_value: previous.map(|p| p.content),
+                new_value: Some(plan.content),
+                metadata: Some(
+                    serde_json::json!({
+                        "revision": plan.revision,
+                        "execution_process_id": ctx.execution_process.id,
+                    })
+                    .to_string(),
+                ),
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Extract the last plan presented via a plan tool from the MsgStore history
+    fn extract_presented_plan(&self, exec_id: &Uuid) -> Option<String> {
+        let msg_stores = self.msg_stores.try_read().ok()?;
+        let msg_store = msg_stores.get(exec_id)?;
+
+        msg_store.get_history().iter().rev().find_map(|msg| {
+            let LogMsg::JsonPatch(patch) = msg else {
+                return None;
+            };
+            match extract_normalized_entry_from_patch(patch)?.1.entry_type {
+                NormalizedEntryType::ToolUse {
+                    action_type: ActionType::PlanPresentation { plan },
+                    ..
+                } if !plan.trim().is_empty() => Some(plan),
+                _ => None,
+            }
+        })
+    }
+
     /// Copy project files and images to the workspace.
     /// Skips files/images that already exist (fast no-op if all exist).
     async fn copy_files_and_images(
@@ -684,11 +1390,9 @@ impl LocalContainerService {
         let repos = WorkspaceRepo::find_repos_with_copy_files(&self.db.pool, workspace.id).await?;
 
         for repo in &repos {
-            if let Some(copy_files) = &repo.copy_files
-                && !copy_files.trim().is_empty()
-            {
+            if !repo.copy_files.is_empty() {
                 let worktree_path = workspace_dir.join(&repo.name);
-                self.copy_project_files(&repo.path, &worktree_path, copy_files)
+                self.copy_project_files(&repo.path, &worktree_path, &repo.copy_files)
                     .await
                     .unwrap_or_else(|e| {
                         tracing::warn!(
@@ -831,6 +1535,175 @@ impl LocalContainerService {
     }
 }
 
+/// Why the container gave up on a running execution process.
+#[derive(Debug, Clone, Copy)]
+enum ExecutionTimeout {
+    Idle(Duration),
+    MaxRuntime(Duration),
+    /// A tool approval request expired and its timeout action interrupts the run
+    ApprovalExpired,
+}
+
+impl ExecutionTimeout {
+    fn message(&self) -> String {
+        match self {
+            Self::Idle(limit) => format!(
+                "Execution timed out: no output for {} minute(s)",
+                limit.as_secs() / 60
+            ),
+            Self::MaxRuntime(limit) => format!(
+                "Execution timed out: exceeded maximum runtime of {} minute(s)",
+                limit.as_secs() / 60
+            ),
+            Self::ApprovalExpired => {
+                "Execution interrupted: tool approval request expired".to_string()
+            }
+        }
+    }
+
+    fn error_type(&self) -> NormalizedEntryError {
+        match self {
+            Self::Idle(_) => NormalizedEntryError::IdleTimeout,
+            Self::MaxRuntime(_) => NormalizedEntryError::RuntimeLimitExceeded,
+            Self::ApprovalExpired => NormalizedEntryError::Other,
+        }
+    }
+
+    /// Status the process ends with. An expired approval interrupts the run like any
+    /// other failure; only the idle and runtime limits count as timing out.
+    fn process_status(&self) -> ExecutionProcessStatus {
+        match self {
+            Self::Idle(_) | Self::MaxRuntime(_) => ExecutionProcessStatus::TimedOut,
+            Self::ApprovalExpired => ExecutionProcessStatus::Failed,
+        }
+    }
+}
+
+/// The `VK_*` variables every process started in the workspace gets
+fn workspace_env_vars(workspace: &Workspace, task: &Task, project: &Project) -> ExecutionEnv {
+    let mut env = ExecutionEnv::new();
+    env.insert("VK_PROJECT_NAME", &project.name);
+    env.insert("VK_PROJECT_ID", project.id.to_string());
+    env.insert("VK_TASK_ID", task.id.to_string());
+    env.insert("VK_WORKSPACE_ID", workspace.id.to_string());
+    env.insert("VK_WORKSPACE_BRANCH", &workspace.branch);
+    env
+}
+
+/// Run a setup script of a pooled workspace to completion. Only the end of its output is
+/// kept, with secrets masked, for the error when it fails.
+async fn run_pooled_setup_script(
+    dir: &Path,
+    script: &str,
+    env: &ExecutionEnv,
+    redactor: &SecretRedactor,
+) -> Result<(), ContainerError> {
+    let (shell_cmd, shell_arg) = get_shell_command();
+    let mut command = tokio::process::Command::new(shell_cmd);
+    command
+        .kill_on_drop(true)
+        .stdin(std::process::Stdio::null())
+        .arg(shell_arg)
+        .arg(script)
+        .current_dir(dir);
+    env.apply_to_command(&mut command);
+
+    let output = tokio::time::timeout(POOLED_SETUP_TIMEOUT, command.output())
+        .await
+        .map_err(|_| {
+            ContainerError::Other(anyhow!("Setup script in {} timed out", dir.display()))
+        })??;
+    if !output.status.success() {
+        // Mask before cutting, so a secret straddling the cut isn't left half visible
+        let stderr = redactor.redact(&String::from_utf8_lossy(&output.stderr));
+        let tail_start = stderr
+            .char_indices()
+            .rev()
+            .nth(POOLED_SETUP_ERROR_CHARS)
+            .map_or(0, |(index, _)| index);
+        return Err(ContainerError::Other(anyhow!(
+            "Setup script in {} failed ({}): {}",
+            dir.display(),
+            output.status,
+            stderr[tail_start..].trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Decoded output chunks with secrets masked, including across chunk boundaries
+fn redacted_output<R>(
+    reader: R,
+    redactor: SecretRedactor,
+) -> impl Stream<Item = std::io::Result<String>> + Send + 'static
+where
+    R: tokio::io::AsyncRead + Send + 'static,
+{
+    let redactor = Arc::new(std::sync::Mutex::new(redactor));
+    let tail = redactor.clone();
+    ReaderStream::new(reader)
+        .map_ok(move |chunk| {
+            redactor
+                .lock()
+                .unwrap()
+                .push(&String::from_utf8_lossy(&chunk))
+        })
+        .chain(futures::stream::once(async move {
+            Ok(tail.lock().unwrap().finish())
+        }))
+        .try_filter(|text| futures::future::ready(!text.is_empty()))
+}
+
+/// Resolve once the process has been silent for longer than the idle timeout or
+/// has run past its wall-clock limit. Never resolves when no limit is configured.
+async fn wait_for_execution_timeout(
+    mut output_rx: Option<broadcast::Receiver<LogMsg>>,
+    timeouts: ExecutionTimeouts,
+) -> ExecutionTimeout {
+    let started_at = Instant::now();
+    let runtime_deadline = timeouts.max_runtime.map(|limit| started_at + limit);
+    let mut last_output_at = started_at;
+
+    loop {
+        let idle_deadline = timeouts.idle.map(|limit| last_output_at + limit);
+        let Some(next_deadline) = idle_deadline.into_iter().chain(runtime_deadline).min() else {
+            return std::future::pending().await;
+        };
+
+        let received = tokio::select! {
+            received = async {
+                match output_rx.as_mut() {
+                    Some(rx) => Some(rx.recv().await),
+                    None => std::future::pending().await,
+                }
+            } => received,
+            _ = tokio::time::sleep_until(next_deadline) => None,
+        };
+
+        match received {
+            Some(Ok(LogMsg::Stdout(_) | LogMsg::Stderr(_)))
+            | Some(Err(broadcast::error::RecvError::Lagged(_))) => {
+                last_output_at = Instant::now();
+            }
+            Some(Ok(_)) => {}
+            Some(Err(broadcast::error::RecvError::Closed)) => output_rx = None,
+            None => {
+                let now = Instant::now();
+                if let (Some(limit), Some(deadline)) = (timeouts.max_runtime, runtime_deadline)
+                    && now >= deadline
+                {
+                    return ExecutionTimeout::MaxRuntime(limit);
+                }
+                if let (Some(limit), Some(deadline)) = (timeouts.idle, idle_deadline)
+                    && now >= deadline
+                {
+                    return ExecutionTimeout::Idle(limit);
+                }
+            }
+        }
+    }
+}
+
 fn failure_exit_status() -> std::process::ExitStatus {
     #[cfg(unix)]
     {
@@ -866,6 +1739,30 @@ impl ContainerService for LocalContainerService {
         &self.notification_service
     }
 
+    fn execution_scheduler(&self) -> &ExecutionScheduler {
+        &self.execution_scheduler
+    }
+
+    fn dev_servers(&self) -> &DevServerRegistry {
+        &self.dev_servers
+    }
+
+    fn terminals(&self) -> &TerminalService {
+        &self.terminals
+    }
+
+    fn secrets(&self) -> &SecretService {
+        &self.secrets
+    }
+
+    fn workspace_pool(&self) -> &WorkspacePool {
+        &self.workspace_pool
+    }
+
+    async fn scheduler_config(&self) -> SchedulerConfig {
+        self.config.read().await.scheduler.clone()
+    }
+
     async fn git_branch_prefix(&self) -> String {
         self.config.read().await.git_branch_prefix.clone()
     }
@@ -899,12 +1796,18 @@ impl ContainerService for LocalContainerService {
             .iter()
             .map(|wr| (wr.repo_id, wr.target_branch.clone()))
             .collect();
+        let mut sparse_checkouts =
+            SparseCheckout::for_project(&self.db.pool, task.project_id).await?;
 
         let workspace_inputs: Vec<RepoWorkspaceInput> = repositories
             .iter()
             .map(|repo| {
                 let target_branch = target_branches.get(&repo.id).cloned().unwrap_or_default();
-                RepoWorkspaceInput::new(repo.clone(), target_branch)
+                RepoWorkspaceInput::new(
+                    repo.clone(),
+                    target_branch,
+                    sparse_checkouts.remove(&repo.id),
+                )
             })
             .collect();
 
@@ -935,8 +1838,84 @@ impl ContainerService for LocalContainerService {
             .to_string())
     }
 
+    async fn claim_pooled_workspace(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<Option<ContainerRef>, ContainerError> {
+        let task = workspace
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        let targets: Vec<(Uuid, String)> =
+            WorkspaceRepo::find_by_workspace_id(&self.db.pool, workspace.id)
+                .await?
+                .into_iter()
+                .map(|wr| (wr.repo_id, wr.target_branch))
+                .collect();
+        let Some(pooled) = self.workspace_pool.claim(task.project_id, &targets) else {
+            return Ok(None);
+        };
+        self.workspace_pool.request_refill();
+
+        if !self.is_pooled_workspace_current(&pooled) {
+            tracing::info!(
+                "Pooled workspace {} is behind its target branches, creating workspace {} instead",
+                pooled.id,
+                workspace.id
+            );
+            tokio::spawn(async move { pooled.discard().await });
+            return Ok(None);
+        }
+
+        // The pooled worktrees become the workspace's by renaming their branch
+        let mut renamed: Vec<PathBuf> = Vec::new();
+        for repo in &pooled.repos {
+            let worktree_path = pooled.workspace_dir.join(&repo.repo_name);
+            if let Err(e) =
+                self.git()
+                    .rename_local_branch(&worktree_path, &pooled.branch, &workspace.branch)
+            {
+                tracing::warn!("Failed to claim pooled workspace {}: {}", pooled.id, e);
+                for path in &renamed {
+                    if let Err(e) =
+                        self.git()
+                            .rename_local_branch(path, &workspace.branch, &pooled.branch)
+                    {
+                        tracing::error!(
+                            "Failed to roll back branch rename in {}: {}",
+                            path.display(),
+                            e
+                        );
+                    }
+                }
+                tokio::spawn(async move { pooled.discard().await });
+                return Ok(None);
+            }
+            renamed.push(worktree_path);
+        }
+
+        // Project files were copied when the workspace was pooled
+        if let Err(e) = self
+            .image_service
+            .copy_images_by_task_to_worktree(&pooled.workspace_dir, workspace.task_id)
+            .await
+        {
+            tracing::warn!("Failed to copy task images to workspace: {}", e);
+        }
+
+        let container_ref = pooled.workspace_dir.to_string_lossy().to_string();
+        Workspace::update_container_ref(&self.db.pool, workspace.id, &container_ref).await?;
+        tracing::info!(
+            "Workspace {} claimed pooled workspace {}",
+            workspace.id,
+            pooled.id
+        );
+        Ok(Some(container_ref))
+    }
+
     async fn delete(&self, workspace: &Workspace) -> Result<(), ContainerError> {
         self.try_stop(workspace, true).await;
+        self.terminals.close_workspace(workspace.id);
         Self::cleanup_workspace(&self.db, workspace).await;
         Ok(())
     }
@@ -953,21 +1932,30 @@ impl ContainerService for LocalContainerService {
                 "Workspace has no repositories configured"
             )));
         }
+        WorkspaceDiskUsage::touch(&self.db.pool, workspace.id).await?;
 
+        let task = workspace
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
         let workspace_dir = if let Some(container_ref) = &workspace.container_ref {
             PathBuf::from(container_ref)
         } else {
-            let task = workspace
-                .parent_task(&self.db.pool)
-                .await?
-                .ok_or(sqlx::Error::RowNotFound)?;
+            // Evicted or expired: the worktrees are recreated from the workspace's branches
+            tracing::info!("Recreating worktrees of workspace {}", workspace.id);
             let workspace_dir_name =
                 LocalContainerService::dir_name_from_workspace(&workspace.id, &task.title);
             WorkspaceManager::get_workspace_base_dir().join(&workspace_dir_name)
         };
 
-        WorkspaceManager::ensure_workspace_exists(&workspace_dir, &repositories, &workspace.branch)
-            .await?;
+        let sparse_checkouts = SparseCheckout::for_project(&self.db.pool, task.project_id).await?;
+        WorkspaceManager::ensure_workspace_exists(
+            &workspace_dir,
+            &repositories,
+            &workspace.branch,
+            &sparse_checkouts,
+        )
+        .await?;
 
         if workspace.container_ref.is_none() {
             Workspace::update_container_ref(
@@ -1010,6 +1998,78 @@ impl ContainerService for LocalContainerService {
         Ok(true)
     }
 
+    async fn measure_workspace_disk_usage(&self) -> Result<WorkspaceDiskSummary, ContainerError> {
+        for workspace in WorkspaceDiskUsage::find_all(&self.db.pool).await? {
+            let workspace_dir = PathBuf::from(&workspace.container_ref);
+            let bytes = tokio::task::spawn_blocking(move || dir_size(&workspace_dir))
+                .await
+                .map_err(|e| ContainerError::Other(anyhow!("Disk usage task failed: {e}")))?;
+            WorkspaceDiskUsage::record(&self.db.pool, workspace.workspace_id, bytes as i64).await?;
+        }
+
+        let workspaces = WorkspaceDiskUsage::find_all(&self.db.pool).await?;
+        let config = self.config.read().await.workspace_disk.clone();
+        Ok(DiskBudget::new(&config, &workspaces).summary(workspaces))
+    }
+
+    async fn collect_workspace_garbage(
+        &self,
+        force: bool,
+    ) -> Result<WorkspaceGcReport, ContainerError> {
+        let summary = self.measure_workspace_disk_usage().await?;
+        let config = self.config.read().await.workspace_disk.clone();
+        let mut budget = DiskBudget::new(&config, &summary.workspaces);
+        let mut report = WorkspaceGcReport::default();
+
+        for usage in &summary.workspaces {
+            if !budget.should_evict(usage)
+                || usage.has_running_process
+                || self.has_running_processes(usage.task_id).await?
+            {
+                continue;
+            }
+            if !force && self.has_unpushed_work(usage).await? {
+                report.skipped_unpushed.push(usage.workspace_id);
+                continue;
+            }
+            let Some(workspace) = Workspace::find_by_id(&self.db.pool, usage.workspace_id).await?
+            else {
+                continue;
+            };
+
+            tracing::info!(
+                "Evicting workspace {} ({} bytes, last used {})",
+                workspace.id,
+                usage.bytes,
+                usage.last_used_at
+            );
+            self.terminals.close_workspace(workspace.id);
+            Self::cleanup_workspace(&self.db, &workspace).await;
+            WorkspaceDiskUsage::record(&self.db.pool, workspace.id, 0).await?;
+
+            budget.release(usage);
+            report.evicted.push(workspace.id);
+            report.reclaimed_bytes += usage.bytes;
+        }
+
+        report.over_budget = budget.is_exceeded();
+        Ok(report)
+    }
+
+    async fn workspace_env(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<(ExecutionEnv, SecretRedactor), ContainerError> {
+        let (task, project) = self.workspace_task_and_project(workspace).await?;
+        let mut env = workspace_env_vars(workspace, &task, &project);
+        let secrets = self
+            .secrets
+            .workspace_env(&self.db.pool, project.id, workspace.id)
+            .await?;
+        env.merge(&secrets);
+        Ok((env, SecretRedactor::new(secrets.into_values())))
+    }
+
     async fn start_execution_inner(
         &self,
         workspace: &Workspace,
@@ -1025,6 +2085,38 @@ impl ContainerService for LocalContainerService {
             )))?;
         let current_dir = PathBuf::from(container_ref);
 
+        let (task, project) = self.workspace_task_and_project(workspace).await?;
+        let mut env = workspace_env_vars(workspace, &task, &project);
+        let secrets = self
+            .secrets
+            .workspace_env(&self.db.pool, project.id, workspace.id)
+            .await?;
+        env.merge(&secrets);
+        let redactor = SecretRedactor::new(secrets.into_values());
+
+        // Every workspace's dev server listens on its own port
+        let dev_server = if execution_process.run_reason == ExecutionProcessRunReason::DevServer {
+            let config = self.config.read().await.dev_server.clone();
+            let server = self
+                .dev_servers
+                .allocate(workspace.id, execution_process.id, &config)
+                .map_err(|e| ContainerError::Other(anyhow!(e)))?;
+            env.insert("PORT", server.port.to_string());
+            if let Some(var) = config.port_env_var.as_deref().filter(|v| !v.is_empty()) {
+                env.insert(var, server.port.to_string());
+            }
+            Some((server.port, config))
+        } else {
+            None
+        };
+
+        let cmd_overrides = executor_action
+            .executor_profile_id()
+            .and_then(|profile_id| ExecutorConfigs::get_cached().get_coding_agent(profile_id))
+            .map(|agent| agent.cmd_overrides().clone());
+
+        // Expired approvals with the interrupt action stop the run via the exit monitor
+        let approval_interrupt = Arc::new(Notify::new());
         let approvals_service: Arc<dyn ExecutorApprovalService> =
             match executor_action.base_executor() {
                 Some(
@@ -1038,31 +2130,16 @@ impl ContainerService for LocalContainerService {
                     self.db.clone(),
                     self.notification_service.clone(),
                     execution_process.id,
+                    ApprovalTimeoutSettings::resolve(
+                        &self.config.read().await.approvals,
+                        &project,
+                        cmd_overrides.as_ref(),
+                    ),
+                    approval_interrupt.clone(),
                 ),
                 _ => Arc::new(NoopExecutorApprovalService {}),
             };
 
-        // Build ExecutionEnv with VK_* variables
-        let mut env = ExecutionEnv::new();
-
-        // Load task and project context for environment variables
-        let task = workspace
-            .parent_task(&self.db.pool)
-            .await?
-            .ok_or(ContainerError::Other(anyhow!(
-                "Task not found for workspace"
-            )))?;
-        let project = task
-            .parent_project(&self.db.pool)
-            .await?
-            .ok_or(ContainerError::Other(anyhow!("Project not found for task")))?;
-
-        env.insert("VK_PROJECT_NAME", &project.name);
-        env.insert("VK_PROJECT_ID", project.id.to_string());
-        env.insert("VK_TASK_ID", task.id.to_string());
-        env.insert("VK_WORKSPACE_ID", workspace.id.to_string());
-        env.insert("VK_WORKSPACE_BRANCH", &workspace.branch);
-
         // Create the child and stream, add to execution tracker with timeout
         let mut spawned = tokio::time::timeout(
             Duration::from_secs(30),
@@ -1075,9 +2152,14 @@ impl ContainerService for LocalContainerService {
             ))
         })??;
 
-        self.track_child_msgs_in_store(execution_process.id, &mut spawned.child)
+        self.track_child_msgs_in_store(execution_process.id, &mut spawned.child, redactor)
             .await;
 
+        if let Some((port, config)) = dev_server {
+            self.spawn_dev_server_readiness_check(workspace.id, execution_process.id, port, config)
+                .await;
+        }
+
         self.add_child_to_store(execution_process.id, spawned.child)
             .await;
 
@@ -1087,8 +2169,19 @@ impl ContainerService for LocalContainerService {
                 .await;
         }
 
-        // Spawn unified exit monitor: watches OS exit and optional executor signal
-        let _hn = self.spawn_exit_monitor(&execution_process.id, spawned.exit_signal);
+        // Idle/runtime limits come from the executor profile; scripts run unbounded
+        let timeouts = cmd_overrides
+            .as_ref()
+            .map(CmdOverrides::timeouts)
+            .unwrap_or_default();
+
+        // Spawn unified exit monitor: watches OS exit, optional executor signal and timeouts
+        let _hn = self.spawn_exit_monitor(
+            &execution_process.id,
+            spawned.exit_signal,
+            timeouts,
+            approval_interrupt,
+        );
 
         Ok(())
     }
@@ -1098,6 +2191,12 @@ impl ContainerService for LocalContainerService {
         execution_process: &ExecutionProcess,
         status: ExecutionProcessStatus,
     ) -> Result<(), ContainerError> {
+        if execution_process.status == ExecutionProcessStatus::Queued {
+            return self
+                .cancel_queued_execution(execution_process, status)
+                .await;
+        }
+
         let child = self
             .get_child_from_store(&execution_process.id)
             .await
@@ -1293,26 +2392,38 @@ impl ContainerService for LocalContainerService {
     }
 
     /// Copy files from the original project directory to the worktree.
-    /// Skips files that already exist at target with same size.
+    /// Skips files that already exist at target.
     async fn copy_project_files(
         &self,
         source_dir: &Path,
         target_dir: &Path,
-        copy_files: &str,
+        copy_files: &[CopyFileRule],
     ) -> Result<(), ContainerError> {
         let source_dir = source_dir.to_path_buf();
         let target_dir = target_dir.to_path_buf();
-        let copy_files = copy_files.to_string();
+        let copy_files = copy_files.to_vec();
 
-        tokio::time::timeout(
+        let report = tokio::time::timeout(
             std::time::Duration::from_secs(30),
-            tokio::task::spawn_blocking(move || {
-                copy::copy_project_files_impl(&source_dir, &target_dir, &copy_files)
+            tokio::task::spawn_blocking({
+                let target_dir = target_dir.clone();
+                move || copy::copy_project_files_impl(&source_dir, &target_dir, &copy_files)
             }),
         )
         .await
         .map_err(|_| ContainerError::Other(anyhow!("Copy project files timed out after 30s")))?
-        .map_err(|e| ContainerError::Other(anyhow!("Copy files task failed: {e}")))?
+        .map_err(|e| ContainerError::Other(anyhow!("Copy files task failed: {e}")))??;
+
+        tracing::info!(
+            "Project files in {}: {} copied ({} bytes), {} linked, {} dirs linked, {} bytes saved",
+            target_dir.display(),
+            report.files_copied,
+            report.bytes_copied,
+            report.files_linked,
+            report.dirs_linked,
+            report.bytes_saved
+        );
+        Ok(())
     }
 
     async fn kill_all_running_processes(&self) -> Result<(), ContainerError> {
@@ -1347,3 +2458,131 @@ fn success_exit_status() -> std::process::ExitStatus {
         ExitStatusExt::from_raw(0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(idle_ms: Option<u64>, max_runtime_ms: Option<u64>) -> ExecutionTimeouts {
+        ExecutionTimeouts {
+            idle: idle_ms.map(Duration::from_millis),
+            max_runtime: max_runtime_ms.map(Duration::from_millis),
+        }
+    }
+
+    /// Push stdout every 10ms for `for_ms`, keeping the process from going idle
+    fn spawn_output(store: Arc<MsgStore>, for_ms: u64) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            for _ in 0..for_ms / 10 {
+                store.push_stdout("working\n");
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_execution_timeout_disabled_never_resolves() {
+        let result = tokio::time::timeout(
+            Duration::from_millis(200),
+            wait_for_execution_timeout(None, ExecutionTimeouts::default()),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_execution_timeout_idle_without_output() {
+        let store = MsgStore::new();
+        let timeout =
+            wait_for_execution_timeout(Some(store.get_receiver()), limits(Some(50), None)).await;
+        assert!(
+            matches!(timeout, ExecutionTimeout::Idle(limit) if limit == Duration::from_millis(50))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_execution_timeout_output_resets_idle_timer() {
+        let store = Arc::new(MsgStore::new());
+        let rx = store.get_receiver();
+        let output = spawn_output(store.clone(), 400);
+
+        let started_at = Instant::now();
+        let timeout = wait_for_execution_timeout(Some(rx), limits(Some(150), None)).await;
+        assert!(matches!(timeout, ExecutionTimeout::Idle(_)));
+        assert!(started_at.elapsed() >= Duration::from_millis(400));
+        output.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_execution_timeout_patches_do_not_count_as_output() {
+        let store = Arc::new(MsgStore::new());
+        let rx = store.get_receiver();
+        let patches = {
+            let store = store.clone();
+            tokio::spawn(async move {
+                for _ in 0..20 {
+                    store.push_patch(json_patch::Patch(vec![]));
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+        };
+
+        let started_at = Instant::now();
+        let timeout = wait_for_execution_timeout(Some(rx), limits(Some(50), None)).await;
+        assert!(matches!(timeout, ExecutionTimeout::Idle(_)));
+        assert!(started_at.elapsed() < Duration::from_millis(200));
+        patches.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_execution_timeout_max_runtime_despite_output() {
+        let store = Arc::new(MsgStore::new());
+        let rx = store.get_receiver();
+        let output = spawn_output(store.clone(), 400);
+
+        let timeout = wait_for_execution_timeout(Some(rx), limits(Some(150), Some(100))).await;
+        assert!(
+            matches!(timeout, ExecutionTimeout::MaxRuntime(limit) if limit == Duration::from_millis(100))
+        );
+        output.abort();
+    }
+
+    #[test]
+    fn test_execution_timeout_process_status() {
+        let limit = Duration::from_secs(60);
+        assert_eq!(
+            ExecutionTimeout::Idle(limit).process_status(),
+            ExecutionProcessStatus::TimedOut
+        );
+        assert_eq!(
+            ExecutionTimeout::MaxRuntime(limit).process_status(),
+            ExecutionProcessStatus::TimedOut
+        );
+        assert_eq!(
+            ExecutionTimeout::ApprovalExpired.process_status(),
+            ExecutionProcessStatus::Failed
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pooled_setup_script_error_masks_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut env = ExecutionEnv::new();
+        env.insert("API_KEY", "sk-live-1234");
+        let redactor = SecretRedactor::new(["sk-live-1234".to_string()]);
+
+        let error = run_pooled_setup_script(
+            dir.path(),
+            "echo \"auth failed for $API_KEY\" >&2; exit 1",
+            &env,
+            &redactor,
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+
+        assert!(error.contains("auth failed for"), "{error}");
+        assert!(!error.contains("sk-live-1234"), "{error}");
+    }
+}
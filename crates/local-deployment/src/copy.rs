@@ -1,40 +1,60 @@
 use std::{
     collections::HashSet,
-    fs,
+    fs, io,
     path::{Path, PathBuf},
 };
 
 use anyhow::anyhow;
+use db::models::project_repo::{CopyFileRule, CopyMode};
 use globwalk::GlobWalkerBuilder;
-use services::services::container::ContainerError;
+use services::services::{container::ContainerError, workspace_disk::dir_size};
+
+/// What copying a repo's project files into a worktree did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct CopyReport {
+    pub files_copied: u64,
+    /// Files symlinked, hardlinked or reflinked instead of copied
+    pub files_linked: u64,
+    pub dirs_linked: u64,
+    pub bytes_copied: u64,
+    /// Size of the linked files and directories, which take no extra space in the worktree
+    pub bytes_saved: u64,
+}
 
 /// Normalize pattern for cross-platform glob matching (convert backslashes to forward slashes)
 fn normalize_pattern(pattern: &str) -> String {
     pattern.replace('\\', "/")
 }
 
-/// Copy project files from source to target directory based on glob patterns.
-/// Skips files that already exist at target with same size.
+/// Copy project files from source to target directory based on glob patterns, using each
+/// rule's mode and falling back to a plain copy where linking isn't possible.
+/// Skips files that already exist at target.
 pub(crate) fn copy_project_files_impl(
     source_dir: &Path,
     target_dir: &Path,
-    copy_files: &str,
-) -> Result<(), ContainerError> {
-    let patterns: Vec<&str> = copy_files
-        .split(',')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect();
+    rules: &[CopyFileRule],
+) -> Result<CopyReport, ContainerError> {
+    let mut report = CopyReport::default();
 
     // Track files to avoid duplicates
     let mut seen = HashSet::new();
 
-    for pattern in patterns {
-        let pattern = normalize_pattern(pattern);
+    for rule in rules {
+        let pattern = normalize_pattern(rule.pattern.trim());
+        if pattern.is_empty() {
+            continue;
+        }
         let pattern_path = source_dir.join(&pattern);
 
         if pattern_path.is_file() {
-            if let Err(e) = copy_single_file(&pattern_path, source_dir, target_dir, &mut seen) {
+            if let Err(e) = copy_single_file(
+                &pattern_path,
+                source_dir,
+                target_dir,
+                rule.mode,
+                &mut seen,
+                &mut report,
+            ) {
                 tracing::warn!(
                     "Failed to copy file {} (from {}): {}",
                     pattern,
@@ -45,6 +65,21 @@ pub(crate) fn copy_project_files_impl(
             continue;
         }
 
+        if pattern_path.is_dir() && rule.mode == CopyMode::Symlink {
+            match symlink_dir(&pattern_path, source_dir, target_dir) {
+                Ok(true) => {
+                    report.dirs_linked += 1;
+                    report.bytes_saved += dir_size(&pattern_path);
+                    continue;
+                }
+                // Already in the worktree, link its files one by one instead
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::warn!("Failed to symlink directory {pattern}, copying it: {e}");
+                }
+            }
+        }
+
         let glob_pattern = if pattern_path.is_dir() {
             // For directories, append /** to match all contents recursively
             format!("{pattern}/**")
@@ -64,21 +99,28 @@ pub(crate) fn copy_project_files_impl(
         };
 
         for entry in walker.flatten() {
-            if let Err(e) = copy_single_file(entry.path(), source_dir, target_dir, &mut seen) {
+            if let Err(e) = copy_single_file(
+                entry.path(),
+                source_dir,
+                target_dir,
+                rule.mode,
+                &mut seen,
+                &mut report,
+            ) {
                 tracing::warn!("Failed to copy file {:?}: {e}", entry.path());
             }
         }
     }
 
-    Ok(())
+    Ok(report)
 }
 
-fn copy_single_file(
+/// Path of `source_file` inside `target_root`, after checking it doesn't escape `source_root`
+fn target_path(
     source_file: &Path,
     source_root: &Path,
     target_root: &Path,
-    seen: &mut HashSet<PathBuf>,
-) -> Result<bool, ContainerError> {
+) -> Result<(PathBuf, PathBuf), ContainerError> {
     let canonical_source = source_root.canonicalize()?;
     let canonical_file = source_file.canonicalize()?;
     // Validate path is within source_dir
@@ -88,17 +130,50 @@ fn copy_single_file(
         )));
     }
 
-    if !seen.insert(canonical_file.clone()) {
-        return Ok(false);
-    }
-
     let relative_path = source_file.strip_prefix(source_root).map_err(|e| {
         ContainerError::Other(anyhow!(
             "Failed to get relative path for {source_file:?}: {e}"
         ))
     })?;
 
-    let target_file = target_root.join(relative_path);
+    Ok((canonical_file, target_root.join(relative_path)))
+}
+
+/// Link a whole directory into the worktree. Returns false if the target already exists.
+fn symlink_dir(
+    source_dir: &Path,
+    source_root: &Path,
+    target_root: &Path,
+) -> Result<bool, ContainerError> {
+    let (canonical_dir, target_dir) = target_path(source_dir, source_root, target_root)?;
+    if target_dir.symlink_metadata().is_ok() {
+        return Ok(false);
+    }
+    if let Some(parent) = target_dir.parent()
+        && !parent.exists()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&canonical_dir, &target_dir)?;
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_dir(&canonical_dir, &target_dir)?;
+    Ok(true)
+}
+
+fn copy_single_file(
+    source_file: &Path,
+    source_root: &Path,
+    target_root: &Path,
+    mode: CopyMode,
+    seen: &mut HashSet<PathBuf>,
+    report: &mut CopyReport,
+) -> Result<bool, ContainerError> {
+    let (canonical_file, target_file) = target_path(source_file, source_root, target_root)?;
+
+    if !seen.insert(canonical_file.clone()) {
+        return Ok(false);
+    }
 
     if target_file.exists() {
         return Ok(false);
@@ -109,11 +184,45 @@ fn copy_single_file(
     {
         fs::create_dir_all(parent)?;
     }
+
+    let len = fs::metadata(&canonical_file)?.len();
+    match link_file(&canonical_file, &target_file, mode) {
+        Ok(true) => {
+            report.files_linked += 1;
+            report.bytes_saved += len;
+            return Ok(true);
+        }
+        Ok(false) => {}
+        Err(e) => {
+            tracing::debug!(
+                "Could not {mode:?} {}, copying it instead: {e}",
+                source_file.display()
+            );
+            // A failed reflink can leave an empty file behind
+            let _ = fs::remove_file(&target_file);
+        }
+    }
     fs::copy(source_file, &target_file)?;
+    report.files_copied += 1;
+    report.bytes_copied += len;
 
     Ok(true)
 }
 
+/// Put `source` at `target` without copying its contents. Returns false for `CopyMode::Copy`.
+fn link_file(source: &Path, target: &Path, mode: CopyMode) -> io::Result<bool> {
+    match mode {
+        CopyMode::Copy => return Ok(false),
+        #[cfg(unix)]
+        CopyMode::Symlink => std::os::unix::fs::symlink(source, target)?,
+        #[cfg(windows)]
+        CopyMode::Symlink => std::os::windows::fs::symlink_file(source, target)?,
+        CopyMode::Hardlink => fs::hard_link(source, target)?,
+        CopyMode::Reflink => reflink_copy::reflink(source, target)?,
+    }
+    Ok(true)
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -121,6 +230,17 @@ mod tests {
     use tempfile::TempDir;
 
     use super::*;
+
+    fn rules(patterns: &str) -> Vec<CopyFileRule> {
+        patterns
+            .split(',')
+            .map(|pattern| CopyFileRule {
+                pattern: pattern.to_string(),
+                mode: CopyMode::Copy,
+            })
+            .collect()
+    }
+
     #[test]
     fn test_copy_project_files_mixed_patterns() {
         let source_dir = TempDir::new().unwrap();
@@ -141,7 +261,7 @@ mod tests {
         copy_project_files_impl(
             source_dir.path(),
             target_dir.path(),
-            ".env, *.json, src, config",
+            &rules(".env, *.json, src, config"),
         )
         .unwrap();
 
@@ -157,8 +277,11 @@ mod tests {
         let source_dir = TempDir::new().unwrap();
         let target_dir = TempDir::new().unwrap();
 
-        let result =
-            copy_project_files_impl(source_dir.path(), target_dir.path(), "nonexistent.txt");
+        let result = copy_project_files_impl(
+            source_dir.path(),
+            target_dir.path(),
+            &rules("nonexistent.txt"),
+        );
 
         assert!(result.is_ok());
         assert!(!target_dir.path().join("nonexistent.txt").exists());
@@ -169,7 +292,7 @@ mod tests {
         let source_dir = TempDir::new().unwrap();
         let target_dir = TempDir::new().unwrap();
 
-        let result = copy_project_files_impl(source_dir.path(), target_dir.path(), "");
+        let result = copy_project_files_impl(source_dir.path(), target_dir.path(), &rules(""));
 
         assert!(result.is_ok());
         assert_eq!(fs::read_dir(target_dir.path()).unwrap().count(), 0);
@@ -182,7 +305,12 @@ mod tests {
 
         fs::write(source_dir.path().join("test.txt"), "content").unwrap();
 
-        copy_project_files_impl(source_dir.path(), target_dir.path(), "  test.txt  ,  ").unwrap();
+        copy_project_files_impl(
+            source_dir.path(),
+            target_dir.path(),
+            &rules("  test.txt  ,  "),
+        )
+        .unwrap();
 
         assert!(target_dir.path().join("test.txt").exists());
     }
@@ -200,7 +328,7 @@ mod tests {
         fs::create_dir(&nested_dir).unwrap();
         fs::write(nested_dir.join("deep.txt"), "deep").unwrap();
 
-        copy_project_files_impl(source_dir.path(), target_dir.path(), "config").unwrap();
+        copy_project_files_impl(source_dir.path(), target_dir.path(), &rules("config")).unwrap();
 
         assert!(target_dir.path().join("config/app.json").exists());
         assert!(target_dir.path().join("config/nested/deep.txt").exists());
@@ -217,7 +345,11 @@ mod tests {
         fs::write(&outside_file, "secret").unwrap();
 
         // Pattern referencing parent directory should resolve to outside_file and be rejected
-        let result = copy_project_files_impl(source_dir.path(), target_dir.path(), "../secret.txt");
+        let result = copy_project_files_impl(
+            source_dir.path(),
+            target_dir.path(),
+            &rules("../secret.txt"),
+        );
 
         assert!(result.is_ok());
         assert_eq!(fs::read_dir(target_dir.path()).unwrap().count(), 0);
@@ -244,7 +376,12 @@ mod tests {
         fs::write(deep_dir.join("deep.yml"), "deep: config").unwrap();
 
         // Copy all YAML files recursively
-        copy_project_files_impl(source_dir.path(), target_dir.path(), "config/**/*.yml").unwrap();
+        copy_project_files_impl(
+            source_dir.path(),
+            target_dir.path(),
+            &rules("config/**/*.yml"),
+        )
+        .unwrap();
 
         // Verify only YAML files are copied
         assert!(target_dir.path().join("config/app.yml").exists());
@@ -278,8 +415,12 @@ mod tests {
         fs::write(src_dir.join("main.rs"), "main code").unwrap();
 
         // Copy with overlapping patterns: glob and specific file
-        copy_project_files_impl(source_dir.path(), target_dir.path(), "src/*.rs, src/lib.rs")
-            .unwrap();
+        copy_project_files_impl(
+            source_dir.path(),
+            target_dir.path(),
+            &rules("src/*.rs, src/lib.rs"),
+        )
+        .unwrap();
 
         // Verify file exists once (deduplication works)
         let target_file = target_dir.path().join("src/lib.rs");
@@ -301,7 +442,8 @@ mod tests {
         fs::write(src_dir.join("lib.rs"), "library code").unwrap();
 
         // Copy single file by exact path (exercises fast path)
-        copy_project_files_impl(source_dir.path(), target_dir.path(), "src/lib.rs").unwrap();
+        copy_project_files_impl(source_dir.path(), target_dir.path(), &rules("src/lib.rs"))
+            .unwrap();
 
         // Verify file is copied
         let target_file = target_dir.path().join("src/lib.rs");
@@ -320,8 +462,97 @@ mod tests {
         std::fs::create_dir(&loop_dir).unwrap();
         symlink(".", loop_dir.join("self")).unwrap(); // loop/self -> loop
 
-        copy_project_files_impl(src.path(), dst.path(), "loop").unwrap();
+        copy_project_files_impl(src.path(), dst.path(), &rules("loop")).unwrap();
 
         assert_eq!(std::fs::read_dir(dst.path()).unwrap().count(), 0);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_mode_links_directories_as_a_whole() {
+        let src = TempDir::new().unwrap();
+        let dst = TempDir::new().unwrap();
+
+        let cache_dir = src.path().join("node_modules/pkg");
+        fs::create_dir_all(&cache_dir).unwrap();
+        fs::write(cache_dir.join("index.js"), "module").unwrap();
+        fs::write(cache_dir.join("data.bin"), vec![0u8; 4096]).unwrap();
+
+        let report = copy_project_files_impl(
+            src.path(),
+            dst.path(),
+            &[CopyFileRule {
+                pattern: "node_modules".to_string(),
+                mode: CopyMode::Symlink,
+            }],
+        )
+        .unwrap();
+
+        let linked = dst.path().join("node_modules");
+        assert!(linked.symlink_metadata().unwrap().file_type().is_symlink());
+        assert_eq!(
+            fs::read_to_string(linked.join("pkg/index.js")).unwrap(),
+            "module"
+        );
+        assert_eq!(report.dirs_linked, 1);
+        assert_eq!(report.files_copied, 0);
+        assert_eq!(report.bytes_saved, 4096 + 6);
+    }
+
+    #[test]
+    fn test_hardlink_mode_reports_bytes_saved() {
+        let src = TempDir::new().unwrap();
+        let dst = TempDir::new().unwrap();
+
+        fs::write(src.path().join("big.bin"), vec![0u8; 4096]).unwrap();
+        fs::write(src.path().join(".env"), "KEY=value").unwrap();
+
+        let report = copy_project_files_impl(
+            src.path(),
+            dst.path(),
+            &[
+                CopyFileRule {
+                    pattern: "big.bin".to_string(),
+                    mode: CopyMode::Hardlink,
+                },
+                CopyFileRule {
+                    pattern: ".env".to_string(),
+                    mode: CopyMode::Copy,
+                },
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(fs::read(dst.path().join("big.bin")).unwrap().len(), 4096);
+        assert_eq!(report.files_linked, 1);
+        assert_eq!(report.bytes_saved, 4096);
+        assert_eq!(report.files_copied, 1);
+        assert_eq!(report.bytes_copied, 9);
+    }
+
+    #[test]
+    fn test_reflink_mode_falls_back_to_copy() {
+        let src = TempDir::new().unwrap();
+        let dst = TempDir::new().unwrap();
+
+        fs::write(src.path().join("data.txt"), "contents").unwrap();
+
+        let report = copy_project_files_impl(
+            src.path(),
+            dst.path(),
+            &[CopyFileRule {
+                pattern: "data.txt".to_string(),
+                mode: CopyMode::Reflink,
+            }],
+        )
+        .unwrap();
+
+        // Whether the temp filesystem supports reflinks or not, the file arrives once
+        assert_eq!(
+            fs::read_to_string(dst.path().join("data.txt")).unwrap(),
+            "contents"
+        );
+        assert_eq!(report.files_linked + report.files_copied, 1);
+        assert_eq!(report.bytes_saved + report.bytes_copied, 8);
+    }
 }
@@ -122,8 +122,8 @@ impl Deployment for LocalDeployment {
             });
         }
 
-        let approvals = Approvals::new(msg_stores.clone());
-        let queued_message_service = QueuedMessageService::new();
+        let approvals = Approvals::new(db.clone(), msg_stores.clone());
+        let queued_message_service = QueuedMessageService::new(db.clone());
 
         let share_config = ShareConfig::from_env();
 
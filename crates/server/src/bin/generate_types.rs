@@ -25,14 +25,27 @@ fn generate_types_content() -> String {
         db::models::project::SearchMatchType::decl(),
         db::models::repo::Repo::decl(),
         db::models::project_repo::ProjectRepo::decl(),
+        db::models::project_repo::CopyMode::decl(),
+        db::models::project_repo::CopyFileRule::decl(),
         db::models::project_repo::CreateProjectRepo::decl(),
         db::models::project_repo::UpdateProjectRepo::decl(),
+        db::models::approval_policy_rule::ApprovalPolicyRule::decl(),
+        db::models::approval_policy_rule::ApprovalPolicyDecision::decl(),
+        db::models::approval_policy_rule::ApprovalPolicyPathScope::decl(),
+        db::models::approval_policy_rule::CreateApprovalPolicyRule::decl(),
+        db::models::approval_policy_rule::UpdateApprovalPolicyRule::decl(),
+        db::models::approval_record::ApprovalRecord::decl(),
+        db::models::approval_record::ApprovalRecordStatus::decl(),
+        db::models::approval_record::ApprovalResponder::decl(),
         db::models::workspace_repo::WorkspaceRepo::decl(),
         db::models::workspace_repo::CreateWorkspaceRepo::decl(),
         db::models::workspace_repo::RepoWithTargetBranch::decl(),
         db::models::tag::Tag::decl(),
         db::models::tag::CreateTag::decl(),
         db::models::tag::UpdateTag::decl(),
+        db::models::mcp_token::McpToken::decl(),
+        db::models::mcp_token::CreateMcpToken::decl(),
+        db::models::secret::Secret::decl(),
         db::models::task::TaskStatus::decl(),
         db::models::task::TaskIntent::decl(),
         db::models::task::Task::decl(),
@@ -42,6 +55,12 @@ fn generate_types_content() -> String {
         db::models::task::UpdateTask::decl(),
         db::models::task_history::TaskHistory::decl(),
         db::models::task_history::TaskHistoryEventType::decl(),
+        db::models::task_plan::TaskPlan::decl(),
+        db::models::task_plan::TaskPlanStatus::decl(),
+        db::models::task_transcript::TaskTranscript::decl(),
+        db::models::search::SearchHitKind::decl(),
+        db::models::search::SearchHit::decl(),
+        db::models::execution_process_logs::LogStorageUsage::decl(),
         db::models::scratch::DraftFollowUpData::decl(),
         db::models::scratch::ScratchPayload::decl(),
         db::models::scratch::ScratchType::decl(),
@@ -56,6 +75,11 @@ fn generate_types_content() -> String {
         db::models::execution_process::ExecutionProcessStatus::decl(),
         db::models::execution_process::ExecutionProcessRunReason::decl(),
         db::models::execution_process_repo_state::ExecutionProcessRepoState::decl(),
+        db::models::execution_queue::ExecutionQueueEntry::decl(),
+        db::models::execution_queue::QueuedExecution::decl(),
+        db::models::executor_fallback::ExecutorFallback::decl(),
+        db::models::attempt_comparison::AttemptComparison::decl(),
+        db::models::attempt_comparison::AttemptComparisonCandidate::decl(),
         db::models::merge::Merge::decl(),
         db::models::merge::DirectMerge::decl(),
         db::models::merge::PrMerge::decl(),
@@ -100,6 +124,11 @@ fn generate_types_content() -> String {
         server::routes::repo::RegisterRepoRequest::decl(),
         server::routes::repo::InitRepoRequest::decl(),
         server::routes::tags::TagSearchParams::decl(),
+        server::routes::mcp::CreateMcpTokenResponse::decl(),
+        server::routes::terminals::CreateTerminalRequest::decl(),
+        server::routes::secrets::SecretScopeQuery::decl(),
+        server::routes::secrets::CreateSecretRequest::decl(),
+        server::routes::secrets::UpdateSecretRequest::decl(),
         server::routes::oauth::TokenResponse::decl(),
         server::routes::config::UserSystemInfo::decl(),
         server::routes::config::Environment::decl(),
@@ -111,10 +140,24 @@ fn generate_types_content() -> String {
         server::routes::config::CheckAgentAvailabilityQuery::decl(),
         server::routes::oauth::CurrentUserResponse::decl(),
         server::routes::sessions::CreateFollowUpAttempt::decl(),
+        server::routes::search::SearchQuery::decl(),
+        server::routes::maintenance::LogMaintenanceRequest::decl(),
+        services::services::log_maintenance::LogMaintenanceReport::decl(),
+        server::routes::maintenance::WorkspaceDiskQuery::decl(),
+        server::routes::maintenance::WorkspaceGcRequest::decl(),
+        db::models::workspace_disk_usage::WorkspaceDiskUsage::decl(),
+        services::services::workspace_disk::WorkspaceDiskSummary::decl(),
+        services::services::workspace_disk::WorkspaceGcReport::decl(),
+        server::routes::execution_processes::ForkPoint::decl(),
+        server::routes::execution_processes::ForkSessionRequest::decl(),
+        server::routes::execution_processes::ForkSessionResponse::decl(),
         server::routes::task_attempts::ChangeTargetBranchRequest::decl(),
         server::routes::task_attempts::ChangeTargetBranchResponse::decl(),
         server::routes::task_attempts::MergeTaskAttemptRequest::decl(),
         server::routes::task_attempts::PushTaskAttemptRequest::decl(),
+        server::routes::task_attempts::WorkspaceRepoDiff::decl(),
+        server::routes::task_attempts::WorkspaceRepoSparseCheckout::decl(),
+        server::routes::task_attempts::WidenSparseCheckoutRequest::decl(),
         server::routes::task_attempts::RenameBranchRequest::decl(),
         server::routes::task_attempts::RenameBranchResponse::decl(),
         server::routes::task_attempts::OpenEditorRequest::decl(),
@@ -122,6 +165,8 @@ fn generate_types_content() -> String {
         server::routes::shared_tasks::AssignSharedTaskRequest::decl(),
         server::routes::tasks::ShareTaskResponse::decl(),
         server::routes::tasks::CreateAndStartTaskRequest::decl(),
+        server::routes::tasks::UpdateTaskPlanRequest::decl(),
+        server::routes::tasks::ApproveTaskPlanRequest::decl(),
         server::routes::tasks::GenerateJiraTemplateRequest::decl(),
         server::routes::tasks::GenerateJiraTemplateResponse::decl(),
         server::routes::tasks::CreateJiraTicketRequest::decl(),
@@ -130,9 +175,12 @@ fn generate_types_content() -> String {
         server::routes::images::ImageResponse::decl(),
         server::routes::images::ImageMetadata::decl(),
         server::routes::task_attempts::CreateTaskAttemptBody::decl(),
+        server::routes::task_attempts::CreateTaskAttemptResponse::decl(),
         server::routes::task_attempts::WorkspaceRepoInput::decl(),
         server::routes::task_attempts::RunAgentSetupRequest::decl(),
         server::routes::task_attempts::RunAgentSetupResponse::decl(),
+        server::routes::attempt_comparisons::PickComparisonWinnerRequest::decl(),
+        server::routes::attempt_comparisons::JudgeComparisonRequest::decl(),
         server::routes::task_attempts::gh_cli_setup::GhCliSetupError::decl(),
         server::routes::task_attempts::RebaseTaskAttemptRequest::decl(),
         server::routes::task_attempts::AbortConflictsRequest::decl(),
@@ -156,6 +204,10 @@ fn generate_types_content() -> String {
         services::services::flow_manager::FlowAction::decl(),
         services::services::flow_manager::FlowActionStatus::decl(),
         services::services::flow_manager::FlowSummary::decl(),
+        services::services::attempt_comparison::AttemptComparisonView::decl(),
+        services::services::attempt_comparison::ComparisonCandidate::decl(),
+        services::services::attempt_comparison::ComparisonRepoStats::decl(),
+        services::services::attempt_comparison::ComparisonJudge::decl(),
         server::routes::task_attempts::RepoBranchStatus::decl(),
         services::services::filesystem::DirectoryEntry::decl(),
         services::services::filesystem::DirectoryListResponse::decl(),
@@ -169,13 +221,28 @@ fn generate_types_content() -> String {
         services::services::config::JiraConfig::decl(),
         services::services::config::SlackConfig::decl(),
         services::services::config::ConfluenceConfig::decl(),
+        services::services::config::SchedulerConfig::decl(),
+        services::services::config::ApprovalConfig::decl(),
+        services::services::config::LogRetentionConfig::decl(),
+        services::services::config::DevServerConfig::decl(),
+        services::services::config::DevServerReadinessCheck::decl(),
+        services::services::config::WorkspacePoolConfig::decl(),
+        services::services::config::WorkspaceDiskConfig::decl(),
+        services::services::dev_server::DevServerStatus::decl(),
+        services::services::dev_server::DevServerInfo::decl(),
         services::services::config::SoundFile::decl(),
         services::services::config::UiLanguage::decl(),
         services::services::config::ShowcaseState::decl(),
         services::services::git::GitBranch::decl(),
         services::services::share::SharedTaskDetails::decl(),
-        services::services::queued_message::QueuedMessage::decl(),
+        db::models::queued_message::QueuedMessage::decl(),
         services::services::queued_message::QueueStatus::decl(),
+        services::services::transcript::TranscriptFormat::decl(),
+        services::services::transcript::Transcript::decl(),
+        services::services::transcript::TranscriptTurn::decl(),
+        services::services::terminal::TerminalSize::decl(),
+        services::services::terminal::TerminalSession::decl(),
+        services::services::terminal::TerminalClientMessage::decl(),
         services::services::git::ConflictOp::decl(),
         executors::actions::ExecutorAction::decl(),
         executors::mcp_config::McpConfig::decl(),
@@ -190,6 +257,8 @@ fn generate_types_content() -> String {
         executors::profile::ExecutorProfileId::decl(),
         executors::profile::ExecutorConfig::decl(),
         executors::profile::ExecutorConfigs::decl(),
+        executors::fallback::FallbackReason::decl(),
+        executors::approvals::ApprovalTimeoutAction::decl(),
         executors::executors::BaseAgentCapability::decl(),
         executors::executors::claude::ClaudeCode::decl(),
         executors::executors::gemini::Gemini::decl(),
@@ -0,0 +1,31 @@
+use db::DBService;
+use services::services::{config::load_config_from_file, log_maintenance::LogMaintenanceService};
+use utils::assets::config_path;
+
+/// Compress finished execution process logs and apply the configured retention, then
+/// print the space reclaimed. Pass `--vacuum` to also shrink the database file.
+fn main() -> anyhow::Result<()> {
+    let vacuum = std::env::args().any(|arg| arg == "--vacuum");
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let config = load_config_from_file(&config_path()).await;
+            let db = DBService::new().await?;
+            let report =
+                LogMaintenanceService::run(&db.pool, &config.log_retention, vacuum).await?;
+
+            println!("Compressed processes:   {}", report.compressed_processes);
+            println!("Expired processes:      {}", report.expired_processes);
+            println!("Orphaned rows removed:  {}", report.orphaned_rows);
+            println!("Log bytes reclaimed:    {}", report.reclaimed_bytes);
+            println!(
+                "Database size:          {} -> {} bytes{}",
+                report.database_bytes_before,
+                report.database_bytes_after,
+                if report.vacuumed { " (vacuumed)" } else { "" }
+            );
+            Ok(())
+        })
+}
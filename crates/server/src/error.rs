@@ -21,7 +21,9 @@ use services::services::{
     project::ProjectServiceError,
     remote_client::RemoteClientError,
     repo::RepoError as RepoServiceError,
+    secrets::SecretError,
     share::ShareError,
+    terminal::TerminalError,
     worktree_manager::WorktreeError,
 };
 use thiserror::Error;
@@ -376,6 +378,28 @@ impl From<RepoServiceError> for ApiError {
     }
 }
 
+impl From<TerminalError> for ApiError {
+    fn from(err: TerminalError) -> Self {
+        match err {
+            TerminalError::NotFound(id) => ApiError::BadRequest(format!("Terminal {id} not found")),
+            TerminalError::Spawn(msg) => ApiError::Conflict(msg),
+            TerminalError::Io(io_err) => ApiError::Io(io_err),
+        }
+    }
+}
+
+impl From<SecretError> for ApiError {
+    fn from(err: SecretError) -> Self {
+        match err {
+            SecretError::InvalidName(_) => ApiError::BadRequest(err.to_string()),
+            SecretError::Database(db_err) => ApiError::Database(db_err),
+            SecretError::MasterKey(_) | SecretError::Encrypt | SecretError::Decrypt => {
+                ApiError::Io(std::io::Error::other(err.to_string()))
+            }
+        }
+    }
+}
+
 impl From<ProjectRepoError> for ApiError {
     fn from(err: ProjectRepoError) -> Self {
         match err {
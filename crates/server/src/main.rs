@@ -1,7 +1,7 @@
 use anyhow::{self, Error as AnyhowError};
 use deployment::{Deployment, DeploymentError};
 use server::{DeploymentImpl, routes};
-use services::services::container::ContainerService;
+use services::services::{container::ContainerService, search};
 use sqlx::Error as SqlxError;
 use strip_ansi_escapes::strip;
 use thiserror::Error;
@@ -62,7 +62,15 @@ async fn main() -> Result<(), TaskCopilotError> {
         .backfill_repo_names()
         .await
         .map_err(DeploymentError::from)?;
+    if let Err(e) = deployment.approvals().restore_pending().await {
+        tracing::warn!("Failed to restore pending approvals: {}", e);
+    }
+    // Resume executions that were still queued when the server last stopped
+    if let Err(e) = deployment.container().dispatch_queued_executions().await {
+        tracing::warn!("Failed to dispatch queued executions: {}", e);
+    }
     deployment.spawn_pr_monitor_service().await;
+    deployment.spawn_log_maintenance_service().await;
     // Pre-warm file search cache for most active projects
     let deployment_for_cache = deployment.clone();
     tokio::spawn(async move {
@@ -75,6 +83,16 @@ async fn main() -> Result<(), TaskCopilotError> {
         }
     });
 
+    // Index the log entries of runs persisted before log entries were searchable
+    let deployment_for_search = deployment.clone();
+    tokio::spawn(async move {
+        match search::backfill_log_entries(deployment_for_search.container()).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Indexed log entries of {} execution processes", count),
+            Err(e) => tracing::warn!("Failed to backfill the search index: {}", e),
+        }
+    });
+
     // Verify shared tasks in background
     let deployment_for_verification = deployment.clone();
     tokio::spawn(async move {
@@ -111,6 +129,7 @@ async fn main() -> Result<(), TaskCopilotError> {
     }
 
     tracing::info!("Server running on http://{host}:{actual_port}");
+    routes::mcp::set_backend_url(format!("http://{host}:{actual_port}"));
 
     if !cfg!(debug_assertions) {
         tracing::info!("Opening browser...");
@@ -1,13 +1,19 @@
-use std::{future::Future, str::FromStr};
+use std::{
+    future::Future,
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
 
 use db::models::{
+    approval_record::ApprovalRecord,
+    execution_process::{ExecutionProcess, ExecutionProcessStatus},
     project::Project,
     repo::Repo,
     tag::Tag,
     task::{CreateTask, Task, TaskStatus, TaskWithAttemptStatus, UpdateTask},
     workspace::{Workspace, WorkspaceContext},
 };
-use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+use executors::{executors::BaseCodingAgent, logs::NormalizedEntry, profile::ExecutorProfileId};
 use regex::Regex;
 use rmcp::{
     ErrorData, ServerHandler,
@@ -19,11 +25,17 @@ use rmcp::{
 };
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json;
+use utils::approvals::{ApprovalResponse, ApprovalStatus};
 use uuid::Uuid;
 
 use crate::routes::{
     containers::ContainerQuery,
-    task_attempts::{CreateTaskAttemptBody, WorkspaceRepoInput},
+    sessions::CreateFollowUpAttempt,
+    task_attempts::{
+        CreateTaskAttemptBody, MergeTaskAttemptRequest, WidenSparseCheckoutRequest,
+        WorkspaceRepoDiff, WorkspaceRepoInput, WorkspaceRepoSparseCheckout,
+        pr::CreateGitHubPrRequest,
+    },
 };
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -111,12 +123,18 @@ pub struct TaskSummary {
     pub title: String,
     #[schemars(description = "Current status of the task")]
     pub status: String,
+    #[schemars(
+        description = "Scheduling priority; queued runs of higher priority tasks start first"
+    )]
+    pub priority: i64,
     #[schemars(description = "When the task was created")]
     pub created_at: String,
     #[schemars(description = "When the task was last updated")]
     pub updated_at: String,
     #[schemars(description = "Whether the task has an in-progress execution attempt")]
     pub has_in_progress_attempt: Option<bool>,
+    #[schemars(description = "Whether the task has an attempt waiting in the execution queue")]
+    pub has_queued_attempt: Option<bool>,
     #[schemars(description = "Whether the last execution attempt failed")]
     pub last_attempt_failed: Option<bool>,
 }
@@ -127,9 +145,11 @@ impl TaskSummary {
             id: task.id.to_string(),
             title: task.title.to_string(),
             status: task.status.to_string(),
+            priority: task.priority,
             created_at: task.created_at.to_rfc3339(),
             updated_at: task.updated_at.to_rfc3339(),
             has_in_progress_attempt: Some(task.has_in_progress_attempt),
+            has_queued_attempt: Some(task.has_queued_attempt),
             last_attempt_failed: Some(task.last_attempt_failed),
         }
     }
@@ -145,6 +165,10 @@ pub struct TaskDetails {
     pub description: Option<String>,
     #[schemars(description = "Current status of the task")]
     pub status: String,
+    #[schemars(
+        description = "Scheduling priority; queued runs of higher priority tasks start first"
+    )]
+    pub priority: i64,
     #[schemars(description = "When the task was created")]
     pub created_at: String,
     #[schemars(description = "When the task was last updated")]
@@ -162,6 +186,7 @@ impl TaskDetails {
             title: task.title,
             description: task.description,
             status: task.status.to_string(),
+            priority: task.priority,
             created_at: task.created_at.to_rfc3339(),
             updated_at: task.updated_at.to_rfc3339(),
             has_in_progress_attempt: None,
@@ -194,6 +219,10 @@ pub struct UpdateTaskRequest {
     pub description: Option<String>,
     #[schemars(description = "New status: 'todo', 'inprogress', 'inreview', 'done', 'cancelled'")]
     pub status: Option<String>,
+    #[schemars(
+        description = "New scheduling priority; queued runs of higher priority tasks start first"
+    )]
+    pub priority: Option<i64>,
 }
 
 #[derive(Debug, Serialize, schemars::JsonSchema)]
@@ -251,12 +280,187 @@ pub struct GetTaskResponse {
     pub task: TaskDetails,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ExportSessionTranscriptRequest {
+    #[schemars(description = "The ID of the session whose conversation should be exported")]
+    pub session_id: Uuid,
+    #[schemars(description = "Output format: 'markdown' (default), 'html' or 'json'")]
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetWorkspaceDiffRequest {
+    #[schemars(description = "The ID of the workspace whose changes should be returned")]
+    pub workspace_id: Uuid,
+    #[schemars(
+        description = "Only return paths and line counts, without file contents (default: false)"
+    )]
+    pub stats_only: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetWorkspaceDiffResponse {
+    pub workspace_id: String,
+    pub files_changed: usize,
+    pub repos: Vec<WorkspaceRepoDiff>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct WidenSparseCheckoutToolRequest {
+    #[schemars(description = "The ID of the workspace whose checkout should be widened")]
+    pub workspace_id: Uuid,
+    #[schemars(description = "The ID of the repository in the workspace")]
+    pub repo_id: Uuid,
+    #[schemars(
+        description = "Directories to check out, relative to the repository root (e.g. 'libs/shared')"
+    )]
+    pub dirs: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetExecutionLogsRequest {
+    #[schemars(description = "The ID of the execution process whose logs should be returned")]
+    pub execution_process_id: Uuid,
+    #[schemars(description = "Only return the last N entries of the conversation")]
+    pub tail: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetExecutionLogsResponse {
+    pub execution_process_id: String,
+    pub total_entries: usize,
+    pub entries: Vec<NormalizedEntry>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SendFollowUpRequest {
+    #[schemars(description = "The ID of the session to continue")]
+    pub session_id: Uuid,
+    #[schemars(description = "The follow-up prompt for the coding agent")]
+    pub prompt: String,
+    #[schemars(description = "Optional executor variant, if needed")]
+    pub variant: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SendFollowUpResponse {
+    pub session_id: String,
+    pub execution_process_id: String,
+    pub status: ExecutionProcessStatus,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct StopExecutionRequest {
+    #[schemars(description = "The ID of the execution process to stop")]
+    pub execution_process_id: Uuid,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct StopExecutionResponse {
+    pub stopped_execution_process_id: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ListPendingApprovalsRequest {
+    #[schemars(description = "Only list approvals requested by this execution process")]
+    pub execution_process_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListPendingApprovalsResponse {
+    pub approvals: Vec<ApprovalRecord>,
+    pub count: usize,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RespondToApprovalRequest {
+    #[schemars(description = "The ID of the pending approval")]
+    pub approval_id: String,
+    #[schemars(description = "The ID of the execution process that requested the approval")]
+    pub execution_process_id: Uuid,
+    #[schemars(description = "Whether to approve (true) or deny (false) the tool call")]
+    pub approve: bool,
+    #[schemars(description = "Optional reason passed back to the agent when denying")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RespondToApprovalResponse {
+    pub approval_id: String,
+    pub status: ApprovalStatus,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CreatePullRequestRequest {
+    #[schemars(description = "The ID of the workspace to open the pull request from")]
+    pub workspace_id: Uuid,
+    #[schemars(description = "The ID of the repository in the workspace")]
+    pub repo_id: Uuid,
+    #[schemars(description = "The title of the pull request")]
+    pub title: String,
+    #[schemars(description = "Optional body of the pull request")]
+    pub body: Option<String>,
+    #[schemars(description = "Branch to merge into; defaults to the workspace's target branch")]
+    pub target_branch: Option<String>,
+    #[schemars(description = "Open the pull request as a draft (default: false)")]
+    pub draft: Option<bool>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct CreatePullRequestResponse {
+    pub workspace_id: String,
+    pub pr_url: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MergeWorkspaceRequest {
+    #[schemars(description = "The ID of the workspace to merge")]
+    pub workspace_id: Uuid,
+    #[schemars(description = "The ID of the repository in the workspace to merge")]
+    pub repo_id: Uuid,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct MergeWorkspaceResponse {
+    pub workspace_id: String,
+    pub repo_id: String,
+    pub merged: bool,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct TagSummary {
+    #[schemars(description = "The name used to reference the tag as @tag_name")]
+    pub tag_name: String,
+    #[schemars(description = "The text the tag expands to")]
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ListTagsResponse {
+    pub tags: Vec<TagSummary>,
+    pub count: usize,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ExpandTagsRequest {
+    #[schemars(description = "Text containing @tag_name references to expand")]
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ExpandTagsResponse {
+    pub text: String,
+}
+
+/// Workspace context served by `get_context`, kept up to date by the owner of the handle
+pub type SharedMcpContext = Arc<RwLock<Option<McpContext>>>;
+
 #[derive(Debug, Clone)]
 pub struct TaskServer {
     client: reqwest::Client,
     base_url: String,
     tool_router: ToolRouter<TaskServer>,
-    context: Option<McpContext>,
+    context: SharedMcpContext,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
@@ -288,14 +492,25 @@ impl TaskServer {
             client: reqwest::Client::new(),
             base_url: base_url.to_string(),
             tool_router: Self::tool_router(),
-            context: None,
+            context: SharedMcpContext::default(),
         }
     }
 
-    pub async fn init(mut self) -> Self {
+    pub async fn init(self) -> Self {
         let context = self.fetch_context_at_startup().await;
+        self.with_context(context)
+    }
+
+    /// Serve a context that is already known instead of discovering it from the working
+    /// directory
+    pub fn with_context(self, context: Option<McpContext>) -> Self {
+        self.with_shared_context(Arc::new(RwLock::new(context)))
+    }
 
-        if context.is_none() {
+    /// Serve a context the caller keeps up to date, e.g. resolved from the client's MCP
+    /// token on every request. `get_context` is registered if a context is available now.
+    pub fn with_shared_context(mut self, context: SharedMcpContext) -> Self {
+        if context.read().unwrap().is_none() {
             self.tool_router.map.remove("get_context");
             tracing::debug!("VK context not available, get_context tool will not be registered");
         } else {
@@ -334,8 +549,12 @@ impl TaskServer {
             return None;
         }
 
-        let ctx = api_response.data?;
+        Some(McpContext::from_workspace_context(api_response.data?))
+    }
+}
 
+impl McpContext {
+    pub fn from_workspace_context(ctx: WorkspaceContext) -> Self {
         // Map RepoWithTargetBranch to McpRepoContext
         let workspace_repos: Vec<McpRepoContext> = ctx
             .workspace_repos
@@ -347,14 +566,14 @@ impl TaskServer {
             })
             .collect();
 
-        Some(McpContext {
+        Self {
             project_id: ctx.project.id,
             task_id: ctx.task.id,
             task_title: ctx.task.title,
             workspace_id: ctx.workspace.id,
             workspace_branch: ctx.workspace.branch,
             workspace_repos,
-        })
+        }
     }
 }
 
@@ -418,6 +637,53 @@ impl TaskServer {
             .ok_or_else(|| Self::err("VK API response missing data field", None).unwrap())
     }
 
+    /// Like `send_json`, for routes whose success response carries no data
+    async fn send_ok(&self, rb: reqwest::RequestBuilder) -> Result<(), CallToolResult> {
+        let resp = rb
+            .send()
+            .await
+            .map_err(|e| Self::err("Failed to connect to VK API", Some(&e.to_string())).unwrap())?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            return Err(
+                Self::err(format!("VK API returned error status: {}", status), None).unwrap(),
+            );
+        }
+
+        let api_response = resp
+            .json::<ApiResponseEnvelope<serde_json::Value>>()
+            .await
+            .map_err(|e| {
+                Self::err("Failed to parse VK API response", Some(&e.to_string())).unwrap()
+            })?;
+
+        if !api_response.success {
+            let msg = api_response.message.as_deref().unwrap_or("Unknown error");
+            return Err(Self::err("VK API returned error", Some(msg)).unwrap());
+        }
+
+        Ok(())
+    }
+
+    async fn send_text(&self, rb: reqwest::RequestBuilder) -> Result<String, CallToolResult> {
+        let resp = rb
+            .send()
+            .await
+            .map_err(|e| Self::err("Failed to connect to VK API", Some(&e.to_string())).unwrap())?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            return Err(
+                Self::err(format!("VK API returned error status: {}", status), None).unwrap(),
+            );
+        }
+
+        resp.text()
+            .await
+            .map_err(|e| Self::err("Failed to read VK API response", Some(&e.to_string())).unwrap())
+    }
+
     fn url(&self, path: &str) -> String {
         format!(
             "{}/{}",
@@ -485,10 +751,16 @@ impl TaskServer {
         description = "Return project, task, and workspace metadata for the current workspace session context."
     )]
     async fn get_context(&self) -> Result<CallToolResult, ErrorData> {
-        // Context was fetched at startup and cached
-        // This tool is only registered if context exists, so unwrap is safe
-        let context = self.context.as_ref().expect("VK context should exist");
-        TaskServer::success(context)
+        // This tool is only registered if a context existed when the session started, but
+        // a shared context is re-resolved and its workspace may have been deleted since
+        let context = self.context.read().unwrap().clone();
+        match context {
+            Some(context) => TaskServer::success(&context),
+            None => Self::err(
+                "The workspace of this connection no longer exists".to_string(),
+                None::<String>,
+            ),
+        }
     }
 
     #[tool(
@@ -701,6 +973,7 @@ impl TaskServer {
             task_id,
             executor_profile_id,
             repos: workspace_repos,
+            additional_executor_profile_ids: None,
         };
 
         let url = self.url("/api/task-attempts");
@@ -728,6 +1001,7 @@ impl TaskServer {
             title,
             description,
             status,
+            priority,
         }): Parameters<UpdateTaskRequest>,
     ) -> Result<CallToolResult, ErrorData> {
         let status = if let Some(ref status_str) = status {
@@ -755,6 +1029,7 @@ impl TaskServer {
             description: expanded_description,
             status,
             intent: None,
+            priority,
             parent_workspace_id: None,
             image_ids: None,
         };
@@ -809,13 +1084,377 @@ impl TaskServer {
 
         TaskServer::success(&response)
     }
+
+    #[tool(
+        description = "Export the full conversation of a workspace session (messages, tool calls, diffs and approvals) as Markdown, HTML or JSON. `session_id` is required!"
+    )]
+    async fn export_session_transcript(
+        &self,
+        Parameters(ExportSessionTranscriptRequest { session_id, format }): Parameters<
+            ExportSessionTranscriptRequest,
+        >,
+    ) -> Result<CallToolResult, ErrorData> {
+        let format = format
+            .unwrap_or_else(|| "markdown".to_string())
+            .to_lowercase();
+        if !matches!(format.as_str(), "markdown" | "html" | "json") {
+            return Self::err(
+                "Invalid format. Use 'markdown', 'html' or 'json'".to_string(),
+                Some(format),
+            );
+        }
+
+        let url = self.url(&format!(
+            "/api/sessions/{}/transcript?format={}",
+            session_id, format
+        ));
+        match self.send_text(self.client.get(&url)).await {
+            Ok(transcript) => Ok(CallToolResult::success(vec![Content::text(transcript)])),
+            Err(e) => Ok(e),
+        }
+    }
+
+    #[tool(
+        description = "Get the changes a workspace has made against its target branch, per repository. `workspace_id` is required!"
+    )]
+    async fn get_workspace_diff(
+        &self,
+        Parameters(GetWorkspaceDiffRequest {
+            workspace_id,
+            stats_only,
+        }): Parameters<GetWorkspaceDiffRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url(&format!(
+            "/api/task-attempts/{}/diff?stats_only={}",
+            workspace_id,
+            stats_only.unwrap_or(false)
+        ));
+        let repos: Vec<WorkspaceRepoDiff> = match self.send_json(self.client.get(&url)).await {
+            Ok(repos) => repos,
+            Err(e) => return Ok(e),
+        };
+
+        let response = GetWorkspaceDiffResponse {
+            workspace_id: workspace_id.to_string(),
+            files_changed: repos.iter().map(|r| r.diffs.len()).sum(),
+            repos,
+        };
+
+        TaskServer::success(&response)
+    }
+
+    #[tool(
+        description = "Check out more directories in a workspace repository that only has part of the repository checked out (sparse checkout). Use this when code you need is missing from the workspace. `workspace_id`, `repo_id` and `dirs` are required!"
+    )]
+    async fn widen_sparse_checkout(
+        &self,
+        Parameters(WidenSparseCheckoutToolRequest {
+            workspace_id,
+            repo_id,
+            dirs,
+        }): Parameters<WidenSparseCheckoutToolRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if dirs.is_empty() {
+            return Self::err(
+                "At least one directory is required.".to_string(),
+                None::<String>,
+            );
+        }
+
+        let payload = WidenSparseCheckoutRequest { repo_id, dirs };
+        let url = self.url(&format!(
+            "/api/task-attempts/{}/sparse-checkout",
+            workspace_id
+        ));
+        let sparse_checkout: WorkspaceRepoSparseCheckout =
+            match self.send_json(self.client.post(&url).json(&payload)).await {
+                Ok(sparse_checkout) => sparse_checkout,
+                Err(e) => return Ok(e),
+            };
+
+        TaskServer::success(&sparse_checkout)
+    }
+
+    #[tool(
+        description = "Read the normalized conversation (messages, tool calls, errors) of an execution process. `execution_process_id` is required!"
+    )]
+    async fn get_execution_logs(
+        &self,
+        Parameters(GetExecutionLogsRequest {
+            execution_process_id,
+            tail,
+        }): Parameters<GetExecutionLogsRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url(&format!(
+            "/api/execution-processes/{}/normalized-logs",
+            execution_process_id
+        ));
+        let mut entries: Vec<NormalizedEntry> = match self.send_json(self.client.get(&url)).await {
+            Ok(entries) => entries,
+            Err(e) => return Ok(e),
+        };
+
+        let total_entries = entries.len();
+        if let Some(tail) = tail {
+            entries.drain(..total_entries.saturating_sub(tail));
+        }
+
+        let response = GetExecutionLogsResponse {
+            execution_process_id: execution_process_id.to_string(),
+            total_entries,
+            entries,
+        };
+
+        TaskServer::success(&response)
+    }
+
+    #[tool(
+        description = "Send a follow-up prompt to the coding agent of a workspace session. `session_id` and `prompt` are required!"
+    )]
+    async fn send_follow_up(
+        &self,
+        Parameters(SendFollowUpRequest {
+            session_id,
+            prompt,
+            variant,
+        }): Parameters<SendFollowUpRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if prompt.trim().is_empty() {
+            return Self::err("Prompt must not be empty.".to_string(), None::<String>);
+        }
+
+        let payload = CreateFollowUpAttempt {
+            prompt: self.expand_tags(&prompt).await,
+            variant,
+            retry_process_id: None,
+            force_when_dirty: None,
+            perform_git_reset: None,
+        };
+
+        let url = self.url(&format!("/api/sessions/{}/follow-up", session_id));
+        let process: ExecutionProcess =
+            match self.send_json(self.client.post(&url).json(&payload)).await {
+                Ok(process) => process,
+                Err(e) => return Ok(e),
+            };
+
+        let response = SendFollowUpResponse {
+            session_id: process.session_id.to_string(),
+            execution_process_id: process.id.to_string(),
+            status: process.status,
+        };
+
+        TaskServer::success(&response)
+    }
+
+    #[tool(description = "Stop a running execution process. `execution_process_id` is required!")]
+    async fn stop_execution(
+        &self,
+        Parameters(StopExecutionRequest {
+            execution_process_id,
+        }): Parameters<StopExecutionRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url(&format!(
+            "/api/execution-processes/{}/stop",
+            execution_process_id
+        ));
+        if let Err(e) = self.send_ok(self.client.post(&url)).await {
+            return Ok(e);
+        }
+
+        let response = StopExecutionResponse {
+            stopped_execution_process_id: execution_process_id.to_string(),
+        };
+
+        TaskServer::success(&response)
+    }
+
+    #[tool(
+        description = "List tool approvals that coding agents are waiting on, optionally for a single execution process."
+    )]
+    async fn list_pending_approvals(
+        &self,
+        Parameters(ListPendingApprovalsRequest {
+            execution_process_id,
+        }): Parameters<ListPendingApprovalsRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url("/api/approvals/pending");
+        let mut rb = self.client.get(&url);
+        if let Some(execution_process_id) = execution_process_id {
+            rb = rb.query(&[("execution_process_id", execution_process_id)]);
+        }
+        let approvals: Vec<ApprovalRecord> = match self.send_json(rb).await {
+            Ok(approvals) => approvals,
+            Err(e) => return Ok(e),
+        };
+
+        let response = ListPendingApprovalsResponse {
+            count: approvals.len(),
+            approvals,
+        };
+
+        TaskServer::success(&response)
+    }
+
+    #[tool(
+        description = "Approve or deny a pending tool approval. Use `list_pending_approvals` to find the `approval_id` and `execution_process_id`."
+    )]
+    async fn respond_to_approval(
+        &self,
+        Parameters(RespondToApprovalRequest {
+            approval_id,
+            execution_process_id,
+            approve,
+            reason,
+        }): Parameters<RespondToApprovalRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let payload = ApprovalResponse {
+            execution_process_id,
+            status: if approve {
+                ApprovalStatus::Approved
+            } else {
+                ApprovalStatus::Denied { reason }
+            },
+        };
+
+        // This route answers with the bare status rather than the usual envelope
+        let url = self.url(&format!("/api/approvals/{}/respond", approval_id));
+        let body = match self.send_text(self.client.post(&url).json(&payload)).await {
+            Ok(body) => body,
+            Err(e) => return Ok(e),
+        };
+        let status: ApprovalStatus = match serde_json::from_str(&body) {
+            Ok(status) => status,
+            Err(e) => return Self::err("Failed to parse VK API response", Some(&e.to_string())),
+        };
+
+        let response = RespondToApprovalResponse {
+            approval_id,
+            status,
+        };
+
+        TaskServer::success(&response)
+    }
+
+    #[tool(
+        description = "Push a workspace branch and open a GitHub pull request for one of its repositories. `workspace_id`, `repo_id` and `title` are required!"
+    )]
+    async fn create_pull_request(
+        &self,
+        Parameters(CreatePullRequestRequest {
+            workspace_id,
+            repo_id,
+            title,
+            body,
+            target_branch,
+            draft,
+        }): Parameters<CreatePullRequestRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let body = match body {
+            Some(body) => Some(self.expand_tags(&body).await),
+            None => None,
+        };
+
+        let payload = CreateGitHubPrRequest {
+            title,
+            body,
+            target_branch,
+            draft,
+            repo_id,
+            auto_generate_description: false,
+        };
+
+        let url = self.url(&format!("/api/task-attempts/{}/pr", workspace_id));
+        let pr_url: String = match self.send_json(self.client.post(&url).json(&payload)).await {
+            Ok(pr_url) => pr_url,
+            Err(e) => return Ok(e),
+        };
+
+        let response = CreatePullRequestResponse {
+            workspace_id: workspace_id.to_string(),
+            pr_url,
+        };
+
+        TaskServer::success(&response)
+    }
+
+    #[tool(
+        description = "Merge a workspace's changes for one repository into its target branch. `workspace_id` and `repo_id` are required!"
+    )]
+    async fn merge_workspace(
+        &self,
+        Parameters(MergeWorkspaceRequest {
+            workspace_id,
+            repo_id,
+        }): Parameters<MergeWorkspaceRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url(&format!("/api/task-attempts/{}/merge", workspace_id));
+        if let Err(e) = self
+            .send_ok(
+                self.client
+                    .post(&url)
+                    .json(&MergeTaskAttemptRequest { repo_id }),
+            )
+            .await
+        {
+            return Ok(e);
+        }
+
+        let response = MergeWorkspaceResponse {
+            workspace_id: workspace_id.to_string(),
+            repo_id: repo_id.to_string(),
+            merged: true,
+        };
+
+        TaskServer::success(&response)
+    }
+
+    #[tool(
+        description = "List the tags that can be referenced as @tag_name in task descriptions and prompts"
+    )]
+    async fn list_tags(&self) -> Result<CallToolResult, ErrorData> {
+        let url = self.url("/api/tags");
+        let tags: Vec<Tag> = match self.send_json(self.client.get(&url)).await {
+            Ok(tags) => tags,
+            Err(e) => return Ok(e),
+        };
+
+        let tag_summaries: Vec<TagSummary> = tags
+            .into_iter()
+            .map(|t| TagSummary {
+                tag_name: t.tag_name,
+                content: t.content,
+            })
+            .collect();
+
+        let response = ListTagsResponse {
+            count: tag_summaries.len(),
+            tags: tag_summaries,
+        };
+
+        TaskServer::success(&response)
+    }
+
+    #[tool(
+        description = "Replace the @tag_name references in a text with the content of those tags. Unknown tags are left as-is."
+    )]
+    async fn expand_tags_in_text(
+        &self,
+        Parameters(ExpandTagsRequest { text }): Parameters<ExpandTagsRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let response = ExpandTagsResponse {
+            text: self.expand_tags(&text).await,
+        };
+
+        TaskServer::success(&response)
+    }
 }
 
 #[tool_handler]
 impl ServerHandler for TaskServer {
     fn get_info(&self) -> ServerInfo {
-        let mut instruction = "A task and project management server. If you need to create or update tickets or tasks then use these tools. Most of them absolutely require that you pass the `project_id` of the project that you are currently working on. You can get project ids by using `list projects`. Call `list_tasks` to fetch the `task_ids` of all the tasks in a project`.. TOOLS: 'list_projects', 'list_tasks', 'create_task', 'start_workspace_session', 'get_task', 'update_task', 'delete_task', 'list_repos'. Make sure to pass `project_id` or `task_id` where required. You can use list tools to get the available ids.".to_string();
-        if self.context.is_some() {
+        let mut instruction = "A task and project management server. If you need to create or update tickets or tasks then use these tools. Most of them absolutely require that you pass the `project_id` of the project that you are currently working on. You can get project ids by using `list projects`. Call `list_tasks` to fetch the `task_ids` of all the tasks in a project`.. TOOLS: 'list_projects', 'list_tasks', 'create_task', 'start_workspace_session', 'get_task', 'update_task', 'delete_task', 'list_repos', 'export_session_transcript', 'get_workspace_diff', 'widen_sparse_checkout', 'get_execution_logs', 'send_follow_up', 'stop_execution', 'list_pending_approvals', 'respond_to_approval', 'create_pull_request', 'merge_workspace', 'list_tags', 'expand_tags_in_text'. Make sure to pass `project_id` or `task_id` where required. You can use list tools to get the available ids.".to_string();
+        if self.context.read().unwrap().is_some() {
             let context_instruction = "Use 'get_context' to fetch project/task/workspace metadata for the active Vibe Kanban workspace session when available.";
             instruction = format!("{} {}", context_instruction, instruction);
         }
@@ -831,3 +1470,443 @@ impl ServerHandler for TaskServer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeSet, HashMap},
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{
+        Json, Router,
+        extract::State,
+        http::{Method, StatusCode, Uri},
+    };
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    /// A request received by the mock VK API
+    #[derive(Debug, Clone)]
+    struct Recorded {
+        route: String,
+        query: Option<String>,
+        body: Option<Value>,
+    }
+
+    #[derive(Clone)]
+    struct MockApi {
+        responses: Arc<HashMap<String, (StatusCode, Value)>>,
+        requests: Arc<Mutex<Vec<Recorded>>>,
+    }
+
+    impl MockApi {
+        fn request(&self, route: &str) -> Recorded {
+            self.requests
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|request| request.route == route)
+                .cloned()
+                .unwrap_or_else(|| panic!("no request to {route}"))
+        }
+    }
+
+    async fn respond(
+        State(api): State<MockApi>,
+        method: Method,
+        uri: Uri,
+        body: String,
+    ) -> (StatusCode, Json<Value>) {
+        let route = format!("{method} {}", uri.path());
+        api.requests.lock().unwrap().push(Recorded {
+            route: route.clone(),
+            query: uri.query().map(str::to_string),
+            body: serde_json::from_str(&body).ok(),
+        });
+        match api.responses.get(&route) {
+            Some((status, body)) => (*status, Json(body.clone())),
+            None => (StatusCode::NOT_FOUND, Json(Value::Null)),
+        }
+    }
+
+    /// Serve `responses`, keyed by "METHOD /path", and point a task server at them
+    async fn server_with(responses: Vec<(String, StatusCode, Value)>) -> (TaskServer, MockApi) {
+        let api = MockApi {
+            responses: Arc::new(
+                responses
+                    .into_iter()
+                    .map(|(route, status, body)| (route, (status, body)))
+                    .collect(),
+            ),
+            requests: Arc::default(),
+        };
+        let app = Router::new().fallback(respond).with_state(api.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (TaskServer::new(&format!("http://{addr}")), api)
+    }
+
+    fn ok(data: Value) -> Value {
+        json!({ "success": true, "data": data, "message": null })
+    }
+
+    /// Whether the tool call failed, and the JSON it returned
+    fn tool_output(result: Result<CallToolResult, ErrorData>) -> (bool, Value) {
+        let result = serde_json::to_value(result.unwrap()).unwrap();
+        let text = result["content"][0]["text"].as_str().unwrap();
+        (
+            result["isError"] == json!(true),
+            serde_json::from_str(text).unwrap(),
+        )
+    }
+
+    fn required_fields<T: schemars::JsonSchema>() -> BTreeSet<String> {
+        let schema = serde_json::to_value(schemars::schema_for!(T)).unwrap();
+        serde_json::from_value(schema["required"].clone()).unwrap_or_default()
+    }
+
+    fn set(fields: &[&str]) -> BTreeSet<String> {
+        fields.iter().map(|field| field.to_string()).collect()
+    }
+
+    #[test]
+    fn tool_parameters_require_ids_and_leave_options_optional() {
+        assert_eq!(
+            required_fields::<GetWorkspaceDiffRequest>(),
+            set(&["workspace_id"])
+        );
+        assert_eq!(
+            required_fields::<GetExecutionLogsRequest>(),
+            set(&["execution_process_id"])
+        );
+        assert_eq!(
+            required_fields::<SendFollowUpRequest>(),
+            set(&["session_id", "prompt"])
+        );
+        assert_eq!(
+            required_fields::<RespondToApprovalRequest>(),
+            set(&["approval_id", "execution_process_id", "approve"])
+        );
+        assert_eq!(
+            required_fields::<CreatePullRequestRequest>(),
+            set(&["workspace_id", "repo_id", "title"])
+        );
+        assert_eq!(
+            required_fields::<MergeWorkspaceRequest>(),
+            set(&["workspace_id", "repo_id"])
+        );
+        assert!(required_fields::<ListPendingApprovalsRequest>().is_empty());
+
+        let request: RespondToApprovalRequest = serde_json::from_value(json!({
+            "approval_id": "call-1",
+            "execution_process_id": Uuid::nil(),
+            "approve": false
+        }))
+        .unwrap();
+        assert!(request.reason.is_none());
+        assert!(serde_json::from_value::<SendFollowUpRequest>(json!({ "prompt": "hi" })).is_err());
+        assert!(
+            serde_json::from_value::<MergeWorkspaceRequest>(json!({
+                "workspace_id": "not-a-uuid",
+                "repo_id": Uuid::nil()
+            }))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn tools_are_registered_and_get_context_needs_a_context() {
+        let server = TaskServer::new("http://localhost").with_context(None);
+        for tool in [
+            "get_workspace_diff",
+            "widen_sparse_checkout",
+            "get_execution_logs",
+            "send_follow_up",
+            "stop_execution",
+            "list_pending_approvals",
+            "respond_to_approval",
+            "create_pull_request",
+            "merge_workspace",
+            "list_tags",
+            "expand_tags_in_text",
+        ] {
+            assert!(server.tool_router.map.contains_key(tool), "{tool}");
+        }
+        assert!(!server.tool_router.map.contains_key("get_context"));
+    }
+
+    #[tokio::test]
+    async fn get_context_serves_the_current_value_of_a_shared_context() {
+        let context = |task_title: &str| McpContext {
+            project_id: Uuid::nil(),
+            task_id: Uuid::nil(),
+            task_title: task_title.to_string(),
+            workspace_id: Uuid::nil(),
+            workspace_branch: "vk/task".to_string(),
+            workspace_repos: Vec::new(),
+        };
+        let shared = SharedMcpContext::new(RwLock::new(Some(context("task"))));
+        let server = TaskServer::new("http://localhost").with_shared_context(shared.clone());
+        assert!(server.tool_router.map.contains_key("get_context"));
+
+        *shared.write().unwrap() = Some(context("renamed"));
+        let (failed, output) = tool_output(server.get_context().await);
+        assert!(!failed);
+        assert_eq!(output["task_title"], "renamed");
+
+        *shared.write().unwrap() = None;
+        let (failed, _) = tool_output(server.get_context().await);
+        assert!(failed);
+    }
+
+    #[tokio::test]
+    async fn get_execution_logs_returns_the_tail_of_the_conversation() {
+        let process_id = Uuid::new_v4();
+        let entries = (0..3)
+            .map(|i| {
+                json!({
+                    "timestamp": null,
+                    "entry_type": { "type": "assistant_message" },
+                    "content": format!("entry {i}"),
+                    "metadata": null
+                })
+            })
+            .collect::<Vec<_>>();
+        let (server, _) = server_with(vec![(
+            format!("GET /api/execution-processes/{process_id}/normalized-logs"),
+            StatusCode::OK,
+            ok(json!(entries)),
+        )])
+        .await;
+
+        let (failed, output) = tool_output(
+            server
+                .get_execution_logs(Parameters(GetExecutionLogsRequest {
+                    execution_process_id: process_id,
+                    tail: Some(2),
+                }))
+                .await,
+        );
+
+        assert!(!failed);
+        assert_eq!(output["total_entries"], 3);
+        let contents = output["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["content"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(contents, ["entry 1", "entry 2"]);
+    }
+
+    #[tokio::test]
+    async fn send_follow_up_expands_tags_and_reports_the_new_process() {
+        let session_id = Uuid::new_v4();
+        let process_id = Uuid::new_v4();
+        let now = "2026-01-01T00:00:00Z";
+        let (server, api) = server_with(vec![
+            (
+                "GET /api/tags".to_string(),
+                StatusCode::OK,
+                ok(json!([{
+                    "id": Uuid::new_v4(),
+                    "tag_name": "style",
+                    "content": "Use tabs.",
+                    "created_at": now,
+                    "updated_at": now
+                }])),
+            ),
+            (
+                format!("POST /api/sessions/{session_id}/follow-up"),
+                StatusCode::OK,
+                ok(json!({
+                    "id": process_id,
+                    "session_id": session_id,
+                    "run_reason": "codingagent",
+                    "executor_action": {},
+                    "status": "running",
+                    "exit_code": null,
+                    "dropped": false,
+                    "started_at": now,
+                    "completed_at": null,
+                    "created_at": now,
+                    "updated_at": now
+                })),
+            ),
+        ])
+        .await;
+
+        let (failed, output) = tool_output(
+            server
+                .send_follow_up(Parameters(SendFollowUpRequest {
+                    session_id,
+                    prompt: "Fix the lint. @style".to_string(),
+                    variant: Some("PLAN".to_string()),
+                }))
+                .await,
+        );
+
+        assert!(!failed);
+        assert_eq!(output["execution_process_id"], process_id.to_string());
+        assert_eq!(output["status"], "running");
+        let body = api
+            .request(&format!("POST /api/sessions/{session_id}/follow-up"))
+            .body
+            .unwrap();
+        assert_eq!(body["prompt"], "Fix the lint. Use tabs.");
+        assert_eq!(body["variant"], "PLAN");
+    }
+
+    #[tokio::test]
+    async fn empty_prompts_and_directory_lists_are_rejected_before_calling_the_api() {
+        let (server, api) = server_with(Vec::new()).await;
+
+        let (failed, _) = tool_output(
+            server
+                .send_follow_up(Parameters(SendFollowUpRequest {
+                    session_id: Uuid::new_v4(),
+                    prompt: "  ".to_string(),
+                    variant: None,
+                }))
+                .await,
+        );
+        assert!(failed);
+        let (failed, _) = tool_output(
+            server
+                .widen_sparse_checkout(Parameters(WidenSparseCheckoutToolRequest {
+                    workspace_id: Uuid::new_v4(),
+                    repo_id: Uuid::new_v4(),
+                    dirs: Vec::new(),
+                }))
+                .await,
+        );
+        assert!(failed);
+        assert!(api.requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn respond_to_approval_sends_the_denial_and_reads_the_bare_status() {
+        let process_id = Uuid::new_v4();
+        let denied = json!({ "status": "denied", "reason": "not now" });
+        let (server, api) = server_with(vec![(
+            "POST /api/approvals/call-1/respond".to_string(),
+            StatusCode::OK,
+            denied.clone(),
+        )])
+        .await;
+
+        let (failed, output) = tool_output(
+            server
+                .respond_to_approval(Parameters(RespondToApprovalRequest {
+                    approval_id: "call-1".to_string(),
+                    execution_process_id: process_id,
+                    approve: false,
+                    reason: Some("not now".to_string()),
+                }))
+                .await,
+        );
+
+        assert!(!failed);
+        assert_eq!(output["approval_id"], "call-1");
+        assert_eq!(output["status"], denied);
+        assert_eq!(
+            api.request("POST /api/approvals/call-1/respond").body,
+            Some(json!({ "execution_process_id": process_id, "status": denied }))
+        );
+    }
+
+    #[tokio::test]
+    async fn list_pending_approvals_and_diff_pass_their_filters_as_query_parameters() {
+        let process_id = Uuid::new_v4();
+        let workspace_id = Uuid::new_v4();
+        let (server, api) = server_with(vec![
+            (
+                "GET /api/approvals/pending".to_string(),
+                StatusCode::OK,
+                ok(json!([])),
+            ),
+            (
+                format!("GET /api/task-attempts/{workspace_id}/diff"),
+                StatusCode::OK,
+                ok(json!([{ "repo_id": Uuid::new_v4(), "repo_name": "app", "diffs": [] }])),
+            ),
+        ])
+        .await;
+
+        let (failed, output) = tool_output(
+            server
+                .list_pending_approvals(Parameters(ListPendingApprovalsRequest {
+                    execution_process_id: Some(process_id),
+                }))
+                .await,
+        );
+        assert!(!failed);
+        assert_eq!(output["count"], 0);
+        assert_eq!(
+            api.request("GET /api/approvals/pending").query,
+            Some(format!("execution_process_id={process_id}"))
+        );
+
+        let (failed, output) = tool_output(
+            server
+                .get_workspace_diff(Parameters(GetWorkspaceDiffRequest {
+                    workspace_id,
+                    stats_only: None,
+                }))
+                .await,
+        );
+        assert!(!failed);
+        assert_eq!(output["files_changed"], 0);
+        assert_eq!(output["repos"][0]["repo_name"], "app");
+        assert_eq!(
+            api.request(&format!("GET /api/task-attempts/{workspace_id}/diff"))
+                .query,
+            Some("stats_only=false".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn merge_and_stop_surface_api_failures() {
+        let workspace_id = Uuid::new_v4();
+        let repo_id = Uuid::new_v4();
+        let (server, api) = server_with(vec![(
+            format!("POST /api/task-attempts/{workspace_id}/merge"),
+            StatusCode::OK,
+            json!({ "success": false, "data": null, "message": "Merge conflicts" }),
+        )])
+        .await;
+
+        let (failed, output) = tool_output(
+            server
+                .merge_workspace(Parameters(MergeWorkspaceRequest {
+                    workspace_id,
+                    repo_id,
+                }))
+                .await,
+        );
+        assert!(failed);
+        assert_eq!(output["details"], "Merge conflicts");
+        assert_eq!(
+            api.request(&format!("POST /api/task-attempts/{workspace_id}/merge"))
+                .body,
+            Some(json!({ "repo_id": repo_id }))
+        );
+
+        // No route for the stop request, so the mock API answers 404
+        let (failed, output) = tool_output(
+            server
+                .stop_execution(Parameters(StopExecutionRequest {
+                    execution_process_id: Uuid::new_v4(),
+                }))
+                .await,
+        );
+        assert!(failed);
+        assert_eq!(
+            output["error"],
+            "VK API returned error status: 404 Not Found"
+        );
+    }
+}
@@ -1,3 +1,5 @@
 pub mod model_loaders;
+pub mod origin;
 
 pub use model_loaders::*;
+pub use origin::*;
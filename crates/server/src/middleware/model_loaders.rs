@@ -5,8 +5,8 @@ use axum::{
     response::Response,
 };
 use db::models::{
-    execution_process::ExecutionProcess, project::Project, session::Session, tag::Tag, task::Task,
-    workspace::Workspace,
+    attempt_comparison::AttemptComparison, execution_process::ExecutionProcess, project::Project,
+    session::Session, tag::Tag, task::Task, workspace::Workspace,
 };
 use deployment::Deployment;
 use uuid::Uuid;
@@ -169,3 +169,30 @@ pub async fn load_session_middleware(
     request.extensions_mut().insert(session);
     Ok(next.run(request).await)
 }
+
+pub async fn load_attempt_comparison_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(comparison_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let comparison = match AttemptComparison::find_by_id(&deployment.db().pool, comparison_id).await
+    {
+        Ok(Some(comparison)) => comparison,
+        Ok(None) => {
+            tracing::warn!("AttemptComparison {} not found", comparison_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch attempt comparison {}: {}",
+                comparison_id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(comparison);
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,117 @@
+use axum::{
+    extract::Request,
+    http::{HeaderMap, StatusCode, header},
+    middleware::Next,
+    response::Response,
+};
+
+/// Only let requests from pages served by this app through. Browsers say where a
+/// request came from in `Sec-Fetch-Site`, or failing that in `Origin`; requests from
+/// other sites, and scripts that send neither, are refused.
+pub async fn require_same_origin(request: Request, next: Next) -> Result<Response, StatusCode> {
+    if !is_same_origin(request.headers()) {
+        tracing::warn!(
+            "Rejected request to {} from outside the app",
+            request.uri().path()
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(next.run(request).await)
+}
+
+fn is_same_origin(headers: &HeaderMap) -> bool {
+    if let Some(site) = headers.get("sec-fetch-site") {
+        return site == "same-origin";
+    }
+    let (Some(origin), Some(host)) = (headers.get(header::ORIGIN), headers.get(header::HOST))
+    else {
+        return false;
+    };
+    let (Ok(origin), Ok(host)) = (origin.to_str(), host.to_str()) else {
+        return false;
+    };
+    origin
+        .split_once("://")
+        .is_some_and(|(_, authority)| authority == host)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, http::HeaderValue, middleware::from_fn, routing::post};
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn only_requests_from_the_app_itself_are_same_origin() {
+        assert!(is_same_origin(&headers(&[(
+            "sec-fetch-site",
+            "same-origin"
+        )])));
+        assert!(is_same_origin(&headers(&[
+            ("origin", "http://localhost:3000"),
+            ("host", "localhost:3000"),
+        ])));
+
+        assert!(!is_same_origin(&HeaderMap::new()));
+        assert!(!is_same_origin(&headers(&[("host", "localhost:3000")])));
+        for site in ["cross-site", "same-site", "none"] {
+            assert!(
+                !is_same_origin(&headers(&[("sec-fetch-site", site)])),
+                "{site}"
+            );
+        }
+        // A matching Origin doesn't override what the browser reports
+        assert!(!is_same_origin(&headers(&[
+            ("sec-fetch-site", "cross-site"),
+            ("origin", "http://localhost:3000"),
+            ("host", "localhost:3000"),
+        ])));
+        assert!(!is_same_origin(&headers(&[
+            ("origin", "http://evil.example"),
+            ("host", "localhost:3000"),
+        ])));
+        assert!(!is_same_origin(&headers(&[
+            ("origin", "http://localhost:3001"),
+            ("host", "localhost:3000"),
+        ])));
+    }
+
+    #[tokio::test]
+    async fn unauthenticated_token_mints_are_rejected() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp/tokens", listener.local_addr().unwrap());
+        let app = Router::new()
+            .route("/mcp/tokens", post(|| async { "minted" }))
+            .layer(from_fn(require_same_origin));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = reqwest::Client::new();
+
+        let response = client.post(&url).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = client
+            .post(&url)
+            .header("origin", "http://evil.example")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = client
+            .post(&url)
+            .header("sec-fetch-site", "same-origin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "minted");
+    }
+}
@@ -1,13 +1,37 @@
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    routing::post,
+    response::Json as ResponseJson,
+    routing::{get, post},
 };
+use db::models::approval_record::ApprovalRecord;
 use deployment::Deployment;
-use utils::approvals::{ApprovalResponse, ApprovalStatus};
+use serde::Deserialize;
+use utils::{
+    approvals::{ApprovalResponse, ApprovalStatus},
+    response::ApiResponse,
+};
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize)]
+pub struct PendingApprovalsQuery {
+    pub execution_process_id: Option<Uuid>,
+}
 
-use crate::DeploymentImpl;
+/// Approvals still waiting for a response, including ones restored after a restart
+pub async fn get_pending_approvals(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<PendingApprovalsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<ApprovalRecord>>>, ApiError> {
+    let mut approvals = ApprovalRecord::find_pending(&deployment.db().pool).await?;
+    if let Some(execution_process_id) = query.execution_process_id {
+        approvals.retain(|approval| approval.execution_process_id == execution_process_id);
+    }
+    Ok(ResponseJson(ApiResponse::success(approvals)))
+}
 
 pub async fn respond_to_approval(
     State(deployment): State<DeploymentImpl>,
@@ -30,5 +54,7 @@ pub async fn respond_to_approval(
 }
 
 pub fn router() -> Router<DeploymentImpl> {
-    Router::new().route("/approvals/{id}/respond", post(respond_to_approval))
+    Router::new()
+        .route("/approvals/pending", get(get_pending_approvals))
+        .route("/approvals/{id}/respond", post(respond_to_approval))
 }
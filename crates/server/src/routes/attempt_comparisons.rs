@@ -0,0 +1,224 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Query, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use db::models::{
+    attempt_comparison::{AttemptComparison, AttemptComparisonCandidate},
+    coding_agent_turn::{CodingAgentTurn, CreateCodingAgentTurn},
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    session::{CreateSession, Session},
+    task::Task,
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use executors::{
+    actions::{ExecutorAction, ExecutorActionType, review_agent::ReviewAgentRequest},
+    profile::ExecutorProfileId,
+};
+use serde::Deserialize;
+use services::services::{
+    attempt_comparison::{AttemptComparisonView, build_comparison_view, build_judge_prompt},
+    container::ContainerService,
+};
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_attempt_comparison_middleware};
+
+#[derive(Debug, Deserialize)]
+pub struct AttemptComparisonQuery {
+    pub task_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct PickComparisonWinnerRequest {
+    pub workspace_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct JudgeComparisonRequest {
+    pub executor_profile_id: ExecutorProfileId,
+}
+
+pub async fn get_attempt_comparisons(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<AttemptComparisonQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<AttemptComparison>>>, ApiError> {
+    let comparisons =
+        AttemptComparison::find_by_task_id(&deployment.db().pool, query.task_id).await?;
+    Ok(ResponseJson(ApiResponse::success(comparisons)))
+}
+
+pub async fn get_attempt_comparison(
+    Extension(comparison): Extension<AttemptComparison>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<AttemptComparisonView>>, ApiError> {
+    let view = build_comparison_view(&deployment.db().pool, deployment.git(), comparison).await?;
+    Ok(ResponseJson(ApiResponse::success(view)))
+}
+
+/// Keep the winning workspace and stop and remove the worktrees of all others
+pub async fn pick_comparison_winner(
+    Extension(comparison): Extension<AttemptComparison>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<PickComparisonWinnerRequest>,
+) -> Result<ResponseJson<ApiResponse<AttemptComparisonView>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    if comparison.winner_workspace_id.is_some() {
+        return Err(ApiError::BadRequest(
+            "A winner has already been picked for this comparison".to_string(),
+        ));
+    }
+
+    let candidates = AttemptComparisonCandidate::find_by_comparison_id(pool, comparison.id).await?;
+    if !candidates
+        .iter()
+        .any(|c| c.workspace_id == payload.workspace_id)
+    {
+        return Err(ApiError::BadRequest(
+            "Workspace is not part of this comparison".to_string(),
+        ));
+    }
+
+    AttemptComparison::set_winner(pool, comparison.id, payload.workspace_id).await?;
+
+    for candidate in candidates
+        .iter()
+        .filter(|c| c.workspace_id != payload.workspace_id)
+    {
+        let Some(workspace) = Workspace::find_by_id(pool, candidate.workspace_id).await? else {
+            continue;
+        };
+        if let Err(e) = deployment.container().delete(&workspace).await {
+            tracing::error!(
+                "Failed to clean up losing workspace {} of comparison {}: {}",
+                workspace.id,
+                comparison.id,
+                e
+            );
+        }
+    }
+
+    tracing::info!(
+        "Picked workspace {} as winner of comparison {}",
+        payload.workspace_id,
+        comparison.id
+    );
+
+    let comparison = AttemptComparison::find_by_id(pool, comparison.id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let view = build_comparison_view(pool, deployment.git(), comparison).await?;
+    Ok(ResponseJson(ApiResponse::success(view)))
+}
+
+/// Start a judge agent that ranks the candidates. It runs in its own session in
+/// the first candidate's workspace and reads the other worktrees directly.
+pub async fn judge_attempt_comparison(
+    Extension(comparison): Extension<AttemptComparison>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<JudgeComparisonRequest>,
+) -> Result<ResponseJson<ApiResponse<AttemptComparisonView>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    if comparison.winner_workspace_id.is_some() {
+        return Err(ApiError::BadRequest(
+            "Cannot judge a comparison after a winner has been picked".to_string(),
+        ));
+    }
+    if let Some(judge_id) = comparison.judge_execution_process_id
+        && let Some(judge) = ExecutionProcess::find_by_id(pool, judge_id).await?
+        && matches!(
+            judge.status,
+            ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
+        )
+    {
+        return Err(ApiError::BadRequest(
+            "A judge is already running for this comparison".to_string(),
+        ));
+    }
+
+    let task = Task::find_by_id(pool, comparison.task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let view = build_comparison_view(pool, deployment.git(), comparison.clone()).await?;
+    let Some(host) = view.candidates.first().map(|c| c.workspace.clone()) else {
+        return Err(ApiError::BadRequest(
+            "Comparison has no candidates".to_string(),
+        ));
+    };
+    deployment
+        .container()
+        .ensure_container_exists(&host)
+        .await?;
+
+    let session = Session::create(
+        pool,
+        &CreateSession {
+            executor: Some(payload.executor_profile_id.to_string()),
+        },
+        Uuid::new_v4(),
+        host.id,
+    )
+    .await?;
+
+    let prompt = build_judge_prompt(&task, &view.candidates);
+    let action = ExecutorAction::new(
+        ExecutorActionType::ReviewAgentRequest(ReviewAgentRequest {
+            prompt: prompt.clone(),
+            executor_profile_id: payload.executor_profile_id,
+            working_dir: None,
+        }),
+        None,
+    );
+    let judge = deployment
+        .container()
+        .start_execution(
+            &host,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::ReviewAgent,
+        )
+        .await?;
+
+    // A turn record makes the judge's final message available as its summary
+    CodingAgentTurn::create(
+        pool,
+        &CreateCodingAgentTurn {
+            execution_process_id: judge.id,
+            prompt: Some(prompt),
+        },
+        Uuid::new_v4(),
+    )
+    .await?;
+    AttemptComparison::set_judge_execution_process_id(pool, comparison.id, judge.id).await?;
+
+    let comparison = AttemptComparison::find_by_id(pool, comparison.id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let view = build_comparison_view(pool, deployment.git(), comparison).await?;
+    Ok(ResponseJson(ApiResponse::success(view)))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let comparison_router = Router::new()
+        .route("/", get(get_attempt_comparison))
+        .route("/winner", post(pick_comparison_winner))
+        .route("/judge", post(judge_attempt_comparison))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_attempt_comparison_middleware,
+        ));
+
+    let inner = Router::new()
+        .route("/", get(get_attempt_comparisons))
+        .nest("/{comparison_id}", comparison_router);
+
+    Router::new().nest("/attempt-comparisons", inner)
+}
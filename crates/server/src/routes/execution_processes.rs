@@ -1,6 +1,6 @@
 use anyhow;
 use axum::{
-    Extension, Router,
+    Extension, Json, Router,
     extract::{
         Path, Query, State,
         ws::{WebSocket, WebSocketUpgrade},
@@ -10,17 +10,43 @@ use axum::{
     routing::{get, post},
 };
 use db::models::{
-    execution_process::{ExecutionProcess, ExecutionProcessError, ExecutionProcessStatus},
+    execution_process::{
+        ExecutionProcess, ExecutionProcessError, ExecutionProcessRunReason, ExecutionProcessStatus,
+    },
     execution_process_repo_state::ExecutionProcessRepoState,
+    execution_queue::{ExecutionQueueEntry, QueuedExecution},
+    project::Project,
+    project_repo::ProjectRepo,
+    session::{CreateSession, Session},
+    task::Task,
+    task_history::{CreateTaskHistory, TaskHistory, TaskHistoryEventType},
+    workspace::{CreateWorkspace, Workspace},
+    workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
 };
 use deployment::Deployment;
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
+        coding_agent_initial::CodingAgentInitialRequest,
+    },
+    executors::BaseAgentCapability,
+    logs::NormalizedEntry,
+    profile::{ExecutorConfigs, ExecutorProfileId},
+};
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
-use serde::Deserialize;
-use services::services::container::ContainerService;
+use serde::{Deserialize, Serialize};
+use services::services::{container::ContainerService, transcript};
+use sqlx::{Error as SqlxError, SqlitePool};
+use ts_rs::TS;
 use utils::{log_msg::LogMsg, response::ApiResponse};
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_execution_process_middleware};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_execution_process_middleware,
+    routes::task_attempts::{discard_workspace, util::reset_fork_worktrees_to_process},
+};
 
 #[derive(Debug, Deserialize)]
 pub struct ExecutionProcessQuery {
@@ -30,6 +56,33 @@ pub struct ExecutionProcessQuery {
     pub show_soft_deleted: Option<bool>,
 }
 
+/// Which repository state of the source process a fork starts from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ForkPoint {
+    /// The state the process started from, as if it never ran
+    Before,
+    /// The state the process left behind
+    #[default]
+    After,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ForkSessionRequest {
+    pub prompt: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub point: Option<ForkPoint>,
+    pub variant: Option<String>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ForkSessionResponse {
+    pub workspace: Workspace,
+    pub session: Session,
+    pub execution_process: ExecutionProcess,
+}
+
 pub async fn get_execution_process_by_id(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(_deployment): State<DeploymentImpl>,
@@ -143,6 +196,16 @@ pub async fn stream_normalized_logs_ws(
     }))
 }
 
+/// Normalized conversation of an execution process as a single snapshot, for clients that
+/// can't follow the WebSocket stream
+pub async fn get_normalized_logs(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<NormalizedEntry>>>, ApiError> {
+    let entries = transcript::normalized_entries(deployment.container(), &execution_process).await;
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
 async fn handle_normalized_logs_ws(
     socket: WebSocket,
     stream: impl futures_util::Stream<Item = anyhow::Result<LogMsg>> + Unpin + Send + 'static,
@@ -243,12 +306,286 @@ pub async fn get_execution_process_repo_states(
     Ok(ResponseJson(ApiResponse::success(repo_states)))
 }
 
+/// List executions waiting for a scheduler slot, in dispatch order
+pub async fn get_execution_queue(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<QueuedExecution>>>, ApiError> {
+    let queue = ExecutionQueueEntry::find_all_with_context(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(queue)))
+}
+
+/// The source of a fork, validated before anything is created
+struct ForkPlan {
+    source_workspace: Workspace,
+    task: Task,
+    project: Project,
+    executor_profile_id: ExecutorProfileId,
+    /// The agent session to resume; None starts the conversation over
+    agent_session_id: Option<String>,
+}
+
+impl ForkPlan {
+    fn action_type(&self, prompt: String, working_dir: Option<String>) -> ExecutorActionType {
+        let executor_profile_id = self.executor_profile_id.clone();
+        match &self.agent_session_id {
+            Some(agent_session_id) => {
+                ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                    prompt,
+                    session_id: agent_session_id.clone(),
+                    executor_profile_id,
+                    working_dir,
+                })
+            }
+            None => ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt,
+                executor_profile_id,
+                working_dir,
+            }),
+        }
+    }
+}
+
+async fn plan_fork(
+    pool: &SqlitePool,
+    execution_process: &ExecutionProcess,
+    point: ForkPoint,
+    variant: Option<String>,
+) -> Result<ForkPlan, ApiError> {
+    if execution_process.run_reason != ExecutionProcessRunReason::CodingAgent {
+        return Err(ApiError::BadRequest(
+            "Only coding agent turns can be forked".to_string(),
+        ));
+    }
+    if execution_process.dropped {
+        return Err(ApiError::BadRequest(
+            "Cannot fork from a dropped turn".to_string(),
+        ));
+    }
+    if point == ForkPoint::After
+        && matches!(
+            execution_process.status,
+            ExecutionProcessStatus::Running | ExecutionProcessStatus::Queued
+        )
+    {
+        return Err(ApiError::Conflict(
+            "Wait for the turn to finish before forking after it".to_string(),
+        ));
+    }
+
+    let (source_workspace, source_session) = execution_process
+        .parent_workspace_and_session(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let task = source_workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let project = task
+        .parent_project(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    let source_profile_id =
+        ExecutionProcess::latest_executor_profile_for_session(pool, source_session.id).await?;
+    let executor_profile_id = ExecutorProfileId {
+        executor: source_profile_id.executor,
+        variant: variant.or(source_profile_id.variant),
+    };
+
+    let agent_session_id = ExecutionProcess::find_coding_agent_turn_session_id_until(
+        pool,
+        source_session.id,
+        execution_process.id,
+        point == ForkPoint::After,
+    )
+    .await?;
+
+    // Resuming an agent session that can't be forked would continue the source conversation
+    if agent_session_id.is_some()
+        && !ExecutorConfigs::get_cached()
+            .get_coding_agent_or_default(&executor_profile_id)
+            .capabilities()
+            .contains(&BaseAgentCapability::SessionFork)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "{} does not support forking sessions",
+            executor_profile_id.executor
+        )));
+    }
+
+    Ok(ForkPlan {
+        source_workspace,
+        task,
+        project,
+        executor_profile_id,
+        agent_session_id,
+    })
+}
+
+/// Fork the conversation at a coding agent turn into a new workspace and branch. The
+/// new worktrees start from the repo state before or after the turn and the agent
+/// resumes its session from that point; the source workspace is left untouched.
+pub async fn fork_execution_process(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ForkSessionRequest>,
+) -> Result<ResponseJson<ApiResponse<ForkSessionResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let point = payload.point.unwrap_or_default();
+    let plan = plan_fork(pool, &execution_process, point, payload.variant.clone()).await?;
+
+    let workspace_id = Uuid::new_v4();
+    let git_branch_name = deployment
+        .container()
+        .git_branch_from_workspace(&workspace_id, &plan.task.title)
+        .await;
+
+    let workspace = Workspace::create(
+        pool,
+        &CreateWorkspace {
+            branch: git_branch_name,
+            agent_working_dir: plan.source_workspace.agent_working_dir.clone(),
+        },
+        workspace_id,
+        plan.task.id,
+    )
+    .await?;
+
+    let (workspace, session, new_execution_process) = match start_fork(
+        &deployment,
+        &plan,
+        &workspace,
+        &execution_process,
+        point,
+        payload.prompt.clone(),
+    )
+    .await
+    {
+        Ok(started) => started,
+        Err(e) => {
+            discard_workspace(&deployment, workspace.id).await;
+            return Err(e);
+        }
+    };
+
+    if let Err(e) = TaskHistory::create(
+        pool,
+        &CreateTaskHistory {
+            task_id: plan.task.id,
+            event_type: TaskHistoryEventType::ChangeRequested,
+            old_value: None,
+            new_value: Some(payload.prompt),
+            metadata: Some(
+                serde_json::json!({
+                    "forked_from_process_id": execution_process.id,
+                    "forked_from_workspace_id": plan.source_workspace.id,
+                    "workspace_id": workspace.id,
+                })
+                .to_string(),
+            ),
+        },
+    )
+    .await
+    {
+        tracing::error!("Failed to create fork history: {:?}", e);
+    }
+
+    tracing::info!(
+        "Forked execution process {} of workspace {} into workspace {}",
+        execution_process.id,
+        plan.source_workspace.id,
+        workspace.id
+    );
+
+    Ok(ResponseJson(ApiResponse::success(ForkSessionResponse {
+        workspace,
+        session,
+        execution_process: new_execution_process,
+    })))
+}
+
+/// Set up the fork's worktrees at the fork point and start the agent in a new session
+async fn start_fork(
+    deployment: &DeploymentImpl,
+    plan: &ForkPlan,
+    workspace: &Workspace,
+    execution_process: &ExecutionProcess,
+    point: ForkPoint,
+    prompt: String,
+) -> Result<(Workspace, Session, ExecutionProcess), ApiError> {
+    let pool = &deployment.db().pool;
+
+    let workspace_repos: Vec<CreateWorkspaceRepo> =
+        WorkspaceRepo::find_by_workspace_id(pool, plan.source_workspace.id)
+            .await?
+            .into_iter()
+            .map(|r| CreateWorkspaceRepo {
+                repo_id: r.repo_id,
+                target_branch: r.target_branch,
+            })
+            .collect();
+    WorkspaceRepo::create_many(pool, workspace.id, &workspace_repos).await?;
+
+    deployment.container().create(workspace).await?;
+    let workspace = Workspace::find_by_id(pool, workspace.id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    reset_fork_worktrees_to_process(
+        deployment,
+        pool,
+        &workspace,
+        execution_process,
+        point == ForkPoint::After,
+    )
+    .await?;
+
+    let session = Session::create(
+        pool,
+        &CreateSession {
+            executor: Some(plan.executor_profile_id.executor.to_string()),
+        },
+        Uuid::new_v4(),
+        workspace.id,
+    )
+    .await?;
+
+    let project_repos = ProjectRepo::find_by_project_id_with_names(pool, plan.project.id).await?;
+    let cleanup_action = deployment
+        .container()
+        .cleanup_actions_for_repos(&project_repos);
+
+    let working_dir = workspace
+        .agent_working_dir
+        .as_ref()
+        .filter(|dir| !dir.is_empty())
+        .cloned();
+    let action = ExecutorAction::new(
+        plan.action_type(prompt, working_dir),
+        cleanup_action.map(Box::new),
+    );
+
+    let new_execution_process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+
+    Ok((workspace, session, new_execution_process))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let workspace_id_router = Router::new()
         .route("/", get(get_execution_process_by_id))
         .route("/stop", post(stop_execution_process))
         .route("/repo-states", get(get_execution_process_repo_states))
+        .route("/fork", post(fork_execution_process))
         .route("/raw-logs/ws", get(stream_raw_logs_ws))
+        .route("/normalized-logs", get(get_normalized_logs))
         .route("/normalized-logs/ws", get(stream_normalized_logs_ws))
         .layer(from_fn_with_state(
             deployment.clone(),
@@ -257,7 +594,137 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     let workspaces_router = Router::new()
         .route("/stream/ws", get(stream_execution_processes_ws))
+        .route("/queue", get(get_execution_queue))
         .nest("/{id}", workspace_id_router);
 
     Router::new().nest("/execution-processes", workspaces_router)
 }
+
+#[cfg(test)]
+mod tests {
+    use db::{
+        models::coding_agent_turn::{CodingAgentTurn, CreateCodingAgentTurn},
+        test_utils::{
+            create_coding_agent_process_in_session, create_session, create_task, create_workspace,
+            test_pool,
+        },
+    };
+
+    use super::*;
+
+    /// Two finished turns of one session that ran agent sessions "first" and "second"
+    async fn create_finished_turns(pool: &SqlitePool) -> [ExecutionProcess; 2] {
+        let task = create_task(pool).await;
+        let workspace = create_workspace(pool, task.id).await;
+        let session = create_session(pool, workspace.id).await;
+
+        let mut turns = Vec::new();
+        for agent_session_id in ["first", "second"] {
+            let process = create_coding_agent_process_in_session(pool, session.id).await;
+            CodingAgentTurn::create(
+                pool,
+                &CreateCodingAgentTurn {
+                    execution_process_id: process.id,
+                    prompt: None,
+                },
+                Uuid::new_v4(),
+            )
+            .await
+            .unwrap();
+            CodingAgentTurn::update_agent_session_id(pool, process.id, agent_session_id)
+                .await
+                .unwrap();
+            ExecutionProcess::update_completion(
+                pool,
+                process.id,
+                ExecutionProcessStatus::Completed,
+                Some(0),
+            )
+            .await
+            .unwrap();
+            turns.push(
+                ExecutionProcess::find_by_id(pool, process.id)
+                    .await
+                    .unwrap()
+                    .unwrap(),
+            );
+        }
+        turns.try_into().unwrap()
+    }
+
+    /// The agent session the fork's first action resumes, if any
+    async fn resumed_session(
+        pool: &SqlitePool,
+        process: &ExecutionProcess,
+        point: ForkPoint,
+    ) -> Option<String> {
+        let plan = plan_fork(pool, process, point, None).await.unwrap();
+        match plan.action_type("prompt".to_string(), None) {
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => Some(request.session_id),
+            ExecutorActionType::CodingAgentInitialRequest(_) => None,
+            _ => panic!("fork must start a coding agent"),
+        }
+    }
+
+    #[tokio::test]
+    async fn fork_resumes_the_agent_session_of_the_fork_point() {
+        let pool = test_pool().await;
+        let [first, second] = create_finished_turns(&pool).await;
+
+        assert_eq!(
+            resumed_session(&pool, &first, ForkPoint::Before).await,
+            None
+        );
+        assert_eq!(
+            resumed_session(&pool, &first, ForkPoint::After)
+                .await
+                .as_deref(),
+            Some("first")
+        );
+        assert_eq!(
+            resumed_session(&pool, &second, ForkPoint::Before)
+                .await
+                .as_deref(),
+            Some("first")
+        );
+        assert_eq!(
+            resumed_session(&pool, &second, ForkPoint::After)
+                .await
+                .as_deref(),
+            Some("second")
+        );
+    }
+
+    #[tokio::test]
+    async fn fork_rejects_turns_it_cannot_start_from() {
+        let pool = test_pool().await;
+        let [first, second] = create_finished_turns(&pool).await;
+
+        let mut running = second.clone();
+        running.status = ExecutionProcessStatus::Running;
+        assert!(matches!(
+            plan_fork(&pool, &running, ForkPoint::After, None).await,
+            Err(ApiError::Conflict(_))
+        ));
+        // Its starting state is already known
+        assert!(
+            plan_fork(&pool, &running, ForkPoint::Before, None)
+                .await
+                .is_ok()
+        );
+
+        let mut dropped = first.clone();
+        dropped.dropped = true;
+        assert!(matches!(
+            plan_fork(&pool, &dropped, ForkPoint::Before, None).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        let mut setup = first;
+        setup.run_reason = ExecutionProcessRunReason::SetupScript;
+        assert!(matches!(
+            plan_fork(&pool, &setup, ForkPoint::After, None).await,
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+}
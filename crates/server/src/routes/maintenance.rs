@@ -0,0 +1,89 @@
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use db::models::workspace_disk_usage::WorkspaceDiskUsage;
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::{
+    container::ContainerService,
+    log_maintenance::{LogMaintenanceReport, LogMaintenanceService},
+    workspace_disk::{DiskBudget, WorkspaceDiskSummary, WorkspaceGcReport},
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Default, Deserialize, TS)]
+pub struct LogMaintenanceRequest {
+    /// Rebuild the database afterwards so freed pages are returned to the filesystem
+    #[serde(default)]
+    pub vacuum: bool,
+}
+
+/// Run log compression and retention now and report the space reclaimed
+pub async fn run_log_maintenance(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<LogMaintenanceRequest>,
+) -> Result<ResponseJson<ApiResponse<LogMaintenanceReport>>, ApiError> {
+    let config = deployment.config().read().await.log_retention.clone();
+    let report = LogMaintenanceService::run(&deployment.db().pool, &config, payload.vacuum).await?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+#[derive(Debug, Default, Deserialize, TS)]
+pub struct WorkspaceDiskQuery {
+    /// Measure the worktrees now instead of returning the last measurement
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+#[derive(Debug, Default, Deserialize, TS)]
+pub struct WorkspaceGcRequest {
+    /// Also evict workspaces with uncommitted or unpushed changes
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Disk usage of every workspace on disk, against the configured budgets
+pub async fn get_workspace_disk_usage(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<WorkspaceDiskQuery>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceDiskSummary>>, ApiError> {
+    let summary = if query.refresh {
+        deployment
+            .container()
+            .measure_workspace_disk_usage()
+            .await?
+    } else {
+        let workspaces = WorkspaceDiskUsage::find_all(&deployment.db().pool).await?;
+        let config = deployment.config().read().await.workspace_disk.clone();
+        DiskBudget::new(&config, &workspaces).summary(workspaces)
+    };
+    Ok(ResponseJson(ApiResponse::success(summary)))
+}
+
+/// Evict least recently used workspaces until the disk budgets are met
+pub async fn collect_workspace_garbage(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<WorkspaceGcRequest>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceGcReport>>, ApiError> {
+    let report = deployment
+        .container()
+        .collect_workspace_garbage(payload.force)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/maintenance/logs", post(run_log_maintenance))
+        .route("/maintenance/workspaces", get(get_workspace_disk_usage))
+        .route(
+            "/maintenance/workspaces/gc",
+            post(collect_workspace_garbage),
+        )
+}
@@ -0,0 +1,377 @@
+//! The task MCP server over streamable HTTP, so remote agents and IDEs can connect to the
+//! backend directly instead of spawning the stdio `mcp_task_server` binary.
+//!
+//! Clients authenticate with a bearer token minted in the app at `/api/mcp/tokens`, which
+//! only accepts requests from the app's own pages. Every token gets its own MCP service and
+//! session manager, and a token bound to a workspace serves that workspace through
+//! `get_context`. The workspace context is resolved again on every request.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+};
+
+use axum::{
+    Extension, Json, Router,
+    body::Body,
+    extract::{Path, Request, State},
+    http::{HeaderMap, StatusCode, header},
+    middleware::from_fn,
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::{any, delete, get},
+};
+use db::models::{
+    mcp_token::{CreateMcpToken, McpToken},
+    task::Task,
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use rand::{Rng, distributions::Alphanumeric};
+use rmcp::transport::streamable_http_server::{
+    StreamableHttpService, session::local::LocalSessionManager,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use tokio::sync::RwLock;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    mcp::task_server::{McpContext, SharedMcpContext, TaskServer},
+    middleware::require_same_origin,
+};
+
+const TOKEN_PREFIX: &str = "vk_mcp_";
+
+/// URL the tools use to call back into the REST API, set once the listener is bound
+static BACKEND_URL: OnceLock<String> = OnceLock::new();
+
+pub fn set_backend_url(url: String) {
+    let _ = BACKEND_URL.set(url);
+}
+
+type McpService = StreamableHttpService<TaskServer, LocalSessionManager>;
+
+/// MCP service of a token and the workspace context its sessions serve
+#[derive(Clone)]
+struct TokenService {
+    service: McpService,
+    context: SharedMcpContext,
+}
+
+/// MCP services of the tokens that have connected since startup
+#[derive(Default)]
+pub struct McpServices(RwLock<HashMap<Uuid, TokenService>>);
+
+#[derive(Debug, Serialize, TS)]
+pub struct CreateMcpTokenResponse {
+    pub token: McpToken,
+    /// The bearer token; it is not stored and can't be retrieved again
+    pub secret: String,
+}
+
+pub async fn handle_mcp_request(
+    State(deployment): State<DeploymentImpl>,
+    Extension(services): Extension<Arc<McpServices>>,
+    request: Request,
+) -> Result<Response, ApiError> {
+    let Some(base_url) = BACKEND_URL.get() else {
+        return Ok(StatusCode::SERVICE_UNAVAILABLE.into_response());
+    };
+    let pool = &deployment.db().pool;
+    let Some(token) = authenticate(pool, request.headers()).await? else {
+        return Ok((
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+        )
+            .into_response());
+    };
+    // Sessions outlive workspace renames, repo changes and deletions, so don't cache this
+    let context = token_context(pool, &token).await?;
+
+    let existing = services.0.read().await.get(&token.id).cloned();
+    let token_service = match existing {
+        Some(token_service) => token_service,
+        None => {
+            let shared_context = SharedMcpContext::default();
+            let server_context = shared_context.clone();
+            let base_url = base_url.clone();
+            let service = StreamableHttpService::new(
+                move || Ok(TaskServer::new(&base_url).with_shared_context(server_context.clone())),
+                LocalSessionManager::default().into(),
+                Default::default(),
+            );
+            services
+                .0
+                .write()
+                .await
+                .entry(token.id)
+                .or_insert(TokenService {
+                    service,
+                    context: shared_context,
+                })
+                .clone()
+        }
+    };
+    *token_service.context.write().unwrap() = context;
+
+    Ok(token_service.service.handle(request).await.map(Body::new))
+}
+
+async fn authenticate(
+    pool: &SqlitePool,
+    headers: &HeaderMap,
+) -> Result<Option<McpToken>, ApiError> {
+    let Some(secret) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return Ok(None);
+    };
+
+    let token_hash = hash_secret(secret.trim());
+    let Some(token) = McpToken::find_by_token_hash(pool, &token_hash).await? else {
+        return Ok(None);
+    };
+    McpToken::touch(pool, token.id).await?;
+    Ok(Some(token))
+}
+
+async fn token_context(
+    pool: &SqlitePool,
+    token: &McpToken,
+) -> Result<Option<McpContext>, ApiError> {
+    let Some(workspace_id) = token.workspace_id else {
+        return Ok(None);
+    };
+
+    let Some(workspace) = Workspace::find_by_id(pool, workspace_id).await? else {
+        return Ok(None);
+    };
+    let Some(task) = Task::find_by_id(pool, workspace.task_id).await? else {
+        return Ok(None);
+    };
+    let ctx = Workspace::load_context(pool, workspace.id, task.id, task.project_id).await?;
+    Ok(Some(McpContext::from_workspace_context(ctx)))
+}
+
+pub async fn get_mcp_tokens(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<McpToken>>>, ApiError> {
+    let tokens = McpToken::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(tokens)))
+}
+
+pub async fn create_mcp_token(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateMcpToken>,
+) -> Result<ResponseJson<ApiResponse<CreateMcpTokenResponse>>, ApiError> {
+    if payload.name.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Token name must not be empty".to_string(),
+        ));
+    }
+
+    let pool = &deployment.db().pool;
+    if let Some(workspace_id) = payload.workspace_id
+        && Workspace::find_by_id(pool, workspace_id).await?.is_none()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Workspace {workspace_id} not found"
+        )));
+    }
+
+    let secret = generate_secret();
+    let token = McpToken::create(pool, &payload, &hash_secret(&secret)).await?;
+    Ok(ResponseJson(ApiResponse::success(CreateMcpTokenResponse {
+        token,
+        secret,
+    })))
+}
+
+pub async fn delete_mcp_token(
+    State(deployment): State<DeploymentImpl>,
+    Extension(services): Extension<Arc<McpServices>>,
+    Path(token_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = McpToken::delete(&deployment.db().pool, token_id).await?;
+    if rows_affected == 0 {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+    // Drop the token's sessions; further requests with it are rejected
+    services.0.write().await.remove(&token_id);
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+fn generate_secret() -> String {
+    let random: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    format!("{TOKEN_PREFIX}{random}")
+}
+
+fn hash_secret(secret: &str) -> String {
+    let mut output = String::with_capacity(64);
+    let digest = Sha256::digest(secret.as_bytes());
+    for byte in digest {
+        use std::fmt::Write;
+        let _ = write!(output, "{:02x}", byte);
+    }
+    output
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    // Anyone who can mint a token can use the MCP server, so only the app itself may
+    let token_routes = Router::new()
+        .route("/mcp/tokens", get(get_mcp_tokens).post(create_mcp_token))
+        .route("/mcp/tokens/{token_id}", delete(delete_mcp_token))
+        .layer(from_fn(require_same_origin));
+
+    Router::new()
+        .route("/mcp", any(handle_mcp_request))
+        .merge(token_routes)
+        .layer(Extension(Arc::new(McpServices::default())))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+    use db::test_utils::{create_task, create_workspace, test_pool};
+
+    use super::*;
+
+    async fn create_token(pool: &SqlitePool, workspace_id: Option<Uuid>) -> (McpToken, String) {
+        let secret = generate_secret();
+        let token = McpToken::create(
+            pool,
+            &CreateMcpToken {
+                name: "client".to_string(),
+                workspace_id,
+            },
+            &hash_secret(&secret),
+        )
+        .await
+        .unwrap();
+        (token, secret)
+    }
+
+    fn bearer(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn secrets_are_prefixed_random_and_stored_as_sha256_hex() {
+        let secret = generate_secret();
+        assert!(secret.starts_with(TOKEN_PREFIX));
+        assert_eq!(secret.len(), TOKEN_PREFIX.len() + 40);
+        assert!(
+            secret[TOKEN_PREFIX.len()..]
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric())
+        );
+        assert_ne!(secret, generate_secret());
+
+        assert_eq!(
+            hash_secret("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(hash_secret(&secret), hash_secret(&secret));
+        assert_ne!(hash_secret(&secret), hash_secret(&generate_secret()));
+    }
+
+    #[tokio::test]
+    async fn a_valid_bearer_token_authenticates_and_is_marked_used() {
+        let pool = test_pool().await;
+        let (token, secret) = create_token(&pool, None).await;
+        assert!(token.last_used_at.is_none());
+
+        let authenticated = authenticate(&pool, &bearer(&format!("Bearer {secret}")))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(authenticated.id, token.id);
+        let stored = McpToken::find_by_token_hash(&pool, &hash_secret(&secret))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.last_used_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn missing_malformed_and_unknown_tokens_are_rejected() {
+        let pool = test_pool().await;
+        let (_, secret) = create_token(&pool, None).await;
+
+        assert!(
+            authenticate(&pool, &HeaderMap::new())
+                .await
+                .unwrap()
+                .is_none()
+        );
+        for header in [
+            secret.clone(),
+            format!("Basic {secret}"),
+            format!("Bearer {}", generate_secret()),
+            format!("Bearer {}", hash_secret(&secret)),
+        ] {
+            assert!(
+                authenticate(&pool, &bearer(&header))
+                    .await
+                    .unwrap()
+                    .is_none(),
+                "{header}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn revoked_tokens_are_rejected() {
+        let pool = test_pool().await;
+        let (token, secret) = create_token(&pool, None).await;
+        let headers = bearer(&format!("Bearer {secret}"));
+        assert!(authenticate(&pool, &headers).await.unwrap().is_some());
+
+        assert_eq!(McpToken::delete(&pool, token.id).await.unwrap(), 1);
+
+        assert!(authenticate(&pool, &headers).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn token_context_follows_the_current_state_of_its_workspace() {
+        let pool = test_pool().await;
+        let (unbound, _) = create_token(&pool, None).await;
+        assert!(token_context(&pool, &unbound).await.unwrap().is_none());
+
+        let task = create_task(&pool).await;
+        let workspace = create_workspace(&pool, task.id).await;
+        let (token, _) = create_token(&pool, Some(workspace.id)).await;
+        let context = token_context(&pool, &token).await.unwrap().unwrap();
+        assert_eq!(context.workspace_id, workspace.id);
+        assert_eq!(context.task_title, "task");
+
+        sqlx::query("UPDATE tasks SET title = 'renamed' WHERE id = $1")
+            .bind(workspace.task_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let context = token_context(&pool, &token).await.unwrap().unwrap();
+        assert_eq!(context.task_title, "renamed");
+
+        sqlx::query("DELETE FROM workspaces WHERE id = $1")
+            .bind(workspace.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(token_context(&pool, &token).await.unwrap().is_none());
+    }
+}
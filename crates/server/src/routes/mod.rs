@@ -1,11 +1,13 @@
 use axum::{
     Router,
+    middleware::from_fn_with_state,
     routing::{IntoMakeService, get},
 };
 
 use crate::DeploymentImpl;
 
 pub mod approvals;
+pub mod attempt_comparisons;
 pub mod config;
 pub mod containers;
 pub mod filesystem;
@@ -15,19 +17,31 @@ pub mod execution_processes;
 pub mod frontend;
 pub mod health;
 pub mod images;
+pub mod maintenance;
+pub mod mcp;
 pub mod oauth;
 pub mod organizations;
 pub mod portfolios;
+pub mod preview;
 pub mod projects;
 pub mod repo;
 pub mod scratch;
+pub mod search;
+pub mod secrets;
 pub mod sessions;
 pub mod shared_tasks;
 pub mod tags;
 pub mod task_attempts;
 pub mod tasks;
+pub mod terminals;
+pub mod transcripts;
 
 pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
+    // Dev server previews live outside /api so proxied apps get the whole path space
+    let preview_routes = preview::router().with_state(deployment.clone());
+    let preview_subdomains =
+        from_fn_with_state(deployment.clone(), preview::route_preview_subdomain);
+
     // Create routers with different middleware layers
     let base_routes = Router::new()
         .route("/health", get(health::health_check))
@@ -38,6 +52,7 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .merge(tasks::router(&deployment))
         .merge(shared_tasks::router())
         .merge(task_attempts::router(&deployment))
+        .merge(attempt_comparisons::router(&deployment))
         .merge(execution_processes::router(&deployment))
         .merge(tags::router(&deployment))
         .merge(oauth::router())
@@ -46,8 +61,14 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .merge(repo::router())
         .merge(events::router(&deployment))
         .merge(approvals::router())
+        .merge(transcripts::router())
         .merge(scratch::router(&deployment))
+        .merge(search::router())
+        .merge(maintenance::router())
+        .merge(mcp::router())
         .merge(sessions::router(&deployment))
+        .merge(terminals::router())
+        .merge(secrets::router())
         .nest("/images", images::routes())
         .with_state(deployment);
 
@@ -55,5 +76,7 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .route("/", get(frontend::serve_frontend_root))
         .route("/{*path}", get(frontend::serve_frontend))
         .nest("/api", base_routes)
+        .merge(preview_routes)
+        .layer(preview_subdomains)
         .into_make_service()
 }
@@ -0,0 +1,522 @@
+//! Reverse proxy to the dev servers of workspaces, so previews load through the same origin
+//! and port as the app, e.g. when it runs on a remote machine.
+//!
+//! A workspace's dev server is served from the root of its own subdomain
+//! `{workspace_id}.<host>` (e.g. `http://{workspace_id}.localhost:3000`), a separate origin
+//! from the app. Where subdomains don't resolve it is also served at
+//! `/preview/{workspace_id}/...`, which shares the app's origin; those responses are
+//! sandboxed into an opaque origin by their Content-Security-Policy so the previewed app
+//! can't read the app's storage or call its API. Websocket upgrades, such as hot reload
+//! connections, are proxied as well.
+
+use std::sync::LazyLock;
+
+use axum::{
+    Router,
+    body::Body,
+    extract::{
+        FromRequestParts, Path, Request, State,
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, Uri, header},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+    routing::{any, get},
+};
+use deployment::Deployment;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use services::services::container::ContainerService;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    MaybeTlsStream, WebSocketStream, connect_async,
+    tungstenite::{self, client::IntoClientRequest, protocol::frame::coding::CloseCode},
+};
+use uuid::Uuid;
+
+use crate::DeploymentImpl;
+
+const PREVIEW_PREFIX: &str = "/preview";
+/// Runs previews served below the app's origin in a unique opaque origin. Scripts, forms and
+/// popups keep working; same-origin access to the app does not.
+const PREVIEW_SANDBOX_CSP: &str =
+    "sandbox allow-scripts allow-forms allow-popups allow-modals allow-downloads";
+
+/// Redirects are passed on to the browser (rewritten), not followed
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .no_gzip()
+        .no_brotli()
+        .no_deflate()
+        .build()
+        .expect("preview proxy client should build")
+});
+
+#[derive(Debug, Deserialize)]
+pub struct PreviewPath {
+    workspace_id: Uuid,
+}
+
+/// Relative URLs only resolve below the preview prefix with a trailing slash
+pub async fn redirect_preview_root(Path(params): Path<PreviewPath>, uri: Uri) -> Redirect {
+    let query = uri.query().map(|q| format!("?{q}")).unwrap_or_default();
+    Redirect::temporary(&format!("{PREVIEW_PREFIX}/{}/{query}", params.workspace_id))
+}
+
+pub async fn proxy_preview(
+    State(deployment): State<DeploymentImpl>,
+    Path(params): Path<PreviewPath>,
+    request: Request,
+) -> Response {
+    let prefix = format!("{PREVIEW_PREFIX}/{}", params.workspace_id);
+    proxy(&deployment, params.workspace_id, &prefix, request).await
+}
+
+/// Send every request for `{workspace_id}.<host>` to that workspace's dev server
+pub async fn route_preview_subdomain(
+    State(deployment): State<DeploymentImpl>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match subdomain_workspace_id(request.headers()) {
+        Some(workspace_id) => proxy(&deployment, workspace_id, "", request).await,
+        None => next.run(request).await,
+    }
+}
+
+fn subdomain_workspace_id(headers: &HeaderMap) -> Option<Uuid> {
+    let host = headers.get(header::HOST)?.to_str().ok()?;
+    let (label, _) = host.split_once('.')?;
+    Uuid::parse_str(label).ok()
+}
+
+async fn proxy(
+    deployment: &DeploymentImpl,
+    workspace_id: Uuid,
+    prefix: &str,
+    request: Request,
+) -> Response {
+    let Some(port) = deployment
+        .container()
+        .dev_servers()
+        .get(workspace_id)
+        .filter(|server| server.status.is_active())
+        .map(|server| server.port)
+    else {
+        return (
+            StatusCode::NOT_FOUND,
+            "No dev server is running for this workspace",
+        )
+            .into_response();
+    };
+
+    let path_and_query = upstream_path_and_query(request.uri(), prefix);
+    if is_websocket_upgrade(request.headers()) {
+        proxy_websocket(port, &path_and_query, request).await
+    } else {
+        proxy_http(port, prefix, &path_and_query, request).await
+    }
+}
+
+/// The request's path below `prefix`, with the query string
+fn upstream_path_and_query(uri: &Uri, prefix: &str) -> String {
+    let path = uri.path().strip_prefix(prefix).unwrap_or(uri.path());
+    let path = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{path}")
+    };
+    match uri.query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path,
+    }
+}
+
+fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::UPGRADE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"))
+}
+
+/// Headers that only apply to a single connection and must not be forwarded
+fn is_hop_by_hop(name: &HeaderName) -> bool {
+    [
+        header::CONNECTION,
+        header::PROXY_AUTHENTICATE,
+        header::PROXY_AUTHORIZATION,
+        header::TE,
+        header::TRAILER,
+        header::TRANSFER_ENCODING,
+        header::UPGRADE,
+    ]
+    .contains(name)
+        || name.as_str() == "keep-alive"
+}
+
+async fn proxy_http(port: u16, prefix: &str, path_and_query: &str, request: Request) -> Response {
+    let (parts, body) = request.into_parts();
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in &parts.headers {
+        // Dev servers check Host against their allowed hosts, so it is set to the upstream
+        if !is_hop_by_hop(name) && name != header::HOST {
+            headers.append(name, value.clone());
+        }
+    }
+    if let Some(host) = parts.headers.get(header::HOST) {
+        headers.insert("x-forwarded-host", host.clone());
+    }
+    if !prefix.is_empty()
+        && let Ok(prefix) = HeaderValue::from_str(prefix)
+    {
+        headers.insert("x-forwarded-prefix", prefix);
+    }
+
+    let upstream = match CLIENT
+        .request(
+            parts.method,
+            format!("http://localhost:{port}{path_and_query}"),
+        )
+        .headers(headers)
+        .body(reqwest::Body::wrap_stream(body.into_data_stream()))
+        .send()
+        .await
+    {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            tracing::debug!("Preview request to port {} failed: {}", port, e);
+            return (
+                StatusCode::BAD_GATEWAY,
+                format!("Dev server is not reachable: {e}"),
+            )
+                .into_response();
+        }
+    };
+
+    let mut response = Response::builder().status(upstream.status());
+    for (name, value) in upstream.headers() {
+        if is_hop_by_hop(name) {
+            continue;
+        }
+        if name == header::LOCATION
+            && let Ok(location) = value.to_str()
+            && let Ok(location) = HeaderValue::from_str(&rewrite_location(location, port, prefix))
+        {
+            response = response.header(name, location);
+        } else {
+            response = response.header(name, value);
+        }
+    }
+    if !prefix.is_empty() {
+        response = response.header(header::CONTENT_SECURITY_POLICY, PREVIEW_SANDBOX_CSP);
+    }
+    response
+        .body(Body::from_stream(upstream.bytes_stream()))
+        .unwrap_or_else(|e| (StatusCode::BAD_GATEWAY, e.to_string()).into_response())
+}
+
+/// Point redirects to the dev server's own origin, or to its root, back through the proxy
+fn rewrite_location(location: &str, port: u16, prefix: &str) -> String {
+    for origin in [
+        format!("http://localhost:{port}"),
+        format!("http://127.0.0.1:{port}"),
+    ] {
+        if let Some(rest) = location.strip_prefix(&origin)
+            && (rest.is_empty() || rest.starts_with(['/', '?', '#']))
+        {
+            let rest = if rest.starts_with('/') {
+                rest.to_string()
+            } else {
+                format!("/{rest}")
+            };
+            return format!("{prefix}{rest}");
+        }
+    }
+    if location.starts_with('/') && !location.starts_with("//") {
+        return format!("{prefix}{location}");
+    }
+    location.to_string()
+}
+
+async fn proxy_websocket(port: u16, path_and_query: &str, request: Request) -> Response {
+    let (mut parts, _) = request.into_parts();
+    let ws = match WebSocketUpgrade::from_request_parts(&mut parts, &()).await {
+        Ok(ws) => ws,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    let mut upstream_request =
+        match format!("ws://localhost:{port}{path_and_query}").into_client_request() {
+            Ok(upstream_request) => upstream_request,
+            Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        };
+    // Dev servers like Vite only accept their hot reload socket with its subprotocol
+    for name in [header::SEC_WEBSOCKET_PROTOCOL, header::COOKIE] {
+        if let Some(value) = parts.headers.get(&name) {
+            upstream_request.headers_mut().insert(name, value.clone());
+        }
+    }
+
+    let (upstream, upstream_response) = match connect_async(upstream_request).await {
+        Ok(connected) => connected,
+        Err(e) => {
+            tracing::debug!("Preview websocket to port {} failed: {}", port, e);
+            return (
+                StatusCode::BAD_GATEWAY,
+                format!("Dev server is not reachable: {e}"),
+            )
+                .into_response();
+        }
+    };
+    let ws = match upstream_response
+        .headers()
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(protocol) => ws.protocols([protocol.to_string()]),
+        None => ws,
+    };
+
+    ws.on_upgrade(move |socket| pipe_websocket(socket, upstream))
+}
+
+async fn pipe_websocket(client: WebSocket, upstream: WebSocketStream<MaybeTlsStream<TcpStream>>) {
+    let (mut client_tx, mut client_rx) = client.split();
+    let (mut upstream_tx, mut upstream_rx) = upstream.split();
+
+    let to_upstream = async {
+        while let Some(Ok(msg)) = client_rx.next().await {
+            if upstream_tx.send(to_upstream_message(msg)).await.is_err() {
+                break;
+            }
+        }
+        let _ = upstream_tx.close().await;
+    };
+    let to_client = async {
+        while let Some(Ok(msg)) = upstream_rx.next().await {
+            let Some(msg) = to_client_message(msg) else {
+                continue;
+            };
+            if client_tx.send(msg).await.is_err() {
+                break;
+            }
+        }
+        let _ = client_tx.close().await;
+    };
+
+    tokio::select! {
+        _ = to_upstream => {}
+        _ = to_client => {}
+    }
+}
+
+fn to_upstream_message(msg: Message) -> tungstenite::Message {
+    match msg {
+        Message::Text(text) => tungstenite::Message::Text(text.as_str().into()),
+        Message::Binary(data) => tungstenite::Message::Binary(data),
+        Message::Ping(data) => tungstenite::Message::Ping(data),
+        Message::Pong(data) => tungstenite::Message::Pong(data),
+        Message::Close(frame) => {
+            tungstenite::Message::Close(frame.map(|frame| tungstenite::protocol::CloseFrame {
+                code: CloseCode::from(frame.code),
+                reason: frame.reason.as_str().into(),
+            }))
+        }
+    }
+}
+
+fn to_client_message(msg: tungstenite::Message) -> Option<Message> {
+    Some(match msg {
+        tungstenite::Message::Text(text) => Message::Text(text.as_str().into()),
+        tungstenite::Message::Binary(data) => Message::Binary(data),
+        tungstenite::Message::Ping(data) => Message::Ping(data),
+        tungstenite::Message::Pong(data) => Message::Pong(data),
+        tungstenite::Message::Close(frame) => Message::Close(frame.map(|frame| CloseFrame {
+            code: frame.code.into(),
+            reason: frame.reason.as_str().into(),
+        })),
+        tungstenite::Message::Frame(_) => return None,
+    })
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/preview/{workspace_id}", get(redirect_preview_root))
+        .route("/preview/{workspace_id}/", any(proxy_preview))
+        .route("/preview/{workspace_id}/{*path}", any(proxy_preview))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::Method, routing::post};
+
+    use super::*;
+
+    fn uri(uri: &str) -> Uri {
+        uri.parse().unwrap()
+    }
+
+    #[test]
+    fn upstream_path_strips_the_preview_prefix_and_keeps_the_query() {
+        let prefix = "/preview/3f2d6a1e-0000-0000-0000-000000000000";
+        assert_eq!(
+            upstream_path_and_query(&uri(&format!("{prefix}/assets/app.js?v=1&x=2")), prefix),
+            "/assets/app.js?v=1&x=2"
+        );
+        assert_eq!(
+            upstream_path_and_query(&uri(&format!("{prefix}/")), prefix),
+            "/"
+        );
+        assert_eq!(
+            upstream_path_and_query(&uri(&format!("{prefix}?tab=2")), prefix),
+            "/?tab=2"
+        );
+        // Subdomain previews have no prefix and forward the path unchanged
+        assert_eq!(
+            upstream_path_and_query(&uri("/login?next=%2F"), ""),
+            "/login?next=%2F"
+        );
+    }
+
+    #[test]
+    fn redirects_to_the_dev_server_go_back_through_the_proxy() {
+        let prefix = "/preview/3f2d6a1e-0000-0000-0000-000000000000";
+        assert_eq!(
+            rewrite_location("http://localhost:5173/login", 5173, prefix),
+            format!("{prefix}/login")
+        );
+        assert_eq!(
+            rewrite_location("http://127.0.0.1:5173?next=1", 5173, prefix),
+            format!("{prefix}/?next=1")
+        );
+        assert_eq!(
+            rewrite_location("http://localhost:5173", 5173, prefix),
+            format!("{prefix}/")
+        );
+        assert_eq!(
+            rewrite_location("/dashboard#top", 5173, prefix),
+            format!("{prefix}/dashboard#top")
+        );
+        assert_eq!(rewrite_location("/dashboard", 5173, ""), "/dashboard");
+    }
+
+    #[test]
+    fn redirects_elsewhere_are_left_alone() {
+        let prefix = "/preview/3f2d6a1e-0000-0000-0000-000000000000";
+        for location in [
+            "https://github.com/login",
+            "//cdn.example.com/app.js",
+            "http://localhost:51730/login",
+            "http://localhost:3000/login",
+            "next",
+        ] {
+            assert_eq!(rewrite_location(location, 5173, prefix), location);
+        }
+    }
+
+    #[test]
+    fn only_uuid_subdomains_are_routed_to_previews() {
+        let workspace_id = Uuid::new_v4();
+        let host = |host: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::HOST, HeaderValue::from_str(host).unwrap());
+            headers
+        };
+        assert_eq!(
+            subdomain_workspace_id(&host(&format!("{workspace_id}.localhost:3000"))),
+            Some(workspace_id)
+        );
+        assert_eq!(subdomain_workspace_id(&host("localhost:3000")), None);
+        assert_eq!(subdomain_workspace_id(&host("app.example.com")), None);
+        assert_eq!(subdomain_workspace_id(&HeaderMap::new()), None);
+    }
+
+    /// Echo the request path and body, and redirect `/redirect` to the dev server's origin
+    async fn upstream() -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let app = Router::new()
+            .route(
+                "/echo",
+                post(|uri: Uri, body: String| async move { format!("{uri} {body}") }),
+            )
+            .route(
+                "/redirect",
+                get(move || async move {
+                    Redirect::temporary(&format!("http://localhost:{port}/login"))
+                }),
+            );
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        port
+    }
+
+    fn request(method: Method, uri: &str, body: &str) -> Request {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::HOST, "localhost:3000")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn text(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn path_previews_are_sandboxed_and_stream_request_bodies() {
+        let port = upstream().await;
+        let prefix = "/preview/3f2d6a1e-0000-0000-0000-000000000000";
+
+        let response = proxy_http(
+            port,
+            prefix,
+            "/echo?q=1",
+            request(Method::POST, &format!("{prefix}/echo?q=1"), "hello"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response
+                .headers()
+                .get(header::CONTENT_SECURITY_POLICY)
+                .unwrap(),
+            PREVIEW_SANDBOX_CSP
+        );
+        assert_eq!(text(response).await, "/echo?q=1 hello");
+
+        let response = proxy_http(
+            port,
+            prefix,
+            "/redirect",
+            request(Method::GET, &format!("{prefix}/redirect"), ""),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(
+            response.headers().get(header::LOCATION).unwrap(),
+            &format!("{prefix}/login")
+        );
+    }
+
+    #[tokio::test]
+    async fn subdomain_previews_keep_their_own_origin_unsandboxed() {
+        let port = upstream().await;
+
+        let response = proxy_http(port, "", "/echo", request(Method::POST, "/echo", "hi")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response
+                .headers()
+                .get(header::CONTENT_SECURITY_POLICY)
+                .is_none()
+        );
+        assert_eq!(text(response).await, "/echo hi");
+    }
+}
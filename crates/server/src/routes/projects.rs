@@ -10,9 +10,13 @@ use axum::{
     http::StatusCode,
     middleware::from_fn_with_state,
     response::{IntoResponse, Json as ResponseJson},
-    routing::{get, post},
+    routing::{get, post, put},
 };
 use db::models::{
+    approval_policy_rule::{
+        ApprovalPolicyRule, CreateApprovalPolicyRule, UpdateApprovalPolicyRule,
+    },
+    approval_record::ApprovalRecord,
     project::{CreateProject, Project, ProjectError, SearchResult, UpdateProject},
     project_repo::{CreateProjectRepo, ProjectRepo, UpdateProjectRepo},
     repo::Repo,
@@ -21,8 +25,8 @@ use deployment::Deployment;
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::Deserialize;
 use services::services::{
-    file_search_cache::SearchQuery, project::ProjectServiceError,
-    remote_client::CreateRemoteProjectPayload,
+    approvals::policy::validate_rule, file_search_cache::SearchQuery, project::ProjectServiceError,
+    remote_client::CreateRemoteProjectPayload, sparse_checkout::SparseCheckout,
 };
 use ts_rs::TS;
 use utils::{
@@ -369,11 +373,21 @@ pub async fn search_project_files(
         }
     };
 
+    let sparse_checkouts =
+        match SparseCheckout::for_project(&deployment.db().pool, project.id).await {
+            Ok(sparse_checkouts) => sparse_checkouts,
+            Err(e) => {
+                tracing::error!("Failed to get sparse checkouts: {}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
     match deployment
         .project()
         .search_files(
             deployment.file_search_cache().as_ref(),
             &repositories,
+            &sparse_checkouts,
             &search_query,
         )
         .await
@@ -532,6 +546,90 @@ pub async fn update_project_repository(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ProjectApprovalsQuery {
+    pub limit: Option<i64>,
+}
+
+/// Most recent approval requests across all tasks of the project
+pub async fn get_project_approvals(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ProjectApprovalsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<ApprovalRecord>>>, ApiError> {
+    let limit = query.limit.unwrap_or(200).clamp(1, 1000);
+    let approvals =
+        ApprovalRecord::find_by_project_id(&deployment.db().pool, project.id, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(approvals)))
+}
+
+pub async fn get_approval_policy_rules(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ApprovalPolicyRule>>>, ApiError> {
+    let rules = ApprovalPolicyRule::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(rules)))
+}
+
+pub async fn create_approval_policy_rule(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateApprovalPolicyRule>,
+) -> Result<ResponseJson<ApiResponse<ApprovalPolicyRule>>, ApiError> {
+    validate_rule(
+        payload.tool_name.as_deref(),
+        payload.command_pattern.as_deref(),
+        payload.path_glob.as_deref(),
+    )
+    .map_err(ApiError::BadRequest)?;
+
+    let rule = ApprovalPolicyRule::create(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(rule)))
+}
+
+pub async fn update_approval_policy_rule(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, rule_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateApprovalPolicyRule>,
+) -> Result<ResponseJson<ApiResponse<ApprovalPolicyRule>>, ApiError> {
+    let pool = &deployment.db().pool;
+    match ApprovalPolicyRule::find_by_id(pool, rule_id).await? {
+        Some(rule) if rule.project_id == project_id => {}
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Approval rule not found in project".to_string(),
+            ));
+        }
+    }
+    validate_rule(
+        payload.tool_name.as_deref(),
+        payload.command_pattern.as_deref(),
+        payload.path_glob.as_deref(),
+    )
+    .map_err(ApiError::BadRequest)?;
+
+    let rule = ApprovalPolicyRule::update(pool, rule_id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(rule)))
+}
+
+pub async fn delete_approval_policy_rule(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, rule_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    match ApprovalPolicyRule::find_by_id(pool, rule_id).await? {
+        Some(rule) if rule.project_id == project_id => {}
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Approval rule not found in project".to_string(),
+            ));
+        }
+    }
+
+    ApprovalPolicyRule::delete(pool, rule_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let project_id_router = Router::new()
         .route(
@@ -550,6 +648,11 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
             "/repositories",
             get(get_project_repositories).post(add_project_repository),
         )
+        .route("/approvals", get(get_project_approvals))
+        .route(
+            "/approval-rules",
+            get(get_approval_policy_rules).post(create_approval_policy_rule),
+        )
         .layer(from_fn_with_state(
             deployment.clone(),
             load_project_middleware,
@@ -563,6 +666,10 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
                 .put(update_project_repository)
                 .delete(delete_project_repository),
         )
+        .route(
+            "/{project_id}/approval-rules/{rule_id}",
+            put(update_approval_policy_rule).delete(delete_approval_policy_rule),
+        )
         .route("/stream/ws", get(stream_projects_ws))
         .nest("/{id}", project_id_router);
 
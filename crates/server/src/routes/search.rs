@@ -0,0 +1,50 @@
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use db::models::search::{SearchFilter, SearchHit, SearchHitKind, SearchIndex};
+use deployment::Deployment;
+use serde::Deserialize;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SearchQuery {
+    /// Free text; every term must match, as a word prefix
+    pub q: String,
+    pub project_id: Option<Uuid>,
+    pub kind: Option<SearchHitKind>,
+    /// Only return hits indexed at or after this time
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
+
+/// Full-text search across tasks, agent turn prompts/summaries and agent log entries,
+/// best matches first
+pub async fn search(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<SearchQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<SearchHit>>>, ApiError> {
+    let filter = SearchFilter {
+        project_id: query.project_id,
+        kind: query.kind,
+        since: query.since,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let hits = SearchIndex::search(&deployment.db().pool, &query.q, &filter, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(hits)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/search", get(search))
+}
@@ -0,0 +1,130 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+    routing::{get, put},
+};
+use db::models::{project::Project, repo::Repo, secret::Secret};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::{container::ContainerService, secrets::validate_secret_name};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Secrets belong to either a project or a repo
+#[derive(Debug, Deserialize, TS)]
+pub struct SecretScopeQuery {
+    pub project_id: Option<Uuid>,
+    pub repo_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateSecretRequest {
+    pub project_id: Option<Uuid>,
+    pub repo_id: Option<Uuid>,
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateSecretRequest {
+    pub value: String,
+}
+
+pub async fn get_secrets(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<SecretScopeQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<Secret>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let secrets = match (query.project_id, query.repo_id) {
+        (Some(project_id), None) => Secret::find_by_project_id(pool, project_id).await?,
+        (None, Some(repo_id)) => Secret::find_by_repo_id(pool, repo_id).await?,
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Exactly one of project_id and repo_id is required".to_string(),
+            ));
+        }
+    };
+    Ok(ResponseJson(ApiResponse::success(secrets)))
+}
+
+pub async fn create_secret(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateSecretRequest>,
+) -> Result<ResponseJson<ApiResponse<Secret>>, ApiError> {
+    let pool = &deployment.db().pool;
+    match (payload.project_id, payload.repo_id) {
+        (Some(project_id), None) => {
+            Project::find_by_id(pool, project_id)
+                .await?
+                .ok_or_else(|| ApiError::BadRequest("Project not found".to_string()))?;
+        }
+        (None, Some(repo_id)) => {
+            Repo::find_by_id(pool, repo_id)
+                .await?
+                .ok_or_else(|| ApiError::BadRequest("Repository not found".to_string()))?;
+        }
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Exactly one of project_id and repo_id is required".to_string(),
+            ));
+        }
+    }
+    validate_secret_name(&payload.name)?;
+
+    let ciphertext = deployment.container().secrets().encrypt(&payload.value)?;
+    let secret = Secret::create(
+        pool,
+        payload.project_id,
+        payload.repo_id,
+        &payload.name,
+        &ciphertext,
+    )
+    .await
+    .map_err(|e| match e.as_database_error() {
+        Some(db_err) if db_err.is_unique_violation() => {
+            ApiError::Conflict(format!("A secret named {} already exists", payload.name))
+        }
+        _ => ApiError::Database(e),
+    })?;
+    Ok(ResponseJson(ApiResponse::success(secret)))
+}
+
+pub async fn update_secret(
+    State(deployment): State<DeploymentImpl>,
+    Path(secret_id): Path<Uuid>,
+    Json(payload): Json<UpdateSecretRequest>,
+) -> Result<ResponseJson<ApiResponse<Secret>>, ApiError> {
+    let pool = &deployment.db().pool;
+    Secret::find_by_id(pool, secret_id)
+        .await?
+        .ok_or(ApiError::Database(sqlx::Error::RowNotFound))?;
+
+    let ciphertext = deployment.container().secrets().encrypt(&payload.value)?;
+    let secret = Secret::update_value(pool, secret_id, &ciphertext).await?;
+    Ok(ResponseJson(ApiResponse::success(secret)))
+}
+
+pub async fn delete_secret(
+    State(deployment): State<DeploymentImpl>,
+    Path(secret_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = Secret::delete(&deployment.db().pool, secret_id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/secrets", get(get_secrets).post(create_secret))
+        .route(
+            "/secrets/{secret_id}",
+            put(update_secret).delete(delete_secret),
+        )
+}
@@ -4,7 +4,7 @@ use axum::{
     Extension, Json, Router,
     extract::{Query, State},
     middleware::from_fn_with_state,
-    response::Json as ResponseJson,
+    response::{Json as ResponseJson, Response},
     routing::{get, post},
 };
 use db::models::{
@@ -22,16 +22,21 @@ use executors::{
     },
     profile::ExecutorProfileId,
 };
-use serde::Deserialize;
-use services::services::container::ContainerService;
+use serde::{Deserialize, Serialize};
+use services::services::{container::ContainerService, transcript::Transcript};
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
 use crate::{
-    DeploymentImpl, error::ApiError, middleware::load_session_middleware,
-    routes::task_attempts::util::restore_worktrees_to_process,
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_session_middleware,
+    routes::{
+        task_attempts::util::restore_worktrees_to_process,
+        transcripts::{TranscriptQuery, transcript_response},
+    },
 };
 
 #[derive(Debug, Deserialize)]
@@ -86,7 +91,7 @@ pub async fn create_session(
     Ok(ResponseJson(ApiResponse::success(session)))
 }
 
-#[derive(Debug, Deserialize, TS)]
+#[derive(Debug, Serialize, Deserialize, TS)]
 pub struct CreateFollowUpAttempt {
     pub prompt: String,
     pub variant: Option<String>,
@@ -247,10 +252,21 @@ pub async fn follow_up(
     Ok(ResponseJson(ApiResponse::success(execution_process)))
 }
 
+/// Export the conversation of every turn in the session as Markdown, HTML or JSON
+pub async fn export_transcript(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TranscriptQuery>,
+) -> Result<Response, ApiError> {
+    let transcript = Transcript::from_session(deployment.container(), &session).await?;
+    transcript_response(&transcript, query.format.unwrap_or_default())
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let session_id_router = Router::new()
         .route("/", get(get_session))
         .route("/follow-up", post(follow_up))
+        .route("/transcript", get(export_transcript))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_session_middleware,
@@ -1,24 +1,40 @@
 use axum::{
-    Extension, Json, Router, extract::State, middleware::from_fn_with_state,
-    response::Json as ResponseJson, routing::get,
+    Extension, Json, Router,
+    extract::{
+        Path, State,
+        ws::{WebSocket, WebSocketUpgrade},
+    },
+    middleware::from_fn_with_state,
+    response::{IntoResponse, Json as ResponseJson},
+    routing::{get, put},
 };
 use db::models::{scratch::DraftFollowUpData, session::Session};
 use deployment::Deployment;
+use futures_util::{SinkExt, StreamExt};
 use serde::Deserialize;
+use serde_json::json;
 use services::services::queued_message::QueueStatus;
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::{log_msg::LogMsg, response::ApiResponse};
+use uuid::Uuid;
 
 use crate::{DeploymentImpl, error::ApiError, middleware::load_session_middleware};
 
-/// Request body for queueing a follow-up message
+/// Request body for queueing or editing a follow-up message
 #[derive(Debug, Deserialize, TS)]
 pub struct QueueMessageRequest {
     pub message: String,
     pub variant: Option<String>,
 }
 
-/// Queue a follow-up message to be executed when the current execution finishes
+/// Request body for reordering the queue of a session
+#[derive(Debug, Deserialize, TS)]
+pub struct ReorderQueueRequest {
+    /// Every queued message id of the session, in the new send order
+    pub message_ids: Vec<Uuid>,
+}
+
+/// Append a follow-up message to be executed once the queued turns before it finish
 pub async fn queue_message(
     Extension(session): Extension<Session>,
     State(deployment): State<DeploymentImpl>,
@@ -29,27 +45,23 @@ pub async fn queue_message(
         variant: payload.variant,
     };
 
-    let queued = deployment
+    let status = deployment
         .queued_message_service()
-        .queue_message(session.id, data);
+        .queue_message(session.id, data)
+        .await?;
 
-    drop(deployment);
-
-    Ok(ResponseJson(ApiResponse::success(QueueStatus::Queued {
-        message: queued,
-    })))
+    Ok(ResponseJson(ApiResponse::success(status)))
 }
 
-/// Cancel a queued follow-up message
+/// Cancel all queued follow-up messages
 pub async fn cancel_queued_message(
     Extension(session): Extension<Session>,
     State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<QueueStatus>>, ApiError> {
     deployment
         .queued_message_service()
-        .cancel_queued(session.id);
-
-    drop(deployment);
+        .cancel_queued(session.id)
+        .await?;
 
     Ok(ResponseJson(ApiResponse::success(QueueStatus::Empty)))
 }
@@ -59,12 +71,111 @@ pub async fn get_queue_status(
     Extension(session): Extension<Session>,
     State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<QueueStatus>>, ApiError> {
-    let status = deployment.queued_message_service().get_status(session.id);
+    let status = deployment
+        .queued_message_service()
+        .get_status(session.id)
+        .await?;
+
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+pub async fn reorder_queue(
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ReorderQueueRequest>,
+) -> Result<ResponseJson<ApiResponse<QueueStatus>>, ApiError> {
+    let status = deployment
+        .queued_message_service()
+        .reorder(session.id, &payload.message_ids)
+        .await?
+        .ok_or_else(|| {
+            ApiError::BadRequest(
+                "Reorder must list every queued message of the session exactly once".to_string(),
+            )
+        })?;
+
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+pub async fn update_queued_message(
+    State(deployment): State<DeploymentImpl>,
+    Path((session_id, message_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<QueueMessageRequest>,
+) -> Result<ResponseJson<ApiResponse<QueueStatus>>, ApiError> {
+    let data = DraftFollowUpData {
+        message: payload.message,
+        variant: payload.variant,
+    };
+
+    let status = deployment
+        .queued_message_service()
+        .update_message(session_id, message_id, data)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Queued message not found".to_string()))?;
+
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+pub async fn delete_queued_message(
+    State(deployment): State<DeploymentImpl>,
+    Path((session_id, message_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<QueueStatus>>, ApiError> {
+    let status = deployment
+        .queued_message_service()
+        .remove_message(session_id, message_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Queued message not found".to_string()))?;
 
     Ok(ResponseJson(ApiResponse::success(status)))
 }
 
+pub async fn stream_queue_ws(
+    ws: WebSocketUpgrade,
+    Extension(session): Extension<Session>,
+    State(deployment): State<DeploymentImpl>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_queue_ws(socket, deployment, session.id).await {
+            tracing::warn!("queue WS closed: {}", e);
+        }
+    })
+}
+
+async fn handle_queue_ws(
+    socket: WebSocket,
+    deployment: DeploymentImpl,
+    session_id: Uuid,
+) -> anyhow::Result<()> {
+    let mut stream = deployment
+        .queued_message_service()
+        .stream_status(session_id)
+        .await?;
+
+    let (mut sender, mut receiver) = socket.split();
+
+    tokio::spawn(async move { while let Some(Ok(_)) = receiver.next().await {} });
+
+    while let Some(status) = stream.next().await {
+        let patch = json!([{
+            "op": "replace",
+            "path": "/queue",
+            "value": status
+        }]);
+        let msg = LogMsg::JsonPatch(serde_json::from_value(patch)?).to_ws_message_unchecked();
+        if sender.send(msg).await.is_err() {
+            tracing::debug!("Client disconnected from queue stream");
+            break;
+        }
+    }
+    Ok(())
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let message_router = Router::new().route(
+        "/{message_id}",
+        put(update_queued_message).delete(delete_queued_message),
+    );
+
     Router::new()
         .route(
             "/",
@@ -72,8 +183,11 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
                 .post(queue_message)
                 .delete(cancel_queued_message),
         )
+        .route("/order", put(reorder_queue))
+        .route("/stream/ws", get(stream_queue_ws))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_session_middleware,
         ))
+        .merge(message_router)
 }
@@ -8,6 +8,7 @@ pub mod util;
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
+    sync::{Arc, atomic::AtomicUsize},
 };
 
 use axum::{
@@ -22,7 +23,9 @@ use axum::{
     routing::{get, post},
 };
 use db::models::{
+    attempt_comparison::{AttemptComparison, AttemptComparisonCandidate},
     execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    executor_fallback::ExecutorFallback,
     merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
     project::{Project, ProjectError},
     project_repo::ProjectRepo,
@@ -45,12 +48,15 @@ use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
     container::ContainerService,
-    git::{ConflictOp, GitCliError, GitServiceError},
+    dev_server::DevServerInfo,
+    diff_stream::apply_stream_omit_policy,
+    git::{ConflictOp, DiffTarget, GitCliError, GitServiceError},
     github::GitHubService,
+    sparse_checkout::SparseCheckout,
 };
-use sqlx::Error as SqlxError;
+use sqlx::{Error as SqlxError, SqlitePool};
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::{diff::Diff, response::ApiResponse};
 use uuid::Uuid;
 
 use crate::{
@@ -89,6 +95,30 @@ pub struct DiffStreamQuery {
     pub stats_only: bool,
 }
 
+/// Changes of one workspace repository against its target branch
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct WorkspaceRepoDiff {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub diffs: Vec<Diff>,
+}
+
+/// Directories checked out in one workspace repository
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct WorkspaceRepoSparseCheckout {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    /// Cone-mode directories, or `null` when the whole repository is checked out
+    pub dirs: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct WidenSparseCheckoutRequest {
+    pub repo_id: Uuid,
+    /// Directories to check out in addition to the current ones
+    pub dirs: Vec<String>,
+}
+
 pub async fn get_task_attempts(
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<TaskAttemptQuery>,
@@ -109,8 +139,28 @@ pub struct CreateTaskAttemptBody {
     pub task_id: Uuid,
     pub executor_profile_id: ExecutorProfileId,
     pub repos: Vec<WorkspaceRepoInput>,
+    /// Race these profiles against `executor_profile_id`, each in its own workspace,
+    /// and group the attempts into a comparison
+    #[serde(default)]
+    #[ts(optional)]
+    pub additional_executor_profile_ids: Option<Vec<ExecutorProfileId>>,
+}
+
+/// The created attempt. Best-of-N requests also return the comparison grouping the
+/// attempts and every candidate workspace.
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct CreateTaskAttemptResponse {
+    #[serde(flatten)]
+    pub workspace: Workspace,
+    pub comparison_id: Option<Uuid>,
+    /// Workspaces of a best-of-N request in the order their profiles were requested;
+    /// empty for a single attempt
+    pub candidates: Vec<Workspace>,
 }
 
+/// Upper bound on workspaces created by a single best-of-N request
+const MAX_COMPARISON_CANDIDATES: usize = 8;
+
 #[derive(Debug, Serialize, Deserialize, ts_rs::TS)]
 pub struct WorkspaceRepoInput {
     pub repo_id: Uuid,
@@ -129,15 +179,20 @@ pub struct RunAgentSetupResponse {}
 pub async fn create_task_attempt(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateTaskAttemptBody>,
-) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
-    let executor_profile_id = payload.executor_profile_id.clone();
-
+) -> Result<ResponseJson<ApiResponse<CreateTaskAttemptResponse>>, ApiError> {
     if payload.repos.is_empty() {
         return Err(ApiError::BadRequest(
             "At least one repository is required".to_string(),
         ));
     }
 
+    let additional_profiles = payload.additional_executor_profile_ids.unwrap_or_default();
+    if additional_profiles.len() + 1 > MAX_COMPARISON_CANDIDATES {
+        return Err(ApiError::BadRequest(format!(
+            "At most {MAX_COMPARISON_CANDIDATES} attempts can be compared at once"
+        )));
+    }
+
     let pool = &deployment.db().pool;
     let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
         .await?
@@ -154,6 +209,120 @@ pub async fn create_task_attempt(
         .filter(|dir| !dir.is_empty())
         .cloned();
 
+    if additional_profiles.is_empty() {
+        let workspace = create_and_start_workspace(
+            &deployment,
+            &task,
+            agent_working_dir,
+            &payload.repos,
+            &payload.executor_profile_id,
+        )
+        .await?;
+
+        drop(deployment);
+
+        tracing::info!("Created attempt for task {}", task.id);
+        return Ok(ResponseJson(ApiResponse::success(
+            CreateTaskAttemptResponse {
+                workspace,
+                comparison_id: None,
+                candidates: Vec::new(),
+            },
+        )));
+    }
+
+    // Best-of-N: one workspace per profile, all branching from the same targets. The
+    // comparison is only recorded once every candidate exists; if any of them fails,
+    // the ones already started are removed again.
+    let profiles: Vec<ExecutorProfileId> = std::iter::once(payload.executor_profile_id)
+        .chain(additional_profiles)
+        .collect();
+    let mut workspaces = Vec::new();
+    for executor_profile_id in &profiles {
+        match create_and_start_workspace(
+            &deployment,
+            &task,
+            agent_working_dir.clone(),
+            &payload.repos,
+            executor_profile_id,
+        )
+        .await
+        {
+            Ok(workspace) => workspaces.push(workspace),
+            Err(e) => {
+                for workspace in &workspaces {
+                    discard_workspace(&deployment, workspace.id).await;
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    let comparison = match create_comparison(pool, task.id, &workspaces, &profiles).await {
+        Ok(comparison) => comparison,
+        Err(e) => {
+            for workspace in &workspaces {
+                discard_workspace(&deployment, workspace.id).await;
+            }
+            return Err(e.into());
+        }
+    };
+
+    drop(deployment);
+
+    tracing::info!(
+        "Created comparison {} for task {} with {} attempts",
+        comparison.id,
+        task.id,
+        workspaces.len()
+    );
+
+    // The first candidate stands in for the attempt
+    Ok(ResponseJson(ApiResponse::success(
+        CreateTaskAttemptResponse {
+            workspace: workspaces[0].clone(),
+            comparison_id: Some(comparison.id),
+            candidates: workspaces,
+        },
+    )))
+}
+
+/// Record a comparison and its candidates in one transaction
+async fn create_comparison(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    workspaces: &[Workspace],
+    profiles: &[ExecutorProfileId],
+) -> Result<AttemptComparison, SqlxError> {
+    let mut tx = pool.begin().await?;
+    let comparison = AttemptComparison::create(&mut *tx, Uuid::new_v4(), task_id).await?;
+    for (position, (workspace, executor_profile_id)) in workspaces.iter().zip(profiles).enumerate()
+    {
+        AttemptComparisonCandidate::create(
+            &mut *tx,
+            comparison.id,
+            workspace.id,
+            executor_profile_id,
+            position as i64,
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(comparison)
+}
+
+/// Create a workspace with its repos and initial session, then start it with the
+/// given profile. Start failures are logged; the workspace is still returned. If the
+/// workspace can't be set up it is removed again.
+async fn create_and_start_workspace(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    agent_working_dir: Option<String>,
+    repos: &[WorkspaceRepoInput],
+    executor_profile_id: &ExecutorProfileId,
+) -> Result<Workspace, ApiError> {
+    let pool = &deployment.db().pool;
+
     let attempt_id = Uuid::new_v4();
     let git_branch_name = deployment
         .container()
@@ -167,12 +336,11 @@ pub async fn create_task_attempt(
             agent_working_dir,
         },
         attempt_id,
-        payload.task_id,
+        task.id,
     )
     .await?;
 
-    let workspace_repos: Vec<CreateWorkspaceRepo> = payload
-        .repos
+    let workspace_repos: Vec<CreateWorkspaceRepo> = repos
         .iter()
         .map(|r| CreateWorkspaceRepo {
             repo_id: r.repo_id,
@@ -180,18 +348,24 @@ pub async fn create_task_attempt(
         })
         .collect();
 
-    WorkspaceRepo::create_many(pool, workspace.id, &workspace_repos).await?;
-
     // Create initial session for the workspace
-    let _session = Session::create(
-        pool,
-        &CreateSession {
-            executor: Some(executor_profile_id.to_string()),
-        },
-        Uuid::new_v4(),
-        workspace.id,
-    )
-    .await?;
+    let setup = async {
+        WorkspaceRepo::create_many(pool, workspace.id, &workspace_repos).await?;
+        Session::create(
+            pool,
+            &CreateSession {
+                executor: Some(executor_profile_id.to_string()),
+            },
+            Uuid::new_v4(),
+            workspace.id,
+        )
+        .await?;
+        Ok::<_, ApiError>(())
+    };
+    if let Err(e) = setup.await {
+        discard_workspace(deployment, workspace.id).await;
+        return Err(e);
+    }
 
     if let Err(err) = deployment
         .container()
@@ -201,11 +375,26 @@ pub async fn create_task_attempt(
         tracing::error!("Failed to start task attempt: {}", err);
     }
 
-    drop(deployment);
-
-    tracing::info!("Created attempt for task {}", task.id);
+    Ok(workspace)
+}
 
-    Ok(ResponseJson(ApiResponse::success(workspace)))
+/// Stop a workspace created by a request that failed part way, remove its worktrees
+/// and delete it. Failures are logged; the original error is what the caller reports.
+pub(crate) async fn discard_workspace(deployment: &DeploymentImpl, workspace_id: Uuid) {
+    let pool = &deployment.db().pool;
+    // Reload to pick up the container ref set while the workspace was started
+    match Workspace::find_by_id(pool, workspace_id).await {
+        Ok(Some(workspace)) => {
+            if let Err(e) = deployment.container().delete(&workspace).await {
+                tracing::error!("Failed to clean up workspace {}: {}", workspace_id, e);
+            }
+        }
+        Ok(None) => return,
+        Err(e) => tracing::error!("Failed to load workspace {}: {}", workspace_id, e),
+    }
+    if let Err(e) = Workspace::delete(pool, workspace_id).await {
+        tracing::error!("Failed to delete workspace {}: {}", workspace_id, e);
+    }
 }
 
 #[axum::debug_handler]
@@ -248,6 +437,52 @@ pub async fn stream_task_attempt_diff_ws(
     })
 }
 
+/// One-off snapshot of the diff stream, for clients that can't hold a WebSocket open
+pub async fn get_task_attempt_diff(
+    Query(params): Query<DiffStreamQuery>,
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspaceRepoDiff>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let workspace_root = PathBuf::from(container_ref);
+    let repos =
+        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id).await?;
+
+    let sent_bytes = Arc::new(AtomicUsize::new(0));
+    let mut repo_diffs = Vec::with_capacity(repos.len());
+    for RepoWithTargetBranch {
+        repo,
+        target_branch,
+    } in repos
+    {
+        let base_commit =
+            deployment
+                .git()
+                .get_base_commit(&repo.path, &workspace.branch, &target_branch)?;
+        let mut diffs = deployment.git().get_diffs(
+            DiffTarget::Worktree {
+                worktree_path: &workspace_root.join(&repo.name),
+                base_commit: &base_commit,
+            },
+            None,
+        )?;
+        for diff in &mut diffs {
+            apply_stream_omit_policy(diff, &sent_bytes, params.stats_only);
+        }
+        repo_diffs.push(WorkspaceRepoDiff {
+            repo_id: repo.id,
+            repo_name: repo.name,
+            diffs,
+        });
+    }
+
+    Ok(ResponseJson(ApiResponse::success(repo_diffs)))
+}
+
 async fn handle_task_attempt_diff_ws(
     socket: WebSocket,
     deployment: DeploymentImpl,
@@ -1061,14 +1296,14 @@ pub async fn start_dev_server(
         .await?
         .ok_or(SqlxError::RowNotFound)?;
 
-    // Stop any existing dev servers for this project
+    // Replace this workspace's dev server; other workspaces keep theirs on their own ports
     let existing_dev_servers =
-        match ExecutionProcess::find_running_dev_servers_by_project(pool, project.id).await {
+        match ExecutionProcess::find_running_dev_servers_by_workspace(pool, workspace.id).await {
             Ok(servers) => servers,
             Err(e) => {
                 tracing::error!(
-                    "Failed to find running dev servers for project {}: {}",
-                    project.id,
+                    "Failed to find running dev servers for workspace {}: {}",
+                    workspace.id,
                     e
                 );
                 return Err(ApiError::Workspace(WorkspaceError::ValidationError(
@@ -1079,9 +1314,9 @@ pub async fn start_dev_server(
 
     for dev_server in existing_dev_servers {
         tracing::info!(
-            "Stopping existing dev server {} for project {}",
+            "Stopping existing dev server {} for workspace {}",
             dev_server.id,
-            project.id
+            workspace.id
         );
 
         if let Err(e) = deployment
@@ -1092,6 +1327,10 @@ pub async fn start_dev_server(
             tracing::error!("Failed to stop dev server {}: {}", dev_server.id, e);
         }
     }
+    deployment
+        .container()
+        .dev_servers()
+        .reset_restarts(workspace.id);
 
     // Get dev script from project (dev_script is project-level, not per-repo)
     let dev_script = match &project.dev_script {
@@ -1153,6 +1392,14 @@ pub async fn start_dev_server(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+pub async fn get_dev_server(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<DevServerInfo>>>, ApiError> {
+    let dev_server = deployment.container().dev_servers().get(workspace.id);
+    Ok(ResponseJson(ApiResponse::success(dev_server)))
+}
+
 pub async fn get_task_attempt_children(
     Extension(workspace): Extension<Workspace>,
     State(deployment): State<DeploymentImpl>,
@@ -1385,15 +1632,91 @@ pub async fn get_task_attempt_repos(
     Ok(ResponseJson(ApiResponse::success(repos)))
 }
 
+pub async fn get_task_attempt_executor_fallbacks(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutorFallback>>>, ApiError> {
+    let fallbacks =
+        ExecutorFallback::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+
+    Ok(ResponseJson(ApiResponse::success(fallbacks)))
+}
+
+pub async fn get_task_attempt_sparse_checkout(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspaceRepoSparseCheckout>>>, ApiError> {
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let workspace_root = PathBuf::from(container_ref);
+    let repos =
+        WorkspaceRepo::find_repos_for_workspace(&deployment.db().pool, workspace.id).await?;
+
+    let mut sparse_checkouts = Vec::with_capacity(repos.len());
+    for repo in repos {
+        let dirs = deployment
+            .git()
+            .get_sparse_checkout(&workspace_root.join(&repo.name))?;
+        sparse_checkouts.push(WorkspaceRepoSparseCheckout {
+            repo_id: repo.id,
+            repo_name: repo.name,
+            dirs,
+        });
+    }
+
+    Ok(ResponseJson(ApiResponse::success(sparse_checkouts)))
+}
+
+/// Check out more directories in a sparse workspace repository, e.g. when the agent needs
+/// code outside the directories the workspace was created with
+pub async fn widen_task_attempt_sparse_checkout(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<WidenSparseCheckoutRequest>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceRepoSparseCheckout>>, ApiError> {
+    let Some(sparse_checkout) = SparseCheckout::new(&payload.dirs) else {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Directories must be inside the repository and not its root",
+        )));
+    };
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let repo = WorkspaceRepo::find_repos_for_workspace(&deployment.db().pool, workspace.id)
+        .await?
+        .into_iter()
+        .find(|repo| repo.id == payload.repo_id)
+        .ok_or(RepoError::NotFound)?;
+
+    let dirs = deployment.git().widen_sparse_checkout(
+        &PathBuf::from(container_ref).join(&repo.name),
+        sparse_checkout.dirs(),
+    )?;
+
+    Ok(ResponseJson(ApiResponse::success(
+        WorkspaceRepoSparseCheckout {
+            repo_id: repo.id,
+            repo_name: repo.name,
+            dirs,
+        },
+    )))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_attempt_id_router = Router::new()
         .route("/", get(get_task_attempt))
         .route("/run-agent-setup", post(run_agent_setup))
         .route("/gh-cli-setup", post(gh_cli_setup_handler))
         .route("/start-dev-server", post(start_dev_server))
+        .route("/dev-server", get(get_dev_server))
         .route("/run-setup-script", post(run_setup_script))
         .route("/run-cleanup-script", post(run_cleanup_script))
         .route("/branch-status", get(get_task_attempt_branch_status))
+        .route("/diff", get(get_task_attempt_diff))
         .route("/diff/ws", get(stream_task_attempt_diff_ws))
         .route("/merge", post(merge_task_attempt))
         .route("/push", post(push_task_attempt_branch))
@@ -1409,6 +1732,14 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/change-target-branch", post(change_target_branch))
         .route("/rename-branch", post(rename_branch))
         .route("/repos", get(get_task_attempt_repos))
+        .route(
+            "/executor-fallbacks",
+            get(get_task_attempt_executor_fallbacks),
+        )
+        .route(
+            "/sparse-checkout",
+            get(get_task_attempt_sparse_checkout).post(widen_task_attempt_sparse_checkout),
+        )
         .layer(from_fn_with_state(
             deployment.clone(),
             load_workspace_middleware,
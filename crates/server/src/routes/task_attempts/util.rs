@@ -81,3 +81,59 @@ pub async fn restore_worktrees_to_process(
 
     Ok(())
 }
+
+/// Reset the worktrees of a freshly created fork workspace to the state of the source
+/// process, either before it ran or after it finished. Repos without a recorded commit
+/// are left at their target branch.
+pub async fn reset_fork_worktrees_to_process(
+    deployment: &DeploymentImpl,
+    pool: &SqlitePool,
+    fork: &Workspace,
+    process: &ExecutionProcess,
+    after: bool,
+) -> Result<(), ApiError> {
+    let repos = WorkspaceRepo::find_repos_for_workspace(pool, fork.id).await?;
+    let repo_states =
+        ExecutionProcessRepoState::find_by_execution_process_id(pool, process.id).await?;
+
+    let container_ref = deployment.container().ensure_container_exists(fork).await?;
+    let workspace_dir = PathBuf::from(container_ref);
+
+    for repo in &repos {
+        let repo_state = repo_states.iter().find(|s| s.repo_id == repo.id);
+
+        let recorded = if after {
+            repo_state.and_then(|s| s.after_head_commit.clone().or(s.before_head_commit.clone()))
+        } else {
+            repo_state.and_then(|s| s.before_head_commit.clone())
+        };
+        let target_oid = match recorded {
+            Some(oid) => Some(oid),
+            None => {
+                ExecutionProcess::find_prev_after_head_commit(
+                    pool,
+                    process.session_id,
+                    process.id,
+                    repo.id,
+                )
+                .await?
+            }
+        };
+
+        if let Some(oid) = target_oid {
+            let outcome = deployment.git().reconcile_worktree_to_commit(
+                &workspace_dir.join(&repo.name),
+                &oid,
+                WorktreeResetOptions::new(true, true, false, true),
+            );
+            if outcome.needed && !outcome.applied {
+                return Err(ApiError::Conflict(format!(
+                    "Failed to reset {} to commit {}",
+                    repo.name, oid
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -13,22 +13,27 @@ use axum::{
     routing::{delete, get, post, put},
 };
 use db::models::{
-    execution_process::ExecutionProcess,
-    execution_process::ExecutionProcessRunReason,
+    approval_record::ApprovalRecord,
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
     image::TaskImage,
     project::{Project, ProjectError},
     repo::Repo,
     task::{CreateTask, Task, TaskStatus, TaskWithAttemptStatus, UpdateTask},
-    task_history::TaskHistory,
+    task_history::{CreateTaskHistory, TaskHistory, TaskHistoryEventType},
+    task_plan::{TaskPlan, TaskPlanStatus},
+    task_transcript::TaskTranscript,
     workspace::{CreateWorkspace, Workspace},
     workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
 };
 use deployment::Deployment;
-use executors::profile::ExecutorProfileId;
+use executors::{actions::ExecutorActionType, profile::ExecutorProfileId};
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use services::services::{
-    container::ContainerService, share::ShareError, workspace_manager::WorkspaceManager,
+    container::ContainerService,
+    share::ShareError,
+    transcript::{TRANSCRIPT_VERSION, Transcript},
+    workspace_manager::WorkspaceManager,
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
@@ -180,11 +185,11 @@ pub async fn create_task_and_start(
     let pool = &deployment.db().pool;
 
     let task_id = Uuid::new_v4();
-    
+
     // Ensure task starts with Todo status so history is created when it transitions to InProgress
     let mut create_task_data = payload.task.clone();
     create_task_data.status = Some(TaskStatus::Todo);
-    
+
     let task = Task::create(pool, &create_task_data, task_id).await?;
 
     if let Some(image_ids) = &payload.task.image_ids {
@@ -239,12 +244,15 @@ pub async fn create_task_and_start(
     )
     .await?;
 
-    let is_attempt_running = deployment
+    let started_process = deployment
         .container()
         .start_workspace(&workspace, payload.executor_profile_id.clone())
         .await
         .inspect_err(|err| tracing::error!("Failed to start task attempt: {}", err))
-        .is_ok();
+        .ok();
+    let is_attempt_running = started_process.is_some();
+    let is_attempt_queued =
+        started_process.is_some_and(|process| process.status == ExecutionProcessStatus::Queued);
 
     let task = Task::find_by_id(pool, task.id)
         .await?
@@ -254,6 +262,7 @@ pub async fn create_task_and_start(
     Ok(ResponseJson(ApiResponse::success(TaskWithAttemptStatus {
         task,
         has_in_progress_attempt: is_attempt_running,
+        has_queued_attempt: is_attempt_queued,
         last_attempt_failed: false,
         executor: payload.executor_profile_id.executor.to_string(),
     })))
@@ -276,6 +285,7 @@ pub async fn update_task(
     };
     let status = payload.status.unwrap_or(existing_task.status);
     let intent = payload.intent.unwrap_or(existing_task.intent);
+    let priority = payload.priority.unwrap_or(existing_task.priority);
     let parent_workspace_id = payload
         .parent_workspace_id
         .or(existing_task.parent_workspace_id);
@@ -288,6 +298,7 @@ pub async fn update_task(
         description,
         status,
         intent,
+        priority,
         parent_workspace_id,
     )
     .await?;
@@ -385,6 +396,13 @@ pub async fn delete_task(
     // Commit the transaction - if this fails, all changes are rolled back
     tx.commit().await?;
 
+    for attempt in &attempts {
+        deployment
+            .container()
+            .terminals()
+            .close_workspace(attempt.id);
+    }
+
     if total_children_affected > 0 {
         tracing::info!(
             "Nullified {} child task references before deleting task {}",
@@ -563,6 +581,206 @@ pub async fn get_task_history(
     Ok(ResponseJson(ApiResponse::success(history)))
 }
 
+pub async fn get_task_approvals(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ApprovalRecord>>>, ApiError> {
+    let approvals = ApprovalRecord::find_by_task_id(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(approvals)))
+}
+
+pub async fn get_task_transcripts(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskTranscript>>>, ApiError> {
+    let transcripts = TaskTranscript::find_by_task_id(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(transcripts)))
+}
+
+/// Attach a JSON transcript exported from another session to this task
+pub async fn import_task_transcript(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(transcript): Json<Transcript>,
+) -> Result<ResponseJson<ApiResponse<TaskTranscript>>, ApiError> {
+    if transcript.version > TRANSCRIPT_VERSION {
+        return Err(ApiError::BadRequest(format!(
+            "Unsupported transcript version {} (newest supported is {})",
+            transcript.version, TRANSCRIPT_VERSION
+        )));
+    }
+
+    let value = serde_json::to_value(&transcript)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid transcript: {e}")))?;
+    let record = TaskTranscript::create(
+        &deployment.db().pool,
+        task.id,
+        Some(transcript.session_id),
+        &transcript.title,
+        &value,
+    )
+    .await?;
+
+    Ok(ResponseJson(ApiResponse::success(record)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateTaskPlanRequest {
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ApproveTaskPlanRequest {
+    /// Final edits to apply before approving
+    pub content: Option<String>,
+    /// Profile for the execution run. Defaults to the planning run's executor
+    /// without its variant, e.g. Claude Code instead of Claude Code PLAN.
+    pub executor_profile_id: Option<ExecutorProfileId>,
+}
+
+pub async fn get_task_plan(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<TaskPlan>>>, ApiError> {
+    let plan = TaskPlan::find_by_task_id(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(plan)))
+}
+
+pub async fn update_task_plan(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateTaskPlanRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskPlan>>, ApiError> {
+    let plan = edit_task_plan(&deployment, &task, payload.content).await?;
+    Ok(ResponseJson(ApiResponse::success(plan)))
+}
+
+/// Approve the task's draft plan and launch an execution run seeded with it
+pub async fn approve_task_plan(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ApproveTaskPlanRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskPlan>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let mut plan = match payload.content {
+        Some(content) => edit_task_plan(&deployment, &task, content).await?,
+        None => TaskPlan::find_by_task_id(pool, task.id)
+            .await?
+            .filter(|plan| plan.status == TaskPlanStatus::Draft)
+            .ok_or_else(|| ApiError::BadRequest("Task has no draft plan".to_string()))?,
+    };
+
+    let executor_profile_id = match payload.executor_profile_id {
+        Some(profile) => profile,
+        None => planning_profile(&deployment, &plan).await?.ok_or_else(|| {
+            ApiError::BadRequest(
+                "Could not determine the planning profile; pass executor_profile_id".to_string(),
+            )
+        })?,
+    };
+
+    // Claim the draft before starting the run so a concurrent approval cannot start
+    // a second execution of the same plan
+    plan = TaskPlan::mark_approved(pool, task.id)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("Plan has already been approved".to_string()))?;
+    let execution_process = match deployment
+        .container()
+        .start_plan_execution(&plan, &task, executor_profile_id.clone())
+        .await
+    {
+        Ok(execution_process) => execution_process,
+        Err(e) => {
+            TaskPlan::reopen(pool, task.id).await?;
+            return Err(e.into());
+        }
+    };
+    plan = TaskPlan::set_execution_process(pool, task.id, execution_process.id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    TaskHistory::create(
+        pool,
+        &CreateTaskHistory {
+            task_id: task.id,
+            event_type: TaskHistoryEventType::PlanApproved,
+            old_value: None,
+            new_value: Some(plan.content.clone()),
+            metadata: Some(
+                serde_json::json!({
+                    "revision": plan.revision,
+                    "executor_profile_id": executor_profile_id,
+                    "execution_process_id": execution_process.id,
+                })
+                .to_string(),
+            ),
+        },
+    )
+    .await?;
+
+    tracing::info!(
+        "Approved plan revision {} for task {}, started execution {}",
+        plan.revision,
+        task.id,
+        execution_process.id
+    );
+
+    Ok(ResponseJson(ApiResponse::success(plan)))
+}
+
+async fn edit_task_plan(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    content: String,
+) -> Result<TaskPlan, ApiError> {
+    let pool = &deployment.db().pool;
+    let previous = TaskPlan::find_by_task_id(pool, task.id).await?;
+    let Some(previous) = previous.filter(|plan| plan.status == TaskPlanStatus::Draft) else {
+        return Err(ApiError::BadRequest("Task has no draft plan".to_string()));
+    };
+    if previous.content == content {
+        return Ok(previous);
+    }
+
+    let plan = TaskPlan::update_content(pool, task.id, &content)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Task has no draft plan".to_string()))?;
+
+    TaskHistory::create(
+        pool,
+        &CreateTaskHistory {
+            task_id: task.id,
+            event_type: TaskHistoryEventType::PlanEdited,
+            old_value: Some(previous.content),
+            new_value: Some(plan.content.clone()),
+            metadata: Some(serde_json::json!({ "revision": plan.revision }).to_string()),
+        },
+    )
+    .await?;
+
+    Ok(plan)
+}
+
+/// Profile of the run that produced the plan, without its variant
+async fn planning_profile(
+    deployment: &DeploymentImpl,
+    plan: &TaskPlan,
+) -> Result<Option<ExecutorProfileId>, ApiError> {
+    let Some(source_id) = plan.source_execution_process_id else {
+        return Ok(None);
+    };
+    let Some(source) = ExecutionProcess::find_by_id(&deployment.db().pool, source_id).await? else {
+        return Ok(None);
+    };
+    let profile = match source.executor_action().map(|action| action.typ()) {
+        Ok(ExecutorActionType::CodingAgentInitialRequest(request)) => &request.executor_profile_id,
+        Ok(ExecutorActionType::CodingAgentFollowUpRequest(request)) => &request.executor_profile_id,
+        _ => return Ok(None),
+    };
+    Ok(Some(ExecutorProfileId::new(profile.executor)))
+}
+
 pub async fn trigger_review(
     Extension(task): Extension<Task>,
     State(deployment): State<DeploymentImpl>,
@@ -670,6 +888,13 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/", delete(delete_task))
         .route("/share", post(share_task))
         .route("/history", get(get_task_history))
+        .route("/approvals", get(get_task_approvals))
+        .route(
+            "/transcripts",
+            get(get_task_transcripts).post(import_task_transcript),
+        )
+        .route("/plan", get(get_task_plan).put(update_task_plan))
+        .route("/plan/approve", post(approve_task_plan))
         .route("/trigger-review", post(trigger_review))
         .route("/generate-jira-template", post(generate_jira_template))
         .route("/jira-ticket", post(create_jira_ticket));
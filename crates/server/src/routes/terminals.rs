@@ -0,0 +1,167 @@
+use std::path::PathBuf;
+
+use axum::{
+    Json, Router,
+    extract::{
+        Path, Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::{IntoResponse, Json as ResponseJson},
+    routing::{delete, get},
+};
+use db::models::workspace::Workspace;
+use deployment::Deployment;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use services::services::{
+    container::ContainerService,
+    terminal::{TerminalClientMessage, TerminalSession, TerminalSize},
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize)]
+pub struct TerminalQuery {
+    pub workspace_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTerminalRequest {
+    pub workspace_id: Uuid,
+    pub cols: u16,
+    pub rows: u16,
+    /// Record the terminal output, with secrets masked, to a log file
+    #[serde(default)]
+    pub record: bool,
+}
+
+pub async fn get_terminals(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TerminalQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<TerminalSession>>>, ApiError> {
+    let terminals = deployment.container().terminals().list(query.workspace_id);
+    Ok(ResponseJson(ApiResponse::success(terminals)))
+}
+
+pub async fn create_terminal(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTerminalRequest>,
+) -> Result<ResponseJson<ApiResponse<TerminalSession>>, ApiError> {
+    let workspace = Workspace::find_by_id(&deployment.db().pool, payload.workspace_id)
+        .await?
+        .ok_or(ApiError::Database(sqlx::Error::RowNotFound))?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    // Start where the agents run
+    let cwd = match workspace
+        .agent_working_dir
+        .as_deref()
+        .filter(|dir| !dir.is_empty())
+    {
+        Some(dir) => PathBuf::from(&container_ref).join(dir),
+        None => PathBuf::from(&container_ref),
+    };
+    // The shell can print its secrets like any other process, e.g. `env`
+    let (env, redactor) = deployment.container().workspace_env(&workspace).await?;
+
+    let terminal = deployment.container().terminals().open(
+        workspace.id,
+        &cwd,
+        &env,
+        redactor,
+        TerminalSize {
+            cols: payload.cols,
+            rows: payload.rows,
+        },
+        payload.record,
+    )?;
+    Ok(ResponseJson(ApiResponse::success(terminal)))
+}
+
+pub async fn delete_terminal(
+    State(deployment): State<DeploymentImpl>,
+    Path(terminal_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    deployment.container().terminals().kill(terminal_id)?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn stream_terminal_ws(
+    ws: WebSocketUpgrade,
+    State(deployment): State<DeploymentImpl>,
+    Path(terminal_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (scrollback, output) = deployment.container().terminals().attach(terminal_id)?;
+
+    Ok(ws.on_upgrade(move |socket| async move {
+        if let Err(e) =
+            handle_terminal_ws(socket, deployment, terminal_id, scrollback, output).await
+        {
+            tracing::warn!("terminal WS closed: {}", e);
+        }
+    }))
+}
+
+/// Output goes out as binary frames; input and resizes come in as `TerminalClientMessage`s.
+/// Closing the socket detaches from the terminal without killing the shell.
+async fn handle_terminal_ws(
+    socket: WebSocket,
+    deployment: DeploymentImpl,
+    terminal_id: Uuid,
+    scrollback: Vec<u8>,
+    mut output: broadcast::Receiver<Vec<u8>>,
+) -> anyhow::Result<()> {
+    let terminals = deployment.container().terminals().clone();
+    let (mut sender, mut receiver) = socket.split();
+
+    if !scrollback.is_empty() {
+        sender.send(Message::Binary(scrollback.into())).await?;
+    }
+
+    loop {
+        tokio::select! {
+            chunk = output.recv() => match chunk {
+                Ok(chunk) => sender.send(Message::Binary(chunk.into())).await?,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::debug!("Terminal {} client skipped {} chunks", terminal_id, skipped);
+                }
+                // The shell exited
+                Err(RecvError::Closed) => break,
+            },
+            msg = receiver.next() => match msg {
+                Some(Ok(Message::Text(text))) => {
+                    match serde_json::from_str::<TerminalClientMessage>(text.as_str()) {
+                        Ok(TerminalClientMessage::Input { data }) => {
+                            terminals.write(terminal_id, data.as_bytes())?
+                        }
+                        Ok(TerminalClientMessage::Resize { cols, rows }) => {
+                            terminals.resize(terminal_id, TerminalSize { cols, rows })?
+                        }
+                        Err(e) => tracing::debug!("Ignoring terminal message: {}", e),
+                    }
+                }
+                Some(Ok(Message::Binary(data))) => terminals.write(terminal_id, &data)?,
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+        }
+    }
+
+    let _ = sender.close().await;
+    Ok(())
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/terminals", get(get_terminals).post(create_terminal))
+        .route("/terminals/{terminal_id}", delete(delete_terminal))
+        .route("/terminals/{terminal_id}/ws", get(stream_terminal_ws))
+}
@@ -0,0 +1,81 @@
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::get,
+};
+use db::models::task_transcript::TaskTranscript;
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::transcript::{Transcript, TranscriptFormat};
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize)]
+pub struct TranscriptQuery {
+    /// Defaults to JSON, the format accepted by imports
+    pub format: Option<TranscriptFormat>,
+}
+
+/// Render a transcript as a downloadable file in the requested format
+pub fn transcript_response(
+    transcript: &Transcript,
+    format: TranscriptFormat,
+) -> Result<Response, ApiError> {
+    let body = transcript
+        .render(format)
+        .map_err(|e| ApiError::BadRequest(format!("Failed to render transcript: {e}")))?;
+    let filename = format!(
+        "transcript-{}.{}",
+        transcript.session_id.simple(),
+        format.extension()
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Download an imported transcript
+pub async fn get_transcript(
+    State(deployment): State<DeploymentImpl>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<TranscriptQuery>,
+) -> Result<Response, ApiError> {
+    let record = TaskTranscript::find_by_id(&deployment.db().pool, id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Transcript not found".to_string()))?;
+    let transcript: Transcript = serde_json::from_value(record.transcript.0)
+        .map_err(|e| ApiError::BadRequest(format!("Stored transcript is invalid: {e}")))?;
+
+    transcript_response(&transcript, query.format.unwrap_or_default())
+}
+
+pub async fn delete_transcript(
+    State(deployment): State<DeploymentImpl>,
+    Path(id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let removed = TaskTranscript::delete(&deployment.db().pool, id).await?;
+    if removed == 0 {
+        return Err(ApiError::BadRequest("Transcript not found".to_string()));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route(
+        "/transcripts/{id}",
+        get(get_transcript).delete(delete_transcript),
+    )
+}
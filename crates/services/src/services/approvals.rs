@@ -1,20 +1,32 @@
 pub mod executor_approvals;
+pub mod policy;
 
-use std::{collections::HashMap, sync::Arc, time::Duration as StdDuration};
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration as StdDuration};
 
 use dashmap::DashMap;
-use db::models::{
-    execution_process::ExecutionProcess,
-    task::{Task, TaskStatus},
+use db::{
+    DBService,
+    models::{
+        approval_policy_rule::{ApprovalPolicyDecision, ApprovalPolicyRule},
+        approval_record::{ApprovalRecord, ApprovalResponder},
+        execution_process::{ExecutionProcess, ExecutionProcessStatus},
+        project::Project,
+        task::{Task, TaskStatus},
+    },
 };
 use executors::{
-    approvals::ToolCallMetadata,
+    approvals::{ApprovalTimeoutAction, ToolCallMetadata},
+    command::CmdOverrides,
     logs::{
         NormalizedEntry, NormalizedEntryType, ToolStatus,
-        utils::patch::{ConversationPatch, extract_normalized_entry_from_patch},
+        utils::{
+            EntryIndexProvider,
+            patch::{ConversationPatch, extract_normalized_entry_from_patch},
+        },
     },
 };
-use futures::future::{BoxFuture, FutureExt, Shared};
+use futures::future::{self, BoxFuture, FutureExt, Shared};
+use policy::PolicyMatch;
 use sqlx::{Error as SqlxError, SqlitePool};
 use thiserror::Error;
 use tokio::sync::{RwLock, oneshot};
@@ -25,6 +37,8 @@ use utils::{
 };
 use uuid::Uuid;
 
+use crate::services::config::ApprovalConfig;
+
 #[derive(Debug)]
 struct PendingApproval {
     entry_index: usize,
@@ -42,8 +56,54 @@ pub struct ToolContext {
     pub execution_process_id: Uuid,
 }
 
+/// How long approval requests of one execution wait for the user and what happens
+/// when nobody answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApprovalTimeoutSettings {
+    pub timeout: chrono::Duration,
+    pub action: ApprovalTimeoutAction,
+    pub reminder_interval: Option<StdDuration>,
+}
+
+impl ApprovalTimeoutSettings {
+    /// Project settings take precedence over the executor profile, which takes
+    /// precedence over the global config. Reminders are configured globally.
+    pub fn resolve(
+        config: &ApprovalConfig,
+        project: &Project,
+        profile: Option<&CmdOverrides>,
+    ) -> Self {
+        let timeout_minutes = project
+            .approval_timeout_minutes
+            .filter(|minutes| *minutes > 0)
+            .or_else(|| {
+                profile
+                    .and_then(|overrides| overrides.approval_timeout_minutes)
+                    .filter(|minutes| *minutes > 0)
+                    .map(i64::from)
+            })
+            .unwrap_or(i64::from(config.timeout_minutes))
+            .max(1);
+        let action = project
+            .approval_timeout_action
+            .or_else(|| profile.and_then(|overrides| overrides.approval_timeout_action))
+            .unwrap_or(config.timeout_action);
+        let reminder_interval = config
+            .reminder_interval_minutes
+            .filter(|minutes| *minutes > 0)
+            .map(|minutes| StdDuration::from_secs(u64::from(minutes) * 60));
+
+        Self {
+            timeout: chrono::Duration::minutes(timeout_minutes),
+            action,
+            reminder_interval,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Approvals {
+    db: DBService,
     pending: Arc<DashMap<String, PendingApproval>>,
     completed: Arc<DashMap<String, ApprovalStatus>>,
     msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
@@ -66,18 +126,93 @@ pub enum ApprovalError {
 }
 
 impl Approvals {
-    pub fn new(msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>) -> Self {
+    pub fn new(db: DBService, msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>) -> Self {
         Self {
+            db,
             pending: Arc::new(DashMap::new()),
             completed: Arc::new(DashMap::new()),
             msg_stores,
         }
     }
 
+    /// Decide the request with the project's approval policy, or park it until the
+    /// user responds or it times out. `on_timeout` decides how an unanswered request
+    /// resolves.
     pub async fn create_with_waiter(
         &self,
         request: ApprovalRequest,
+        on_timeout: ApprovalTimeoutAction,
     ) -> Result<(ApprovalRequest, ApprovalWaiter), ApprovalError> {
+        if let Err(e) = ApprovalRecord::create(&self.db.pool, &request).await {
+            tracing::warn!("Failed to persist approval request {}: {}", request.id, e);
+        }
+
+        match self.evaluate_policy(&request).await {
+            Some(policy_match) if policy_match.decision != ApprovalPolicyDecision::Ask => {
+                let waiter = self.resolve_by_policy(&request, policy_match).await;
+                return Ok((request, waiter));
+            }
+            Some(policy_match) => tracing::debug!(
+                "Approval policy rule {} requires asking for tool '{}'",
+                policy_match.rule_id,
+                request.tool_name
+            ),
+            None => {}
+        }
+
+        let waiter = self.park(&request, on_timeout).await?;
+        Ok((request, waiter))
+    }
+
+    /// Re-register approvals that were still pending when the server stopped. Requests
+    /// whose executor is no longer running can never be answered, so they are closed.
+    pub async fn restore_pending(&self) -> Result<(), ApprovalError> {
+        let pool = &self.db.pool;
+        for record in ApprovalRecord::find_pending(pool).await? {
+            if self.pending.contains_key(&record.id) {
+                continue;
+            }
+
+            let executor_alive = ExecutionProcess::find_by_id(pool, record.execution_process_id)
+                .await?
+                .is_some_and(|process| process.status == ExecutionProcessStatus::Running)
+                && self
+                    .msg_store_by_id(&record.execution_process_id)
+                    .await
+                    .is_some();
+            if !executor_alive || record.timeout_at <= chrono::Utc::now() {
+                ApprovalRecord::record_response(
+                    pool,
+                    &record.id,
+                    &ApprovalStatus::TimedOut,
+                    ApprovalResponder::System,
+                    None,
+                )
+                .await?;
+                continue;
+            }
+
+            let request = ApprovalRequest {
+                id: record.id,
+                tool_name: record.tool_name,
+                tool_input: record.tool_input.0,
+                tool_call_id: record.tool_call_id,
+                execution_process_id: record.execution_process_id,
+                created_at: record.created_at,
+                timeout_at: record.timeout_at,
+            };
+            self.park(&request, ApprovalTimeoutAction::Deny).await?;
+            tracing::info!("Restored pending approval {}", request.id);
+        }
+        Ok(())
+    }
+
+    /// Mark the tool call as pending approval and wait for a response or the timeout
+    async fn park(
+        &self,
+        request: &ApprovalRequest,
+        on_timeout: ApprovalTimeoutAction,
+    ) -> Result<ApprovalWaiter, ApprovalError> {
         let (tx, rx) = oneshot::channel();
         let waiter: ApprovalWaiter = rx
             .map(|result| result.unwrap_or(ApprovalStatus::TimedOut))
@@ -129,8 +264,135 @@ impl Approvals {
             );
         }
 
-        self.spawn_timeout_watcher(req_id.clone(), request.timeout_at, waiter.clone());
-        Ok((request, waiter))
+        self.spawn_timeout_watcher(
+            req_id.clone(),
+            request.timeout_at,
+            on_timeout,
+            waiter.clone(),
+        );
+        Ok(waiter)
+    }
+
+    /// Whether the request has already been decided, e.g. by an approval policy rule
+    pub fn is_completed(&self, id: &str) -> bool {
+        self.completed.contains_key(id)
+    }
+
+    async fn evaluate_policy(&self, request: &ApprovalRequest) -> Option<PolicyMatch> {
+        let ctx = match ExecutionProcess::load_context(&self.db.pool, request.execution_process_id)
+            .await
+        {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to load context for approval policy of execution process {}: {}",
+                    request.execution_process_id,
+                    e
+                );
+                return None;
+            }
+        };
+        let rules =
+            match ApprovalPolicyRule::find_by_project_id(&self.db.pool, ctx.project.id).await {
+                Ok(rules) => rules,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to load approval policy rules for project {}: {}",
+                        ctx.project.id,
+                        e
+                    );
+                    return None;
+                }
+            };
+
+        let worktree = ctx.workspace.container_ref.as_deref().map(Path::new);
+        policy::evaluate(&rules, &request.tool_name, &request.tool_input, worktree)
+    }
+
+    /// Complete a request decided by a policy rule and record the decision in the
+    /// conversation, so reviewers can see which rule approved or denied the tool call.
+    async fn resolve_by_policy(
+        &self,
+        request: &ApprovalRequest,
+        policy_match: PolicyMatch,
+    ) -> ApprovalWaiter {
+        let reason = (!policy_match.reason.is_empty()).then(|| policy_match.reason.clone());
+        let (status, verb) = match policy_match.decision {
+            ApprovalPolicyDecision::Allow => (ApprovalStatus::Approved, "Auto-approved"),
+            _ => (
+                ApprovalStatus::Denied {
+                    reason: Some(match &reason {
+                        Some(reason) => format!("Denied by approval policy: {reason}"),
+                        None => "Denied by approval policy".to_string(),
+                    }),
+                },
+                "Auto-denied",
+            ),
+        };
+        self.completed.insert(request.id.clone(), status.clone());
+        if let Err(e) = ApprovalRecord::record_response(
+            &self.db.pool,
+            &request.id,
+            &status,
+            ApprovalResponder::Policy,
+            Some(policy_match.rule_id),
+        )
+        .await
+        {
+            tracing::warn!(
+                "Failed to persist policy decision for approval {}: {}",
+                request.id,
+                e
+            );
+        }
+
+        tracing::info!(
+            "{} tool '{}' of execution process {} by approval policy rule {}",
+            verb,
+            request.tool_name,
+            request.execution_process_id,
+            policy_match.rule_id
+        );
+
+        if let Some(store) = self.msg_store_by_id(&request.execution_process_id).await {
+            if let ApprovalStatus::Denied { reason } = &status
+                && let Some((idx, tool)) =
+                    find_matching_tool_use(store.clone(), &request.tool_call_id)
+                && let Some(denied_entry) = tool.with_tool_status(ToolStatus::Denied {
+                    reason: reason.clone(),
+                })
+            {
+                store.push_patch(ConversationPatch::replace(idx, denied_entry));
+            }
+
+            let content = match &reason {
+                Some(reason) => format!(
+                    "{verb} `{}` by approval policy rule {}: {reason}",
+                    request.tool_name, policy_match.rule_id
+                ),
+                None => format!(
+                    "{verb} `{}` by approval policy rule {}",
+                    request.tool_name, policy_match.rule_id
+                ),
+            };
+            let entry_index = EntryIndexProvider::start_from(&store).next();
+            store.push_patch(ConversationPatch::add_normalized_entry(
+                entry_index,
+                NormalizedEntry {
+                    timestamp: None,
+                    entry_type: NormalizedEntryType::SystemMessage,
+                    content,
+                    metadata: Some(serde_json::json!({
+                        "approval_id": request.id,
+                        "tool_call_id": request.tool_call_id,
+                        "approval_policy_rule_id": policy_match.rule_id,
+                        "decision": policy_match.decision,
+                    })),
+                },
+            ));
+        }
+
+        future::ready(status).boxed().shared()
     }
 
     #[tracing::instrument(skip(self, id, req))]
@@ -144,6 +406,18 @@ impl Approvals {
             self.completed.insert(id.to_string(), req.status.clone());
             let _ = p.response_tx.send(req.status.clone());
 
+            if let Err(e) = ApprovalRecord::record_response(
+                pool,
+                id,
+                &req.status,
+                ApprovalResponder::User,
+                None,
+            )
+            .await
+            {
+                tracing::warn!("Failed to persist response to approval {}: {}", id, e);
+            }
+
             if let Some(store) = self.msg_store_by_id(&p.execution_process_id).await {
                 let status = ToolStatus::from_approval_status(&req.status).ok_or(
                     ApprovalError::Custom(anyhow::anyhow!("Invalid approval status")),
@@ -194,11 +468,13 @@ impl Approvals {
         &self,
         id: String,
         timeout_at: chrono::DateTime<chrono::Utc>,
+        on_timeout: ApprovalTimeoutAction,
         waiter: ApprovalWaiter,
     ) {
         let pending = self.pending.clone();
         let completed = self.completed.clone();
         let msg_stores = self.msg_stores.clone();
+        let db = self.db.clone();
 
         let now = chrono::Utc::now();
         let to_wait = (timeout_at - now)
@@ -207,14 +483,19 @@ impl Approvals {
         let deadline = tokio::time::Instant::now() + to_wait;
 
         tokio::spawn(async move {
-            let status = tokio::select! {
+            let (status, expired) = tokio::select! {
                 biased;
 
-                resolved = waiter.clone() => resolved,
-                _ = tokio::time::sleep_until(deadline) => ApprovalStatus::TimedOut,
+                resolved = waiter.clone() => (resolved, false),
+                _ = tokio::time::sleep_until(deadline) => match on_timeout {
+                    ApprovalTimeoutAction::Approve => (ApprovalStatus::Approved, true),
+                    ApprovalTimeoutAction::Deny | ApprovalTimeoutAction::Interrupt => {
+                        (ApprovalStatus::TimedOut, true)
+                    }
+                },
             };
 
-            let is_timeout = matches!(&status, ApprovalStatus::TimedOut);
+            let is_timeout = expired || matches!(&status, ApprovalStatus::TimedOut);
             completed.insert(id.clone(), status.clone());
 
             if is_timeout && let Some((_, pending_approval)) = pending.remove(&id) {
@@ -222,15 +503,28 @@ impl Approvals {
                     tracing::debug!("approval '{}' timeout notification receiver dropped", id);
                 }
 
+                if let Err(e) = ApprovalRecord::record_response(
+                    &db.pool,
+                    &id,
+                    &status,
+                    ApprovalResponder::Timeout,
+                    None,
+                )
+                .await
+                {
+                    tracing::warn!("Failed to persist timeout of approval {}: {}", id, e);
+                }
+
                 let store = {
                     let map = msg_stores.read().await;
                     map.get(&pending_approval.execution_process_id).cloned()
                 };
 
                 if let Some(store) = store {
-                    if let Some(updated_entry) = pending_approval
-                        .entry
-                        .with_tool_status(ToolStatus::TimedOut)
+                    if let Some(updated_entry) =
+                        ToolStatus::from_approval_status(&status).and_then(|tool_status| {
+                            pending_approval.entry.with_tool_status(tool_status)
+                        })
                     {
                         store.push_patch(ConversationPatch::replace(
                             pending_approval.entry_index,
@@ -398,4 +692,176 @@ mod tests {
             "Should not match different tool ids"
         );
     }
+
+    #[test]
+    fn timeout_settings_prefer_project_over_profile_over_config() {
+        let config = ApprovalConfig {
+            timeout_minutes: 60,
+            timeout_action: ApprovalTimeoutAction::Deny,
+            reminder_interval_minutes: Some(0),
+        };
+        let mut project = Project {
+            id: Uuid::new_v4(),
+            name: "project".to_string(),
+            dev_script: None,
+            dev_script_working_dir: None,
+            default_agent_working_dir: None,
+            remote_project_id: None,
+            portfolio_id: None,
+            include_task_id_in_commits: false,
+            approval_timeout_minutes: None,
+            approval_timeout_action: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        let profile = CmdOverrides {
+            approval_timeout_minutes: Some(10),
+            approval_timeout_action: Some(ApprovalTimeoutAction::Interrupt),
+            ..Default::default()
+        };
+
+        let settings = ApprovalTimeoutSettings::resolve(&config, &project, None);
+        assert_eq!(settings.timeout, chrono::Duration::minutes(60));
+        assert_eq!(settings.action, ApprovalTimeoutAction::Deny);
+        assert_eq!(settings.reminder_interval, None);
+
+        let settings = ApprovalTimeoutSettings::resolve(&config, &project, Some(&profile));
+        assert_eq!(settings.timeout, chrono::Duration::minutes(10));
+        assert_eq!(settings.action, ApprovalTimeoutAction::Interrupt);
+
+        project.approval_timeout_minutes = Some(5);
+        project.approval_timeout_action = Some(ApprovalTimeoutAction::Approve);
+        let settings = ApprovalTimeoutSettings::resolve(&config, &project, Some(&profile));
+        assert_eq!(settings.timeout, chrono::Duration::minutes(5));
+        assert_eq!(settings.action, ApprovalTimeoutAction::Approve);
+    }
+
+    /// A database with a running coding agent process and its registered MsgStore
+    async fn setup_running_process() -> (Approvals, Uuid, Arc<MsgStore>) {
+        use db::test_utils::{
+            create_coding_agent_process, create_task, create_workspace, test_pool,
+        };
+
+        let pool = test_pool().await;
+        let task = create_task(&pool).await;
+        let workspace = create_workspace(&pool, task.id).await;
+        let process = create_coding_agent_process(&pool, workspace.id).await;
+
+        let store = Arc::new(MsgStore::new());
+        let msg_stores = Arc::new(RwLock::new(HashMap::from([(process.id, store.clone())])));
+        (
+            Approvals::new(DBService { pool }, msg_stores),
+            process.id,
+            store,
+        )
+    }
+
+    async fn persist_request(
+        approvals: &Approvals,
+        execution_process_id: Uuid,
+        tool_call_id: &str,
+        timeout_at: chrono::DateTime<chrono::Utc>,
+    ) -> ApprovalRequest {
+        let request = ApprovalRequest {
+            id: Uuid::new_v4().to_string(),
+            tool_name: "Read".to_string(),
+            tool_input: serde_json::json!({ "file_path": "foo.rs" }),
+            tool_call_id: tool_call_id.to_string(),
+            execution_process_id,
+            created_at: chrono::Utc::now(),
+            timeout_at,
+        };
+        ApprovalRecord::create(&approvals.db.pool, &request)
+            .await
+            .unwrap();
+        request
+    }
+
+    #[tokio::test]
+    async fn restore_pending_reparks_requests_of_running_executors() {
+        let (approvals, process_id, store) = setup_running_process().await;
+        store.push_patch(ConversationPatch::add_normalized_entry(
+            0,
+            create_tool_use_entry("Read", "foo.rs", "foo-id", ToolStatus::Created),
+        ));
+        let request = persist_request(
+            &approvals,
+            process_id,
+            "foo-id",
+            chrono::Utc::now() + chrono::Duration::hours(1),
+        )
+        .await;
+
+        approvals.restore_pending().await.unwrap();
+        assert!(approvals.pending.contains_key(&request.id));
+        let entry = store
+            .get_history()
+            .iter()
+            .rev()
+            .find_map(|msg| match msg {
+                LogMsg::JsonPatch(patch) => extract_normalized_entry_from_patch(patch),
+                _ => None,
+            })
+            .map(|(_, entry)| entry)
+            .unwrap();
+        assert!(matches!(
+            entry.entry_type,
+            NormalizedEntryType::ToolUse {
+                status: ToolStatus::PendingApproval { ref approval_id, .. },
+                ..
+            } if *approval_id == request.id
+        ));
+
+        // Restoring again does not register the request twice
+        approvals.restore_pending().await.unwrap();
+        assert_eq!(approvals.pending.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn restore_pending_closes_unanswerable_requests() {
+        let (approvals, process_id, _) = setup_running_process().await;
+        let pool = approvals.db.pool.clone();
+        let expired = persist_request(
+            &approvals,
+            process_id,
+            "expired-id",
+            chrono::Utc::now() - chrono::Duration::minutes(1),
+        )
+        .await;
+        approvals.restore_pending().await.unwrap();
+        let record = ApprovalRecord::find_by_id(&pool, &expired.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            record.status,
+            db::models::approval_record::ApprovalRecordStatus::TimedOut
+        );
+        assert_eq!(record.responder, Some(ApprovalResponder::System));
+
+        // The executor exited while the server was down
+        ExecutionProcess::update_completion(
+            &pool,
+            process_id,
+            ExecutionProcessStatus::Killed,
+            None,
+        )
+        .await
+        .unwrap();
+        let orphaned = persist_request(
+            &approvals,
+            process_id,
+            "orphaned-id",
+            chrono::Utc::now() + chrono::Duration::hours(1),
+        )
+        .await;
+        approvals.restore_pending().await.unwrap();
+        assert!(!approvals.pending.contains_key(&orphaned.id));
+        assert!(
+            ApprovalRecord::find_pending(&pool)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
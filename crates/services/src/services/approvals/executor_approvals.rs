@@ -2,18 +2,25 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use db::{self, DBService};
-use executors::approvals::{ExecutorApprovalError, ExecutorApprovalService};
+use executors::approvals::{ApprovalTimeoutAction, ExecutorApprovalError, ExecutorApprovalService};
 use serde_json::Value;
+use tokio::sync::Notify;
 use utils::approvals::{ApprovalRequest, ApprovalStatus, CreateApprovalRequest};
 use uuid::Uuid;
 
-use crate::services::{approvals::Approvals, notification::NotificationService};
+use crate::services::{
+    approvals::{ApprovalTimeoutSettings, ApprovalWaiter, Approvals},
+    notification::NotificationService,
+};
 
 pub struct ExecutorApprovalBridge {
     approvals: Approvals,
     db: DBService,
     notification_service: NotificationService,
     execution_process_id: Uuid,
+    timeouts: ApprovalTimeoutSettings,
+    /// Signalled when a request expires and the timeout action interrupts the run
+    interrupt: Arc<Notify>,
 }
 
 impl ExecutorApprovalBridge {
@@ -22,14 +29,51 @@ impl ExecutorApprovalBridge {
         db: DBService,
         notification_service: NotificationService,
         execution_process_id: Uuid,
+        timeouts: ApprovalTimeoutSettings,
+        interrupt: Arc<Notify>,
     ) -> Arc<Self> {
         Arc::new(Self {
             approvals,
             db,
             notification_service,
             execution_process_id,
+            timeouts,
+            interrupt,
         })
     }
+
+    /// Wait for the response, reminding the user while the request is pending
+    async fn wait_with_reminders(
+        &self,
+        request: &ApprovalRequest,
+        waiter: ApprovalWaiter,
+    ) -> ApprovalStatus {
+        let Some(interval) = self.timeouts.reminder_interval else {
+            return waiter.await;
+        };
+
+        loop {
+            tokio::select! {
+                status = waiter.clone() => return status,
+                _ = tokio::time::sleep(interval) => {
+                    let remaining = request.timeout_at - chrono::Utc::now();
+                    if remaining <= chrono::Duration::zero() {
+                        continue;
+                    }
+                    self.notification_service
+                        .notify(
+                            "Approval Still Needed",
+                            &format!(
+                                "Tool '{}' is still waiting for approval and expires in {} minute(s)",
+                                request.tool_name,
+                                remaining.num_minutes().max(1)
+                            ),
+                        )
+                        .await;
+                }
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -40,32 +84,36 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
         tool_input: Value,
         tool_call_id: &str,
     ) -> Result<ApprovalStatus, ExecutorApprovalError> {
-        super::ensure_task_in_review(&self.db.pool, self.execution_process_id).await;
-
-        let request = ApprovalRequest::from_create(
+        let request = ApprovalRequest::from_create_with_timeout(
             CreateApprovalRequest {
                 tool_name: tool_name.to_string(),
                 tool_input,
                 tool_call_id: tool_call_id.to_string(),
             },
             self.execution_process_id,
+            self.timeouts.timeout,
         );
 
-        let (_, waiter) = self
+        let (request, waiter) = self
             .approvals
-            .create_with_waiter(request)
+            .create_with_waiter(request, self.timeouts.action)
             .await
             .map_err(ExecutorApprovalError::request_failed)?;
 
-        // Play notification sound when approval is needed
-        self.notification_service
-            .notify(
-                "Approval Needed",
-                &format!("Tool '{}' requires approval", tool_name),
-            )
-            .await;
+        // Requests decided by an approval policy rule never wait for the user
+        if !self.approvals.is_completed(&request.id) {
+            super::ensure_task_in_review(&self.db.pool, self.execution_process_id).await;
+
+            // Play notification sound when approval is needed
+            self.notification_service
+                .notify(
+                    "Approval Needed",
+                    &format!("Tool '{}' requires approval", tool_name),
+                )
+                .await;
+        }
 
-        let status = waiter.clone().await;
+        let status = self.wait_with_reminders(&request, waiter).await;
 
         if matches!(status, ApprovalStatus::Pending) {
             return Err(ExecutorApprovalError::request_failed(
@@ -73,6 +121,17 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
             ));
         }
 
+        if matches!(status, ApprovalStatus::TimedOut)
+            && self.timeouts.action == ApprovalTimeoutAction::Interrupt
+        {
+            tracing::info!(
+                "Approval {} expired, interrupting execution process {}",
+                request.id,
+                self.execution_process_id
+            );
+            self.interrupt.notify_one();
+        }
+
         Ok(status)
     }
 }
@@ -0,0 +1,324 @@
+use std::path::{Component, Path, PathBuf};
+
+use db::models::approval_policy_rule::{
+    ApprovalPolicyDecision, ApprovalPolicyPathScope, ApprovalPolicyRule,
+};
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Input keys that hold the command of shell tools (Claude `Bash`, Codex exec)
+const COMMAND_KEYS: &[&str] = &["command", "cmd"];
+/// Input keys that hold a single file path
+const PATH_KEYS: &[&str] = &["file_path", "notebook_path", "path", "filePath"];
+/// Input keys that hold a map keyed by file path (Codex apply_patch)
+const PATH_MAP_KEYS: &[&str] = &["file_changes", "changes"];
+
+/// The rule that decided an approval request
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyMatch {
+    pub rule_id: Uuid,
+    pub decision: ApprovalPolicyDecision,
+    pub reason: String,
+}
+
+/// Evaluate enabled rules in order and return the first one that matches the tool call.
+/// `worktree` is the workspace directory used to resolve relative paths and path scopes.
+pub fn evaluate(
+    rules: &[ApprovalPolicyRule],
+    tool_name: &str,
+    tool_input: &Value,
+    worktree: Option<&Path>,
+) -> Option<PolicyMatch> {
+    let command = extract_command(tool_input);
+    let paths = extract_paths(tool_input, worktree);
+
+    rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .find(
+            |rule| match rule_matches(rule, tool_name, command.as_deref(), &paths, worktree) {
+                Ok(matched) => matched,
+                Err(e) => {
+                    tracing::warn!("Skipping invalid approval policy rule {}: {}", rule.id, e);
+                    false
+                }
+            },
+        )
+        .map(|rule| PolicyMatch {
+            rule_id: rule.id,
+            decision: rule.decision,
+            reason: rule.reason.clone(),
+        })
+}
+
+/// Check that the patterns of a rule compile, so broken rules are rejected when saved
+/// rather than silently skipped during evaluation.
+pub fn validate_rule(
+    tool_name: Option<&str>,
+    command_pattern: Option<&str>,
+    path_glob: Option<&str>,
+) -> Result<(), String> {
+    if let Some(pattern) = tool_name {
+        glob_matcher(pattern).map_err(|e| format!("Invalid tool name pattern: {e}"))?;
+    }
+    if let Some(pattern) = command_pattern {
+        Regex::new(pattern).map_err(|e| format!("Invalid command pattern: {e}"))?;
+    }
+    if let Some(pattern) = path_glob {
+        glob_matcher(pattern).map_err(|e| format!("Invalid path glob: {e}"))?;
+    }
+    Ok(())
+}
+
+fn rule_matches(
+    rule: &ApprovalPolicyRule,
+    tool_name: &str,
+    command: Option<&str>,
+    paths: &[PathBuf],
+    worktree: Option<&Path>,
+) -> Result<bool, String> {
+    if let Some(pattern) = &rule.tool_name
+        && !glob_matcher(pattern)
+            .map_err(|e| e.to_string())?
+            .is_match(tool_name)
+    {
+        return Ok(false);
+    }
+
+    if let Some(pattern) = &rule.command_pattern {
+        let regex = Regex::new(pattern).map_err(|e| e.to_string())?;
+        if !command.is_some_and(|command| regex.is_match(command)) {
+            return Ok(false);
+        }
+    }
+
+    if rule.path_glob.is_none() && rule.path_scope == ApprovalPolicyPathScope::Any {
+        return Ok(true);
+    }
+    if paths.is_empty() {
+        return Ok(false);
+    }
+    let glob = rule
+        .path_glob
+        .as_deref()
+        .map(glob_matcher)
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let path_matches = |path: &PathBuf| {
+        let relative = worktree.and_then(|root| path.strip_prefix(root).ok());
+        let in_scope = match rule.path_scope {
+            ApprovalPolicyPathScope::Any => true,
+            ApprovalPolicyPathScope::InsideWorktree => relative.is_some(),
+            ApprovalPolicyPathScope::OutsideWorktree => worktree.is_some() && relative.is_none(),
+        };
+        in_scope
+            && glob.as_ref().is_none_or(|glob| {
+                glob.is_match(path) || relative.is_some_and(|rel| glob.is_match(rel))
+            })
+    };
+
+    // Allowing needs every touched path to match; denying or asking needs any one
+    Ok(match rule.decision {
+        ApprovalPolicyDecision::Allow => paths.iter().all(path_matches),
+        ApprovalPolicyDecision::Deny | ApprovalPolicyDecision::Ask => {
+            paths.iter().any(path_matches)
+        }
+    })
+}
+
+fn glob_matcher(pattern: &str) -> Result<GlobMatcher, globset::Error> {
+    Ok(GlobBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()?
+        .compile_matcher())
+}
+
+/// ACP agents wrap the original tool input in `tool_call.rawInput`
+fn input_objects(tool_input: &Value) -> Vec<&Value> {
+    let mut objects = vec![tool_input];
+    if let Some(raw_input) = tool_input.pointer("/tool_call/rawInput") {
+        objects.push(raw_input);
+    }
+    objects
+}
+
+fn extract_command(tool_input: &Value) -> Option<String> {
+    input_objects(tool_input).into_iter().find_map(|object| {
+        COMMAND_KEYS.iter().find_map(|key| match object.get(key)? {
+            Value::String(command) => Some(command.clone()),
+            Value::Array(parts) => Some(
+                parts
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            _ => None,
+        })
+    })
+}
+
+fn extract_paths(tool_input: &Value, worktree: Option<&Path>) -> Vec<PathBuf> {
+    let mut raw_paths: Vec<&str> = Vec::new();
+    for object in input_objects(tool_input) {
+        raw_paths.extend(PATH_KEYS.iter().filter_map(|key| object.get(key)?.as_str()));
+        if let Some(paths) = object.get("paths").and_then(Value::as_array) {
+            raw_paths.extend(paths.iter().filter_map(Value::as_str));
+        }
+        for key in PATH_MAP_KEYS {
+            if let Some(map) = object.get(key).and_then(Value::as_object) {
+                raw_paths.extend(map.keys().map(String::as_str));
+            }
+        }
+    }
+    if let Some(locations) = tool_input
+        .pointer("/tool_call/locations")
+        .and_then(Value::as_array)
+    {
+        raw_paths.extend(
+            locations
+                .iter()
+                .filter_map(|location| location.get("path")?.as_str()),
+        );
+    }
+
+    let mut paths: Vec<PathBuf> = raw_paths
+        .into_iter()
+        .filter(|path| !path.is_empty())
+        .map(|path| resolve_path(path, worktree))
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Make a path absolute against the worktree and normalize `.` and `..` lexically,
+/// since the file may not exist yet.
+fn resolve_path(path: &str, worktree: Option<&Path>) -> PathBuf {
+    let path = Path::new(path);
+    let joined = match worktree {
+        Some(root) if path.is_relative() => root.join(path),
+        _ => path.to_path_buf(),
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::json;
+
+    use super::*;
+
+    fn rule(decision: ApprovalPolicyDecision) -> ApprovalPolicyRule {
+        ApprovalPolicyRule {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            position: 0,
+            tool_name: None,
+            command_pattern: None,
+            path_glob: None,
+            path_scope: ApprovalPolicyPathScope::Any,
+            decision,
+            reason: String::new(),
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_decides() {
+        let deny_rm = ApprovalPolicyRule {
+            tool_name: Some("bash".to_string()),
+            command_pattern: Some(r"\brm\s+-rf\b".to_string()),
+            reason: "No recursive deletes".to_string(),
+            ..rule(ApprovalPolicyDecision::Deny)
+        };
+        let allow_bash = ApprovalPolicyRule {
+            tool_name: Some("Bash".to_string()),
+            ..rule(ApprovalPolicyDecision::Allow)
+        };
+        let rules = vec![deny_rm.clone(), allow_bash.clone()];
+
+        let decided = evaluate(&rules, "Bash", &json!({"command": "rm -rf target"}), None);
+        assert_eq!(decided.map(|m| m.rule_id), Some(deny_rm.id));
+
+        let decided = evaluate(&rules, "Bash", &json!({"command": "cargo test"}), None);
+        assert_eq!(decided.map(|m| m.rule_id), Some(allow_bash.id));
+
+        // Codex sends the command as an argv array
+        let decided = evaluate(
+            &rules,
+            "bash",
+            &json!({"command": ["bash", "-lc", "rm -rf /"]}),
+            None,
+        );
+        assert_eq!(decided.map(|m| m.rule_id), Some(deny_rm.id));
+
+        assert_eq!(evaluate(&rules, "Edit", &json!({}), None), None);
+    }
+
+    #[test]
+    fn path_scopes_resolve_against_worktree() {
+        let worktree = Path::new("/tmp/worktrees/abcd");
+        let allow_inside = ApprovalPolicyRule {
+            path_glob: Some("*.rs".to_string()),
+            path_scope: ApprovalPolicyPathScope::InsideWorktree,
+            ..rule(ApprovalPolicyDecision::Allow)
+        };
+        let deny_outside = ApprovalPolicyRule {
+            path_scope: ApprovalPolicyPathScope::OutsideWorktree,
+            ..rule(ApprovalPolicyDecision::Deny)
+        };
+        let rules = vec![allow_inside.clone(), deny_outside.clone()];
+
+        let decided = evaluate(
+            &rules,
+            "Edit",
+            &json!({"file_path": "repo/src/main.rs"}),
+            Some(worktree),
+        );
+        assert_eq!(decided.map(|m| m.rule_id), Some(allow_inside.id));
+
+        let decided = evaluate(
+            &rules,
+            "Edit",
+            &json!({"file_path": "repo/../../escape.rs"}),
+            Some(worktree),
+        );
+        assert_eq!(decided.map(|m| m.rule_id), Some(deny_outside.id));
+
+        // A patch touching one file outside the worktree is not allowed by the inside rule
+        let decided = evaluate(
+            &rules,
+            "edit",
+            &json!({"file_changes": {
+                "/tmp/worktrees/abcd/repo/lib.rs": {},
+                "/etc/hosts.rs": {}
+            }}),
+            Some(worktree),
+        );
+        assert_eq!(decided.map(|m| m.rule_id), Some(deny_outside.id));
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(validate_rule(Some("mcp__*"), Some(r"^git\s"), Some("src/**")).is_ok());
+        assert!(validate_rule(None, Some("("), None).is_err());
+        assert!(validate_rule(None, None, Some("src/[")).is_err());
+    }
+}
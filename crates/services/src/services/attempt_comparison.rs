@@ -0,0 +1,338 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use db::models::{
+    attempt_comparison::{AttemptComparison, AttemptComparisonCandidate},
+    coding_agent_turn::CodingAgentTurn,
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    execution_process_logs::ExecutionProcessLogs,
+    session::Session,
+    task::Task,
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use executors::profile::ExecutorProfileId;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+use super::git::{DiffTarget, GitService};
+
+/// Everything needed to pick a winner of a best-of-N run.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AttemptComparisonView {
+    pub comparison: AttemptComparison,
+    pub candidates: Vec<ComparisonCandidate>,
+    pub judge: Option<ComparisonJudge>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ComparisonCandidate {
+    pub workspace: Workspace,
+    pub executor_profile_id: ExecutorProfileId,
+    pub position: i64,
+    pub is_winner: bool,
+    /// Status of the latest coding agent run
+    pub agent_status: Option<ExecutionProcessStatus>,
+    /// Status of the latest cleanup script run, which is where projects run their checks
+    pub check_status: Option<ExecutionProcessStatus>,
+    pub check_exit_code: Option<i64>,
+    /// Wall-clock time spent in coding agent runs
+    pub duration_seconds: Option<i64>,
+    /// Cost reported by the executor, for executors that report one
+    pub cost_usd: Option<f64>,
+    pub files_changed: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    pub repos: Vec<ComparisonRepoStats>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ComparisonRepoStats {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub base_commit: Option<String>,
+    pub files_changed: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    pub uncommitted_count: Option<usize>,
+    pub untracked_count: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ComparisonJudge {
+    pub execution_process_id: Uuid,
+    pub status: ExecutionProcessStatus,
+    /// Final message of the judge agent, containing its ranking
+    pub summary: Option<String>,
+}
+
+/// Collect diff stats, check results, duration and cost for every candidate.
+/// Git failures leave the affected stats empty rather than failing the view.
+pub async fn build_comparison_view(
+    pool: &SqlitePool,
+    git: &GitService,
+    comparison: AttemptComparison,
+) -> Result<AttemptComparisonView, sqlx::Error> {
+    let candidates = AttemptComparisonCandidate::find_by_comparison_id(pool, comparison.id).await?;
+
+    let mut summaries = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let Some(workspace) = Workspace::find_by_id(pool, candidate.workspace_id).await? else {
+            continue;
+        };
+        summaries.push(summarize_candidate(pool, git, &comparison, candidate, workspace).await?);
+    }
+
+    let judge = match comparison.judge_execution_process_id {
+        Some(id) => match ExecutionProcess::find_by_id(pool, id).await? {
+            Some(process) => Some(ComparisonJudge {
+                execution_process_id: process.id,
+                status: process.status,
+                summary: CodingAgentTurn::find_by_execution_process_id(pool, process.id)
+                    .await?
+                    .and_then(|turn| turn.summary),
+            }),
+            None => None,
+        },
+        None => None,
+    };
+
+    Ok(AttemptComparisonView {
+        comparison,
+        candidates: summaries,
+        judge,
+    })
+}
+
+async fn summarize_candidate(
+    pool: &SqlitePool,
+    git: &GitService,
+    comparison: &AttemptComparison,
+    candidate: AttemptComparisonCandidate,
+    workspace: Workspace,
+) -> Result<ComparisonCandidate, sqlx::Error> {
+    let mut agent_runs = Vec::new();
+    for session in Session::find_by_workspace_id(pool, workspace.id).await? {
+        agent_runs.extend(
+            ExecutionProcess::find_by_session_id(pool, session.id, false)
+                .await?
+                .into_iter()
+                .filter(|p| p.run_reason == ExecutionProcessRunReason::CodingAgent),
+        );
+    }
+    agent_runs.sort_by_key(|p| p.created_at);
+
+    let duration_seconds = (!agent_runs.is_empty()).then(|| {
+        agent_runs
+            .iter()
+            .filter(|p| p.status != ExecutionProcessStatus::Queued)
+            .map(|p| {
+                (p.completed_at.unwrap_or_else(Utc::now) - p.started_at)
+                    .num_seconds()
+                    .max(0)
+            })
+            .sum::<i64>()
+    });
+
+    let mut cost_usd = None;
+    for process in &agent_runs {
+        let records = ExecutionProcessLogs::find_by_execution_id(pool, process.id).await?;
+        let Ok(messages) = ExecutionProcessLogs::parse_logs(&records) else {
+            continue;
+        };
+        if let Some(cost) = reported_cost_usd(&messages) {
+            *cost_usd.get_or_insert(0.0) += cost;
+        }
+    }
+
+    let check = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+        pool,
+        workspace.id,
+        &ExecutionProcessRunReason::CleanupScript,
+    )
+    .await?;
+
+    let repos = repo_stats(pool, git, &workspace).await?;
+
+    Ok(ComparisonCandidate {
+        executor_profile_id: candidate.executor_profile_id.0,
+        position: candidate.position,
+        is_winner: comparison.winner_workspace_id == Some(workspace.id),
+        agent_status: agent_runs.last().map(|p| p.status.clone()),
+        check_status: check.as_ref().map(|p| p.status.clone()),
+        check_exit_code: check.and_then(|p| p.exit_code),
+        duration_seconds,
+        cost_usd,
+        files_changed: repos.iter().map(|r| r.files_changed).sum(),
+        lines_added: repos.iter().map(|r| r.lines_added).sum(),
+        lines_removed: repos.iter().map(|r| r.lines_removed).sum(),
+        repos,
+        workspace,
+    })
+}
+
+async fn repo_stats(
+    pool: &SqlitePool,
+    git: &GitService,
+    workspace: &Workspace,
+) -> Result<Vec<ComparisonRepoStats>, sqlx::Error> {
+    let repos =
+        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id).await?;
+    let workspace_dir = workspace.container_ref.as_ref().map(PathBuf::from);
+
+    Ok(repos
+        .into_iter()
+        .map(|repo| {
+            let mut stats = ComparisonRepoStats {
+                repo_id: repo.repo.id,
+                repo_name: repo.repo.name.clone(),
+                base_commit: None,
+                files_changed: 0,
+                lines_added: 0,
+                lines_removed: 0,
+                uncommitted_count: None,
+                untracked_count: None,
+            };
+            // Losing candidates have their worktrees removed; only the stored metadata remains
+            let Some(worktree_path) = workspace_dir
+                .as_ref()
+                .map(|dir| dir.join(&repo.repo.name))
+                .filter(|path| path.exists())
+            else {
+                return stats;
+            };
+
+            if let Ok((uncommitted, untracked)) = git.get_worktree_change_counts(&worktree_path) {
+                stats.uncommitted_count = Some(uncommitted);
+                stats.untracked_count = Some(untracked);
+            }
+
+            let Ok(base_commit) =
+                git.get_base_commit(&repo.repo.path, &workspace.branch, &repo.target_branch)
+            else {
+                return stats;
+            };
+            stats.base_commit = Some(base_commit.to_string());
+            if let Ok(diffs) = git.get_diffs(
+                DiffTarget::Worktree {
+                    worktree_path: &worktree_path,
+                    base_commit: &base_commit,
+                },
+                None,
+            ) {
+                stats.files_changed = diffs.len();
+                stats.lines_added = diffs.iter().filter_map(|d| d.additions).sum();
+                stats.lines_removed = diffs.iter().filter_map(|d| d.deletions).sum();
+            }
+            stats
+        })
+        .collect())
+}
+
+/// Sum of the costs reported in the executor's result events, e.g. the
+/// `total_cost_usd` field of Claude Code's final stream-json line.
+fn reported_cost_usd(messages: &[LogMsg]) -> Option<f64> {
+    messages
+        .iter()
+        .filter_map(|msg| match msg {
+            LogMsg::Stdout(chunk) => Some(chunk),
+            _ => None,
+        })
+        .flat_map(|chunk| chunk.lines())
+        .filter(|line| line.contains("total_cost_usd"))
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line.trim()).ok())
+        .filter_map(|value| value.get("total_cost_usd").and_then(|v| v.as_f64()))
+        .reduce(|total, cost| total + cost)
+}
+
+/// Prompt for a judge agent that ranks the candidates of a comparison. The judge
+/// reads each candidate's worktree directly, so only paths and stats are inlined.
+pub fn build_judge_prompt(task: &Task, candidates: &[ComparisonCandidate]) -> String {
+    let mut prompt = format!(
+        r#"# Compare Candidate Implementations
+
+Several coding agents independently implemented the same task:
+
+{}
+
+Each candidate's changes live in its own git worktree, listed below. Inspect them with
+`git -C <worktree> --no-pager diff <base commit>` and by reading the code. Do not modify
+any of the worktrees.
+
+"#,
+        task.to_prompt()
+    );
+
+    for (idx, candidate) in candidates.iter().enumerate() {
+        prompt.push_str(&format!(
+            "## Candidate {} ({})\n\n",
+            idx + 1,
+            candidate.executor_profile_id
+        ));
+        if let Some(status) = &candidate.check_status {
+            prompt.push_str(&format!(
+                "- Checks: {:?} (exit code {})\n",
+                status,
+                candidate
+                    .check_exit_code
+                    .map(|c| c.to_string())
+                    .unwrap_or_else(|| "unknown".to_string())
+            ));
+        }
+        for repo in &candidate.repos {
+            let worktree = candidate
+                .workspace
+                .container_ref
+                .as_ref()
+                .map(|dir| {
+                    PathBuf::from(dir)
+                        .join(&repo.repo_name)
+                        .display()
+                        .to_string()
+                })
+                .unwrap_or_else(|| "(not available)".to_string());
+            prompt.push_str(&format!(
+                "- {}: worktree `{}`, base commit `{}`, {} files changed (+{} -{})\n",
+                repo.repo_name,
+                worktree,
+                repo.base_commit.as_deref().unwrap_or("unknown"),
+                repo.files_changed,
+                repo.lines_added,
+                repo.lines_removed
+            ));
+        }
+        prompt.push('\n');
+    }
+
+    prompt.push_str(
+        r#"## Your Output
+
+Rank the candidates from best to worst. For each, give a short justification covering
+correctness, completeness against the task, code quality and test coverage. End with a
+single line of the form `Winner: Candidate <n>`."#,
+    );
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_reported_costs_from_result_lines() {
+        let messages = vec![
+            LogMsg::Stdout(r#"{"type":"assistant","message":{}}"#.to_string()),
+            LogMsg::Stdout(
+                "{\"type\":\"result\",\"total_cost_usd\":0.25}\n{\"type\":\"system\"}\n"
+                    .to_string(),
+            ),
+            LogMsg::Stderr(r#"{"total_cost_usd":100.0}"#.to_string()),
+            LogMsg::Stdout(r#"{"type":"result","total_cost_usd":0.5}"#.to_string()),
+        ];
+        assert_eq!(reported_cost_usd(&messages), Some(0.75));
+        assert_eq!(reported_cost_usd(&messages[..1]), None);
+    }
+}
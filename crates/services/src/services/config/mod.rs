@@ -27,6 +27,13 @@ pub type GitHubConfig = versions::v9::GitHubConfig;
 pub type JiraConfig = versions::v9::JiraConfig;
 pub type SlackConfig = versions::v9::SlackConfig;
 pub type ConfluenceConfig = versions::v9::ConfluenceConfig;
+pub type SchedulerConfig = versions::v9::SchedulerConfig;
+pub type ApprovalConfig = versions::v9::ApprovalConfig;
+pub type LogRetentionConfig = versions::v9::LogRetentionConfig;
+pub type DevServerConfig = versions::v9::DevServerConfig;
+pub type DevServerReadinessCheck = versions::v9::DevServerReadinessCheck;
+pub type WorkspacePoolConfig = versions::v9::WorkspacePoolConfig;
+pub type WorkspaceDiskConfig = versions::v9::WorkspaceDiskConfig;
 pub type UiLanguage = versions::v9::UiLanguage;
 pub type ShowcaseState = versions::v9::ShowcaseState;
 
@@ -1,5 +1,9 @@
+use std::collections::HashMap;
+
 use anyhow::Error;
-use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+use executors::{
+    approvals::ApprovalTimeoutAction, executors::BaseCodingAgent, profile::ExecutorProfileId,
+};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 pub use v8::{
@@ -52,6 +56,169 @@ pub struct ConfluenceConfig {
     pub parent_page_id: Option<String>,
 }
 
+/// Limits applied by the execution scheduler to coding agent runs.
+/// Executions over the limit wait in a persisted queue until a slot frees up.
+#[derive(Clone, Debug, Serialize, Deserialize, TS, Default)]
+#[ts(export)]
+pub struct SchedulerConfig {
+    /// Maximum number of coding agents running at once across all projects.
+    /// `None` disables the global limit.
+    pub max_concurrent_executions: Option<u32>,
+    /// Maximum number of concurrent runs per executor (e.g. `CLAUDE_CODE`).
+    #[serde(default)]
+    pub max_concurrent_per_executor: HashMap<BaseCodingAgent, u32>,
+}
+
+impl SchedulerConfig {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_concurrent_executions.is_none() && self.max_concurrent_per_executor.is_empty()
+    }
+}
+
+/// Default handling of tool approval requests nobody answers. Projects and executor
+/// profiles can override the timeout and the action.
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ApprovalConfig {
+    pub timeout_minutes: u32,
+    pub timeout_action: ApprovalTimeoutAction,
+    /// Repeat the approval notification at this interval while a request is pending.
+    /// `None` disables reminders.
+    pub reminder_interval_minutes: Option<u32>,
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self {
+            timeout_minutes: 60,
+            timeout_action: ApprovalTimeoutAction::Deny,
+            reminder_interval_minutes: Some(15),
+        }
+    }
+}
+
+/// How execution process logs are stored once their process has finished.
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LogRetentionConfig {
+    /// Pack the raw logs of finished processes into compressed chunks.
+    pub compress_completed: bool,
+    /// Drop the raw stdout/stderr of processes that finished more than this many days ago.
+    /// Their normalized conversation, turn summaries and search index entries are kept.
+    /// `None` keeps raw logs forever.
+    pub raw_log_retention_days: Option<u32>,
+}
+
+impl Default for LogRetentionConfig {
+    fn default() -> Self {
+        Self {
+            compress_completed: true,
+            raw_log_retention_days: None,
+        }
+    }
+}
+
+/// How a dev server decides it is up and serving.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, TS, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum DevServerReadinessCheck {
+    /// The allocated port accepts TCP connections
+    #[default]
+    Tcp,
+    /// `health_check_path` on the allocated port answers with a non-5xx status
+    Http,
+    /// An output line matches `ready_log_pattern`
+    LogLine,
+}
+
+/// Ports, readiness checks and crash handling of the dev servers started from workspaces.
+/// Every workspace can run one dev server at a time.
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DevServerConfig {
+    /// First port handed out to dev servers
+    pub port_range_start: u16,
+    /// Last port handed out to dev servers
+    pub port_range_end: u16,
+    /// Extra environment variable that receives the port next to `PORT`, e.g. `VITE_PORT`
+    pub port_env_var: Option<String>,
+    pub readiness_check: DevServerReadinessCheck,
+    /// Path requested by the `http` readiness check
+    pub health_check_path: String,
+    /// Regex matched against output lines by the `log_line` readiness check
+    pub ready_log_pattern: Option<String>,
+    /// Report the dev server as unhealthy if it isn't ready after this many seconds
+    pub ready_timeout_secs: u32,
+    /// Restart dev servers that exit with a failure, with exponential backoff
+    pub restart_on_crash: bool,
+    /// Give up after this many consecutive restarts
+    pub max_restarts: u32,
+}
+
+impl Default for DevServerConfig {
+    fn default() -> Self {
+        Self {
+            port_range_start: 4100,
+            port_range_end: 4999,
+            port_env_var: None,
+            readiness_check: DevServerReadinessCheck::Tcp,
+            health_check_path: "/".to_string(),
+            ready_log_pattern: None,
+            ready_timeout_secs: 120,
+            restart_on_crash: true,
+            max_restarts: 5,
+        }
+    }
+}
+
+/// Workspaces created and set up ahead of time for the most active projects, so starting a
+/// task attempt can claim one instead of creating worktrees and running setup scripts.
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WorkspacePoolConfig {
+    /// Ready workspaces kept per project. `0` disables the pool
+    pub size_per_project: u32,
+    /// Number of most recently active projects that get a pool
+    pub max_projects: u32,
+    /// How often pooled workspaces are checked against their target branches and refilled
+    pub refresh_interval_secs: u32,
+}
+
+impl Default for WorkspacePoolConfig {
+    fn default() -> Self {
+        Self {
+            size_per_project: 0,
+            max_projects: 3,
+            refresh_interval_secs: 300,
+        }
+    }
+}
+
+/// Disk budgets for workspace worktrees. When a budget is exceeded, the least recently
+/// used workspaces without running processes or unpushed work are evicted; their branches
+/// are kept and the worktrees are recreated the next time they are used.
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WorkspaceDiskConfig {
+    /// Budget for all workspaces together, in MB. `None` for no limit
+    pub total_budget_mb: Option<u32>,
+    /// Budget for the workspaces of each project, in MB. `None` for no limit
+    pub project_budget_mb: Option<u32>,
+    /// How often workspace disk usage is measured and the budgets enforced
+    pub scan_interval_secs: u32,
+}
+
+impl Default for WorkspaceDiskConfig {
+    fn default() -> Self {
+        Self {
+            total_budget_mb: None,
+            project_budget_mb: None,
+            scan_interval_secs: 900,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
 pub struct Config {
     pub config_version: String,
@@ -81,6 +248,18 @@ pub struct Config {
     pub pr_auto_description_enabled: bool,
     #[serde(default)]
     pub pr_auto_description_prompt: Option<String>,
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+    #[serde(default)]
+    pub approvals: ApprovalConfig,
+    #[serde(default)]
+    pub log_retention: LogRetentionConfig,
+    #[serde(default)]
+    pub dev_server: DevServerConfig,
+    #[serde(default)]
+    pub workspace_pool: WorkspacePoolConfig,
+    #[serde(default)]
+    pub workspace_disk: WorkspaceDiskConfig,
 }
 
 impl Config {
@@ -105,6 +284,12 @@ impl Config {
             showcases: old_config.showcases,
             pr_auto_description_enabled: old_config.pr_auto_description_enabled,
             pr_auto_description_prompt: old_config.pr_auto_description_prompt,
+            scheduler: SchedulerConfig::default(),
+            approvals: ApprovalConfig::default(),
+            log_retention: LogRetentionConfig::default(),
+            dev_server: DevServerConfig::default(),
+            workspace_pool: WorkspacePoolConfig::default(),
+            workspace_disk: WorkspaceDiskConfig::default(),
         }
     }
 
@@ -157,6 +342,12 @@ impl Default for Config {
             showcases: ShowcaseState::default(),
             pr_auto_description_enabled: true,
             pr_auto_description_prompt: None,
+            scheduler: SchedulerConfig::default(),
+            approvals: ApprovalConfig::default(),
+            log_retention: LogRetentionConfig::default(),
+            dev_server: DevServerConfig::default(),
+            workspace_pool: WorkspacePoolConfig::default(),
+            workspace_disk: WorkspaceDiskConfig::default(),
         }
     }
 }
@@ -18,11 +18,14 @@ use db::{
         execution_process_repo_state::{
             CreateExecutionProcessRepoState, ExecutionProcessRepoState,
         },
+        execution_queue::ExecutionQueueEntry,
+        executor_fallback::{CreateExecutorFallback, ExecutorFallback},
         project::{Project, UpdateProject},
-        project_repo::{ProjectRepo, ProjectRepoWithName},
+        project_repo::{CopyFileRule, ProjectRepo, ProjectRepoWithName},
         repo::Repo,
         session::{CreateSession, Session, SessionError},
         task::{Task, TaskStatus},
+        task_plan::TaskPlan,
         workspace::{Workspace, WorkspaceError},
         workspace_repo::WorkspaceRepo,
     },
@@ -34,7 +37,9 @@ use executors::{
         review_agent::ReviewAgentRequest,
         script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
     },
+    env::ExecutionEnv,
     executors::{ExecutorError, StandardCodingAgentExecutor},
+    fallback::{self, FallbackReason},
     logs::{NormalizedEntry, NormalizedEntryError, NormalizedEntryType, utils::ConversationPatch},
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
@@ -50,10 +55,18 @@ use utils::{
 use uuid::Uuid;
 
 use crate::services::{
+    config::SchedulerConfig,
+    dev_server::DevServerRegistry,
+    execution_scheduler::ExecutionScheduler,
     git::{GitService, GitServiceError},
     notification::NotificationService,
+    search::LogSearchIndexer,
+    secrets::{SecretError, SecretRedactor, SecretService},
     share::SharePublisher,
+    terminal::TerminalService,
+    workspace_disk::{WorkspaceDiskSummary, WorkspaceGcReport},
     workspace_manager::WorkspaceError as WorkspaceManagerError,
+    workspace_pool::WorkspacePool,
     worktree_manager::WorktreeError,
 };
 pub type ContainerRef = String;
@@ -74,6 +87,8 @@ pub enum ContainerError {
     WorkspaceManager(#[from] WorkspaceManagerError),
     #[error(transparent)]
     Session(#[from] SessionError),
+    #[error(transparent)]
+    Secret(#[from] SecretError),
     #[error("Io error: {0}")]
     Io(#[from] std::io::Error),
     #[error("Failed to kill process: {0}")]
@@ -94,15 +109,42 @@ pub trait ContainerService {
 
     fn notification_service(&self) -> &NotificationService;
 
+    fn execution_scheduler(&self) -> &ExecutionScheduler;
+
+    fn dev_servers(&self) -> &DevServerRegistry;
+
+    fn terminals(&self) -> &TerminalService;
+
+    fn secrets(&self) -> &SecretService;
+
+    fn workspace_pool(&self) -> &WorkspacePool;
+
+    /// Current scheduler limits for coding agent runs
+    async fn scheduler_config(&self) -> SchedulerConfig;
+
     fn workspace_to_current_dir(&self, workspace: &Workspace) -> PathBuf;
 
+    /// `VK_*` environment and secrets the processes started in a workspace get, and a
+    /// redactor that masks those secrets in their output
+    async fn workspace_env(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<(ExecutionEnv, SecretRedactor), ContainerError>;
+
     async fn create(&self, workspace: &Workspace) -> Result<ContainerRef, ContainerError>;
 
+    /// Take over a ready workspace from the pool instead of creating one. Its setup scripts
+    /// have already run. `None` if no pooled workspace matches the workspace's repos.
+    async fn claim_pooled_workspace(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<Option<ContainerRef>, ContainerError>;
+
     async fn kill_all_running_processes(&self) -> Result<(), ContainerError>;
 
     async fn delete(&self, workspace: &Workspace) -> Result<(), ContainerError>;
 
-    /// Check if a task has any running execution processes
+    /// Check if a task has any running or queued execution processes
     async fn has_running_processes(&self, task_id: Uuid) -> Result<bool, ContainerError> {
         let workspaces = Workspace::fetch_all(&self.db().pool, Some(task_id)).await?;
 
@@ -113,7 +155,10 @@ pub trait ContainerService {
                     ExecutionProcess::find_by_session_id(&self.db().pool, session.id, false).await
                 {
                     for process in processes {
-                        if process.status == ExecutionProcessStatus::Running {
+                        if matches!(
+                            process.status,
+                            ExecutionProcessStatus::Running | ExecutionProcessStatus::Queued
+                        ) {
                             return Ok(true);
                         }
                     }
@@ -150,10 +195,12 @@ pub trait ContainerService {
             return false;
         }
 
-        // Always finalize failed or killed executions, regardless of next action
+        // Always finalize failed, killed or timed out executions, regardless of next action
         if matches!(
             ctx.execution_process.status,
-            ExecutionProcessStatus::Failed | ExecutionProcessStatus::Killed
+            ExecutionProcessStatus::Failed
+                | ExecutionProcessStatus::Killed
+                | ExecutionProcessStatus::TimedOut
         ) {
             return true;
         }
@@ -207,6 +254,10 @@ pub trait ContainerService {
                 "❌ '{}' execution failed\nBranch: {:?}\nExecutor: {:?}",
                 ctx.task.title, ctx.workspace.branch, ctx.session.executor
             ),
+            ExecutionProcessStatus::TimedOut => format!(
+                "⏱️ '{}' execution timed out\nBranch: {:?}\nExecutor: {:?}",
+                ctx.task.title, ctx.workspace.branch, ctx.session.executor
+            ),
             _ => {
                 tracing::warn!(
                     "Tried to notify workspace completion for {} but process is still running!",
@@ -483,6 +534,8 @@ pub trait ContainerService {
                                 },
                                 portfolio_id: project.portfolio_id,
                                 include_task_id_in_commits: Some(project.include_task_id_in_commits),
+                                approval_timeout_minutes: None,
+                                approval_timeout_action: None,
                             },
                         )
                         .await?;
@@ -619,7 +672,10 @@ pub trait ContainerService {
                     {
                         continue;
                     }
-                    if process.status == ExecutionProcessStatus::Running {
+                    if matches!(
+                        process.status,
+                        ExecutionProcessStatus::Running | ExecutionProcessStatus::Queued
+                    ) {
                         self.stop_execution(&process, ExecutionProcessStatus::Killed)
                             .await
                             .unwrap_or_else(|e| {
@@ -643,6 +699,17 @@ pub trait ContainerService {
 
     async fn is_container_clean(&self, workspace: &Workspace) -> Result<bool, ContainerError>;
 
+    /// Measure the worktrees of every workspace on disk and record their sizes
+    async fn measure_workspace_disk_usage(&self) -> Result<WorkspaceDiskSummary, ContainerError>;
+
+    /// Evict least recently used workspaces until the disk budgets are met. Workspaces with
+    /// running processes are never evicted, and those with uncommitted or unpushed changes
+    /// only when `force` is set.
+    async fn collect_workspace_garbage(
+        &self,
+        force: bool,
+    ) -> Result<WorkspaceGcReport, ContainerError>;
+
     async fn start_execution_inner(
         &self,
         workspace: &Workspace,
@@ -662,7 +729,7 @@ pub trait ContainerService {
         &self,
         source_dir: &Path,
         target_dir: &Path,
-        copy_files: &str,
+        copy_files: &[CopyFileRule],
     ) -> Result<(), ContainerError>;
 
     /// Stream diff updates as LogMsg for WebSocket endpoints.
@@ -777,19 +844,6 @@ pub trait ContainerService {
                 }
             };
 
-            // Create temporary store and populate
-            // Include JsonPatch messages (already normalized) and Stdout/Stderr (need normalization)
-            let temp_store = Arc::new(MsgStore::new());
-            for msg in raw_messages {
-                if matches!(
-                    msg,
-                    LogMsg::Stdout(_) | LogMsg::Stderr(_) | LogMsg::JsonPatch(_)
-                ) {
-                    temp_store.push(msg);
-                }
-            }
-            temp_store.push_finished();
-
             let process = match ExecutionProcess::find_by_id(&self.db().pool, *id).await {
                 Ok(Some(process)) => process,
                 Ok(None) => {
@@ -843,35 +897,14 @@ pub trait ContainerService {
                 return None;
             };
 
-            // Spawn normalizer on populated store
-            match executor_action.typ() {
-                ExecutorActionType::CodingAgentInitialRequest(request) => {
-                    let executor = ExecutorConfigs::get_cached()
-                        .get_coding_agent_or_default(&request.executor_profile_id);
-                    executor.normalize_logs(temp_store.clone(), &current_dir);
-                }
-                ExecutorActionType::CodingAgentFollowUpRequest(request) => {
-                    let executor = ExecutorConfigs::get_cached()
-                        .get_coding_agent_or_default(&request.executor_profile_id);
-                    executor.normalize_logs(temp_store.clone(), &current_dir);
-                }
-                _ => {
-                    tracing::debug!(
-                        "Executor action doesn't support log normalization: {:?}",
-                        process.executor_action()
-                    );
-                    return None;
-                }
+            let stream = replay_normalized_logs(executor_action, raw_messages, &current_dir);
+            if stream.is_none() {
+                tracing::debug!(
+                    "Executor action doesn't support log normalization: {:?}",
+                    process.executor_action()
+                );
             }
-            Some(
-                temp_store
-                    .history_plus_stream()
-                    .filter(|msg| future::ready(matches!(msg, Ok(LogMsg::JsonPatch(..)))))
-                    .chain(futures::stream::once(async {
-                        Ok::<_, std::io::Error>(LogMsg::Finished)
-                    }))
-                    .boxed(),
-            )
+            stream
         }
     }
 
@@ -889,6 +922,7 @@ pub trait ContainerService {
 
             if let Some(store) = store {
                 let mut stream = store.history_plus_stream();
+                let mut search_indexer = LogSearchIndexer::new(db.pool.clone(), execution_id);
 
                 while let Some(Ok(msg)) = stream.next().await {
                     match &msg {
@@ -942,9 +976,14 @@ pub trait ContainerService {
                         LogMsg::Finished => {
                             break;
                         }
-                        LogMsg::JsonPatch(_) => continue,
+                        LogMsg::JsonPatch(patch) => {
+                            search_indexer.observe(patch);
+                            search_indexer.flush_if_due().await;
+                        }
                     }
                 }
+
+                search_indexer.flush().await;
             }
         })
     }
@@ -954,8 +993,11 @@ pub trait ContainerService {
         workspace: &Workspace,
         executor_profile_id: ExecutorProfileId,
     ) -> Result<ExecutionProcess, ContainerError> {
-        // Create container
-        self.create(workspace).await?;
+        // Claim a ready workspace from the pool, or create one
+        let pooled = self.claim_pooled_workspace(workspace).await?.is_some();
+        if !pooled {
+            self.create(workspace).await?;
+        }
 
         // Get parent task
         let task = workspace
@@ -991,7 +1033,7 @@ pub trait ContainerService {
 
         let repos_with_setup: Vec<_> = project_repos
             .iter()
-            .filter(|pr| pr.setup_script.is_some())
+            .filter(|pr| pr.setup_script.is_some() && !pooled)
             .collect();
 
         let all_parallel = repos_with_setup.iter().all(|pr| pr.parallel_setup_script);
@@ -1112,10 +1154,27 @@ pub trait ContainerService {
             run_reason: run_reason.clone(),
         };
 
+        // Coding agent runs go through the scheduler queue whenever limits are configured
+        let queued_executor = if run_reason == &ExecutionProcessRunReason::CodingAgent
+            && !self.scheduler_config().await.is_unlimited()
+        {
+            executor_action
+                .executor_profile_id()
+                .map(|profile| profile.executor.to_string())
+        } else {
+            None
+        };
+        let initial_status = if queued_executor.is_some() {
+            ExecutionProcessStatus::Queued
+        } else {
+            ExecutionProcessStatus::Running
+        };
+
         let execution_process = ExecutionProcess::create(
             &self.db().pool,
             &create_execution_process,
             Uuid::new_v4(),
+            initial_status,
             &repo_states,
         )
         .await?;
@@ -1144,8 +1203,42 @@ pub trait ContainerService {
             .await?;
         }
 
+        if let Some(executor) = queued_executor {
+            ExecutionQueueEntry::create(
+                &self.db().pool,
+                execution_process.id,
+                task.project_id,
+                &executor,
+            )
+            .await?;
+            tracing::info!(
+                "Queued execution process {} for executor {}",
+                execution_process.id,
+                executor
+            );
+            self.dispatch_queued_executions().await?;
+            return Ok(
+                ExecutionProcess::find_by_id(&self.db().pool, execution_process.id)
+                    .await?
+                    .unwrap_or(execution_process),
+            );
+        }
+
+        self.launch_execution(workspace, &execution_process, executor_action)
+            .await?;
+        Ok(execution_process)
+    }
+
+    /// Start the child process for an execution record, wiring up log normalization
+    /// and persistence. On failure the process is marked failed and the error logged.
+    async fn launch_execution(
+        &self,
+        workspace: &Workspace,
+        execution_process: &ExecutionProcess,
+        executor_action: &ExecutorAction,
+    ) -> Result<(), ContainerError> {
         if let Err(start_error) = self
-            .start_execution_inner(workspace, &execution_process, executor_action)
+            .start_execution_inner(workspace, execution_process, executor_action)
             .await
         {
             // Mark process as failed
@@ -1163,7 +1256,7 @@ pub trait ContainerService {
                     update_error
                 );
             }
-            Task::update_status(&self.db().pool, task.id, TaskStatus::InReview).await?;
+            Task::update_status(&self.db().pool, workspace.task_id, TaskStatus::InReview).await?;
 
             // Emit stderr error message
             let log_message = LogMsg::Stderr(format!("Failed to start execution: {start_error}"));
@@ -1199,6 +1292,16 @@ pub trait ContainerService {
                     .await;
                 }
             };
+
+            // Auth and missing-executable failures can be retried with a fallback profile
+            if let ContainerError::ExecutorError(executor_error) = &start_error
+                && let Some(reason) = FallbackReason::from_executor_error(executor_error)
+                && let Ok(ctx) =
+                    ExecutionProcess::load_context(&self.db().pool, execution_process.id).await
+                && self.try_start_executor_fallback(&ctx, reason).await
+            {
+                return Ok(());
+            }
             return Err(start_error);
         }
 
@@ -1227,7 +1330,317 @@ pub trait ContainerService {
         }
 
         self.spawn_stream_raw_logs_to_db(&execution_process.id);
-        Ok(execution_process)
+        Ok(())
+    }
+
+    /// Start the execution run for an approved plan: a new session in the planning
+    /// workspace whose initial prompt is the task seeded with the plan.
+    async fn start_plan_execution(
+        &self,
+        plan: &TaskPlan,
+        task: &Task,
+        executor_profile_id: ExecutorProfileId,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        let workspace = Workspace::find_by_id(&self.db().pool, plan.workspace_id)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        self.ensure_container_exists(&workspace).await?;
+
+        let session = Session::create(
+            &self.db().pool,
+            &CreateSession {
+                executor: Some(executor_profile_id.executor.to_string()),
+            },
+            Uuid::new_v4(),
+            workspace.id,
+        )
+        .await?;
+
+        let project_repos =
+            ProjectRepo::find_by_project_id_with_names(&self.db().pool, task.project_id).await?;
+        let cleanup_action = self.cleanup_actions_for_repos(&project_repos);
+        let working_dir = workspace
+            .agent_working_dir
+            .as_ref()
+            .filter(|dir| !dir.is_empty())
+            .cloned();
+
+        let action = ExecutorAction::new(
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt: plan.to_execution_prompt(&task.to_prompt()),
+                executor_profile_id,
+                working_dir,
+            }),
+            cleanup_action.map(Box::new),
+        );
+
+        self.start_execution(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await
+    }
+
+    /// Retry a failed coding agent run with the next profile from its fallback chain,
+    /// in a new session of the same workspace. Returns true if a fallback was started.
+    async fn try_start_executor_fallback(
+        &self,
+        ctx: &ExecutionContext,
+        reason: FallbackReason,
+    ) -> bool {
+        let Ok(action) = ctx.execution_process.executor_action() else {
+            return false;
+        };
+        let (prompt, working_dir, failed_profile) = match action.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => (
+                request.prompt.clone(),
+                request.working_dir.clone(),
+                request.executor_profile_id.clone(),
+            ),
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => (
+                self.follow_up_fallback_prompt(ctx, &request.prompt).await,
+                request.working_dir.clone(),
+                request.executor_profile_id.clone(),
+            ),
+            _ => return false,
+        };
+
+        let next_profile = match self
+            .next_fallback_profile(ctx.session.id, &failed_profile)
+            .await
+        {
+            Ok(Some(profile)) => profile,
+            Ok(None) => return false,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to resolve fallback profile for execution {}: {}",
+                    ctx.execution_process.id,
+                    e
+                );
+                return false;
+            }
+        };
+
+        let pool = &self.db().pool;
+        let session = match Session::create(
+            pool,
+            &CreateSession {
+                executor: Some(next_profile.executor.to_string()),
+            },
+            Uuid::new_v4(),
+            ctx.workspace.id,
+        )
+        .await
+        {
+            Ok(session) => session,
+            Err(e) => {
+                tracing::error!("Failed to create fallback session: {}", e);
+                return false;
+            }
+        };
+
+        if let Err(e) = ExecutorFallback::create(
+            pool,
+            &CreateExecutorFallback {
+                workspace_id: ctx.workspace.id,
+                failed_execution_process_id: ctx.execution_process.id,
+                failed_session_id: ctx.session.id,
+                failed_profile: failed_profile.clone(),
+                fallback_session_id: session.id,
+                fallback_profile: next_profile.clone(),
+                reason,
+            },
+        )
+        .await
+        {
+            tracing::error!("Failed to record executor fallback: {}", e);
+            return false;
+        }
+
+        let log_message = LogMsg::Stderr(format!(
+            "Executor {failed_profile} failed ({reason}); retrying with fallback profile {next_profile}\n"
+        ));
+        if let Ok(json_line) = serde_json::to_string(&log_message) {
+            let _ = ExecutionProcessLogs::append_log_line(
+                pool,
+                ctx.execution_process.id,
+                &format!("{json_line}\n"),
+            )
+            .await;
+        }
+        tracing::info!(
+            "Execution {} failed with {}, retrying with {} in session {}",
+            ctx.execution_process.id,
+            reason,
+            next_profile,
+            session.id
+        );
+
+        let fallback_action = ExecutorAction::new(
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt,
+                executor_profile_id: next_profile,
+                working_dir,
+            }),
+            action.next_action.clone(),
+        );
+        match self
+            .start_execution(
+                &ctx.workspace,
+                &session,
+                &fallback_action,
+                &ExecutionProcessRunReason::CodingAgent,
+            )
+            .await
+        {
+            Ok(_) => true,
+            Err(e) => {
+                tracing::error!("Failed to start fallback execution: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Prompt that restarts a failed follow-up on a fallback executor with the
+    /// earlier turns of its session, which the new executor cannot resume
+    async fn follow_up_fallback_prompt(&self, ctx: &ExecutionContext, follow_up: &str) -> String {
+        let pool = &self.db().pool;
+        let processes = ExecutionProcess::find_by_session_id(pool, ctx.session.id, false)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(
+                    "Failed to load earlier turns of session {}: {}",
+                    ctx.session.id,
+                    e
+                );
+                Vec::new()
+            });
+        let mut turns = Vec::new();
+        for process in processes.iter().filter(|p| {
+            p.run_reason == ExecutionProcessRunReason::CodingAgent
+                && p.id != ctx.execution_process.id
+        }) {
+            if let Ok(Some(CodingAgentTurn {
+                prompt: Some(prompt),
+                summary,
+                ..
+            })) = CodingAgentTurn::find_by_execution_process_id(pool, process.id).await
+            {
+                turns.push((prompt, summary));
+            }
+        }
+        fallback::follow_up_prompt(&turns, follow_up)
+    }
+
+    /// The next untried profile in the fallback chain. The chain is defined by the
+    /// profile that started it, found by walking back through earlier hops.
+    async fn next_fallback_profile(
+        &self,
+        session_id: Uuid,
+        failed_profile: &ExecutorProfileId,
+    ) -> Result<Option<ExecutorProfileId>, ContainerError> {
+        let mut tried = vec![failed_profile.clone()];
+        let mut root = failed_profile.clone();
+        let mut current_session = session_id;
+        while let Some(hop) =
+            ExecutorFallback::find_by_fallback_session_id(&self.db().pool, current_session).await?
+        {
+            root = hop.failed_profile.0;
+            tried.push(root.clone());
+            current_session = hop.failed_session_id;
+        }
+
+        let configs = ExecutorConfigs::get_cached();
+        let chain = configs
+            .get_coding_agent(&root)
+            .and_then(|agent| agent.cmd_overrides().fallback_profiles.clone())
+            .unwrap_or_default();
+        Ok(chain.into_iter().find(|profile| {
+            !tried.contains(profile) && configs.get_coding_agent(profile).is_some()
+        }))
+    }
+
+    /// Start queued executions for as long as the scheduler limits allow.
+    /// Called after a queued item is added, when a coding agent finishes and on startup.
+    async fn dispatch_queued_executions(&self) -> Result<(), ContainerError> {
+        let scheduler = self.execution_scheduler().clone();
+        let _guard = scheduler.lock_dispatch().await;
+        let limits = self.scheduler_config().await;
+
+        loop {
+            let queued = ExecutionQueueEntry::find_all(&self.db().pool).await?;
+            if queued.is_empty() {
+                break;
+            }
+            let running: HashMap<String, i64> =
+                ExecutionProcess::count_running_coding_agents_by_executor(&self.db().pool)
+                    .await?
+                    .into_iter()
+                    .collect();
+            let Some(entry) = scheduler.select_next(&queued, &running, &limits).cloned() else {
+                break;
+            };
+
+            ExecutionQueueEntry::delete(&self.db().pool, entry.execution_process_id).await?;
+            scheduler.record_dispatch(entry.project_id);
+            if let Err(e) = self
+                .launch_queued_execution(entry.execution_process_id)
+                .await
+            {
+                tracing::error!(
+                    "Failed to start queued execution process {}: {}",
+                    entry.execution_process_id,
+                    e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Launch a process that was waiting in the queue. The before-head commits are
+    /// re-captured because earlier runs in the workspace may have moved HEAD.
+    async fn launch_queued_execution(
+        &self,
+        execution_process_id: Uuid,
+    ) -> Result<(), ContainerError> {
+        let pool = &self.db().pool;
+        let execution_process = ExecutionProcess::find_by_id(pool, execution_process_id)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        if execution_process.status != ExecutionProcessStatus::Queued {
+            return Ok(());
+        }
+        let (workspace, _) = execution_process
+            .parent_workspace_and_session(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let workspace_root = PathBuf::from(self.ensure_container_exists(&workspace).await?);
+        let workspace = Workspace::find_by_id(pool, workspace.id)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+
+        let repositories = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
+        for repo in &repositories {
+            if let Ok(head) = self.git().get_head_info(&workspace_root.join(&repo.name)) {
+                ExecutionProcessRepoState::update_before_head_commit(
+                    pool,
+                    execution_process.id,
+                    repo.id,
+                    &head.oid,
+                )
+                .await?;
+            }
+        }
+
+        ExecutionProcess::mark_started(pool, execution_process.id).await?;
+        let execution_process = ExecutionProcess::find_by_id(pool, execution_process.id)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let executor_action = execution_process.executor_action()?.clone();
+        self.launch_execution(&workspace, &execution_process, &executor_action)
+            .await
     }
 
     async fn try_start_next_action(&self, ctx: &ExecutionContext) -> Result<(), ContainerError> {
@@ -1269,3 +1682,43 @@ pub trait ContainerService {
         Ok(())
     }
 }
+
+/// Replay persisted log messages through the normalizer of the coding agent that produced
+/// them. Stored JsonPatch messages are passed through as they are. The stream yields the
+/// conversation patches followed by `Finished`; `None` for actions without a normalizer.
+pub fn replay_normalized_logs(
+    action: &ExecutorAction,
+    messages: Vec<LogMsg>,
+    current_dir: &Path,
+) -> Option<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>> {
+    let executor_profile_id = match action.typ() {
+        ExecutorActionType::CodingAgentInitialRequest(request) => &request.executor_profile_id,
+        ExecutorActionType::CodingAgentFollowUpRequest(request) => &request.executor_profile_id,
+        _ => return None,
+    };
+
+    // Include JsonPatch messages (already normalized) and Stdout/Stderr (need normalization)
+    let temp_store = Arc::new(MsgStore::new());
+    for msg in messages {
+        if matches!(
+            msg,
+            LogMsg::Stdout(_) | LogMsg::Stderr(_) | LogMsg::JsonPatch(_)
+        ) {
+            temp_store.push(msg);
+        }
+    }
+    temp_store.push_finished();
+
+    ExecutorConfigs::get_cached()
+        .get_coding_agent_or_default(executor_profile_id)
+        .normalize_logs(temp_store.clone(), current_dir);
+    Some(
+        temp_store
+            .history_plus_stream()
+            .filter(|msg| future::ready(matches!(msg, Ok(LogMsg::JsonPatch(..)))))
+            .chain(futures::stream::once(async {
+                Ok::<_, std::io::Error>(LogMsg::Finished)
+            }))
+            .boxed(),
+    )
+}
@@ -0,0 +1,353 @@
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, TcpListener},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::StreamExt;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+use uuid::Uuid;
+
+use crate::services::config::{DevServerConfig, DevServerReadinessCheck};
+
+const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Error)]
+pub enum DevServerError {
+    #[error("No free port between {0} and {1} for the dev server")]
+    NoFreePort(u16, u16),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum DevServerStatus {
+    /// Process started, readiness check still pending
+    Starting,
+    Ready,
+    /// Still running but never passed its readiness check
+    Unhealthy,
+    /// Crashed and waiting for the backoff before it is started again
+    Restarting,
+    /// Crashed more often than `max_restarts` allows
+    Crashed,
+    Stopped,
+}
+
+impl DevServerStatus {
+    /// Whether the dev server still holds on to its port
+    pub fn is_active(self) -> bool {
+        matches!(
+            self,
+            Self::Starting | Self::Ready | Self::Unhealthy | Self::Restarting
+        )
+    }
+}
+
+/// Dev server of a workspace, as shown on the workspace
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct DevServerInfo {
+    pub workspace_id: Uuid,
+    pub execution_process_id: Option<Uuid>,
+    pub port: u16,
+    pub url: String,
+    pub status: DevServerStatus,
+    /// Restarts since the dev server was last started by hand
+    pub restart_count: u32,
+    pub last_error: Option<String>,
+}
+
+/// Ports and status of the dev servers of all workspaces. Dev servers don't survive a
+/// restart of the app, so this state is kept in memory only.
+#[derive(Clone, Default)]
+pub struct DevServerRegistry {
+    servers: Arc<Mutex<HashMap<Uuid, DevServerInfo>>>,
+}
+
+impl DevServerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, workspace_id: Uuid) -> Option<DevServerInfo> {
+        self.servers.lock().unwrap().get(&workspace_id).cloned()
+    }
+
+    /// Reserve a port for a new dev server process of the workspace. The workspace keeps
+    /// its previous port if that is still free, so restarts keep the same URL.
+    pub fn allocate(
+        &self,
+        workspace_id: Uuid,
+        execution_process_id: Uuid,
+        config: &DevServerConfig,
+    ) -> Result<DevServerInfo, DevServerError> {
+        let mut servers = self.servers.lock().unwrap();
+        let taken: Vec<u16> = servers
+            .values()
+            .filter(|s| s.workspace_id != workspace_id && s.status.is_active())
+            .map(|s| s.port)
+            .collect();
+        let previous = servers.get(&workspace_id);
+
+        let port = previous
+            .map(|s| s.port)
+            .filter(|port| !taken.contains(port) && port_is_free(*port))
+            .or_else(|| {
+                (config.port_range_start..=config.port_range_end)
+                    .find(|port| !taken.contains(port) && port_is_free(*port))
+            })
+            .ok_or(DevServerError::NoFreePort(
+                config.port_range_start,
+                config.port_range_end,
+            ))?;
+
+        let info = DevServerInfo {
+            workspace_id,
+            execution_process_id: Some(execution_process_id),
+            port,
+            url: format!("http://localhost:{port}"),
+            status: DevServerStatus::Starting,
+            restart_count: previous.map(|s| s.restart_count).unwrap_or(0),
+            last_error: None,
+        };
+        servers.insert(workspace_id, info.clone());
+        Ok(info)
+    }
+
+    /// Update the status, unless a newer process has taken over the workspace's dev server
+    pub fn set_status(
+        &self,
+        workspace_id: Uuid,
+        execution_process_id: Uuid,
+        status: DevServerStatus,
+        last_error: Option<String>,
+    ) {
+        let mut servers = self.servers.lock().unwrap();
+        if let Some(server) = servers.get_mut(&workspace_id)
+            && server.execution_process_id == Some(execution_process_id)
+        {
+            server.status = status;
+            if last_error.is_some() {
+                server.last_error = last_error;
+            }
+        }
+    }
+
+    /// Record the outcome of the readiness check of `execution_process_id`. Ignored once the
+    /// process has exited, so a late check can't mask a crash.
+    pub fn report_readiness(
+        &self,
+        workspace_id: Uuid,
+        execution_process_id: Uuid,
+        result: Result<(), String>,
+    ) {
+        let mut servers = self.servers.lock().unwrap();
+        if let Some(server) = servers.get_mut(&workspace_id)
+            && server.execution_process_id == Some(execution_process_id)
+            && server.status == DevServerStatus::Starting
+        {
+            match result {
+                Ok(()) => server.status = DevServerStatus::Ready,
+                Err(e) => {
+                    server.status = DevServerStatus::Unhealthy;
+                    server.last_error = Some(e);
+                }
+            }
+        }
+    }
+
+    /// Count a crash of `execution_process_id` against the restart budget. Returns the
+    /// attempt number if the dev server should be started again.
+    pub fn record_crash(
+        &self,
+        workspace_id: Uuid,
+        execution_process_id: Uuid,
+        error: String,
+        config: &DevServerConfig,
+    ) -> Option<u32> {
+        let mut servers = self.servers.lock().unwrap();
+        let server = servers
+            .get_mut(&workspace_id)
+            .filter(|s| s.execution_process_id == Some(execution_process_id))?;
+        server.last_error = Some(error);
+
+        if !config.restart_on_crash || server.restart_count >= config.max_restarts {
+            server.status = DevServerStatus::Crashed;
+            return None;
+        }
+        server.restart_count += 1;
+        server.status = DevServerStatus::Restarting;
+        Some(server.restart_count)
+    }
+
+    /// Whether the workspace's dev server is still waiting to be restarted after a crash
+    /// of `execution_process_id`, i.e. nobody stopped or replaced it in the meantime
+    pub fn awaiting_restart(&self, workspace_id: Uuid, execution_process_id: Uuid) -> bool {
+        self.get(workspace_id).is_some_and(|s| {
+            s.status == DevServerStatus::Restarting
+                && s.execution_process_id == Some(execution_process_id)
+        })
+    }
+
+    /// Forget earlier crashes when the dev server is started by hand
+    pub fn reset_restarts(&self, workspace_id: Uuid) {
+        if let Some(server) = self.servers.lock().unwrap().get_mut(&workspace_id) {
+            server.restart_count = 0;
+            server.last_error = None;
+        }
+    }
+}
+
+fn port_is_free(port: u16) -> bool {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port)).is_ok()
+}
+
+/// Delay before the given restart attempt (1-based): 1s, 2s, 4s, ... capped at a minute
+pub fn restart_backoff(attempt: u32) -> Duration {
+    let secs = 1u64
+        .checked_shl(attempt.saturating_sub(1))
+        .unwrap_or(u64::MAX);
+    Duration::from_secs(secs).min(MAX_RESTART_BACKOFF)
+}
+
+/// Wait until the dev server passes the configured readiness check. Fails with a message
+/// if the check times out or can't be run.
+pub async fn wait_until_ready(
+    port: u16,
+    config: &DevServerConfig,
+    msg_store: Option<Arc<MsgStore>>,
+) -> Result<(), String> {
+    let timeout = Duration::from_secs(config.ready_timeout_secs.into());
+    match tokio::time::timeout(timeout, readiness_check(port, config, msg_store)).await {
+        Ok(result) => result,
+        Err(_) => Err(format!(
+            "Not ready after {} seconds",
+            config.ready_timeout_secs
+        )),
+    }
+}
+
+async fn readiness_check(
+    port: u16,
+    config: &DevServerConfig,
+    msg_store: Option<Arc<MsgStore>>,
+) -> Result<(), String> {
+    match config.readiness_check {
+        DevServerReadinessCheck::Tcp => {
+            while tokio::net::TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+                .await
+                .is_err()
+            {
+                tokio::time::sleep(READINESS_POLL_INTERVAL).await;
+            }
+            Ok(())
+        }
+        DevServerReadinessCheck::Http => {
+            let url = format!(
+                "http://127.0.0.1:{port}/{}",
+                config.health_check_path.trim_start_matches('/')
+            );
+            let client = reqwest::Client::new();
+            loop {
+                if let Ok(response) = client.get(&url).send().await
+                    && !response.status().is_server_error()
+                {
+                    return Ok(());
+                }
+                tokio::time::sleep(READINESS_POLL_INTERVAL).await;
+            }
+        }
+        DevServerReadinessCheck::LogLine => {
+            let pattern = config
+                .ready_log_pattern
+                .as_deref()
+                .ok_or("No ready log pattern configured")?;
+            let pattern =
+                Regex::new(pattern).map_err(|e| format!("Invalid ready log pattern: {e}"))?;
+            let msg_store = msg_store.ok_or("Dev server output is not available")?;
+            wait_for_log_line(msg_store, &pattern).await
+        }
+    }
+}
+
+async fn wait_for_log_line(msg_store: Arc<MsgStore>, pattern: &Regex) -> Result<(), String> {
+    let mut stream = msg_store.history_plus_stream();
+    while let Some(Ok(msg)) = stream.next().await {
+        match msg {
+            LogMsg::Stdout(chunk) | LogMsg::Stderr(chunk) => {
+                if chunk.lines().any(|line| pattern.is_match(line)) {
+                    return Ok(());
+                }
+            }
+            LogMsg::Finished => break,
+            _ => {}
+        }
+    }
+    Err("Dev server output ended before it reported ready".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_a_minute() {
+        assert_eq!(restart_backoff(1), Duration::from_secs(1));
+        assert_eq!(restart_backoff(2), Duration::from_secs(2));
+        assert_eq!(restart_backoff(4), Duration::from_secs(8));
+        assert_eq!(restart_backoff(7), MAX_RESTART_BACKOFF);
+        assert_eq!(restart_backoff(100), MAX_RESTART_BACKOFF);
+    }
+
+    #[test]
+    fn workspaces_get_distinct_ports() {
+        let registry = DevServerRegistry::new();
+        let config = DevServerConfig {
+            port_range_start: 47100,
+            port_range_end: 47199,
+            ..Default::default()
+        };
+        let first = registry
+            .allocate(Uuid::new_v4(), Uuid::new_v4(), &config)
+            .unwrap();
+        let second = registry
+            .allocate(Uuid::new_v4(), Uuid::new_v4(), &config)
+            .unwrap();
+        assert_ne!(first.port, second.port);
+    }
+
+    #[test]
+    fn crashes_stop_restarting_after_the_budget() {
+        let registry = DevServerRegistry::new();
+        let config = DevServerConfig {
+            port_range_start: 47200,
+            port_range_end: 47299,
+            max_restarts: 1,
+            ..Default::default()
+        };
+        let workspace_id = Uuid::new_v4();
+        let first = Uuid::new_v4();
+        registry.allocate(workspace_id, first, &config).unwrap();
+        assert_eq!(
+            registry.record_crash(workspace_id, first, "exit 1".into(), &config),
+            Some(1)
+        );
+        assert!(registry.awaiting_restart(workspace_id, first));
+
+        let second = Uuid::new_v4();
+        registry.allocate(workspace_id, second, &config).unwrap();
+        assert_eq!(
+            registry.record_crash(workspace_id, second, "exit 1".into(), &config),
+            None
+        );
+        assert_eq!(
+            registry.get(workspace_id).unwrap().status,
+            DevServerStatus::Crashed
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use db::models::execution_queue::ExecutionQueueEntry;
+use executors::executors::BaseCodingAgent;
+use tokio::sync::{Mutex as AsyncMutex, MutexGuard};
+use uuid::Uuid;
+
+use crate::services::config::SchedulerConfig;
+
+/// Decides which queued coding agent run starts next.
+///
+/// Ordering rules:
+/// - Entries of higher priority tasks always go first
+/// - Within a priority, projects take turns: the project that was dispatched
+///   least recently wins, so one busy project cannot starve the others
+/// - Within a project, entries run in FIFO order
+///
+/// The queue itself lives in the database (`execution_queue`); this struct only
+/// holds the dispatch lock and the in-memory round-robin state.
+#[derive(Clone, Default)]
+pub struct ExecutionScheduler {
+    dispatch_lock: Arc<AsyncMutex<()>>,
+    fairness: Arc<Mutex<FairnessState>>,
+}
+
+#[derive(Default)]
+struct FairnessState {
+    tick: u64,
+    last_dispatch: HashMap<Uuid, u64>,
+}
+
+impl ExecutionScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serializes dispatch so two completions cannot both claim the last free slot
+    pub async fn lock_dispatch(&self) -> MutexGuard<'_, ()> {
+        self.dispatch_lock.lock().await
+    }
+
+    /// Record that an entry for `project_id` has been dispatched
+    pub fn record_dispatch(&self, project_id: Uuid) {
+        let mut state = self.fairness.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+        state.last_dispatch.insert(project_id, tick);
+    }
+
+    /// Pick the next entry to start given the currently running coding agents
+    /// per executor. `queued` must be ordered by priority DESC, created_at ASC.
+    pub fn select_next<'a>(
+        &self,
+        queued: &'a [ExecutionQueueEntry],
+        running: &HashMap<String, i64>,
+        limits: &SchedulerConfig,
+    ) -> Option<&'a ExecutionQueueEntry> {
+        let total_running: i64 = running.values().sum();
+        if let Some(max) = limits.max_concurrent_executions
+            && total_running >= i64::from(max)
+        {
+            return None;
+        }
+
+        let state = self.fairness.lock().unwrap();
+        let mut best: Option<(&ExecutionQueueEntry, u64)> = None;
+        for entry in queued {
+            if !has_executor_capacity(entry, running, limits) {
+                continue;
+            }
+            let last = state
+                .last_dispatch
+                .get(&entry.project_id)
+                .copied()
+                .unwrap_or(0);
+            best = match best {
+                None => Some((entry, last)),
+                Some((current, _)) if entry.priority < current.priority => break,
+                // Strictly older dispatch wins; ties keep the earlier (FIFO) entry
+                Some((_, current_last)) if last < current_last => Some((entry, last)),
+                keep => keep,
+            };
+        }
+        best.map(|(entry, _)| entry)
+    }
+}
+
+fn has_executor_capacity(
+    entry: &ExecutionQueueEntry,
+    running: &HashMap<String, i64>,
+    limits: &SchedulerConfig,
+) -> bool {
+    let Ok(executor) = BaseCodingAgent::from_str(&entry.executor) else {
+        return true;
+    };
+    match limits.max_concurrent_per_executor.get(&executor) {
+        Some(max) => running.get(&entry.executor).copied().unwrap_or(0) < i64::from(*max),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+
+    fn entry(project_id: Uuid, executor: &str, priority: i64, age: i64) -> ExecutionQueueEntry {
+        ExecutionQueueEntry {
+            execution_process_id: Uuid::new_v4(),
+            project_id,
+            executor: executor.to_string(),
+            priority,
+            created_at: Utc::now() - Duration::seconds(age),
+        }
+    }
+
+    #[test]
+    fn respects_global_and_executor_limits() {
+        let scheduler = ExecutionScheduler::new();
+        let project = Uuid::new_v4();
+        let queued = vec![
+            entry(project, "CLAUDE_CODE", 0, 20),
+            entry(project, "CODEX", 0, 10),
+        ];
+        let limits = SchedulerConfig {
+            max_concurrent_executions: Some(2),
+            max_concurrent_per_executor: HashMap::from([(BaseCodingAgent::ClaudeCode, 1)]),
+        };
+
+        let running = HashMap::from([("CLAUDE_CODE".to_string(), 1)]);
+        let next = scheduler.select_next(&queued, &running, &limits).unwrap();
+        assert_eq!(next.executor, "CODEX", "Claude Code is at its cap");
+
+        let running = HashMap::from([("CLAUDE_CODE".to_string(), 1), ("CODEX".to_string(), 1)]);
+        assert!(scheduler.select_next(&queued, &running, &limits).is_none());
+    }
+
+    #[test]
+    fn round_robins_projects_within_priority() {
+        let scheduler = ExecutionScheduler::new();
+        let busy = Uuid::new_v4();
+        let quiet = Uuid::new_v4();
+        let queued = vec![
+            entry(busy, "CLAUDE_CODE", 5, 40),
+            entry(busy, "CLAUDE_CODE", 0, 30),
+            entry(busy, "CLAUDE_CODE", 0, 20),
+            entry(quiet, "CLAUDE_CODE", 0, 10),
+        ];
+        let limits = SchedulerConfig::default();
+        let running = HashMap::new();
+
+        // Priority beats fairness
+        let first = scheduler.select_next(&queued, &running, &limits).unwrap();
+        assert_eq!(first.priority, 5);
+        scheduler.record_dispatch(first.project_id);
+
+        // The quiet project goes next even though its entry is newest
+        let second = scheduler
+            .select_next(&queued[1..], &running, &limits)
+            .unwrap();
+        assert_eq!(second.project_id, quiet);
+        scheduler.record_dispatch(second.project_id);
+
+        // Back to the busy project, oldest entry first
+        let remaining = vec![queued[1].clone(), queued[2].clone()];
+        let third = scheduler
+            .select_next(&remaining, &running, &limits)
+            .unwrap();
+        assert_eq!(third.execution_process_id, queued[1].execution_process_id);
+    }
+}
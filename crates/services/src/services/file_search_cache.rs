@@ -24,6 +24,7 @@ use ts_rs::TS;
 use super::{
     file_ranker::{FileRanker, FileStats},
     git::GitService,
+    sparse_checkout::SparseCheckout,
 };
 
 /// Search mode for different use cases
@@ -142,6 +143,7 @@ impl FileSearchCache {
         repo_path: &Path,
         query: &str,
         mode: SearchMode,
+        sparse_checkout: Option<&SparseCheckout>,
     ) -> Result<Vec<SearchResult>, CacheError> {
         let repo_path_buf = repo_path.to_path_buf();
 
@@ -151,7 +153,9 @@ impl FileSearchCache {
             && head_info.oid == cached.head_sha
         {
             // Cache hit - perform fast search with mode-based filtering
-            return Ok(self.search_in_cache(&cached, query, mode).await);
+            return Ok(self
+                .search_in_cache(&cached, query, mode, sparse_checkout)
+                .await);
         }
 
         // Cache miss - trigger background refresh and return error
@@ -233,6 +237,7 @@ impl FileSearchCache {
         cached: &CachedRepo,
         query: &str,
         mode: SearchMode,
+        sparse_checkout: Option<&SparseCheckout>,
     ) -> Vec<SearchResult> {
         let query_lower = query.to_lowercase();
         let mut results = Vec::new();
@@ -254,6 +259,13 @@ impl FileSearchCache {
                     }
                 }
 
+                // Skip files that workspaces don't check out
+                if sparse_checkout.is_some_and(|sparse| {
+                    !sparse.contains(&indexed_file.path, indexed_file.is_file)
+                }) {
+                    continue;
+                }
+
                 results.push(SearchResult {
                     path: indexed_file.path.clone(),
                     is_file: indexed_file.is_file,
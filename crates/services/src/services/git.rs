@@ -6,6 +6,7 @@ use git2::{
     Repository, Sort,
 };
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 use ts_rs::TS;
 use utils::diff::{Diff, DiffChangeKind, FileDiffDetails, compute_line_change_counts};
@@ -47,6 +48,10 @@ pub struct GitService {}
 // their contents omitted from the diff stream to avoid UI crashes.
 const MAX_INLINE_DIFF_BYTES: usize = 2 * 1024 * 1024; // ~2MB
 
+// LFS pointer files start with this line and are always smaller than 1KB
+const LFS_POINTER_PREFIX: &str = "version https://git-lfs.github.com/spec/v1";
+const LFS_POINTER_MAX_BYTES: u64 = 1024;
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 #[ts(rename_all = "snake_case")]
@@ -464,10 +469,14 @@ impl GitService {
                     if content_omitted {
                         (path_opt, None)
                     } else {
-                        let details = delta
-                            .old_file()
-                            .path()
-                            .map(|p| self.create_file_details(p, &delta.old_file().id(), repo));
+                        let details = delta.old_file().path().map(|p| {
+                            self.create_file_details(
+                                p,
+                                &delta.old_file().id(),
+                                delta.old_file().mode(),
+                                repo,
+                            )
+                        });
                         (
                             details.as_ref().and_then(|f| f.file_name.clone()),
                             details.and_then(|f| f.content),
@@ -485,10 +494,14 @@ impl GitService {
                     if content_omitted {
                         (path_opt, None)
                     } else {
-                        let details = delta
-                            .new_file()
-                            .path()
-                            .map(|p| self.create_file_details(p, &delta.new_file().id(), repo));
+                        let details = delta.new_file().path().map(|p| {
+                            self.create_file_details(
+                                p,
+                                &delta.new_file().id(),
+                                delta.new_file().mode(),
+                                repo,
+                            )
+                        });
                         (
                             details.as_ref().and_then(|f| f.file_name.clone()),
                             details.and_then(|f| f.content),
@@ -608,17 +621,82 @@ impl GitService {
         }
     }
 
+    /// Content shown for a submodule side of a diff, the same as `git diff` prints it
+    fn gitlink_content(commit_id: git2::Oid) -> String {
+        format!("Subproject commit {commit_id}\n")
+    }
+
+    /// What git stores for a worktree path when it differs from the file on disk:
+    /// the commit checked out in a submodule, the pointer of an LFS tracked file, or
+    /// the committed file when it is outside the sparse checkout and not on disk.
+    fn stored_worktree_content(repo: &Repository, rel_path: &Path) -> Option<String> {
+        let workdir = repo.workdir()?;
+        if workdir.join(rel_path).symlink_metadata().is_err() {
+            let entry = repo
+                .head()
+                .ok()?
+                .peel_to_tree()
+                .ok()?
+                .get_path(rel_path)
+                .ok()?;
+            return repo
+                .find_blob(entry.id())
+                .ok()
+                .filter(|blob| blob.size() <= MAX_INLINE_DIFF_BYTES)
+                .and_then(|blob| Self::blob_to_string(&blob));
+        }
+        if workdir.join(rel_path).is_dir() {
+            let submodule = repo.find_submodule(rel_path.to_str()?).ok()?;
+            return submodule
+                .workdir_id()
+                .or_else(|| submodule.index_id())
+                .map(Self::gitlink_content);
+        }
+
+        let is_lfs = matches!(
+            repo.get_attr(rel_path, "filter", git2::AttrCheckFlags::FILE_THEN_INDEX),
+            Ok(Some("lfs"))
+        );
+        if is_lfs {
+            Self::read_lfs_pointer(&workdir.join(rel_path))
+        } else {
+            None
+        }
+    }
+
+    /// LFS pointer for a file, read as is when it was never smudged, otherwise
+    /// computed from the checked-out object the way `git lfs clean` does
+    fn read_lfs_pointer(abs_path: &Path) -> Option<String> {
+        let mut file = std::fs::File::open(abs_path).ok()?;
+        if file.metadata().ok()?.len() < LFS_POINTER_MAX_BYTES
+            && let Ok(content) = std::fs::read_to_string(abs_path)
+            && content.starts_with(LFS_POINTER_PREFIX)
+        {
+            return Some(content);
+        }
+
+        let mut hasher = Sha256::new();
+        let size = std::io::copy(&mut file, &mut hasher).ok()?;
+        Some(format!(
+            "{LFS_POINTER_PREFIX}\noid sha256:{:x}\nsize {size}\n",
+            hasher.finalize()
+        ))
+    }
+
     /// Create FileDiffDetails from path and blob with filesystem fallback
     fn create_file_details(
         &self,
         path: &Path,
         blob_id: &git2::Oid,
+        mode: git2::FileMode,
         repo: &Repository,
     ) -> FileDiffDetails {
         let file_name = path.to_string_lossy().to_string();
 
-        // Try to get content from blob first (for non-zero OIDs)
-        let content = if !blob_id.is_zero() {
+        // Submodules point at a commit in another repository, there is no blob to read
+        let content = if mode == git2::FileMode::Commit {
+            Some(Self::gitlink_content(*blob_id))
+        } else if !blob_id.is_zero() {
             repo.find_blob(*blob_id)
                 .ok()
                 .and_then(|blob| Self::blob_to_string(&blob))
@@ -668,6 +746,11 @@ impl GitService {
             ChangeType::Unknown(_) => (e.old_path.clone(), Some(e.path.clone())),
         };
 
+        // Submodules and LFS files are compared by what git stores for them
+        let new_stored = new_path_opt
+            .as_deref()
+            .and_then(|newp| Self::stored_worktree_content(repo, Path::new(newp)));
+
         // Decide if we should omit content by size (either side)
         let mut content_omitted = false;
         // Old side (from base tree)
@@ -683,7 +766,8 @@ impl GitService {
             }
         }
         // New side (from filesystem)
-        if let Some(ref newp) = new_path_opt
+        if new_stored.is_none()
+            && let Some(ref newp) = new_path_opt
             && let Some(workdir) = repo.workdir()
         {
             let abs = workdir.join(newp);
@@ -706,6 +790,9 @@ impl GitService {
                         .find_blob(entry.id())
                         .ok()
                         .and_then(|b| Self::blob_to_string(&b)),
+                    Ok(entry) if entry.kind() == Some(git2::ObjectType::Commit) => {
+                        Some(Self::gitlink_content(entry.id()))
+                    }
                     _ => None,
                 }
             } else {
@@ -713,7 +800,9 @@ impl GitService {
             };
 
             // Load new content from filesystem (worktree) when available
-            let new_content = if let Some(ref newp) = new_path_opt {
+            let new_content = if new_stored.is_some() {
+                new_stored
+            } else if let Some(ref newp) = new_path_opt {
                 let rel = std::path::Path::new(newp);
                 Self::read_file_to_string(repo, rel)
             } else {
@@ -1175,6 +1264,86 @@ impl GitService {
         Ok(())
     }
 
+    /// Like [`Self::add_worktree`], but only checks out the given cone-mode directories
+    pub fn add_sparse_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch: &str,
+        create_branch: bool,
+        dirs: &[String],
+    ) -> Result<(), GitServiceError> {
+        let git = GitCli::new();
+        git.worktree_add_sparse(repo_path, worktree_path, branch, create_branch, dirs)
+            .map_err(|e| GitServiceError::InvalidRepository(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Cone directories checked out in a worktree, or `None` when it checks out everything
+    pub fn get_sparse_checkout(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<Option<Vec<String>>, GitServiceError> {
+        Ok(GitCli::new().sparse_checkout_list(worktree_path)?)
+    }
+
+    /// Check out more directories in a sparse worktree. A worktree that already checks
+    /// out everything is left alone.
+    pub fn widen_sparse_checkout(
+        &self,
+        worktree_path: &Path,
+        dirs: &[String],
+    ) -> Result<Option<Vec<String>>, GitServiceError> {
+        let git = GitCli::new();
+        if !git.is_sparse_checkout(worktree_path)? {
+            return Ok(None);
+        }
+        if !dirs.is_empty() {
+            git.sparse_checkout_add(worktree_path, dirs)?;
+            // Newly checked out directories may contain submodules or LFS files
+            if let Err(e) = self.update_submodules_and_lfs(worktree_path) {
+                tracing::warn!(
+                    "Failed to update submodules or LFS objects in {}: {}",
+                    worktree_path.display(),
+                    e
+                );
+            }
+        }
+        Ok(git.sparse_checkout_list(worktree_path)?)
+    }
+
+    /// Initialize submodules and download LFS objects that a checkout is missing.
+    /// Submodules the worktree has already initialized are left at their current commit.
+    pub fn update_submodules_and_lfs(&self, worktree_path: &Path) -> Result<(), GitServiceError> {
+        let git = GitCli::new();
+
+        if worktree_path.join(".gitmodules").exists() {
+            let paths = git.uninitialized_submodules(worktree_path)?;
+            if !paths.is_empty() {
+                tracing::info!(
+                    "Initializing {} submodule(s) in {}",
+                    paths.len(),
+                    worktree_path.display()
+                );
+                git.submodule_update(worktree_path, &paths)?;
+            }
+        }
+
+        if git.uses_lfs(worktree_path)? {
+            let pointers = git.lfs_pointer_files(worktree_path)?;
+            if !pointers.is_empty() {
+                tracing::info!(
+                    "Pulling {} LFS object(s) in {}",
+                    pointers.len(),
+                    worktree_path.display()
+                );
+                git.lfs_pull(worktree_path)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Remove a worktree
     pub fn remove_worktree(
         &self,
@@ -107,6 +107,91 @@ impl GitCli {
         Ok(())
     }
 
+    /// Add a worktree that only checks out the given cone-mode directories. It is created
+    /// without a checkout, so the files outside the cone are never written.
+    pub fn worktree_add_sparse(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch: &str,
+        create_branch: bool,
+        dirs: &[String],
+    ) -> Result<(), GitCliError> {
+        self.ensure_available()?;
+
+        let mut args: Vec<OsString> = vec!["worktree".into(), "add".into(), "--no-checkout".into()];
+        if create_branch {
+            args.push("-b".into());
+            args.push(OsString::from(branch));
+        }
+        args.push(worktree_path.as_os_str().into());
+        args.push(OsString::from(branch));
+        self.git(repo_path, args)?;
+        self.sparse_checkout_set(worktree_path, dirs)?;
+        self.git(worktree_path, ["checkout", branch])?;
+        Ok(())
+    }
+
+    /// Restrict the checkout to the given directories (`git sparse-checkout set --cone`)
+    pub fn sparse_checkout_set(
+        &self,
+        worktree_path: &Path,
+        dirs: &[String],
+    ) -> Result<(), GitCliError> {
+        let mut args: Vec<OsString> = vec![
+            "sparse-checkout".into(),
+            "set".into(),
+            "--cone".into(),
+            "--".into(),
+        ];
+        args.extend(dirs.iter().map(OsString::from));
+        self.git(worktree_path, args)?;
+        Ok(())
+    }
+
+    /// Widen the checkout with more directories (`git sparse-checkout add`)
+    pub fn sparse_checkout_add(
+        &self,
+        worktree_path: &Path,
+        dirs: &[String],
+    ) -> Result<(), GitCliError> {
+        let mut args: Vec<OsString> = vec!["sparse-checkout".into(), "add".into(), "--".into()];
+        args.extend(dirs.iter().map(OsString::from));
+        self.git(worktree_path, args)?;
+        Ok(())
+    }
+
+    /// Cone directories of a sparse checkout, or `None` when everything is checked out.
+    pub fn sparse_checkout_list(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<Option<Vec<String>>, GitCliError> {
+        if !self.is_sparse_checkout(worktree_path)? {
+            return Ok(None);
+        }
+        let out = self.git(worktree_path, ["sparse-checkout", "list"])?;
+        Ok(Some(
+            out.lines()
+                .map(str::trim)
+                .filter(|dir| !dir.is_empty())
+                .map(str::to_string)
+                .collect(),
+        ))
+    }
+
+    /// Return true if sparse-checkout is enabled for this worktree.
+    pub fn is_sparse_checkout(&self, worktree_path: &Path) -> Result<bool, GitCliError> {
+        // `git config --get` exits with 1 when the key is unset
+        match self.git(
+            worktree_path,
+            ["config", "--type=bool", "--get", "core.sparseCheckout"],
+        ) {
+            Ok(out) => Ok(out.trim() == "true"),
+            Err(GitCliError::CommandFailed(_)) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
     /// Run `git -C <repo> worktree remove <path>`
     pub fn worktree_remove(
         &self,
@@ -204,12 +289,16 @@ impl GitCli {
                 input.extend_from_slice(&p);
                 input.push(0);
             }
-            let args = vec![
+            let mut args = vec![
                 OsString::from("add"),
                 OsString::from("-A"),
                 OsString::from("--pathspec-from-file=-"),
                 OsString::from("--pathspec-file-nul"),
             ];
+            // Paths outside the sparse cone are refused without --sparse
+            if self.is_sparse_checkout(worktree_path)? {
+                args.push(OsString::from("--sparse"));
+            }
             self.git_with_stdin(worktree_path, args, Some(&envs), &input)?;
         }
         // git diff --cached
@@ -289,10 +378,12 @@ impl GitCli {
 
     /// Stage all changes in the working tree (respects sparse-checkout semantics).
     pub fn add_all(&self, worktree_path: &Path) -> Result<(), GitCliError> {
-        self.git(
-            worktree_path,
-            Self::apply_default_excludes(vec!["add", "-A"]),
-        )?;
+        let mut args = vec!["add", "-A"];
+        // Files created outside the sparse cone are refused without --sparse
+        if self.is_sparse_checkout(worktree_path)? {
+            args.push("--sparse");
+        }
+        self.git(worktree_path, Self::apply_default_excludes(args))?;
         Ok(())
     }
 
@@ -415,6 +506,87 @@ impl GitCli {
         }
     }
 
+    /// Paths of the top-level submodules that are not yet initialized, from `git submodule status`.
+    pub fn uninitialized_submodules(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<Vec<String>, GitCliError> {
+        let out = self.git(worktree_path, ["submodule", "status"])?;
+        Ok(Self::parse_uninitialized_submodules(&out))
+    }
+
+    /// Initialize and check out the given submodules and their nested submodules.
+    /// `--recommend-shallow` honours `shallow = true` entries in `.gitmodules`.
+    pub fn submodule_update(
+        &self,
+        worktree_path: &Path,
+        paths: &[String],
+    ) -> Result<(), GitCliError> {
+        let envs = vec![(OsString::from("GIT_TERMINAL_PROMPT"), OsString::from("0"))];
+        self.git_with_env(worktree_path, ["submodule", "sync", "--recursive"], &envs)?;
+
+        let mut args: Vec<OsString> = vec![
+            "submodule".into(),
+            "update".into(),
+            "--init".into(),
+            "--recursive".into(),
+            "--recommend-shallow".into(),
+            "--".into(),
+        ];
+        args.extend(paths.iter().map(OsString::from));
+        match self.git_with_env(worktree_path, args, &envs) {
+            Ok(_) => Ok(()),
+            Err(GitCliError::CommandFailed(msg)) => Err(self.classify_cli_error(msg)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Return true if any tracked `.gitattributes` file routes paths through the LFS filter.
+    pub fn uses_lfs(&self, worktree_path: &Path) -> Result<bool, GitCliError> {
+        let out = self.git(
+            worktree_path,
+            ["ls-files", "-z", "--", ":(glob)**/.gitattributes"],
+        )?;
+        Ok(out
+            .split('\0')
+            .filter(|p| !p.is_empty())
+            .filter_map(|p| std::fs::read_to_string(worktree_path.join(p)).ok())
+            .any(|attrs| attrs.contains("filter=lfs")))
+    }
+
+    /// LFS tracked files that are still pointers in the worktree (`-` in `git lfs ls-files`).
+    pub fn lfs_pointer_files(&self, worktree_path: &Path) -> Result<Vec<String>, GitCliError> {
+        let out = self.git(worktree_path, ["lfs", "ls-files"])?;
+        Ok(out
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .filter_map(|(_oid, rest)| rest.strip_prefix("- "))
+            .map(|path| path.to_string())
+            .collect())
+    }
+
+    /// Download and check out the LFS objects of the current checkout.
+    pub fn lfs_pull(&self, worktree_path: &Path) -> Result<(), GitCliError> {
+        let envs = vec![(OsString::from("GIT_TERMINAL_PROMPT"), OsString::from("0"))];
+        match self.git_with_env(worktree_path, ["lfs", "pull"], &envs) {
+            Ok(_) => Ok(()),
+            Err(GitCliError::CommandFailed(msg)) => Err(self.classify_cli_error(msg)),
+            Err(err) => Err(err),
+        }
+    }
+
+    // Parse `git submodule status` output, where a leading `-` marks an uninitialized
+    // submodule: `-<sha> <path>`. Initialized entries also carry a ` (<describe>)` suffix.
+    fn parse_uninitialized_submodules(output: &str) -> Vec<String> {
+        output
+            .lines()
+            .filter_map(|line| line.strip_prefix('-'))
+            .filter_map(|rest| rest.split_once(' '))
+            .map(|(_sha, path)| path.trim().to_string())
+            .filter(|path| !path.is_empty())
+            .collect()
+    }
+
     // Parse `git diff --name-status` output into structured entries.
     // Handles rename/copy scores like `R100` by matching the first letter.
     fn parse_name_status(output: &str) -> Vec<StatusDiffEntry> {
@@ -0,0 +1,207 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use db::{
+    DBService,
+    models::{
+        execution_process::ExecutionProcess,
+        execution_process_logs::{ExecutionProcessLogs, LogStorageUsage},
+    },
+};
+use futures::{StreamExt, future};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{error, info, warn};
+use ts_rs::TS;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+use crate::services::{
+    config::{Config, LogRetentionConfig},
+    container::replay_normalized_logs,
+};
+
+/// Processes compressed per query while sweeping finished executions
+const COMPRESS_BATCH_SIZE: i64 = 50;
+/// How long replaying an expired process's logs through its normalizer may take
+const NORMALIZE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Outcome of a log maintenance pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct LogMaintenanceReport {
+    /// Finished processes whose raw logs were packed into compressed chunks
+    pub compressed_processes: i64,
+    /// Processes whose raw output was replaced by their normalized conversation under
+    /// the retention policy
+    pub expired_processes: i64,
+    /// Log rows removed because their execution process no longer exists
+    pub orphaned_rows: i64,
+    pub usage_before: LogStorageUsage,
+    pub usage_after: LogStorageUsage,
+    /// Bytes of log storage freed by this pass
+    pub reclaimed_bytes: i64,
+    /// Size of the database file before and after; freed pages are only returned to
+    /// the filesystem by a vacuum
+    pub database_bytes_before: i64,
+    pub database_bytes_after: i64,
+    pub vacuumed: bool,
+}
+
+/// Background service that compresses the logs of finished execution processes and
+/// applies the configured log retention
+pub struct LogMaintenanceService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    poll_interval: Duration,
+}
+
+impl LogMaintenanceService {
+    pub async fn spawn(db: DBService, config: Arc<RwLock<Config>>) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            poll_interval: Duration::from_secs(10 * 60),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting log maintenance service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            let config = self.config.read().await.log_retention.clone();
+            match Self::run(&self.db.pool, &config, false).await {
+                Ok(report) if report.reclaimed_bytes > 0 => info!(
+                    "Log maintenance compressed {} and expired {} processes, reclaiming {} bytes",
+                    report.compressed_processes, report.expired_processes, report.reclaimed_bytes
+                ),
+                Ok(_) => {}
+                Err(e) => error!("Error running log maintenance: {}", e),
+            }
+        }
+    }
+
+    /// Run one maintenance pass: prune orphaned rows, replace raw logs past the retention
+    /// window with the normalized conversation, compress the rest of the finished
+    /// processes and optionally vacuum
+    pub async fn run(
+        pool: &SqlitePool,
+        config: &LogRetentionConfig,
+        vacuum: bool,
+    ) -> Result<LogMaintenanceReport, sqlx::Error> {
+        let usage_before = ExecutionProcessLogs::storage_usage(pool).await?;
+        let database_bytes_before = database_bytes(pool).await?;
+
+        let orphaned_rows = ExecutionProcessLogs::delete_orphaned(pool).await? as i64;
+
+        let mut expired_processes = 0;
+        if let Some(days) = config.raw_log_retention_days {
+            let cutoff = Utc::now() - chrono::Duration::days(days as i64);
+            for id in ExecutionProcessLogs::find_expired(pool, cutoff).await? {
+                // Keep the raw logs until the conversation can be recovered from them
+                let Some(normalized) = normalized_messages(pool, id).await? else {
+                    continue;
+                };
+                ExecutionProcessLogs::expire_raw_logs(pool, id, &normalized).await?;
+                expired_processes += 1;
+            }
+        }
+
+        let mut compressed_processes = 0;
+        if config.compress_completed {
+            loop {
+                let ids =
+                    ExecutionProcessLogs::find_compressible(pool, COMPRESS_BATCH_SIZE).await?;
+                if ids.is_empty() {
+                    break;
+                }
+                for id in ids {
+                    ExecutionProcessLogs::compress_execution(pool, id).await?;
+                    compressed_processes += 1;
+                }
+            }
+        }
+
+        if vacuum {
+            sqlx::query("VACUUM").execute(pool).await?;
+        }
+
+        let usage_after = ExecutionProcessLogs::storage_usage(pool).await?;
+        let database_bytes_after = database_bytes(pool).await?;
+        Ok(LogMaintenanceReport {
+            compressed_processes,
+            expired_processes,
+            orphaned_rows,
+            reclaimed_bytes: (stored_bytes(&usage_before) - stored_bytes(&usage_after)).max(0),
+            usage_before,
+            usage_after,
+            database_bytes_before,
+            database_bytes_after,
+            vacuumed: vacuum,
+        })
+    }
+}
+
+/// Conversation patches of a finished process, replayed from its stored logs. Empty for
+/// processes without a normalizer, e.g. scripts; `None` if the logs could not be
+/// normalized.
+async fn normalized_messages(
+    pool: &SqlitePool,
+    execution_id: Uuid,
+) -> Result<Option<Vec<LogMsg>>, sqlx::Error> {
+    let Some(process) = ExecutionProcess::find_by_id(pool, execution_id).await? else {
+        return Ok(Some(Vec::new()));
+    };
+    let Ok(action) = process.executor_action() else {
+        return Ok(Some(Vec::new()));
+    };
+    let records = ExecutionProcessLogs::find_by_execution_id(pool, execution_id).await?;
+    let messages = match ExecutionProcessLogs::parse_logs(&records) {
+        Ok(messages) => messages,
+        Err(e) => {
+            warn!("Failed to parse logs of execution process {execution_id}: {e}");
+            return Ok(None);
+        }
+    };
+    // The worktree may be gone by now; the normalizer only uses it to relativize paths
+    let current_dir = process
+        .parent_workspace_and_session(pool)
+        .await?
+        .and_then(|(workspace, _)| workspace.container_ref)
+        .map(PathBuf::from)
+        .unwrap_or_default();
+
+    let Some(stream) = replay_normalized_logs(action, messages, &current_dir) else {
+        return Ok(Some(Vec::new()));
+    };
+    let patches = stream
+        .take_while(|msg| future::ready(!matches!(msg, Ok(LogMsg::Finished))))
+        .filter_map(|msg| future::ready(msg.ok()))
+        .collect::<Vec<_>>();
+    match tokio::time::timeout(NORMALIZE_TIMEOUT, patches).await {
+        Ok(patches) => Ok(Some(patches)),
+        Err(_) => {
+            warn!("Timed out normalizing logs of execution process {execution_id}");
+            Ok(None)
+        }
+    }
+}
+
+fn stored_bytes(usage: &LogStorageUsage) -> i64 {
+    usage.raw_bytes + usage.compressed_bytes
+}
+
+async fn database_bytes(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()")
+        .fetch_one(pool)
+        .await
+}
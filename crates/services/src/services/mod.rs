@@ -1,9 +1,12 @@
 pub mod approvals;
+pub mod attempt_comparison;
 pub mod auth;
 pub mod config;
 pub mod container;
+pub mod dev_server;
 pub mod diff_stream;
 pub mod events;
+pub mod execution_scheduler;
 pub mod file_ranker;
 pub mod file_search_cache;
 pub mod filesystem;
@@ -13,6 +16,7 @@ pub mod git;
 pub mod github;
 pub mod image;
 pub mod jira;
+pub mod log_maintenance;
 pub mod notification;
 pub mod oauth_credentials;
 pub mod pr_monitor;
@@ -20,6 +24,13 @@ pub mod project;
 pub mod queued_message;
 pub mod remote_client;
 pub mod repo;
+pub mod search;
+pub mod secrets;
 pub mod share;
+pub mod sparse_checkout;
+pub mod terminal;
+pub mod transcript;
+pub mod workspace_disk;
 pub mod workspace_manager;
+pub mod workspace_pool;
 pub mod worktree_manager;
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
 };
 
@@ -20,6 +20,7 @@ use super::{
     file_search_cache::{CacheError, FileSearchCache, SearchMode, SearchQuery},
     repo::{RepoError, RepoService},
     share::ShareError,
+    sparse_checkout::SparseCheckout,
 };
 
 #[derive(Debug, Error)]
@@ -134,6 +135,8 @@ impl ProjectService {
                     default_agent_working_dir: Some(repo.name),
                     portfolio_id: None,
                     include_task_id_in_commits: None,
+                    approval_timeout_minutes: None,
+                    approval_timeout_action: None,
                 },
             )
             .await?;
@@ -292,10 +295,13 @@ impl ProjectService {
         Ok(repos)
     }
 
+    /// Search the files of the project's repositories. Task form searches leave out the
+    /// files outside a repo's sparse checkout, as workspaces don't have them.
     pub async fn search_files(
         &self,
         cache: &FileSearchCache,
         repositories: &[Repo],
+        sparse_checkouts: &HashMap<Uuid, SparseCheckout>,
         query: &SearchQuery,
     ) -> Result<Vec<SearchResult>> {
         let query_str = query.q.trim();
@@ -310,9 +316,13 @@ impl ProjectService {
                 let repo_name = repo.name.clone();
                 let repo_path = repo.path.clone();
                 let query = query.clone();
+                let sparse_checkout = match query.mode {
+                    SearchMode::TaskForm => sparse_checkouts.get(&repo.id),
+                    SearchMode::Settings => None,
+                };
                 async move {
                     let results = self
-                        .search_single_repo(cache, &repo_path, &query)
+                        .search_single_repo(cache, &repo_path, &query, sparse_checkout)
                         .await
                         .unwrap_or_else(|e| {
                             tracing::warn!("Search failed for repo {}: {}", repo_name, e);
@@ -356,6 +366,7 @@ impl ProjectService {
         cache: &FileSearchCache,
         repo_path: &Path,
         query: &SearchQuery,
+        sparse_checkout: Option<&SparseCheckout>,
     ) -> Result<Vec<SearchResult>> {
         let query_str = query.q.trim();
         if query_str.is_empty() {
@@ -363,11 +374,14 @@ impl ProjectService {
         }
 
         // Try cache first
-        match cache.search(repo_path, query_str, query.mode.clone()).await {
+        match cache
+            .search(repo_path, query_str, query.mode.clone(), sparse_checkout)
+            .await
+        {
             Ok(results) => Ok(results),
             Err(CacheError::Miss) | Err(CacheError::BuildError(_)) => {
                 // Fall back to filesystem search
-                self.search_files_in_repo(repo_path, query_str, query.mode.clone())
+                self.search_files_in_repo(repo_path, query_str, query.mode.clone(), sparse_checkout)
                     .await
             }
         }
@@ -378,6 +392,7 @@ impl ProjectService {
         repo_path: &Path,
         query: &str,
         mode: SearchMode,
+        sparse_checkout: Option<&SparseCheckout>,
     ) -> Result<Vec<SearchResult>> {
         if !repo_path.exists() {
             return Err(ProjectServiceError::PathNotFound(repo_path.to_path_buf()));
@@ -428,6 +443,11 @@ impl ProjectService {
             let relative_path = path
                 .strip_prefix(repo_path)
                 .map_err(std::io::Error::other)?;
+            if let Some(sparse) = sparse_checkout
+                && !sparse.contains(&relative_path.to_string_lossy(), path.is_file())
+            {
+                continue;
+            }
             let relative_path_str = relative_path.to_string_lossy().to_lowercase();
 
             let file_name = path
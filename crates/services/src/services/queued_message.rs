@@ -1,24 +1,14 @@
-use std::sync::Arc;
-
-use chrono::{DateTime, Utc};
-use dashmap::DashMap;
-use db::models::scratch::DraftFollowUpData;
+use db::{
+    DBService,
+    models::{queued_message::QueuedMessage, scratch::DraftFollowUpData},
+};
+use futures::{StreamExt, stream::BoxStream};
 use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
 use ts_rs::TS;
 use uuid::Uuid;
 
-/// Represents a queued follow-up message for a session
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
-pub struct QueuedMessage {
-    /// The session this message is queued for
-    pub session_id: Uuid,
-    /// The follow-up data (message + variant)
-    pub data: DraftFollowUpData,
-    /// Timestamp when the message was queued
-    pub queued_at: DateTime<Utc>,
-}
-
 /// Status of the queue for a session (for frontend display)
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(tag = "status", rename_all = "snake_case")]
@@ -26,67 +16,147 @@ pub struct QueuedMessage {
 pub enum QueueStatus {
     /// No message queued
     Empty,
-    /// Message is queued and waiting for execution to complete
-    Queued { message: QueuedMessage },
+    /// Messages are queued and wait for the running turns to complete, in send order
+    Queued { messages: Vec<QueuedMessage> },
+}
+
+impl From<Vec<QueuedMessage>> for QueueStatus {
+    fn from(messages: Vec<QueuedMessage>) -> Self {
+        if messages.is_empty() {
+            QueueStatus::Empty
+        } else {
+            QueueStatus::Queued { messages }
+        }
+    }
 }
 
-/// In-memory service for managing queued follow-up messages.
-/// One queued message per session.
+/// Service for managing the persisted, ordered queue of follow-up messages of each
+/// session. Every change is broadcast so clients can follow the queue live.
 #[derive(Clone)]
 pub struct QueuedMessageService {
-    queue: Arc<DashMap<Uuid, QueuedMessage>>,
+    db: DBService,
+    updates: broadcast::Sender<(Uuid, QueueStatus)>,
 }
 
 impl QueuedMessageService {
-    pub fn new() -> Self {
-        Self {
-            queue: Arc::new(DashMap::new()),
+    pub fn new(db: DBService) -> Self {
+        let (updates, _) = broadcast::channel(64);
+        Self { db, updates }
+    }
+
+    /// Append a message to the end of the session's queue
+    pub async fn queue_message(
+        &self,
+        session_id: Uuid,
+        data: DraftFollowUpData,
+    ) -> Result<QueueStatus, sqlx::Error> {
+        QueuedMessage::create(&self.db.pool, session_id, &data).await?;
+        self.publish(session_id).await
+    }
+
+    /// Edit a queued message. Returns `None` if it isn't queued for the session.
+    pub async fn update_message(
+        &self,
+        session_id: Uuid,
+        id: Uuid,
+        data: DraftFollowUpData,
+    ) -> Result<Option<QueueStatus>, sqlx::Error> {
+        if !self.belongs_to(session_id, id).await? {
+            return Ok(None);
         }
+        QueuedMessage::update(&self.db.pool, id, &data).await?;
+        self.publish(session_id).await.map(Some)
     }
 
-    /// Queue a message for a session. Replaces any existing queued message.
-    pub fn queue_message(&self, session_id: Uuid, data: DraftFollowUpData) -> QueuedMessage {
-        let queued = QueuedMessage {
-            session_id,
-            data,
-            queued_at: Utc::now(),
-        };
-        self.queue.insert(session_id, queued.clone());
-        queued
+    /// Remove a single queued message. Returns `None` if it isn't queued for the session.
+    pub async fn remove_message(
+        &self,
+        session_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<QueueStatus>, sqlx::Error> {
+        if !self.belongs_to(session_id, id).await? {
+            return Ok(None);
+        }
+        QueuedMessage::delete(&self.db.pool, id).await?;
+        self.publish(session_id).await.map(Some)
     }
 
-    /// Cancel/remove a queued message for a session
-    pub fn cancel_queued(&self, session_id: Uuid) -> Option<QueuedMessage> {
-        self.queue.remove(&session_id).map(|(_, v)| v)
+    /// Reorder the queue. `ordered_ids` must list every queued message of the session
+    /// exactly once, otherwise `None` is returned and the queue is left unchanged.
+    pub async fn reorder(
+        &self,
+        session_id: Uuid,
+        ordered_ids: &[Uuid],
+    ) -> Result<Option<QueueStatus>, sqlx::Error> {
+        if !QueuedMessage::reorder(&self.db.pool, session_id, ordered_ids).await? {
+            return Ok(None);
+        }
+        self.publish(session_id).await.map(Some)
     }
 
-    /// Get the queued message for a session (if any)
-    pub fn get_queued(&self, session_id: Uuid) -> Option<QueuedMessage> {
-        self.queue.get(&session_id).map(|r| r.clone())
+    /// Cancel/remove all queued messages of a session, returning how many were removed
+    pub async fn cancel_queued(&self, session_id: Uuid) -> Result<u64, sqlx::Error> {
+        let removed = QueuedMessage::delete_by_session_id(&self.db.pool, session_id).await?;
+        if removed > 0 {
+            self.publish(session_id).await?;
+        }
+        Ok(removed)
     }
 
-    /// Take (remove and return) the queued message for a session.
-    /// Used by finalization flow to consume the queued message.
-    pub fn take_queued(&self, session_id: Uuid) -> Option<QueuedMessage> {
-        self.queue.remove(&session_id).map(|(_, v)| v)
+    /// Take (remove and return) the next queued message of a session.
+    /// Used by finalization flow to send one message per completed turn.
+    pub async fn take_next(&self, session_id: Uuid) -> Result<Option<QueuedMessage>, sqlx::Error> {
+        let next = QueuedMessage::take_next(&self.db.pool, session_id).await?;
+        if next.is_some() {
+            self.publish(session_id).await?;
+        }
+        Ok(next)
     }
 
     /// Check if a session has a queued message
-    pub fn has_queued(&self, session_id: Uuid) -> bool {
-        self.queue.contains_key(&session_id)
+    pub async fn has_queued(&self, session_id: Uuid) -> Result<bool, sqlx::Error> {
+        QueuedMessage::find_by_session_id(&self.db.pool, session_id)
+            .await
+            .map(|messages| !messages.is_empty())
     }
 
     /// Get queue status for frontend display
-    pub fn get_status(&self, session_id: Uuid) -> QueueStatus {
-        match self.get_queued(session_id) {
-            Some(msg) => QueueStatus::Queued { message: msg },
-            None => QueueStatus::Empty,
-        }
+    pub async fn get_status(&self, session_id: Uuid) -> Result<QueueStatus, sqlx::Error> {
+        QueuedMessage::find_by_session_id(&self.db.pool, session_id)
+            .await
+            .map(QueueStatus::from)
+    }
+
+    /// Current queue status of a session followed by every later change
+    pub async fn stream_status(
+        &self,
+        session_id: Uuid,
+    ) -> Result<BoxStream<'static, QueueStatus>, sqlx::Error> {
+        // Subscribe before loading so no change between the two is missed
+        let updates = BroadcastStream::new(self.updates.subscribe());
+        let initial = self.get_status(session_id).await?;
+
+        let changes = updates.filter_map(move |update| async move {
+            match update {
+                Ok((id, status)) if id == session_id => Some(status),
+                _ => None,
+            }
+        });
+        Ok(futures::stream::once(async move { initial })
+            .chain(changes)
+            .boxed())
+    }
+
+    async fn belongs_to(&self, session_id: Uuid, id: Uuid) -> Result<bool, sqlx::Error> {
+        Ok(QueuedMessage::find_by_id(&self.db.pool, id)
+            .await?
+            .is_some_and(|message| message.session_id == session_id))
     }
-}
 
-impl Default for QueuedMessageService {
-    fn default() -> Self {
-        Self::new()
+    async fn publish(&self, session_id: Uuid) -> Result<QueueStatus, sqlx::Error> {
+        let status = self.get_status(session_id).await?;
+        // No receivers just means nobody is watching the queue
+        let _ = self.updates.send((session_id, status.clone()));
+        Ok(status)
     }
 }
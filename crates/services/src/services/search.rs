@@ -0,0 +1,177 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use db::models::{execution_process::ExecutionProcess, search::SearchIndex};
+use executors::logs::{
+    ActionType, NormalizedEntry, NormalizedEntryType,
+    utils::patch::extract_normalized_entry_from_patch,
+};
+use json_patch::Patch;
+use sqlx::SqlitePool;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+use crate::services::{container::ContainerService, transcript::normalized_log_messages};
+
+/// How long entry updates are batched before being written to the search index.
+/// Streaming assistant messages are replaced many times per second while they grow.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Feeds the normalized assistant messages and tool calls of a running execution
+/// process into the full-text search index as they settle
+pub struct LogSearchIndexer {
+    pool: SqlitePool,
+    execution_process_id: Uuid,
+    pending: HashMap<usize, (String, String)>,
+    last_flush: Instant,
+}
+
+impl LogSearchIndexer {
+    pub fn new(pool: SqlitePool, execution_process_id: Uuid) -> Self {
+        Self {
+            pool,
+            execution_process_id,
+            pending: HashMap::new(),
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// Record the latest version of the entry carried by a conversation patch
+    pub fn observe(&mut self, patch: &Patch) {
+        if let Some((index, entry)) = extract_normalized_entry_from_patch(patch)
+            && let Some(document) = search_document(&entry)
+        {
+            self.pending.insert(index, document);
+        }
+    }
+
+    pub async fn flush_if_due(&mut self) {
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush().await;
+        }
+    }
+
+    pub async fn flush(&mut self) {
+        self.last_flush = Instant::now();
+        for (index, (title, body)) in self.pending.drain() {
+            if let Err(e) = SearchIndex::upsert_log_entry(
+                &self.pool,
+                self.execution_process_id,
+                index as i64,
+                &title,
+                &body,
+            )
+            .await
+            {
+                tracing::warn!(
+                    "Failed to index log entry {} of execution process {}: {}",
+                    index,
+                    self.execution_process_id,
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Index the entries of execution processes whose logs were persisted before log
+/// entries were indexed. Each process leaves the backfill queue once indexed, so the
+/// work is done once. Returns how many processes were indexed.
+pub async fn backfill_log_entries<C>(container: &C) -> Result<usize, sqlx::Error>
+where
+    C: ContainerService + Sync + ?Sized,
+{
+    let pool = &container.db().pool;
+    let mut indexed = 0;
+    for execution_process_id in SearchIndex::find_backfill_queue(pool).await? {
+        if let Some(process) = ExecutionProcess::find_by_id(pool, execution_process_id).await? {
+            let mut indexer = LogSearchIndexer::new(pool.clone(), process.id);
+            for msg in normalized_log_messages(container, &process).await {
+                if let LogMsg::JsonPatch(patch) = msg {
+                    indexer.observe(&patch);
+                }
+            }
+            indexer.flush().await;
+            indexed += 1;
+        }
+        SearchIndex::remove_from_backfill_queue(pool, execution_process_id).await?;
+    }
+    Ok(indexed)
+}
+
+/// Title and body to index for a normalized entry, or `None` for entries that are not
+/// worth searching (user echoes, thinking, loading and system noise)
+pub fn search_document(entry: &NormalizedEntry) -> Option<(String, String)> {
+    match &entry.entry_type {
+        NormalizedEntryType::AssistantMessage if !entry.content.trim().is_empty() => {
+            Some(("assistant".to_string(), entry.content.clone()))
+        }
+        NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type,
+            ..
+        } => {
+            let detail = match action_type {
+                ActionType::FileRead { path } | ActionType::FileEdit { path, .. } => Some(path),
+                ActionType::CommandRun { command, .. } => Some(command),
+                ActionType::Search { query } => Some(query),
+                ActionType::WebFetch { url } => Some(url),
+                _ => None,
+            };
+            let body = match detail {
+                Some(detail) if !entry.content.contains(detail.as_str()) => {
+                    format!("{}\n{}", entry.content, detail)
+                }
+                _ => entry.content.clone(),
+            };
+            Some((tool_name.clone(), body))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::logs::ToolStatus;
+
+    use super::*;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn tool_documents_include_the_touched_path() {
+        let document = search_document(&entry(
+            NormalizedEntryType::ToolUse {
+                tool_name: "Edit".to_string(),
+                action_type: ActionType::FileEdit {
+                    path: "src/payments/webhook.rs".to_string(),
+                    changes: Vec::new(),
+                },
+                status: ToolStatus::Success,
+            },
+            "Edit file",
+        ));
+        assert_eq!(
+            document,
+            Some((
+                "Edit".to_string(),
+                "Edit file\nsrc/payments/webhook.rs".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn thinking_and_user_messages_are_not_indexed() {
+        assert!(search_document(&entry(NormalizedEntryType::Thinking, "hmm")).is_none());
+        assert!(search_document(&entry(NormalizedEntryType::UserMessage, "do it")).is_none());
+    }
+}
@@ -0,0 +1,338 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng, rand_core::RngCore},
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use db::models::secret::Secret;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use uuid::Uuid;
+
+const NONCE_SIZE: usize = 12; // 96 bits for AES-256-GCM
+const KEY_CONTEXT: &[u8] = b"task-copilot secrets v1";
+/// Shorter values would mask ordinary output all over the logs
+const MIN_REDACTED_LEN: usize = 4;
+pub const REDACTED: &str = "[REDACTED]";
+const MASTER_KEY_READ_ATTEMPTS: usize = 50;
+
+#[derive(Debug, Error)]
+pub enum SecretError {
+    #[error("Failed to load the secrets master key: {0}")]
+    MasterKey(String),
+    #[error("Secret could not be decrypted")]
+    Decrypt,
+    #[error("Secret could not be encrypted")]
+    Encrypt,
+    #[error(
+        "Invalid secret name '{0}': use letters, digits and underscores, not starting with a digit or VK_"
+    )]
+    InvalidName(String),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// Encrypts secrets with a key derived from the master key file, which is created with
+/// owner-only permissions on first use
+#[derive(Clone)]
+pub struct SecretService {
+    key_path: PathBuf,
+    key: Arc<OnceLock<[u8; 32]>>,
+}
+
+impl SecretService {
+    pub fn new(key_path: PathBuf) -> Self {
+        Self {
+            key_path,
+            key: Arc::new(OnceLock::new()),
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, SecretError> {
+        let cipher = Aes256Gcm::new(&Key::<Aes256Gcm>::from(self.key()?));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| SecretError::Encrypt)?;
+
+        let mut combined = nonce.to_vec();
+        combined.extend_from_slice(&ciphertext);
+        Ok(STANDARD.encode(combined))
+    }
+
+    pub fn decrypt(&self, encrypted: &str) -> Result<String, SecretError> {
+        let decoded = STANDARD
+            .decode(encrypted)
+            .map_err(|_| SecretError::Decrypt)?;
+        if decoded.len() < NONCE_SIZE {
+            return Err(SecretError::Decrypt);
+        }
+        let (nonce_bytes, ciphertext) = decoded.split_at(NONCE_SIZE);
+        let nonce_bytes: [u8; NONCE_SIZE] =
+            nonce_bytes.try_into().map_err(|_| SecretError::Decrypt)?;
+
+        let cipher = Aes256Gcm::new(&Key::<Aes256Gcm>::from(self.key()?));
+        let plaintext = cipher
+            .decrypt(&Nonce::from(nonce_bytes), ciphertext)
+            .map_err(|_| SecretError::Decrypt)?;
+        String::from_utf8(plaintext).map_err(|_| SecretError::Decrypt)
+    }
+
+    /// Decrypted secrets for the environment of a workspace's processes, by variable name
+    pub async fn workspace_env(
+        &self,
+        pool: &SqlitePool,
+        project_id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<HashMap<String, String>, SecretError> {
+        let secrets = Secret::find_for_workspace(pool, project_id, workspace_id).await?;
+        self.decrypt_all(secrets)
+    }
+
+    /// Decrypted secrets of a project and its repos, for processes that don't belong to a
+    /// workspace yet
+    pub async fn project_env(
+        &self,
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<HashMap<String, String>, SecretError> {
+        let secrets = Secret::find_for_project(pool, project_id).await?;
+        self.decrypt_all(secrets)
+    }
+
+    fn decrypt_all(&self, secrets: Vec<Secret>) -> Result<HashMap<String, String>, SecretError> {
+        let mut env = HashMap::new();
+        for secret in secrets {
+            env.insert(secret.name, self.decrypt(&secret.ciphertext)?);
+        }
+        Ok(env)
+    }
+
+    fn key(&self) -> Result<[u8; 32], SecretError> {
+        if let Some(key) = self.key.get() {
+            return Ok(*key);
+        }
+        let master_key = load_or_create_master_key(&self.key_path)
+            .map_err(|e| SecretError::MasterKey(e.to_string()))?;
+        let mut hasher = Sha256::new();
+        hasher.update(KEY_CONTEXT);
+        hasher.update(&master_key);
+        Ok(*self.key.get_or_init(|| hasher.finalize().into()))
+    }
+}
+
+/// Secrets are injected as environment variables, and must not shadow the VK_* variables
+/// set for every execution
+pub fn validate_secret_name(name: &str) -> Result<(), SecretError> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.to_ascii_uppercase().starts_with("VK_");
+    if valid {
+        Ok(())
+    } else {
+        Err(SecretError::InvalidName(name.to_string()))
+    }
+}
+
+fn load_or_create_master_key(path: &Path) -> std::io::Result<Vec<u8>> {
+    match read_master_key(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => create_master_key(path),
+        // Created, but not written yet
+        Ok(master_key) if master_key.is_empty() => read_created_master_key(path),
+        result => result,
+    }
+}
+
+fn read_master_key(path: &Path) -> std::io::Result<Vec<u8>> {
+    let encoded = fs::read_to_string(path)?;
+    STANDARD
+        .decode(encoded.trim())
+        .map_err(std::io::Error::other)
+}
+
+fn create_master_key(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut master_key = vec![0u8; 32];
+    OsRng.fill_bytes(&mut master_key);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut opts = fs::OpenOptions::new();
+    opts.create_new(true).write(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    let mut file = match opts.open(path) {
+        Ok(file) => file,
+        // Another service or process created the key first; everyone has to use that one
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            return read_created_master_key(path);
+        }
+        Err(e) => return Err(e),
+    };
+    file.write_all(STANDARD.encode(&master_key).as_bytes())?;
+    file.sync_all()?;
+    Ok(master_key)
+}
+
+/// Read a key file that was just created elsewhere, giving its creator a moment to finish
+/// writing it
+fn read_created_master_key(path: &Path) -> std::io::Result<Vec<u8>> {
+    for _ in 0..MASTER_KEY_READ_ATTEMPTS {
+        match read_master_key(path) {
+            Ok(master_key) if !master_key.is_empty() => return Ok(master_key),
+            _ => std::thread::sleep(Duration::from_millis(20)),
+        }
+    }
+    match read_master_key(path)? {
+        master_key if master_key.is_empty() => Err(std::io::Error::other("key file is empty")),
+        master_key => Ok(master_key),
+    }
+}
+
+/// Masks secret values in process output. Output arrives in arbitrary chunks, so the end
+/// of a chunk that could be the start of a secret is held back until the next one.
+#[derive(Debug, Clone, Default)]
+pub struct SecretRedactor {
+    /// Longest first, so a secret containing another one is masked as a whole
+    secrets: Vec<String>,
+    pending: String,
+}
+
+impl SecretRedactor {
+    pub fn new(values: impl IntoIterator<Item = String>) -> Self {
+        let mut secrets: Vec<String> = values
+            .into_iter()
+            .filter(|value| value.len() >= MIN_REDACTED_LEN)
+            .collect();
+        secrets.sort_by_key(|value| std::cmp::Reverse(value.len()));
+        secrets.dedup();
+        Self {
+            secrets,
+            pending: String::new(),
+        }
+    }
+
+    /// Whether there are no secrets to mask, so output can pass through untouched
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+
+    /// Redacted output up to the point where a secret could still be starting
+    pub fn push(&mut self, chunk: &str) -> String {
+        if self.secrets.is_empty() {
+            return chunk.to_string();
+        }
+        let mut text = std::mem::take(&mut self.pending);
+        text.push_str(chunk);
+        let mut text = self.redact(&text);
+
+        let held = self
+            .secrets
+            .iter()
+            .filter_map(|secret| {
+                (1..secret.len())
+                    .rev()
+                    .filter(|&len| secret.is_char_boundary(len))
+                    .find(|&len| text.ends_with(&secret[..len]))
+            })
+            .max()
+            .unwrap_or(0);
+        self.pending = text.split_off(text.len() - held);
+        text
+    }
+
+    /// Whatever was held back, once the output has ended
+    pub fn finish(&mut self) -> String {
+        std::mem::take(&mut self.pending)
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        self.secrets.iter().fold(text.to_string(), |text, secret| {
+            text.replace(secret, REDACTED)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypted_values_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = SecretService::new(dir.path().join("secrets.key"));
+        let encrypted = service.encrypt("hunter22").unwrap();
+        assert!(!encrypted.contains("hunter22"));
+        assert_eq!(service.decrypt(&encrypted).unwrap(), "hunter22");
+
+        // A fresh service picks up the same master key
+        let reloaded = SecretService::new(dir.path().join("secrets.key"));
+        assert_eq!(reloaded.decrypt(&encrypted).unwrap(), "hunter22");
+    }
+
+    #[test]
+    fn a_key_created_concurrently_is_read_instead_of_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.key");
+        let existing = load_or_create_master_key(&path).unwrap();
+
+        // The losing side of the race finds the file already there
+        assert_eq!(create_master_key(&path).unwrap(), existing);
+        assert_eq!(load_or_create_master_key(&path).unwrap(), existing);
+    }
+
+    #[test]
+    fn services_racing_to_create_the_key_agree_on_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.key");
+        let encrypted = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| SecretService::new(path.clone()).encrypt("hunter22")))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        let service = SecretService::new(path.clone());
+        for encrypted in encrypted {
+            assert_eq!(service.decrypt(&encrypted).unwrap(), "hunter22");
+        }
+    }
+
+    #[test]
+    fn secret_names_must_be_env_var_names() {
+        assert!(validate_secret_name("API_TOKEN").is_ok());
+        assert!(validate_secret_name("_private2").is_ok());
+        assert!(validate_secret_name("2FA").is_err());
+        assert!(validate_secret_name("MY-TOKEN").is_err());
+        assert!(validate_secret_name("VK_PROJECT_NAME").is_err());
+        assert!(validate_secret_name("").is_err());
+    }
+
+    #[test]
+    fn secrets_split_across_chunks_are_redacted() {
+        let mut redactor = SecretRedactor::new(["sk-live-1234".to_string()]);
+        let mut output = redactor.push("token=sk-li");
+        output.push_str(&redactor.push("ve-1234 done\n"));
+        output.push_str(&redactor.push("bye sk-"));
+        output.push_str(&redactor.finish());
+        assert_eq!(output, "token=[REDACTED] done\nbye sk-");
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use db::models::{project::Project, project_repo::ProjectRepo};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Cone-mode sparse-checkout directories of a repo, relative to the repo root.
+/// Like git's cone mode, the files directly inside the repo root and inside every
+/// parent of a cone directory are checked out too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseCheckout {
+    dirs: Vec<String>,
+}
+
+impl SparseCheckout {
+    /// `None` when there is nothing to restrict: no valid directories, or one of them
+    /// is the repo root.
+    pub fn new<I, S>(dirs: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut normalized = Vec::new();
+        for dir in dirs {
+            match normalize_dir(dir.as_ref()) {
+                Some(dir) if dir.is_empty() => return None,
+                Some(dir) => normalized.push(dir),
+                None => {}
+            }
+        }
+        normalized.sort();
+        normalized.dedup();
+        (!normalized.is_empty()).then_some(Self { dirs: normalized })
+    }
+
+    /// Sparse checkout of a project repo. An empty configured list falls back to the
+    /// project's agent and dev server working dirs that point into this repo; these
+    /// are relative to the workspace, which holds each repo in a directory of its name.
+    pub fn for_project_repo(
+        project: &Project,
+        repo_name: &str,
+        configured: Option<&[String]>,
+    ) -> Option<Self> {
+        match configured? {
+            [] => {
+                let defaults: Vec<&str> = [
+                    project.default_agent_working_dir.as_deref(),
+                    project.dev_script_working_dir.as_deref(),
+                ]
+                .into_iter()
+                .flatten()
+                .filter_map(|dir| {
+                    let dir = dir.trim().trim_matches('/');
+                    if dir == repo_name {
+                        Some("")
+                    } else {
+                        dir.strip_prefix(repo_name)?.strip_prefix('/')
+                    }
+                })
+                .collect();
+                Self::new(defaults)
+            }
+            dirs => Self::new(dirs),
+        }
+    }
+
+    /// Sparse checkouts of a project's repos, keyed by repo id. Repos that check out
+    /// everything are left out.
+    pub async fn for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<HashMap<Uuid, Self>, sqlx::Error> {
+        let Some(project) = Project::find_by_id(pool, project_id).await? else {
+            return Ok(HashMap::new());
+        };
+        let project_repos = ProjectRepo::find_by_project_id_with_names(pool, project_id).await?;
+        Ok(project_repos
+            .into_iter()
+            .filter_map(|project_repo| {
+                let configured = project_repo
+                    .sparse_checkout
+                    .as_ref()
+                    .map(|dirs| &dirs.0[..]);
+                Self::for_project_repo(&project, &project_repo.repo_name, configured)
+                    .map(|sparse| (project_repo.repo_id, sparse))
+            })
+            .collect())
+    }
+
+    pub fn dirs(&self) -> &[String] {
+        &self.dirs
+    }
+
+    /// Whether a path relative to the repo root is checked out
+    pub fn contains(&self, path: &str, is_file: bool) -> bool {
+        let path = path.trim_matches('/');
+        let parent = if is_file {
+            path.rsplit_once('/').map_or("", |(parent, _)| parent)
+        } else {
+            path
+        };
+        self.dirs
+            .iter()
+            .any(|dir| is_within(path, dir) || parent.is_empty() || is_within(dir, parent))
+    }
+}
+
+/// Whether `path` is `dir` or below it
+fn is_within(path: &str, dir: &str) -> bool {
+    path.strip_prefix(dir)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Repo-relative directory with forward slashes and no leading or trailing slash;
+/// `None` for paths that leave the repo
+fn normalize_dir(dir: &str) -> Option<String> {
+    let dir = dir.trim().replace('\\', "/");
+    let mut components = Vec::new();
+    for component in dir.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => return None,
+            component => components.push(component),
+        }
+    }
+    Some(components.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(agent_dir: Option<&str>, dev_dir: Option<&str>) -> Project {
+        Project {
+            id: Uuid::new_v4(),
+            name: "project".to_string(),
+            dev_script: None,
+            dev_script_working_dir: dev_dir.map(str::to_string),
+            default_agent_working_dir: agent_dir.map(str::to_string),
+            remote_project_id: None,
+            portfolio_id: None,
+            include_task_id_in_commits: false,
+            approval_timeout_minutes: None,
+            approval_timeout_action: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn normalizes_and_rejects_root() {
+        let sparse =
+            SparseCheckout::new(["./services/api/", "web\\app", "services/api", ".."]).unwrap();
+        assert_eq!(sparse.dirs(), ["services/api", "web/app"]);
+        assert!(SparseCheckout::new(["services", "/"]).is_none());
+        assert!(SparseCheckout::new(Vec::<String>::new()).is_none());
+    }
+
+    #[test]
+    fn contains_follows_cone_mode() {
+        let sparse = SparseCheckout::new(["services/api"]).unwrap();
+        assert!(sparse.contains("services/api/src/main.rs", true));
+        assert!(sparse.contains("README.md", true));
+        assert!(sparse.contains("services/Cargo.toml", true));
+        assert!(sparse.contains("services", false));
+        assert!(!sparse.contains("services/web", false));
+        assert!(!sparse.contains("services/web/index.ts", true));
+        assert!(!sparse.contains("services/apiary/lib.rs", true));
+    }
+
+    #[test]
+    fn defaults_to_working_dirs_in_the_repo() {
+        let project = project(Some("mono/services/api"), Some("mono/web"));
+        let sparse = SparseCheckout::for_project_repo(&project, "mono", Some(&[][..])).unwrap();
+        assert_eq!(sparse.dirs(), ["services/api", "web"]);
+
+        // Configured directories win, and no configuration checks out everything
+        let configured = vec!["tools".to_string()];
+        let sparse =
+            SparseCheckout::for_project_repo(&project, "mono", Some(configured.as_slice()));
+        assert_eq!(sparse.unwrap().dirs(), ["tools"]);
+        assert!(SparseCheckout::for_project_repo(&project, "mono", None).is_none());
+
+        // A working dir at the repo root or in another repo gives no restriction
+        let project = self::project(Some("mono"), None);
+        assert!(SparseCheckout::for_project_repo(&project, "mono", Some(&[][..])).is_none());
+        let project = self::project(Some("other/src"), None);
+        assert!(SparseCheckout::for_project_repo(&project, "mono", Some(&[][..])).is_none());
+    }
+}
@@ -0,0 +1,393 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use executors::env::ExecutionEnv;
+use portable_pty::{ChildKiller, CommandBuilder, MasterPty, PtySize, native_pty_system};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::broadcast;
+use ts_rs::TS;
+use utils::{
+    assets::asset_dir,
+    shell::{UnixShell, get_shell_command},
+};
+use uuid::Uuid;
+
+use crate::services::secrets::SecretRedactor;
+
+/// Output kept for clients that attach to a running terminal
+const SCROLLBACK_BYTES: usize = 256 * 1024;
+const OUTPUT_CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Error)]
+pub enum TerminalError {
+    #[error("Terminal {0} not found")]
+    NotFound(Uuid),
+    #[error("Failed to start terminal: {0}")]
+    Spawn(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS)]
+pub struct TerminalSize {
+    pub cols: u16,
+    pub rows: u16,
+}
+
+impl From<TerminalSize> for PtySize {
+    fn from(size: TerminalSize) -> Self {
+        PtySize {
+            rows: size.rows,
+            cols: size.cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TerminalSession {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub shell: String,
+    pub cwd: String,
+    /// Terminal output, with secrets masked, is appended here while the session is recorded
+    pub log_path: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Messages a client sends over the terminal websocket. Output is sent back as binary frames.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TerminalClientMessage {
+    Input { data: String },
+    Resize { cols: u16, rows: u16 },
+}
+
+struct Terminal {
+    session: TerminalSession,
+    writer: Mutex<Box<dyn Write + Send>>,
+    master: Mutex<Box<dyn MasterPty + Send>>,
+    killer: Mutex<Box<dyn ChildKiller + Send + Sync>>,
+    output: Mutex<TerminalOutput>,
+}
+
+struct TerminalOutput {
+    scrollback: VecDeque<u8>,
+    log: Option<File>,
+    tx: broadcast::Sender<Vec<u8>>,
+    /// Masks the workspace's secrets before output is kept, recorded or sent
+    redactor: SecretRedactor,
+    /// Start of a UTF-8 sequence cut off at the end of the last read
+    partial_utf8: Vec<u8>,
+}
+
+impl TerminalOutput {
+    fn new(log: Option<File>, redactor: SecretRedactor) -> Self {
+        Self {
+            scrollback: VecDeque::new(),
+            log,
+            tx: broadcast::channel(OUTPUT_CHANNEL_CAPACITY).0,
+            redactor,
+            partial_utf8: Vec::new(),
+        }
+    }
+
+    /// Output of a PTY read with secrets masked. Whatever could still be part of a secret
+    /// or a character is held back until the next read.
+    fn redact(&mut self, chunk: &[u8]) -> Vec<u8> {
+        if self.redactor.is_empty() {
+            return chunk.to_vec();
+        }
+        let mut bytes = std::mem::take(&mut self.partial_utf8);
+        bytes.extend_from_slice(chunk);
+        let complete = match std::str::from_utf8(&bytes) {
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            _ => bytes.len(),
+        };
+        self.partial_utf8 = bytes.split_off(complete);
+        self.redactor
+            .push(&String::from_utf8_lossy(&bytes))
+            .into_bytes()
+    }
+
+    /// Everything held back, once the shell has exited
+    fn finish(&mut self) -> Vec<u8> {
+        let partial = std::mem::take(&mut self.partial_utf8);
+        let mut rest = self.redactor.push(&String::from_utf8_lossy(&partial));
+        rest.push_str(&self.redactor.finish());
+        rest.into_bytes()
+    }
+
+    fn emit(&mut self, terminal_id: Uuid, chunk: &[u8]) {
+        if chunk.is_empty() {
+            return;
+        }
+        self.scrollback.extend(chunk);
+        let overflow = self.scrollback.len().saturating_sub(SCROLLBACK_BYTES);
+        self.scrollback.drain(..overflow);
+        if let Some(log) = self.log.as_mut()
+            && let Err(e) = log.write_all(chunk)
+        {
+            tracing::warn!("Failed to record terminal {}: {}", terminal_id, e);
+            self.log = None;
+        }
+        let _ = self.tx.send(chunk.to_vec());
+    }
+}
+
+impl Terminal {
+    fn push_output(&self, chunk: &[u8]) {
+        let mut output = self.output.lock().unwrap();
+        let chunk = output.redact(chunk);
+        output.emit(self.session.id, &chunk);
+    }
+
+    fn finish_output(&self) {
+        let mut output = self.output.lock().unwrap();
+        let rest = output.finish();
+        output.emit(self.session.id, &rest);
+    }
+}
+
+/// Interactive shells in workspace worktrees. Terminals end with the app, so they are
+/// only tracked in memory.
+#[derive(Clone, Default)]
+pub struct TerminalService {
+    terminals: Arc<Mutex<HashMap<Uuid, Arc<Terminal>>>>,
+}
+
+impl TerminalService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start the user's login shell in `cwd` on a new PTY. Output is passed through
+    /// `redactor` before it is kept, recorded or sent to clients.
+    pub fn open(
+        &self,
+        workspace_id: Uuid,
+        cwd: &Path,
+        env: &ExecutionEnv,
+        redactor: SecretRedactor,
+        size: TerminalSize,
+        record: bool,
+    ) -> Result<TerminalSession, TerminalError> {
+        let spawn_error = |e: anyhow::Error| TerminalError::Spawn(e.to_string());
+        let pair = native_pty_system()
+            .openpty(size.into())
+            .map_err(spawn_error)?;
+
+        let (shell, _) = get_shell_command();
+        let mut cmd = CommandBuilder::new(&shell);
+        if cfg!(unix) && UnixShell::current_shell().login() {
+            cmd.arg("-l");
+        }
+        cmd.cwd(cwd);
+        cmd.env("TERM", "xterm-256color");
+        for (key, value) in &env.vars {
+            cmd.env(key, value);
+        }
+
+        let mut child = pair.slave.spawn_command(cmd).map_err(spawn_error)?;
+        // The child holds its own handle; ours would keep the PTY open after it exits
+        drop(pair.slave);
+        let mut reader = pair.master.try_clone_reader().map_err(spawn_error)?;
+        let writer = pair.master.take_writer().map_err(spawn_error)?;
+
+        let id = Uuid::new_v4();
+        let log_path = record.then(|| terminal_log_dir().join(format!("{id}.log")));
+        let log = match &log_path {
+            Some(path) => {
+                fs::create_dir_all(terminal_log_dir())?;
+                Some(File::create(path)?)
+            }
+            None => None,
+        };
+
+        let session = TerminalSession {
+            id,
+            workspace_id,
+            shell,
+            cwd: cwd.to_string_lossy().into_owned(),
+            log_path: log_path.map(|path| path.to_string_lossy().into_owned()),
+            created_at: Utc::now(),
+        };
+        let terminal = Arc::new(Terminal {
+            session: session.clone(),
+            writer: Mutex::new(writer),
+            master: Mutex::new(pair.master),
+            killer: Mutex::new(child.clone_killer()),
+            output: Mutex::new(TerminalOutput::new(log, redactor)),
+        });
+        self.terminals.lock().unwrap().insert(id, terminal.clone());
+
+        // PTY reads block, so they get their own thread until the shell exits
+        let terminals = self.terminals.clone();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 8192];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => terminal.push_output(&buf[..n]),
+                }
+            }
+            terminal.finish_output();
+            let _ = child.wait();
+            terminals.lock().unwrap().remove(&id);
+            tracing::debug!("Terminal {} exited", id);
+        });
+
+        Ok(session)
+    }
+
+    pub fn list(&self, workspace_id: Uuid) -> Vec<TerminalSession> {
+        let mut sessions: Vec<TerminalSession> = self
+            .terminals
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|t| t.session.workspace_id == workspace_id)
+            .map(|t| t.session.clone())
+            .collect();
+        sessions.sort_by_key(|s| s.created_at);
+        sessions
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<TerminalSession> {
+        self.terminal(id).ok().map(|t| t.session.clone())
+    }
+
+    /// Recent output and a receiver for everything after it. The receiver closes when the
+    /// shell exits.
+    pub fn attach(
+        &self,
+        id: Uuid,
+    ) -> Result<(Vec<u8>, broadcast::Receiver<Vec<u8>>), TerminalError> {
+        let terminal = self.terminal(id)?;
+        let output = terminal.output.lock().unwrap();
+        Ok((
+            output.scrollback.iter().copied().collect(),
+            output.tx.subscribe(),
+        ))
+    }
+
+    pub fn write(&self, id: Uuid, data: &[u8]) -> Result<(), TerminalError> {
+        let terminal = self.terminal(id)?;
+        let mut writer = terminal.writer.lock().unwrap();
+        writer.write_all(data)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn resize(&self, id: Uuid, size: TerminalSize) -> Result<(), TerminalError> {
+        self.terminal(id)?
+            .master
+            .lock()
+            .unwrap()
+            .resize(size.into())
+            .map_err(|e| TerminalError::Io(std::io::Error::other(e)))
+    }
+
+    pub fn kill(&self, id: Uuid) -> Result<(), TerminalError> {
+        let terminal = self
+            .terminals
+            .lock()
+            .unwrap()
+            .remove(&id)
+            .ok_or(TerminalError::NotFound(id))?;
+        terminal.killer.lock().unwrap().kill()?;
+        Ok(())
+    }
+
+    /// Kill every terminal of a workspace, e.g. before its worktree is removed
+    pub fn close_workspace(&self, workspace_id: Uuid) {
+        let ids: Vec<Uuid> = self
+            .list(workspace_id)
+            .into_iter()
+            .map(|session| session.id)
+            .collect();
+        for id in ids {
+            if let Err(e) = self.kill(id) {
+                tracing::warn!("Failed to kill terminal {}: {}", id, e);
+            }
+        }
+    }
+
+    fn terminal(&self, id: Uuid) -> Result<Arc<Terminal>, TerminalError> {
+        self.terminals
+            .lock()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .ok_or(TerminalError::NotFound(id))
+    }
+}
+
+fn terminal_log_dir() -> PathBuf {
+    asset_dir().join("terminal_logs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redacted(output: &mut TerminalOutput, reads: &[&[u8]]) -> Vec<u8> {
+        let mut redacted: Vec<u8> = reads.iter().flat_map(|read| output.redact(read)).collect();
+        redacted.extend(output.finish());
+        redacted
+    }
+
+    #[test]
+    fn secrets_are_masked_across_reads() {
+        let mut output =
+            TerminalOutput::new(None, SecretRedactor::new(["sk-live-1234".to_string()]));
+        assert_eq!(
+            redacted(&mut output, &[b"$ echo $TOKEN\r\nsk-li", b"ve-1234\r\n$ "]),
+            b"$ echo $TOKEN\r\n[REDACTED]\r\n$ "
+        );
+    }
+
+    #[test]
+    fn characters_split_across_reads_are_kept_whole() {
+        let mut output = TerminalOutput::new(None, SecretRedactor::new(["hunter22".to_string()]));
+        let text = "caf\u{e9} \u{2713} hunter22".as_bytes();
+        assert_eq!(
+            redacted(&mut output, &[&text[..4], &text[4..7], &text[7..]]),
+            "caf\u{e9} \u{2713} [REDACTED]".as_bytes()
+        );
+    }
+
+    #[test]
+    fn output_passes_through_untouched_without_secrets() {
+        let mut output = TerminalOutput::new(None, SecretRedactor::default());
+        let binary: &[u8] = &[0x1b, b'[', b'0', b'm', 0xff, 0xc3];
+        assert_eq!(redacted(&mut output, &[binary]), binary);
+    }
+
+    #[test]
+    fn only_redacted_output_is_kept_for_scrollback() {
+        let mut output =
+            TerminalOutput::new(None, SecretRedactor::new(["sk-live-1234".to_string()]));
+        let mut rx = output.tx.subscribe();
+        for read in [&b"token=sk-live"[..], b"-1234\n"] {
+            let chunk = output.redact(read);
+            output.emit(Uuid::nil(), &chunk);
+        }
+
+        let scrollback: Vec<u8> = output.scrollback.iter().copied().collect();
+        assert_eq!(scrollback, b"token=[REDACTED]\n");
+        let mut sent = Vec::new();
+        while let Ok(chunk) = rx.try_recv() {
+            sent.extend(chunk);
+        }
+        assert_eq!(sent, scrollback);
+    }
+}
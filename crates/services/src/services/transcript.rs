@@ -0,0 +1,656 @@
+use std::{fmt::Write as _, time::Duration};
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    approval_record::ApprovalRecord,
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    session::Session,
+    task::Task,
+    workspace::Workspace,
+};
+use executors::{
+    actions::ExecutorActionType,
+    logs::{
+        ActionType, CommandExitStatus, FileChange, NormalizedEntry, NormalizedEntryType, ToolStatus,
+    },
+};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use ts_rs::TS;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+use crate::services::container::ContainerService;
+
+/// Version of the JSON transcript format written by exports and accepted by imports
+pub const TRANSCRIPT_VERSION: u32 = 1;
+
+/// Upper bound for re-normalizing the stored logs of a single turn
+const NORMALIZE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptFormat {
+    Markdown,
+    Html,
+    #[default]
+    Json,
+}
+
+impl TranscriptFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            TranscriptFormat::Markdown => "text/markdown; charset=utf-8",
+            TranscriptFormat::Html => "text/html; charset=utf-8",
+            TranscriptFormat::Json => "application/json",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TranscriptFormat::Markdown => "md",
+            TranscriptFormat::Html => "html",
+            TranscriptFormat::Json => "json",
+        }
+    }
+}
+
+/// Readable record of an agent run: every coding agent turn of a session with its
+/// normalized conversation and the tool approvals decided during it
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct Transcript {
+    pub version: u32,
+    pub title: String,
+    pub task_id: Uuid,
+    pub workspace_id: Uuid,
+    pub session_id: Uuid,
+    pub branch: String,
+    pub executor: Option<String>,
+    pub exported_at: DateTime<Utc>,
+    pub turns: Vec<TranscriptTurn>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TranscriptTurn {
+    pub execution_process_id: Uuid,
+    pub status: ExecutionProcessStatus,
+    /// Prompt the turn was started with
+    pub prompt: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub entries: Vec<NormalizedEntry>,
+    pub approvals: Vec<ApprovalRecord>,
+}
+
+impl Transcript {
+    /// Collect the transcript of a session from its non-dropped coding agent turns.
+    /// Turns that are still running contribute the entries produced so far.
+    pub async fn from_session<C>(container: &C, session: &Session) -> Result<Self, sqlx::Error>
+    where
+        C: ContainerService + Sync + ?Sized,
+    {
+        let pool = &container.db().pool;
+        let workspace = Workspace::find_by_id(pool, session.workspace_id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        let task = Task::find_by_id(pool, workspace.task_id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        let approvals = ApprovalRecord::find_by_task_id(pool, task.id).await?;
+
+        let processes = ExecutionProcess::find_by_session_id(pool, session.id, false).await?;
+        let mut turns = Vec::new();
+        for process in processes
+            .into_iter()
+            .filter(|p| p.run_reason == ExecutionProcessRunReason::CodingAgent)
+        {
+            let prompt = process
+                .executor_action()
+                .ok()
+                .and_then(|action| match action.typ() {
+                    ExecutorActionType::CodingAgentInitialRequest(request) => {
+                        Some(request.prompt.clone())
+                    }
+                    ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                        Some(request.prompt.clone())
+                    }
+                    _ => None,
+                });
+            let entries = normalized_entries(container, &process).await;
+            turns.push(TranscriptTurn {
+                execution_process_id: process.id,
+                status: process.status.clone(),
+                prompt,
+                started_at: process.started_at,
+                completed_at: process.completed_at,
+                entries,
+                approvals: approvals
+                    .iter()
+                    .filter(|a| a.execution_process_id == process.id)
+                    .cloned()
+                    .collect(),
+            });
+        }
+
+        Ok(Self {
+            version: TRANSCRIPT_VERSION,
+            title: task.title,
+            task_id: task.id,
+            workspace_id: workspace.id,
+            session_id: session.id,
+            branch: workspace.branch,
+            executor: session.executor.clone(),
+            exported_at: Utc::now(),
+            turns,
+        })
+    }
+
+    pub fn render(&self, format: TranscriptFormat) -> Result<String, serde_json::Error> {
+        Ok(match format {
+            TranscriptFormat::Markdown => self.to_markdown(),
+            TranscriptFormat::Html => self.to_html(),
+            TranscriptFormat::Json => serde_json::to_string_pretty(self)?,
+        })
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.title);
+        let _ = writeln!(out, "- Branch: `{}`", self.branch);
+        if let Some(executor) = &self.executor {
+            let _ = writeln!(out, "- Executor: {executor}");
+        }
+        let _ = writeln!(out, "- Session: `{}`", self.session_id);
+        let _ = writeln!(out, "- Exported: {}", self.exported_at.to_rfc3339());
+
+        for (index, turn) in self.turns.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "\n## Turn {} ({})\n\n_Started {}_\n",
+                index + 1,
+                status_label(&turn.status),
+                turn.started_at.to_rfc3339()
+            );
+            if let Some(prompt) = turn.prompt.as_deref().filter(|_| !has_user_message(turn)) {
+                let _ = writeln!(out, "### User\n\n{}\n", prompt.trim());
+            }
+            for entry in &turn.entries {
+                write_markdown_entry(&mut out, entry);
+            }
+            if !turn.approvals.is_empty() {
+                let _ = writeln!(out, "### Approvals\n");
+                for approval in &turn.approvals {
+                    let _ = writeln!(
+                        out,
+                        "- `{}`: {}{}",
+                        approval.tool_name,
+                        approval_status_label(approval),
+                        approval
+                            .denial_reason
+                            .as_deref()
+                            .map(|reason| format!(" ({reason})"))
+                            .unwrap_or_default()
+                    );
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// Self-contained HTML page with inline styles and no external resources
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n<ul class=\"meta\">\n<li>Branch: <code>{}</code></li>\n",
+            escape_html(&self.title),
+            HTML_STYLE,
+            escape_html(&self.title),
+            escape_html(&self.branch)
+        );
+        if let Some(executor) = &self.executor {
+            let _ = writeln!(out, "<li>Executor: {}</li>", escape_html(executor));
+        }
+        let _ = writeln!(
+            out,
+            "<li>Session: <code>{}</code></li>\n<li>Exported: {}</li>\n</ul>",
+            self.session_id,
+            self.exported_at.to_rfc3339()
+        );
+
+        for (index, turn) in self.turns.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "<section class=\"turn\">\n<h2>Turn {} <span class=\"status\">{}</span></h2>\n<p class=\"time\">Started {}</p>",
+                index + 1,
+                status_label(&turn.status),
+                turn.started_at.to_rfc3339()
+            );
+            if let Some(prompt) = turn.prompt.as_deref().filter(|_| !has_user_message(turn)) {
+                let _ = writeln!(
+                    out,
+                    "<div class=\"entry user\"><h3>User</h3><pre class=\"text\">{}</pre></div>",
+                    escape_html(prompt.trim())
+                );
+            }
+            for entry in &turn.entries {
+                write_html_entry(&mut out, entry);
+            }
+            if !turn.approvals.is_empty() {
+                out.push_str("<h3>Approvals</h3>\n<ul class=\"approvals\">\n");
+                for approval in &turn.approvals {
+                    let _ = writeln!(
+                        out,
+                        "<li><code>{}</code>: {}{}</li>",
+                        escape_html(&approval.tool_name),
+                        escape_html(&approval_status_label(approval)),
+                        approval
+                            .denial_reason
+                            .as_deref()
+                            .map(|reason| format!(" ({})", escape_html(reason)))
+                            .unwrap_or_default()
+                    );
+                }
+                out.push_str("</ul>\n");
+            }
+            out.push_str("</section>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// Replay the JSON patches of a turn into the final list of conversation entries
+pub fn entries_from_patches(messages: impl IntoIterator<Item = LogMsg>) -> Vec<NormalizedEntry> {
+    let mut document = json!({ "entries": [] });
+    for message in messages {
+        if let LogMsg::JsonPatch(patch) = message
+            && let Err(e) = json_patch::patch(&mut document, &patch)
+        {
+            tracing::debug!("Skipping conversation patch that does not apply: {}", e);
+        }
+    }
+
+    document["entries"]
+        .as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter(|entry| entry["type"] == "NORMALIZED_ENTRY")
+                .filter_map(|entry| serde_json::from_value(entry["content"].clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Current normalized conversation of an execution process, whether it is still running or
+/// only has persisted logs left
+pub async fn normalized_entries<C>(
+    container: &C,
+    process: &ExecutionProcess,
+) -> Vec<NormalizedEntry>
+where
+    C: ContainerService + Sync + ?Sized,
+{
+    entries_from_patches(normalized_log_messages(container, process).await)
+}
+
+/// Conversation patches of an execution process, snapshotted from its live store or
+/// replayed from its persisted logs
+pub async fn normalized_log_messages<C>(container: &C, process: &ExecutionProcess) -> Vec<LogMsg>
+where
+    C: ContainerService + Sync + ?Sized,
+{
+    // A live store already holds the normalized patches; snapshot it instead of following it
+    if let Some(store) = container.get_msg_store_by_id(&process.id).await {
+        return store.get_history();
+    }
+
+    let Some(stream) = container.stream_normalized_logs(&process.id).await else {
+        return Vec::new();
+    };
+    let messages = stream
+        .take_while(|msg| futures::future::ready(!matches!(msg, Ok(LogMsg::Finished))))
+        .filter_map(|msg| futures::future::ready(msg.ok()))
+        .collect::<Vec<_>>();
+    match tokio::time::timeout(NORMALIZE_TIMEOUT, messages).await {
+        Ok(messages) => messages,
+        Err(_) => {
+            tracing::warn!(
+                "Timed out normalizing logs of execution process {}",
+                process.id
+            );
+            Vec::new()
+        }
+    }
+}
+
+fn has_user_message(turn: &TranscriptTurn) -> bool {
+    turn.entries
+        .iter()
+        .any(|entry| matches!(entry.entry_type, NormalizedEntryType::UserMessage))
+}
+
+fn status_label(status: &ExecutionProcessStatus) -> &'static str {
+    match status {
+        ExecutionProcessStatus::Queued => "queued",
+        ExecutionProcessStatus::Running => "running",
+        ExecutionProcessStatus::Completed => "completed",
+        ExecutionProcessStatus::Failed => "failed",
+        ExecutionProcessStatus::Killed => "killed",
+        ExecutionProcessStatus::TimedOut => "timed out",
+    }
+}
+
+fn approval_status_label(approval: &ApprovalRecord) -> String {
+    serde_json::to_value(approval.status)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn tool_status_label(status: &ToolStatus) -> Option<String> {
+    match status {
+        ToolStatus::Created | ToolStatus::Success => None,
+        ToolStatus::Failed => Some("failed".to_string()),
+        ToolStatus::Denied { reason } => Some(match reason {
+            Some(reason) => format!("denied: {reason}"),
+            None => "denied".to_string(),
+        }),
+        ToolStatus::PendingApproval { .. } => Some("awaiting approval".to_string()),
+        ToolStatus::TimedOut => Some("approval timed out".to_string()),
+    }
+}
+
+/// Fence that is longer than any backtick run in `content`
+fn fence_for(content: &str) -> String {
+    let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn write_code_block(out: &mut String, lang: &str, content: &str) {
+    let fence = fence_for(content);
+    let _ = writeln!(out, "{fence}{lang}\n{}\n{fence}\n", content.trim_end());
+}
+
+fn write_markdown_entry(out: &mut String, entry: &NormalizedEntry) {
+    match &entry.entry_type {
+        NormalizedEntryType::UserMessage => {
+            let _ = writeln!(out, "### User\n\n{}\n", entry.content.trim());
+        }
+        NormalizedEntryType::UserFeedback { denied_tool } => {
+            let _ = writeln!(
+                out,
+                "### User feedback on `{denied_tool}`\n\n{}\n",
+                entry.content.trim()
+            );
+        }
+        NormalizedEntryType::AssistantMessage => {
+            let _ = writeln!(out, "### Assistant\n\n{}\n", entry.content.trim());
+        }
+        NormalizedEntryType::Thinking => {
+            for line in entry.content.trim().lines() {
+                let _ = writeln!(out, "> {line}");
+            }
+            out.push('\n');
+        }
+        NormalizedEntryType::SystemMessage => {
+            let _ = writeln!(out, "_System: {}_\n", entry.content.trim());
+        }
+        NormalizedEntryType::ErrorMessage { .. } => {
+            let _ = writeln!(out, "**Error:** {}\n", entry.content.trim());
+        }
+        NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type,
+            status,
+        } => {
+            let suffix = tool_status_label(status)
+                .map(|label| format!(" _({label})_"))
+                .unwrap_or_default();
+            let _ = writeln!(out, "**Tool `{tool_name}`**{suffix}: {}\n", entry.content);
+            write_markdown_action(out, action_type);
+        }
+        NormalizedEntryType::Loading | NormalizedEntryType::NextAction { .. } => {}
+    }
+}
+
+fn write_markdown_action(out: &mut String, action: &ActionType) {
+    match action {
+        ActionType::FileEdit { path, changes } => {
+            for change in changes {
+                match change {
+                    FileChange::Write { content } => {
+                        let _ = writeln!(out, "Wrote `{path}`:\n");
+                        write_code_block(out, "", content);
+                    }
+                    FileChange::Delete => {
+                        let _ = writeln!(out, "Deleted `{path}`\n");
+                    }
+                    FileChange::Rename { new_path } => {
+                        let _ = writeln!(out, "Renamed `{path}` to `{new_path}`\n");
+                    }
+                    FileChange::Edit { unified_diff, .. } => {
+                        write_code_block(out, "diff", unified_diff)
+                    }
+                }
+            }
+        }
+        ActionType::CommandRun {
+            command,
+            result: Some(result),
+        } => {
+            write_code_block(out, "sh", command);
+            if let Some(output) = result.output.as_deref().filter(|o| !o.trim().is_empty()) {
+                write_code_block(out, "", output);
+            }
+            if let Some(CommandExitStatus::ExitCode { code }) = &result.exit_status
+                && *code != 0
+            {
+                let _ = writeln!(out, "_Exit code {code}_\n");
+            }
+        }
+        ActionType::PlanPresentation { plan } => {
+            let _ = writeln!(out, "{}\n", plan.trim());
+        }
+        ActionType::TodoManagement { todos, .. } => {
+            for todo in todos {
+                let mark = if todo.status == "completed" { "x" } else { " " };
+                let _ = writeln!(out, "- [{mark}] {}", todo.content);
+            }
+            out.push('\n');
+        }
+        _ => {}
+    }
+}
+
+fn write_html_entry(out: &mut String, entry: &NormalizedEntry) {
+    let (class, heading) = match &entry.entry_type {
+        NormalizedEntryType::UserMessage => ("user", "User".to_string()),
+        NormalizedEntryType::UserFeedback { denied_tool } => {
+            ("user", format!("User feedback on {denied_tool}"))
+        }
+        NormalizedEntryType::AssistantMessage => ("assistant", "Assistant".to_string()),
+        NormalizedEntryType::Thinking => ("thinking", "Thinking".to_string()),
+        NormalizedEntryType::SystemMessage => ("system", "System".to_string()),
+        NormalizedEntryType::ErrorMessage { .. } => ("error", "Error".to_string()),
+        NormalizedEntryType::ToolUse {
+            tool_name, status, ..
+        } => (
+            "tool",
+            match tool_status_label(status) {
+                Some(label) => format!("Tool {tool_name} ({label})"),
+                None => format!("Tool {tool_name}"),
+            },
+        ),
+        NormalizedEntryType::Loading | NormalizedEntryType::NextAction { .. } => return,
+    };
+
+    let _ = write!(
+        out,
+        "<div class=\"entry {class}\"><h3>{}</h3><pre class=\"text\">{}</pre>",
+        escape_html(&heading),
+        escape_html(entry.content.trim())
+    );
+    if let NormalizedEntryType::ToolUse { action_type, .. } = &entry.entry_type {
+        write_html_action(out, action_type);
+    }
+    out.push_str("</div>\n");
+}
+
+fn write_html_action(out: &mut String, action: &ActionType) {
+    match action {
+        ActionType::FileEdit { path, changes } => {
+            for change in changes {
+                let body = match change {
+                    FileChange::Write { content } => content.clone(),
+                    FileChange::Delete => format!("Deleted {path}"),
+                    FileChange::Rename { new_path } => format!("Renamed {path} to {new_path}"),
+                    FileChange::Edit { unified_diff, .. } => unified_diff.clone(),
+                };
+                let _ = write!(out, "<pre class=\"code\">{}</pre>", html_diff(&body));
+            }
+        }
+        ActionType::CommandRun {
+            command,
+            result: Some(result),
+        } => {
+            let _ = write!(out, "<pre class=\"code\">$ {}", escape_html(command));
+            if let Some(output) = &result.output {
+                let _ = write!(out, "\n{}", escape_html(output.trim_end()));
+            }
+            out.push_str("</pre>");
+        }
+        ActionType::PlanPresentation { plan } => {
+            let _ = write!(
+                out,
+                "<pre class=\"text\">{}</pre>",
+                escape_html(plan.trim())
+            );
+        }
+        ActionType::TodoManagement { todos, .. } => {
+            out.push_str("<ul>");
+            for todo in todos {
+                let _ = write!(
+                    out,
+                    "<li>[{}] {}</li>",
+                    escape_html(&todo.status),
+                    escape_html(&todo.content)
+                );
+            }
+            out.push_str("</ul>");
+        }
+        ActionType::Tool {
+            arguments: Some(arguments),
+            ..
+        } => {
+            let _ = write!(
+                out,
+                "<pre class=\"code\">{}</pre>",
+                escape_html(&serde_json::to_string_pretty(arguments).unwrap_or_default())
+            );
+        }
+        _ => {}
+    }
+}
+
+/// Escape a diff and highlight added and removed lines
+fn html_diff(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let escaped = escape_html(line);
+            if line.starts_with('+') && !line.starts_with("+++") {
+                format!("<span class=\"add\">{escaped}</span>")
+            } else if line.starts_with('-') && !line.starts_with("---") {
+                format!("<span class=\"del\">{escaped}</span>")
+            } else {
+                escaped
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;max-width:960px;margin:2rem auto;padding:0 1rem;color:#1f2328;line-height:1.5}\
+h1{margin-bottom:.5rem}.meta{color:#59636e;padding-left:1.2rem}\
+.turn{border-top:1px solid #d1d9e0;margin-top:2rem}.status,.time{color:#59636e;font-size:.85em;font-weight:normal}\
+.entry{margin:1rem 0;padding:.5rem .75rem;border-left:3px solid #d1d9e0}.entry h3{margin:0 0 .25rem;font-size:.9em}\
+.user{border-color:#0969da}.assistant{border-color:#1a7f37}.thinking{border-color:#8c959f;color:#59636e}\
+.tool{border-color:#9a6700}.error{border-color:#d1242f}.system{border-color:#8250df}\
+pre{white-space:pre-wrap;word-break:break-word;margin:.25rem 0}pre.text{font-family:inherit}\
+pre.code{background:#f6f8fa;padding:.5rem;border-radius:6px;font-size:.85em;overflow-x:auto}\
+.add{background:#dafbe1;display:block}.del{background:#ffebe9;display:block}";
+
+#[cfg(test)]
+mod tests {
+    use executors::logs::utils::patch::ConversationPatch;
+
+    use super::*;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn entries_from_patches_applies_adds_and_replaces() {
+        let messages = vec![
+            LogMsg::JsonPatch(ConversationPatch::add_normalized_entry(
+                0,
+                entry(NormalizedEntryType::UserMessage, "hi"),
+            )),
+            LogMsg::JsonPatch(ConversationPatch::add_normalized_entry(
+                1,
+                entry(NormalizedEntryType::AssistantMessage, "draft"),
+            )),
+            LogMsg::JsonPatch(ConversationPatch::replace(
+                1,
+                entry(NormalizedEntryType::AssistantMessage, "final"),
+            )),
+            LogMsg::Stdout("ignored".to_string()),
+        ];
+
+        let entries = entries_from_patches(messages);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].content, "hi");
+        assert_eq!(entries[1].content, "final");
+    }
+
+    #[test]
+    fn html_escapes_conversation_content() {
+        let mut out = String::new();
+        write_html_entry(
+            &mut out,
+            &entry(NormalizedEntryType::AssistantMessage, "<script>x</script>"),
+        );
+        assert!(out.contains("&lt;script&gt;"));
+        assert!(!out.contains("<script>"));
+    }
+
+    #[test]
+    fn code_fence_outgrows_backticks_in_content() {
+        assert_eq!(fence_for("plain"), "```");
+        assert_eq!(fence_for("has ``` inside"), "````");
+    }
+}
@@ -0,0 +1,202 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use db::models::workspace_disk_usage::WorkspaceDiskUsage;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::config::WorkspaceDiskConfig;
+
+const BYTES_PER_MB: i64 = 1024 * 1024;
+
+/// Disk usage of all workspaces against the configured budgets
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WorkspaceDiskSummary {
+    pub total_bytes: i64,
+    pub total_budget_bytes: Option<i64>,
+    pub project_budget_bytes: Option<i64>,
+    /// Least recently used first, which is the order they are evicted in
+    pub workspaces: Vec<WorkspaceDiskUsage>,
+}
+
+/// Outcome of enforcing the workspace disk budgets
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct WorkspaceGcReport {
+    /// Workspaces whose worktrees were removed; their branches are kept
+    pub evicted: Vec<Uuid>,
+    pub reclaimed_bytes: i64,
+    /// Over-budget workspaces kept because they have uncommitted or unpushed changes
+    pub skipped_unpushed: Vec<Uuid>,
+    /// Whether usage still exceeds a budget after this pass
+    pub over_budget: bool,
+}
+
+/// Running totals of workspace disk usage, total and per project, while evicting
+#[derive(Debug, Clone)]
+pub struct DiskBudget {
+    total_budget: Option<i64>,
+    project_budget: Option<i64>,
+    total_bytes: i64,
+    project_bytes: HashMap<Uuid, i64>,
+}
+
+impl DiskBudget {
+    pub fn new(config: &WorkspaceDiskConfig, usage: &[WorkspaceDiskUsage]) -> Self {
+        let mut project_bytes: HashMap<Uuid, i64> = HashMap::new();
+        for workspace in usage {
+            *project_bytes.entry(workspace.project_id).or_default() += workspace.bytes;
+        }
+        Self {
+            total_budget: config.total_budget_mb.map(|mb| mb as i64 * BYTES_PER_MB),
+            project_budget: config.project_budget_mb.map(|mb| mb as i64 * BYTES_PER_MB),
+            total_bytes: usage.iter().map(|workspace| workspace.bytes).sum(),
+            project_bytes,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.total_budget.is_some() || self.project_budget.is_some()
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.total_exceeded()
+            || self
+                .project_bytes
+                .keys()
+                .any(|project_id| self.project_exceeded(*project_id))
+    }
+
+    /// Whether evicting the workspace would help meet a budget that is exceeded
+    pub fn should_evict(&self, workspace: &WorkspaceDiskUsage) -> bool {
+        workspace.bytes > 0
+            && (self.total_exceeded() || self.project_exceeded(workspace.project_id))
+    }
+
+    pub fn release(&mut self, workspace: &WorkspaceDiskUsage) {
+        self.total_bytes -= workspace.bytes;
+        if let Some(bytes) = self.project_bytes.get_mut(&workspace.project_id) {
+            *bytes -= workspace.bytes;
+        }
+    }
+
+    pub fn summary(&self, workspaces: Vec<WorkspaceDiskUsage>) -> WorkspaceDiskSummary {
+        WorkspaceDiskSummary {
+            total_bytes: self.total_bytes,
+            total_budget_bytes: self.total_budget,
+            project_budget_bytes: self.project_budget,
+            workspaces,
+        }
+    }
+
+    fn total_exceeded(&self) -> bool {
+        self.total_budget
+            .is_some_and(|budget| self.total_bytes > budget)
+    }
+
+    fn project_exceeded(&self, project_id: Uuid) -> bool {
+        self.project_budget.is_some_and(|budget| {
+            self.project_bytes
+                .get(&project_id)
+                .is_some_and(|bytes| *bytes > budget)
+        })
+    }
+}
+
+/// Size of the files under `path`. Symlinks are not followed, so directories shared with
+/// the original repo by a symlink copy rule don't count.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(file_type) if file_type.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn usage(project_id: Uuid, mb: i64) -> WorkspaceDiskUsage {
+        WorkspaceDiskUsage {
+            workspace_id: Uuid::new_v4(),
+            task_id: Uuid::new_v4(),
+            project_id,
+            container_ref: "/tmp/workspace".to_string(),
+            bytes: mb * BYTES_PER_MB,
+            measured_at: Some(Utc::now()),
+            last_used_at: Utc::now(),
+            has_running_process: false,
+        }
+    }
+
+    fn config(total_budget_mb: Option<u32>, project_budget_mb: Option<u32>) -> WorkspaceDiskConfig {
+        WorkspaceDiskConfig {
+            total_budget_mb,
+            project_budget_mb,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn total_budget_evicts_until_met() {
+        let project_id = Uuid::new_v4();
+        let workspaces = vec![
+            usage(project_id, 300),
+            usage(project_id, 300),
+            usage(project_id, 300),
+        ];
+        let mut budget = DiskBudget::new(&config(Some(700), None), &workspaces);
+
+        let mut evicted = 0;
+        for workspace in &workspaces {
+            if budget.should_evict(workspace) {
+                budget.release(workspace);
+                evicted += 1;
+            }
+        }
+        assert_eq!(evicted, 1);
+        assert!(!budget.is_exceeded());
+    }
+
+    #[test]
+    fn project_budget_only_evicts_that_project() {
+        let (fat, slim) = (Uuid::new_v4(), Uuid::new_v4());
+        let workspaces = vec![usage(slim, 400), usage(fat, 400), usage(fat, 400)];
+        let budget = DiskBudget::new(&config(None, Some(500)), &workspaces);
+
+        assert!(budget.is_exceeded());
+        assert!(!budget.should_evict(&workspaces[0]));
+        assert!(budget.should_evict(&workspaces[1]));
+    }
+
+    #[test]
+    fn no_budget_never_evicts() {
+        let workspaces = vec![usage(Uuid::new_v4(), 100_000)];
+        let budget = DiskBudget::new(&config(None, None), &workspaces);
+
+        assert!(!budget.is_enabled());
+        assert!(!budget.should_evict(&workspaces[0]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dir_size_does_not_follow_symlinks() {
+        let shared = tempfile::tempdir().unwrap();
+        fs::write(shared.path().join("cache.bin"), vec![0u8; 4096]).unwrap();
+
+        let worktree = tempfile::tempdir().unwrap();
+        fs::create_dir(worktree.path().join("src")).unwrap();
+        fs::write(worktree.path().join("src/main.rs"), "fn main() {}").unwrap();
+        std::os::unix::fs::symlink(shared.path(), worktree.path().join("target")).unwrap();
+
+        assert_eq!(dir_size(worktree.path()), 12);
+    }
+}
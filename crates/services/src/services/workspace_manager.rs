@@ -1,4 +1,7 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use db::models::repo::Repo;
 use sqlx::{Pool, Sqlite};
@@ -6,19 +9,25 @@ use thiserror::Error;
 use tracing::{debug, error, info, trace, warn};
 use uuid::Uuid;
 
-use super::worktree_manager::{WorktreeCleanup, WorktreeError, WorktreeManager};
+use super::{
+    sparse_checkout::SparseCheckout,
+    worktree_manager::{WorktreeCleanup, WorktreeError, WorktreeManager},
+};
 
 #[derive(Debug, Clone)]
 pub struct RepoWorkspaceInput {
     pub repo: Repo,
     pub target_branch: String,
+    /// Directories to check out, `None` for the whole repo
+    pub sparse_checkout: Option<SparseCheckout>,
 }
 
 impl RepoWorkspaceInput {
-    pub fn new(repo: Repo, target_branch: String) -> Self {
+    pub fn new(repo: Repo, target_branch: String, sparse_checkout: Option<SparseCheckout>) -> Self {
         Self {
             repo,
             target_branch,
+            sparse_checkout,
         }
     }
 }
@@ -90,6 +99,7 @@ impl WorkspaceManager {
                 &worktree_path,
                 &input.target_branch,
                 true,
+                input.sparse_checkout.as_ref(),
             )
             .await
             {
@@ -137,11 +147,13 @@ impl WorkspaceManager {
         })
     }
 
-    /// Ensure all worktrees in a workspace exist (for cold restart scenarios)
+    /// Ensure all worktrees in a workspace exist (for cold restart scenarios).
+    /// Recreated worktrees check out the repo's entry in `sparse_checkouts`, if any.
     pub async fn ensure_workspace_exists(
         workspace_dir: &Path,
         repos: &[Repo],
         branch_name: &str,
+        sparse_checkouts: &HashMap<Uuid, SparseCheckout>,
     ) -> Result<(), WorkspaceError> {
         if repos.is_empty() {
             return Err(WorkspaceError::NoRepositories);
@@ -166,8 +178,13 @@ impl WorkspaceManager {
                 worktree_path.display()
             );
 
-            WorktreeManager::ensure_worktree_exists(&repo.path, branch_name, &worktree_path)
-                .await?;
+            WorktreeManager::ensure_worktree_exists(
+                &repo.path,
+                branch_name,
+                &worktree_path,
+                sparse_checkouts.get(&repo.id),
+            )
+            .await?;
         }
 
         Ok(())
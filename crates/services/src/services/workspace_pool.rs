@@ -0,0 +1,303 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::Notify;
+use utils::text::short_uuid;
+use uuid::Uuid;
+
+use crate::services::{
+    git::GitCli,
+    workspace_manager::WorkspaceManager,
+    worktree_manager::{WorktreeCleanup, WorktreeManager},
+};
+
+/// Pooled workspaces are checked out on a branch in this namespace until they are claimed
+pub const POOL_BRANCH_PREFIX: &str = "vk-pool/";
+
+#[derive(Debug, Clone)]
+pub struct PooledRepo {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub repo_path: PathBuf,
+    pub target_branch: String,
+    /// Commit of the target branch the worktree was created from
+    pub base_commit: String,
+}
+
+/// A workspace directory with worktrees of all of a project's repos, with project files
+/// copied and setup scripts run, that no task attempt owns yet
+#[derive(Debug, Clone)]
+pub struct PooledWorkspace {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub workspace_dir: PathBuf,
+    pub branch: String,
+    pub repos: Vec<PooledRepo>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PooledWorkspace {
+    pub fn new(project_id: Uuid, repos: Vec<PooledRepo>) -> Self {
+        let id = Uuid::new_v4();
+        Self {
+            id,
+            project_id,
+            workspace_dir: WorkspaceManager::get_workspace_base_dir()
+                .join(format!("pool-{}", short_uuid(&id))),
+            branch: format!("{POOL_BRANCH_PREFIX}{}", short_uuid(&id)),
+            repos,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Whether it was created from exactly these `(repo_id, target_branch)` pairs
+    pub fn matches(&self, targets: &[(Uuid, String)]) -> bool {
+        self.repos.len() == targets.len()
+            && targets.iter().all(|(repo_id, target_branch)| {
+                self.repos
+                    .iter()
+                    .any(|repo| repo.repo_id == *repo_id && repo.target_branch == *target_branch)
+            })
+    }
+
+    /// Remove the worktrees, the directory and the pool branch
+    pub async fn discard(&self) {
+        let cleanup: Vec<WorktreeCleanup> = self
+            .repos
+            .iter()
+            .map(|repo| {
+                WorktreeCleanup::new(
+                    self.workspace_dir.join(&repo.repo_name),
+                    Some(repo.repo_path.clone()),
+                )
+            })
+            .collect();
+        if let Err(e) = WorktreeManager::batch_cleanup_worktrees(&cleanup).await {
+            tracing::warn!("Failed to clean up pooled workspace {}: {}", self.id, e);
+        }
+        if self.workspace_dir.exists()
+            && let Err(e) = tokio::fs::remove_dir_all(&self.workspace_dir).await
+        {
+            tracing::debug!(
+                "Could not remove pooled workspace directory {}: {}",
+                self.workspace_dir.display(),
+                e
+            );
+        }
+        for repo in &self.repos {
+            delete_branch(&repo.repo_path, &self.branch);
+        }
+    }
+}
+
+fn delete_branch(repo_path: &Path, branch: &str) {
+    if let Err(e) = GitCli::new().git(repo_path, ["branch", "-D", branch]) {
+        tracing::debug!(
+            "Could not delete branch {} in {}: {}",
+            branch,
+            repo_path.display(),
+            e
+        );
+    }
+}
+
+/// Delete pool branches left behind by pooled workspaces that no longer exist, e.g. from
+/// before a restart
+pub fn delete_stale_pool_branches(repo_path: &Path, keep: &HashSet<String>) {
+    let pattern = format!("refs/heads/{POOL_BRANCH_PREFIX}");
+    let refs = match GitCli::new().git(
+        repo_path,
+        [
+            "for-each-ref",
+            "--format=%(refname:short)",
+            pattern.as_str(),
+        ],
+    ) {
+        Ok(refs) => refs,
+        Err(e) => {
+            tracing::debug!(
+                "Could not list pool branches in {}: {}",
+                repo_path.display(),
+                e
+            );
+            return;
+        }
+    };
+    for branch in refs.lines().map(str::trim).filter(|b| !b.is_empty()) {
+        if !keep.contains(branch) {
+            delete_branch(repo_path, branch);
+        }
+    }
+}
+
+/// Ready workspaces by project. The pool is refilled in the background and lost on restart;
+/// leftover directories are removed with the other orphaned workspaces at startup.
+#[derive(Clone, Default)]
+pub struct WorkspacePool {
+    workspaces: Arc<Mutex<HashMap<Uuid, Vec<PooledWorkspace>>>>,
+    refill: Arc<Notify>,
+}
+
+impl WorkspacePool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, workspace: PooledWorkspace) {
+        self.workspaces
+            .lock()
+            .unwrap()
+            .entry(workspace.project_id)
+            .or_default()
+            .push(workspace);
+    }
+
+    pub fn count(&self, project_id: Uuid) -> usize {
+        self.workspaces
+            .lock()
+            .unwrap()
+            .get(&project_id)
+            .map_or(0, Vec::len)
+    }
+
+    pub fn list(&self, project_id: Uuid) -> Vec<PooledWorkspace> {
+        self.workspaces
+            .lock()
+            .unwrap()
+            .get(&project_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Every pool branch still in use
+    pub fn branches(&self) -> HashSet<String> {
+        self.workspaces
+            .lock()
+            .unwrap()
+            .values()
+            .flatten()
+            .map(|workspace| workspace.branch.clone())
+            .collect()
+    }
+
+    pub fn remove(&self, id: Uuid) -> Option<PooledWorkspace> {
+        let mut workspaces = self.workspaces.lock().unwrap();
+        for pooled in workspaces.values_mut() {
+            if let Some(index) = pooled.iter().position(|workspace| workspace.id == id) {
+                return Some(pooled.remove(index));
+            }
+        }
+        None
+    }
+
+    /// Take the oldest workspace of the project created from these target branches
+    pub fn claim(&self, project_id: Uuid, targets: &[(Uuid, String)]) -> Option<PooledWorkspace> {
+        let mut workspaces = self.workspaces.lock().unwrap();
+        let pooled = workspaces.get_mut(&project_id)?;
+        let index = pooled
+            .iter()
+            .position(|workspace| workspace.matches(targets))?;
+        Some(pooled.remove(index))
+    }
+
+    /// Take the workspaces of every project not in `keep`
+    pub fn evict_except(&self, keep: &HashSet<Uuid>) -> Vec<PooledWorkspace> {
+        let mut workspaces = self.workspaces.lock().unwrap();
+        let evicted: Vec<Uuid> = workspaces
+            .keys()
+            .filter(|project_id| !keep.contains(project_id))
+            .copied()
+            .collect();
+        evicted
+            .into_iter()
+            .flat_map(|project_id| workspaces.remove(&project_id).unwrap_or_default())
+            .collect()
+    }
+
+    /// Wake the refill task, e.g. after a workspace was claimed
+    pub fn request_refill(&self) {
+        self.refill.notify_one();
+    }
+
+    pub async fn refill_requested(&self) {
+        self.refill.notified().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pooled(project_id: Uuid, targets: &[(Uuid, &str)]) -> PooledWorkspace {
+        let repos = targets
+            .iter()
+            .map(|(repo_id, target_branch)| PooledRepo {
+                repo_id: *repo_id,
+                repo_name: "repo".to_string(),
+                repo_path: PathBuf::from("/repo"),
+                target_branch: target_branch.to_string(),
+                base_commit: "abc123".to_string(),
+            })
+            .collect();
+        PooledWorkspace::new(project_id, repos)
+    }
+
+    #[test]
+    fn claim_takes_the_oldest_matching_workspace() {
+        let pool = WorkspacePool::new();
+        let project_id = Uuid::new_v4();
+        let repo_id = Uuid::new_v4();
+        let on_develop = pooled(project_id, &[(repo_id, "develop")]);
+        let first = pooled(project_id, &[(repo_id, "main")]);
+        let second = pooled(project_id, &[(repo_id, "main")]);
+        pool.add(on_develop.clone());
+        pool.add(first.clone());
+        pool.add(second.clone());
+
+        let targets = vec![(repo_id, "main".to_string())];
+        assert_eq!(pool.claim(project_id, &targets).unwrap().id, first.id);
+        assert_eq!(pool.claim(project_id, &targets).unwrap().id, second.id);
+        assert!(pool.claim(project_id, &targets).is_none());
+        assert_eq!(pool.count(project_id), 1);
+    }
+
+    #[test]
+    fn claim_requires_the_same_repos() {
+        let pool = WorkspacePool::new();
+        let project_id = Uuid::new_v4();
+        let (frontend, backend) = (Uuid::new_v4(), Uuid::new_v4());
+        pool.add(pooled(project_id, &[(frontend, "main"), (backend, "main")]));
+
+        assert!(
+            pool.claim(project_id, &[(frontend, "main".to_string())])
+                .is_none()
+        );
+        assert!(
+            pool.claim(
+                project_id,
+                &[
+                    (backend, "main".to_string()),
+                    (frontend, "main".to_string())
+                ]
+            )
+            .is_some()
+        );
+    }
+
+    #[test]
+    fn evict_except_keeps_active_projects() {
+        let pool = WorkspacePool::new();
+        let (active, inactive) = (Uuid::new_v4(), Uuid::new_v4());
+        pool.add(pooled(active, &[(Uuid::new_v4(), "main")]));
+        pool.add(pooled(inactive, &[(Uuid::new_v4(), "main")]));
+
+        let evicted = pool.evict_except(&HashSet::from([active]));
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].project_id, inactive);
+        assert_eq!(pool.count(active), 1);
+    }
+}
@@ -10,7 +10,10 @@ use thiserror::Error;
 use tracing::{debug, info, trace};
 use utils::{path::normalize_macos_private_alias, shell::resolve_executable_path};
 
-use super::git::{GitService, GitServiceError};
+use super::{
+    git::{GitService, GitServiceError},
+    sparse_checkout::SparseCheckout,
+};
 
 // Global synchronization for worktree creation to prevent race conditions
 static WORKTREE_CREATION_LOCKS: LazyLock<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
@@ -54,13 +57,14 @@ pub enum WorktreeError {
 pub struct WorktreeManager;
 
 impl WorktreeManager {
-    /// Create a worktree with a new branch
+    /// Create a worktree with a new branch, checking out only `sparse_checkout` when given
     pub async fn create_worktree(
         repo_path: &Path,
         branch_name: &str,
         worktree_path: &Path,
         base_branch: &str,
         create_branch: bool,
+        sparse_checkout: Option<&SparseCheckout>,
     ) -> Result<(), WorktreeError> {
         if create_branch {
             let repo_path_owned = repo_path.to_path_buf();
@@ -82,15 +86,19 @@ impl WorktreeManager {
             .map_err(|e| WorktreeError::TaskJoin(format!("Task join error: {e}")))??;
         }
 
-        Self::ensure_worktree_exists(repo_path, branch_name, worktree_path).await
+        Self::ensure_worktree_exists(repo_path, branch_name, worktree_path, sparse_checkout).await
     }
 
     /// Ensure worktree exists, recreating if necessary with proper synchronization
-    /// This is the main entry point for ensuring a worktree exists and prevents race conditions
+    /// This is the main entry point for ensuring a worktree exists and prevents race conditions.
+    /// The sparse checkout, submodules and LFS objects are only set up when the worktree is
+    /// (re)created; an existing worktree keeps what it has, including directories it was
+    /// widened with. This runs before every execution, so it stays off the network otherwise.
     pub async fn ensure_worktree_exists(
         repo_path: &Path,
         branch_name: &str,
         worktree_path: &Path,
+        sparse_checkout: Option<&SparseCheckout>,
     ) -> Result<(), WorktreeError> {
         let path_str = worktree_path.to_string_lossy().to_string();
 
@@ -114,7 +122,32 @@ impl WorktreeManager {
 
         // If worktree doesn't exist or isn't properly set up, recreate it
         info!("Worktree needs recreation at path: {}", path_str);
-        Self::recreate_worktree_internal(repo_path, branch_name, worktree_path).await
+        Self::recreate_worktree_internal(repo_path, branch_name, worktree_path, sparse_checkout)
+            .await?;
+        Self::update_submodules_and_lfs(worktree_path).await;
+        Ok(())
+    }
+
+    /// Initialize submodules and pull LFS objects in the worktree (non-blocking).
+    /// Failures are logged rather than returned: the worktree is usable without them,
+    /// e.g. when a submodule remote needs credentials or git-lfs is not installed.
+    async fn update_submodules_and_lfs(worktree_path: &Path) {
+        let worktree_path = worktree_path.to_path_buf();
+        let result = tokio::task::spawn_blocking({
+            let worktree_path = worktree_path.clone();
+            move || GitService::new().update_submodules_and_lfs(&worktree_path)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::warn!(
+                "Failed to update submodules or LFS objects in {}: {}",
+                worktree_path.display(),
+                e
+            ),
+            Err(e) => tracing::warn!("Task join error updating submodules: {}", e),
+        }
     }
 
     /// Internal worktree recreation function (always recreates)
@@ -122,6 +155,7 @@ impl WorktreeManager {
         repo_path: &Path,
         branch_name: &str,
         worktree_path: &Path,
+        sparse_checkout: Option<&SparseCheckout>,
     ) -> Result<(), WorktreeError> {
         let path_str = worktree_path.to_string_lossy().to_string();
         let branch_name_owned = branch_name.to_string();
@@ -150,6 +184,7 @@ impl WorktreeManager {
             &branch_name_owned,
             &worktree_path_owned,
             &path_str,
+            sparse_checkout,
         )
         .await
     }
@@ -299,16 +334,30 @@ impl WorktreeManager {
         branch_name: &str,
         worktree_path: &Path,
         path_str: &str,
+        sparse_checkout: Option<&SparseCheckout>,
     ) -> Result<(), WorktreeError> {
         let git_repo_path = git_repo_path.to_path_buf();
         let branch_name = branch_name.to_string();
         let worktree_path = worktree_path.to_path_buf();
         let path_str = path_str.to_string();
+        let sparse_dirs = sparse_checkout.map(|sparse| sparse.dirs().to_vec());
 
         tokio::task::spawn_blocking(move || -> Result<(), WorktreeError> {
             // Prefer git CLI for worktree add to inherit sparse-checkout semantics
             let git_service = GitService::new();
-            match git_service.add_worktree(&git_repo_path, &worktree_path, &branch_name, false) {
+            let add_worktree = || match &sparse_dirs {
+                Some(dirs) => git_service.add_sparse_worktree(
+                    &git_repo_path,
+                    &worktree_path,
+                    &branch_name,
+                    false,
+                    dirs,
+                ),
+                None => {
+                    git_service.add_worktree(&git_repo_path, &worktree_path, &branch_name, false)
+                }
+            };
+            match add_worktree() {
                 Ok(()) => {
                     if !worktree_path.exists() {
                         return Err(WorktreeError::Repository(format!(
@@ -333,12 +382,7 @@ impl WorktreeManager {
                     if worktree_path.exists() {
                         std::fs::remove_dir_all(&worktree_path).map_err(WorktreeError::Io)?;
                     }
-                    if let Err(e2) = git_service.add_worktree(
-                        &git_repo_path,
-                        &worktree_path,
-                        &branch_name,
-                        false,
-                    ) {
+                    if let Err(e2) = add_worktree() {
                         return Err(WorktreeError::GitService(e2));
                     }
                     if !worktree_path.exists() {
@@ -6,7 +6,7 @@ use std::{
 
 use git2::{Repository, build::CheckoutBuilder};
 use services::services::{
-    git::{DiffTarget, GitCli, GitService},
+    git::{DiffTarget, GitCli, GitService, WorktreeResetOptions},
     github::{GitHubRepoInfo, GitHubServiceError},
 };
 use tempfile::TempDir;
@@ -466,6 +466,209 @@ fn worktree_diff_permission_only_change() {
     assert_eq!(d.old_content, d.new_content);
 }
 
+#[test]
+fn worktree_diff_shows_submodule_pointer_change() {
+    let td = TempDir::new().unwrap();
+    let s = GitService::new();
+    let lib_path = td.path().join("lib");
+    s.initialize_repo_with_main_branch(&lib_path).unwrap();
+    configure_user(&lib_path, "Test User", "test@example.com");
+
+    let repo_path = init_repo_main(&td);
+    let git = GitCli::new();
+    git.git(
+        &repo_path,
+        [
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "add",
+            lib_path.to_str().unwrap(),
+            "lib",
+        ],
+    )
+    .unwrap();
+    let _ = s.commit(&repo_path, "add lib submodule").unwrap();
+    create_branch(&repo_path, "feature");
+
+    // move the submodule to a new commit
+    let submodule_path = repo_path.join("lib");
+    configure_user(&submodule_path, "Test User", "test@example.com");
+    let old_sha = s.get_head_info(&submodule_path).unwrap().oid;
+    write_file(&submodule_path, "lib.rs", "pub fn lib() {}\n");
+    let _ = s.commit(&submodule_path, "add lib.rs").unwrap();
+    let new_sha = s.get_head_info(&submodule_path).unwrap().oid;
+
+    let base_commit = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Worktree {
+                worktree_path: Path::new(&repo_path),
+                base_commit: &base_commit,
+            },
+            None,
+        )
+        .unwrap();
+    let d = diffs
+        .into_iter()
+        .find(|d| d.new_path.as_deref() == Some("lib"))
+        .expect("submodule diff present");
+    assert!(matches!(d.change, DiffChangeKind::Modified));
+    assert_eq!(
+        d.old_content.as_deref(),
+        Some(format!("Subproject commit {old_sha}\n").as_str())
+    );
+    assert_eq!(
+        d.new_content.as_deref(),
+        Some(format!("Subproject commit {new_sha}\n").as_str())
+    );
+}
+
+#[test]
+fn worktree_diff_compares_lfs_pointers() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    let pointer = "version https://git-lfs.github.com/spec/v1\n\
+                   oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n\
+                   size 12345\n";
+    write_file(
+        &repo_path,
+        ".gitattributes",
+        "*.bin filter=lfs diff=lfs merge=lfs -text\n",
+    );
+    write_file(&repo_path, "model.bin", pointer);
+    let _ = s.commit(&repo_path, "add lfs file").unwrap();
+    create_branch(&repo_path, "feature");
+
+    // the smudged object as it would be checked out by git-lfs
+    write_file(&repo_path, "model.bin", "hello world");
+
+    let base_commit = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Worktree {
+                worktree_path: Path::new(&repo_path),
+                base_commit: &base_commit,
+            },
+            None,
+        )
+        .unwrap();
+    let d = diffs
+        .into_iter()
+        .find(|d| d.new_path.as_deref() == Some("model.bin"))
+        .expect("lfs diff present");
+    assert_eq!(d.old_content.as_deref(), Some(pointer));
+    assert_eq!(
+        d.new_content.as_deref(),
+        Some(
+            "version https://git-lfs.github.com/spec/v1\n\
+             oid sha256:b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9\n\
+             size 11\n"
+        )
+    );
+}
+
+#[test]
+fn sparse_worktree_commits_and_diffs_outside_the_cone() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "services/api/main.rs", "fn main() {}\n");
+    write_file(&repo_path, "web/app.ts", "app\n");
+    let _ = s.commit(&repo_path, "monorepo").unwrap();
+    create_branch(&repo_path, "feature");
+
+    let wt = td.path().join("wt");
+    let dirs = vec!["services/api".to_string()];
+    s.add_sparse_worktree(&repo_path, &wt, "feature", false, &dirs)
+        .unwrap();
+    assert!(wt.join("services/api/main.rs").exists());
+    assert!(!wt.join("web").exists());
+    assert_eq!(s.get_sparse_checkout(&wt).unwrap(), Some(dirs));
+
+    // a file written outside the cone is committed, then dropped from disk again
+    write_file(&wt, "web/new.ts", "new\n");
+    let _ = s.commit(&wt, "outside the cone").unwrap();
+    GitCli::new()
+        .git(&wt, ["sparse-checkout", "reapply"])
+        .unwrap();
+    assert!(!wt.join("web/new.ts").exists());
+
+    let base_commit = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Worktree {
+                worktree_path: &wt,
+                base_commit: &base_commit,
+            },
+            None,
+        )
+        .unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert!(matches!(diffs[0].change, DiffChangeKind::Added));
+    assert_eq!(diffs[0].new_content.as_deref(), Some("new\n"));
+
+    let widened = s.widen_sparse_checkout(&wt, &["web".to_string()]).unwrap();
+    assert_eq!(
+        widened,
+        Some(vec!["services/api".to_string(), "web".to_string()])
+    );
+    assert!(wt.join("web/app.ts").exists());
+}
+
+#[test]
+fn fork_resets_new_worktree_to_before_head_commit_without_touching_source() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+
+    // Source workspace: the first turn commits, the forked turn starts from there
+    let source = td.path().join("source");
+    s.add_worktree(&repo_path, &source, "vk/source", true)
+        .unwrap();
+    write_file(&source, "notes.txt", "turn one\n");
+    let _ = s.commit(&source, "turn one").unwrap();
+    let before_head_commit = s.get_head_info(&source).unwrap().oid;
+    write_file(&source, "notes.txt", "turn two\n");
+    write_file(&source, "extra.txt", "extra\n");
+    let _ = s.commit(&source, "turn two").unwrap();
+    let after_head_commit = s.get_head_info(&source).unwrap().oid;
+    write_file(&source, "extra.txt", "uncommitted\n");
+
+    // Fork workspace: created from the target branch, then reset like a fork
+    let fork = td.path().join("fork");
+    s.add_worktree(&repo_path, &fork, "vk/fork", true).unwrap();
+    let outcome = s.reconcile_worktree_to_commit(
+        &fork,
+        &before_head_commit,
+        WorktreeResetOptions::new(true, true, false, true),
+    );
+    assert!(outcome.needed && outcome.applied);
+
+    let fork_head = s.get_head_info(&fork).unwrap();
+    assert_eq!(fork_head.branch, "vk/fork");
+    assert_eq!(fork_head.oid, before_head_commit);
+    assert_eq!(
+        fs::read_to_string(fork.join("notes.txt")).unwrap(),
+        "turn one\n"
+    );
+    assert!(!fork.join("extra.txt").exists());
+
+    let source_head = s.get_head_info(&source).unwrap();
+    assert_eq!(source_head.branch, "vk/source");
+    assert_eq!(source_head.oid, after_head_commit);
+    assert_eq!(
+        fs::read_to_string(source.join("notes.txt")).unwrap(),
+        "turn two\n"
+    );
+    assert_eq!(
+        fs::read_to_string(source.join("extra.txt")).unwrap(),
+        "uncommitted\n"
+    );
+    assert!(!s.is_worktree_clean(&source).unwrap());
+}
+
 #[test]
 fn github_repo_info_parses_https_and_ssh_urls() {
     let info = GitHubRepoInfo::from_remote_url("https://github.com/owner/repo.git").unwrap();
@@ -18,6 +18,18 @@ pub struct ApprovalRequest {
 
 impl ApprovalRequest {
     pub fn from_create(request: CreateApprovalRequest, execution_process_id: Uuid) -> Self {
+        Self::from_create_with_timeout(
+            request,
+            execution_process_id,
+            Duration::seconds(APPROVAL_TIMEOUT_SECONDS),
+        )
+    }
+
+    pub fn from_create_with_timeout(
+        request: CreateApprovalRequest,
+        execution_process_id: Uuid,
+        timeout: Duration,
+    ) -> Self {
         let now = Utc::now();
         Self {
             id: Uuid::new_v4().to_string(),
@@ -26,7 +38,7 @@ impl ApprovalRequest {
             tool_call_id: request.tool_call_id,
             execution_process_id,
             created_at: now,
-            timeout_at: now + Duration::seconds(APPROVAL_TIMEOUT_SECONDS),
+            timeout_at: now + timeout,
         }
     }
 }
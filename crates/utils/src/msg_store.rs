@@ -1,6 +1,6 @@
 use std::{
     collections::VecDeque,
-    sync::{Arc, RwLock},
+    sync::{Arc, OnceLock, RwLock, atomic::AtomicUsize},
 };
 
 use axum::response::sse::Event;
@@ -27,6 +27,8 @@ struct Inner {
 pub struct MsgStore {
     inner: RwLock<Inner>,
     sender: broadcast::Sender<LogMsg>,
+    /// Next normalized entry index, shared by everything that adds entries to this store
+    entry_index: OnceLock<Arc<AtomicUsize>>,
 }
 
 impl Default for MsgStore {
@@ -44,6 +46,7 @@ impl MsgStore {
                 total_bytes: 0,
             }),
             sender,
+            entry_index: OnceLock::new(),
         }
     }
 
@@ -83,6 +86,15 @@ impl MsgStore {
         self.push(LogMsg::Finished);
     }
 
+    /// Counter for the next normalized entry index, seeded by `init` on first use. Normalizers
+    /// and entries added from outside them (timeouts, policy decisions) draw from the same
+    /// counter so their indexes never collide.
+    pub fn entry_index_counter(&self, init: impl FnOnce() -> usize) -> Arc<AtomicUsize> {
+        self.entry_index
+            .get_or_init(|| Arc::new(AtomicUsize::new(init())))
+            .clone()
+    }
+
     pub fn get_receiver(&self) -> broadcast::Receiver<LogMsg> {
         self.sender.subscribe()
     }
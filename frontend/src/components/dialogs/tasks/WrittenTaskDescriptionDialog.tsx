@@ -82,6 +82,7 @@ const WrittenTaskDescriptionDialogImpl =
             description,
             status: null,
             intent: null,
+            priority: null,
             parent_workspace_id: null,
             image_ids: null,
           });
@@ -1,31 +1,94 @@
 import { useTranslation } from 'react-i18next';
-import { MultiFileSearchTextarea } from '@/components/ui/multi-file-search-textarea';
+import { Plus, Trash2 } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from '@/components/ui/select';
+import { CopyMode, type CopyFileRule } from 'shared/types';
 
 interface CopyFilesFieldProps {
-  value: string;
-  onChange: (value: string) => void;
-  projectId: string;
+  value: CopyFileRule[];
+  onChange: (value: CopyFileRule[]) => void;
   disabled?: boolean;
 }
 
+const COPY_MODES: CopyMode[] = [
+  CopyMode.copy,
+  CopyMode.symlink,
+  CopyMode.hardlink,
+  CopyMode.reflink,
+];
+
 export function CopyFilesField({
   value,
   onChange,
-  projectId,
   disabled = false,
 }: CopyFilesFieldProps) {
   const { t } = useTranslation('projects');
 
+  const updateRule = (index: number, updates: Partial<CopyFileRule>) => {
+    onChange(
+      value.map((rule, i) => (i === index ? { ...rule, ...updates } : rule))
+    );
+  };
+
   return (
-    <MultiFileSearchTextarea
-      value={value}
-      onChange={onChange}
-      placeholder={t('copyFilesPlaceholderWithSearch')}
-      rows={3}
-      disabled={disabled}
-      className="w-full px-3 py-2 text-sm border border-input bg-background text-foreground disabled:opacity-50 rounded-md resize-vertical focus:outline-none focus:ring-2 focus:ring-ring"
-      projectId={projectId}
-      maxRows={6}
-    />
+    <div className="space-y-2">
+      {value.map((rule, index) => (
+        <div key={index} className="flex items-center gap-2">
+          <Input
+            value={rule.pattern}
+            onChange={(e) => updateRule(index, { pattern: e.target.value })}
+            placeholder={t('copyFilesPlaceholder')}
+            disabled={disabled}
+            className="flex-1 font-mono"
+          />
+          <Select
+            value={rule.mode}
+            onValueChange={(mode) =>
+              updateRule(index, { mode: mode as CopyMode })
+            }
+            disabled={disabled}
+          >
+            <SelectTrigger className="w-36">
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              {COPY_MODES.map((mode) => (
+                <SelectItem key={mode} value={mode}>
+                  {t(`copyFilesModes.${mode}`)}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+          <Button
+            type="button"
+            variant="ghost"
+            size="icon"
+            onClick={() => onChange(value.filter((_, i) => i !== index))}
+            disabled={disabled}
+          >
+            <Trash2 className="h-4 w-4" />
+          </Button>
+        </div>
+      ))}
+      <Button
+        type="button"
+        variant="outline"
+        size="sm"
+        onClick={() =>
+          onChange([...value, { pattern: '', mode: CopyMode.copy }])
+        }
+        disabled={disabled}
+      >
+        <Plus className="h-4 w-4 mr-1" />
+        {t('copyFilesAddPattern')}
+      </Button>
+    </div>
   );
 }
@@ -8,6 +8,7 @@ import {
   Clock,
   Cog,
   ArrowLeft,
+  TimerOff,
 } from 'lucide-react';
 import { executionProcessesApi } from '@/lib/api.ts';
 import { ProfileVariantBadge } from '@/components/common/ProfileVariantBadge.tsx';
@@ -72,6 +73,8 @@ function ProcessesTab({ attemptId }: ProcessesTabProps) {
         return <CheckCircle className="h-4 w-4 text-green-500" />;
       case 'failed':
         return <AlertCircle className="h-4 w-4 text-destructive" />;
+      case 'timedout':
+        return <TimerOff className="h-4 w-4 text-destructive" />;
       case 'killed':
         return <Square className="h-4 w-4 text-gray-500" />;
       default:
@@ -86,6 +89,7 @@ function ProcessesTab({ attemptId }: ProcessesTabProps) {
       case 'completed':
         return 'bg-green-50 border-green-200 text-green-800';
       case 'failed':
+      case 'timedout':
         return 'bg-red-50 border-red-200 text-red-800';
       case 'killed':
         return 'bg-gray-50 border-gray-200 text-gray-800';
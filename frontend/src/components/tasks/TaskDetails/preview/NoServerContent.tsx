@@ -134,6 +134,7 @@ export function NoServerContent({
         description: COMPANION_INSTALL_TASK_DESCRIPTION,
         status: null,
         intent: null,
+        priority: null,
         parent_workspace_id: null,
         image_ids: null,
         shared_task_id: null,
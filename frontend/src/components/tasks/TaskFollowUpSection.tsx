@@ -300,7 +300,7 @@ export function TaskFollowUpSection({
 
   // When queued, display the queued message content so user can edit it
   const displayMessage =
-    isQueued && queuedMessage ? queuedMessage.data.message : localMessage;
+    isQueued && queuedMessage ? queuedMessage.message : localMessage;
 
   // Check if there's a pending approval - users shouldn't be able to type during approvals
   const { entries } = useEntries();
@@ -486,7 +486,7 @@ export function TaskFollowUpSection({
           // If queued, cancel queue and use queued message as base (same as editor change behavior)
           if (isQueuedRef.current && queuedMessageRef.current) {
             cancelQueueRef.current();
-            const base = queuedMessageRef.current.data.message;
+            const base = queuedMessageRef.current.message;
             const newMessage = base
               ? `${base}\n\n${imageMarkdown}`
               : imageMarkdown;
@@ -566,7 +566,7 @@ export function TaskFollowUpSection({
       // Same pattern as image paste
       if (isQueuedRef.current && queuedMessageRef.current) {
         cancelQueueRef.current();
-        const base = queuedMessageRef.current.data.message;
+        const base = queuedMessageRef.current.message;
         const newMessage = base ? `${base}\n\n${markdown}` : markdown;
         setLocalMessage(newMessage);
         setFollowUpMessageRef.current(newMessage);
@@ -279,7 +279,8 @@ export const useConversationHistory = ({
               liveProcessStatus === ExecutionProcessStatus.running;
             const processFailedOrKilled =
               liveProcessStatus === ExecutionProcessStatus.failed ||
-              liveProcessStatus === ExecutionProcessStatus.killed;
+              liveProcessStatus === ExecutionProcessStatus.killed ||
+              liveProcessStatus === ExecutionProcessStatus.timedout;
 
             if (isProcessRunning) {
               hasRunningProcess = true;
@@ -341,7 +342,8 @@ export const useConversationHistory = ({
 
             if (
               (executionProcess?.status === ExecutionProcessStatus.failed ||
-                executionProcess?.status === ExecutionProcessStatus.killed) &&
+                executionProcess?.status === ExecutionProcessStatus.killed ||
+                executionProcess?.status === ExecutionProcessStatus.timedout) &&
               index === Object.keys(executionProcessState).length - 1
             ) {
               lastProcessFailedOrKilled = true;
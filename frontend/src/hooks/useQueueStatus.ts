@@ -7,13 +7,15 @@ interface UseQueueStatusResult {
   queueStatus: QueueStatus;
   /** Whether a message is currently queued */
   isQueued: boolean;
-  /** The queued message if any */
+  /** Queued messages in send order */
+  queuedMessages: QueuedMessage[];
+  /** The next queued message if any */
   queuedMessage: QueuedMessage | null;
   /** Whether an operation is in progress */
   isLoading: boolean;
   /** Queue a new message */
   queueMessage: (message: string, variant: string | null) => Promise<void>;
-  /** Cancel the queued message */
+  /** Cancel all queued messages */
   cancelQueue: () => Promise<void>;
   /** Refresh the queue status from the server */
   refresh: () => Promise<void>;
@@ -70,13 +72,15 @@ export function useQueueStatus(sessionId?: string): UseQueueStatusResult {
   }, [sessionId, refresh]);
 
   const isQueued = queueStatus.status === 'queued';
-  const queuedMessage = isQueued
-    ? (queueStatus as Extract<QueueStatus, { status: 'queued' }>).message
-    : null;
+  const queuedMessages = isQueued
+    ? (queueStatus as Extract<QueueStatus, { status: 'queued' }>).messages
+    : [];
+  const queuedMessage = queuedMessages[0] ?? null;
 
   return {
     queueStatus,
     isQueued,
+    queuedMessages,
     queuedMessage,
     isLoading,
     queueMessage,
@@ -10,11 +10,16 @@ import {
   CreateTask,
   CreateAndStartTaskRequest,
   CreateTaskAttemptBody,
+  CreateTaskAttemptResponse,
   CreateTag,
+  CreateTerminalRequest,
+  DevServerInfo,
   DirectoryListResponse,
   DirectoryEntry,
   ExecutionProcess,
   ExecutionProcessRepoState,
+  ForkSessionRequest,
+  ForkSessionResponse,
   GitBranch,
   Portfolio,
   CreatePortfolio,
@@ -26,12 +31,26 @@ import {
   CreateProject,
   CreateProjectRepo,
   UpdateProjectRepo,
+  SearchHit,
+  LogMaintenanceReport,
+  LogMaintenanceRequest,
+  SearchHitKind,
   SearchResult,
   ShareTaskResponse,
   Task,
   TaskRelationships,
+  TaskTranscript,
+  TerminalSession,
+  Secret,
+  CreateSecretRequest,
+  UpdateSecretRequest,
+  Transcript,
+  TranscriptFormat,
   Tag,
   TagSearchParams,
+  McpToken,
+  CreateMcpToken,
+  CreateMcpTokenResponse,
   TaskWithAttemptStatus,
   UpdateProject,
   UpdateTask,
@@ -46,6 +65,8 @@ import {
   RebaseTaskAttemptRequest,
   ChangeTargetBranchRequest,
   ChangeTargetBranchResponse,
+  WidenSparseCheckoutRequest,
+  WorkspaceRepoSparseCheckout,
   RenameBranchRequest,
   RenameBranchResponse,
   CheckEditorAvailabilityResponse,
@@ -96,6 +117,9 @@ import {
   CreateJiraTicketResponse,
   GenerateJiraTemplateRequest,
   GenerateJiraTemplateResponse,
+  WorkspaceDiskSummary,
+  WorkspaceGcReport,
+  WorkspaceGcRequest,
 } from 'shared/types';
 import type { WorkspaceWithSession } from '@/types/attempt';
 import { createWorkspaceWithSession } from '@/types/attempt';
@@ -657,6 +681,52 @@ export const sessionsApi = {
     });
     return handleApiResponse<ExecutionProcess>(response);
   },
+
+  getTranscriptUrl: (sessionId: string, format: TranscriptFormat): string =>
+    `/api/sessions/${sessionId}/transcript?format=${format}`,
+
+  exportTranscript: async (sessionId: string): Promise<Transcript> => {
+    const response = await makeRequest(
+      `/api/sessions/${sessionId}/transcript?format=json`
+    );
+    if (!response.ok) {
+      throw new ApiError(
+        `Failed to export transcript (${response.status})`,
+        response.status,
+        response
+      );
+    }
+    return response.json();
+  },
+};
+
+// Transcript APIs
+export const transcriptsApi = {
+  listForTask: async (taskId: string): Promise<TaskTranscript[]> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/transcripts`);
+    return handleApiResponse<TaskTranscript[]>(response);
+  },
+
+  importToTask: async (
+    taskId: string,
+    transcript: Transcript
+  ): Promise<TaskTranscript> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/transcripts`, {
+      method: 'POST',
+      body: JSON.stringify(transcript),
+    });
+    return handleApiResponse<TaskTranscript>(response);
+  },
+
+  getUrl: (transcriptId: string, format: TranscriptFormat): string =>
+    `/api/transcripts/${transcriptId}?format=${format}`,
+
+  delete: async (transcriptId: string): Promise<void> => {
+    const response = await makeRequest(`/api/transcripts/${transcriptId}`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
 };
 
 // Task Attempts APIs
@@ -687,12 +757,14 @@ export const attemptsApi = {
     return createWorkspaceWithSession(workspace, sessions[0]);
   },
 
-  create: async (data: CreateTaskAttemptBody): Promise<Workspace> => {
+  create: async (
+    data: CreateTaskAttemptBody
+  ): Promise<CreateTaskAttemptResponse> => {
     const response = await makeRequest(`/api/task-attempts`, {
       method: 'POST',
       body: JSON.stringify(data),
     });
-    return handleApiResponse<Workspace>(response);
+    return handleApiResponse<CreateTaskAttemptResponse>(response);
   },
 
   stop: async (attemptId: string): Promise<void> => {
@@ -742,6 +814,29 @@ export const attemptsApi = {
     return handleApiResponse<RepoWithTargetBranch[]>(response);
   },
 
+  getSparseCheckout: async (
+    attemptId: string
+  ): Promise<WorkspaceRepoSparseCheckout[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/sparse-checkout`
+    );
+    return handleApiResponse<WorkspaceRepoSparseCheckout[]>(response);
+  },
+
+  widenSparseCheckout: async (
+    attemptId: string,
+    data: WidenSparseCheckoutRequest
+  ): Promise<WorkspaceRepoSparseCheckout> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/sparse-checkout`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<WorkspaceRepoSparseCheckout>(response);
+  },
+
   merge: async (
     attemptId: string,
     data: MergeTaskAttemptRequest
@@ -861,6 +956,13 @@ export const attemptsApi = {
     return handleApiResponse<void>(response);
   },
 
+  getDevServer: async (attemptId: string): Promise<DevServerInfo | null> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/dev-server`
+    );
+    return handleApiResponse<DevServerInfo | null>(response);
+  },
+
   setupGhCli: async (attemptId: string): Promise<ExecutionProcess> => {
     const response = await makeRequest(
       `/api/task-attempts/${attemptId}/gh-cli-setup`,
@@ -935,6 +1037,20 @@ export const executionProcessesApi = {
     );
     return handleApiResponse<void>(response);
   },
+
+  fork: async (
+    processId: string,
+    data: ForkSessionRequest
+  ): Promise<ForkSessionResponse> => {
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/fork`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<ForkSessionResponse>(response);
+  },
 };
 
 // File System APIs
@@ -1018,6 +1134,56 @@ export const configApi = {
 };
 
 // Task Tags APIs (all tags are global)
+// Full-text search APIs
+export const searchApi = {
+  search: async (params: {
+    q: string;
+    project_id?: string;
+    kind?: SearchHitKind;
+    since?: string;
+    limit?: number;
+  }): Promise<SearchHit[]> => {
+    const query = new URLSearchParams({ q: params.q });
+    if (params.project_id) query.set('project_id', params.project_id);
+    if (params.kind) query.set('kind', params.kind);
+    if (params.since) query.set('since', params.since);
+    if (params.limit) query.set('limit', String(params.limit));
+    const response = await makeRequest(`/api/search?${query.toString()}`);
+    return handleApiResponse<SearchHit[]>(response);
+  },
+};
+
+export const maintenanceApi = {
+  runLogMaintenance: async (
+    data: LogMaintenanceRequest
+  ): Promise<LogMaintenanceReport> => {
+    const response = await makeRequest('/api/maintenance/logs', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<LogMaintenanceReport>(response);
+  },
+
+  getWorkspaceDiskUsage: async (
+    refresh = false
+  ): Promise<WorkspaceDiskSummary> => {
+    const response = await makeRequest(
+      `/api/maintenance/workspaces?refresh=${refresh}`
+    );
+    return handleApiResponse<WorkspaceDiskSummary>(response);
+  },
+
+  collectWorkspaceGarbage: async (
+    data: WorkspaceGcRequest
+  ): Promise<WorkspaceGcReport> => {
+    const response = await makeRequest('/api/maintenance/workspaces/gc', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<WorkspaceGcReport>(response);
+  },
+};
+
 export const tagsApi = {
   list: async (params?: TagSearchParams): Promise<Tag[]> => {
     const queryParam = params?.search
@@ -1051,6 +1217,94 @@ export const tagsApi = {
   },
 };
 
+// MCP endpoint token APIs
+export const mcpTokensApi = {
+  list: async (): Promise<McpToken[]> => {
+    const response = await makeRequest('/api/mcp/tokens');
+    return handleApiResponse<McpToken[]>(response);
+  },
+
+  create: async (data: CreateMcpToken): Promise<CreateMcpTokenResponse> => {
+    const response = await makeRequest('/api/mcp/tokens', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<CreateMcpTokenResponse>(response);
+  },
+
+  delete: async (tokenId: string): Promise<void> => {
+    const response = await makeRequest(`/api/mcp/tokens/${tokenId}`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
+};
+
+// Workspace terminal APIs; the terminal itself streams over /api/terminals/{id}/ws
+export const terminalsApi = {
+  list: async (workspaceId: string): Promise<TerminalSession[]> => {
+    const response = await makeRequest(
+      `/api/terminals?workspace_id=${workspaceId}`
+    );
+    return handleApiResponse<TerminalSession[]>(response);
+  },
+
+  create: async (data: CreateTerminalRequest): Promise<TerminalSession> => {
+    const response = await makeRequest('/api/terminals', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<TerminalSession>(response);
+  },
+
+  kill: async (terminalId: string): Promise<void> => {
+    const response = await makeRequest(`/api/terminals/${terminalId}`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
+};
+
+// Secrets APIs; values are write-only
+export const secretsApi = {
+  list: async (scope: {
+    projectId?: string;
+    repoId?: string;
+  }): Promise<Secret[]> => {
+    const params = new URLSearchParams();
+    if (scope.projectId) params.set('project_id', scope.projectId);
+    if (scope.repoId) params.set('repo_id', scope.repoId);
+    const response = await makeRequest(`/api/secrets?${params.toString()}`);
+    return handleApiResponse<Secret[]>(response);
+  },
+
+  create: async (data: CreateSecretRequest): Promise<Secret> => {
+    const response = await makeRequest('/api/secrets', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Secret>(response);
+  },
+
+  update: async (
+    secretId: string,
+    data: UpdateSecretRequest
+  ): Promise<Secret> => {
+    const response = await makeRequest(`/api/secrets/${secretId}`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Secret>(response);
+  },
+
+  delete: async (secretId: string): Promise<void> => {
+    const response = await makeRequest(`/api/secrets/${secretId}`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
+};
+
 // MCP Servers APIs
 export const mcpServersApi = {
   load: async (query: McpServerQuery): Promise<GetMcpServerResponse> => {
@@ -1428,7 +1682,7 @@ export const queueApi = {
   },
 
   /**
-   * Cancel a queued follow-up message
+   * Cancel all queued follow-up messages
    */
   cancel: async (sessionId: string): Promise<QueueStatus> => {
     const response = await makeRequest(`/api/sessions/${sessionId}/queue`, {
@@ -1437,6 +1691,58 @@ export const queueApi = {
     return handleApiResponse<QueueStatus>(response);
   },
 
+  /**
+   * Edit a single queued follow-up message
+   */
+  update: async (
+    sessionId: string,
+    messageId: string,
+    data: { message: string; variant: string | null }
+  ): Promise<QueueStatus> => {
+    const response = await makeRequest(
+      `/api/sessions/${sessionId}/queue/${messageId}`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<QueueStatus>(response);
+  },
+
+  /**
+   * Remove a single queued follow-up message
+   */
+  remove: async (
+    sessionId: string,
+    messageId: string
+  ): Promise<QueueStatus> => {
+    const response = await makeRequest(
+      `/api/sessions/${sessionId}/queue/${messageId}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<QueueStatus>(response);
+  },
+
+  /**
+   * Reorder the queue; every queued message id must be listed once
+   */
+  reorder: async (
+    sessionId: string,
+    messageIds: string[]
+  ): Promise<QueueStatus> => {
+    const response = await makeRequest(
+      `/api/sessions/${sessionId}/queue/order`,
+      {
+        method: 'PUT',
+        body: JSON.stringify({ message_ids: messageIds }),
+      }
+    );
+    return handleApiResponse<QueueStatus>(response);
+  },
+
+  getStreamUrl: (sessionId: string): string =>
+    `/api/sessions/${sessionId}/queue/stream/ws`,
+
   /**
    * Get the current queue status for a session
    */
@@ -748,6 +748,7 @@ export function ProjectTasks() {
           description: task.description,
           status: newStatus,
           intent: task.intent,
+          priority: null,
           parent_workspace_id: task.parent_workspace_id,
           image_ids: null,
         });
@@ -31,7 +31,13 @@ import { AutoExpandingTextarea } from '@/components/ui/auto-expanding-textarea';
 import { RepoPickerDialog } from '@/components/dialogs/shared/RepoPickerDialog';
 import { portfoliosApi, projectsApi } from '@/lib/api';
 import { repoBranchKeys } from '@/hooks/useRepoBranches';
-import type { Project, ProjectRepo, Repo, UpdateProject } from 'shared/types';
+import type {
+  CopyFileRule,
+  Project,
+  ProjectRepo,
+  Repo,
+  UpdateProject,
+} from 'shared/types';
 import { getPortfolioThemeStyles } from '@/constants/portfolioThemes';
 
 interface ProjectFormState {
@@ -47,7 +53,9 @@ interface RepoScriptsFormState {
   setup_script: string;
   parallel_setup_script: boolean;
   cleanup_script: string;
-  copy_files: string;
+  copy_files: CopyFileRule[];
+  sparse_checkout_enabled: boolean;
+  sparse_checkout: string;
 }
 
 function projectToFormState(project: Project): ProjectFormState {
@@ -68,7 +76,9 @@ function projectRepoToScriptsFormState(
     setup_script: projectRepo?.setup_script ?? '',
     parallel_setup_script: projectRepo?.parallel_setup_script ?? false,
     cleanup_script: projectRepo?.cleanup_script ?? '',
-    copy_files: projectRepo?.copy_files ?? '',
+    copy_files: projectRepo?.copy_files ?? [],
+    sparse_checkout_enabled: projectRepo?.sparse_checkout != null,
+    sparse_checkout: (projectRepo?.sparse_checkout ?? []).join('\n'),
   };
 }
 
@@ -430,6 +440,14 @@ export function ProjectSettings() {
     setScriptsError(null);
     setScriptsSuccess(false);
 
+    const copyFiles = scriptsDraft.copy_files
+      .map((rule) => ({ ...rule, pattern: rule.pattern.trim() }))
+      .filter((rule) => rule.pattern !== '');
+    const sparseCheckout = scriptsDraft.sparse_checkout
+      .split('\n')
+      .map((dir) => dir.trim())
+      .filter((dir) => dir !== '');
+
     try {
       const updatedRepo = await projectsApi.updateRepository(
         selectedProjectId,
@@ -437,8 +455,11 @@ export function ProjectSettings() {
         {
           setup_script: scriptsDraft.setup_script.trim() || null,
           cleanup_script: scriptsDraft.cleanup_script.trim() || null,
-          copy_files: scriptsDraft.copy_files.trim() || null,
+          copy_files: copyFiles.length > 0 ? copyFiles : null,
           parallel_setup_script: scriptsDraft.parallel_setup_script,
+          sparse_checkout: scriptsDraft.sparse_checkout_enabled
+            ? sparseCheckout
+            : null,
         }
       );
       setSelectedProjectRepo(updatedRepo);
@@ -962,13 +983,56 @@ export function ProjectSettings() {
                           onChange={(value) =>
                             updateScriptsDraft({ copy_files: value })
                           }
-                          projectId={selectedProject.id}
                         />
                         <p className="text-sm text-muted-foreground">
                           {t('settings.projects.scripts.copyFiles.helper')}
                         </p>
                       </div>
 
+                      <div className="space-y-2">
+                        <Label>
+                          {t('settings.projects.scripts.sparseCheckout.label')}
+                        </Label>
+                        <div className="flex items-center space-x-2">
+                          <Checkbox
+                            id="sparse-checkout"
+                            checked={scriptsDraft.sparse_checkout_enabled}
+                            onCheckedChange={(checked) =>
+                              updateScriptsDraft({
+                                sparse_checkout_enabled: checked === true,
+                              })
+                            }
+                          />
+                          <Label
+                            htmlFor="sparse-checkout"
+                            className="text-sm font-normal cursor-pointer"
+                          >
+                            {t(
+                              'settings.projects.scripts.sparseCheckout.enableLabel'
+                            )}
+                          </Label>
+                        </div>
+                        {scriptsDraft.sparse_checkout_enabled && (
+                          <AutoExpandingTextarea
+                            id="sparse-checkout-dirs"
+                            value={scriptsDraft.sparse_checkout}
+                            onChange={(e) =>
+                              updateScriptsDraft({
+                                sparse_checkout: e.target.value,
+                              })
+                            }
+                            placeholder={t(
+                              'settings.projects.scripts.sparseCheckout.placeholder'
+                            )}
+                            maxRows={8}
+                            className="w-full px-3 py-2 border border-input bg-background text-foreground rounded-md focus:outline-none focus:ring-2 focus:ring-ring font-mono"
+                          />
+                        )}
+                        <p className="text-sm text-muted-foreground">
+                          {t('settings.projects.scripts.sparseCheckout.helper')}
+                        </p>
+                      </div>
+
                       {/* Scripts Save Buttons */}
                       <div className="flex items-center justify-between pt-4 border-t">
                         {hasUnsavedScriptsChanges ? (
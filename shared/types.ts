@@ -18,11 +18,19 @@ export type CreatePortfolio = { name: string, description: string | null, theme:
 
 export type UpdatePortfolio = { name: string | null, description: string | null, theme: string | null, };
 
-export type Project = { id: string, name: string, dev_script: string | null, dev_script_working_dir: string | null, default_agent_working_dir: string | null, remote_project_id: string | null, portfolio_id: string | null, include_task_id_in_commits: boolean, created_at: Date, updated_at: Date, };
+export type Project = { id: string, name: string, dev_script: string | null, dev_script_working_dir: string | null, default_agent_working_dir: string | null, remote_project_id: string | null, portfolio_id: string | null, include_task_id_in_commits: boolean, 
+/**
+ * Overrides the executor profile's approval timeout when set
+ */
+approval_timeout_minutes: bigint | null, approval_timeout_action: ApprovalTimeoutAction | null, created_at: Date, updated_at: Date, };
 
 export type CreateProject = { name: string, repositories: Array<CreateProjectRepo>, };
 
-export type UpdateProject = { name: string | null, dev_script: string | null, dev_script_working_dir: string | null, default_agent_working_dir: string | null, portfolio_id: string | null, include_task_id_in_commits: boolean | null, };
+export type UpdateProject = { name: string | null, dev_script: string | null, dev_script_working_dir: string | null, default_agent_working_dir: string | null, portfolio_id: string | null, include_task_id_in_commits: boolean | null, 
+/**
+ * `0` removes the project's approval timeout override
+ */
+approval_timeout_minutes?: bigint | null, approval_timeout_action?: ApprovalTimeoutAction | null, };
 
 export type SearchResult = { path: string, is_file: boolean, match_type: SearchMatchType, };
 
@@ -30,11 +38,89 @@ export type SearchMatchType = "FileName" | "DirectoryName" | "FullPath";
 
 export type Repo = { id: string, path: string, name: string, display_name: string, created_at: Date, updated_at: Date, };
 
-export type ProjectRepo = { id: string, project_id: string, repo_id: string, setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean, };
+export type ProjectRepo = { id: string, project_id: string, repo_id: string, setup_script: string | null, cleanup_script: string | null, copy_files: Array<CopyFileRule> | null, parallel_setup_script: boolean, 
+/**
+ * Directories to check out in cone mode; `null` checks out everything and an
+ * empty list uses the project's agent and dev server working dirs
+ */
+sparse_checkout: Array<string> | null, };
+
+export enum CopyMode { copy = "copy", 
+/**
+ * Link to the original, so the worktree shares it (e.g. a build cache). A matched
+ * directory is linked as a whole.
+ */
+symlink = "symlink", 
+/**
+ * Falls back to copying across filesystems
+ */
+hardlink = "hardlink", 
+/**
+ * Copy-on-write clone where the filesystem supports it, otherwise a copy
+ */
+reflink = "reflink" }
+
+export type CopyFileRule = { 
+/**
+ * File, directory or glob, relative to the repo root
+ */
+pattern: string, mode: CopyMode, };
 
 export type CreateProjectRepo = { display_name: string, git_repo_path: string, };
 
-export type UpdateProjectRepo = { setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean | null, };
+export type UpdateProjectRepo = { setup_script: string | null, cleanup_script: string | null, copy_files: Array<CopyFileRule> | null, parallel_setup_script: boolean | null, sparse_checkout: Array<string> | null, };
+
+export type ApprovalPolicyRule = { id: string, project_id: string, 
+/**
+ * Rules are evaluated in ascending position; the first match decides
+ */
+position: bigint, 
+/**
+ * Glob on the tool name, e.g. `Bash` or `mcp__*`. Matches any tool when unset
+ */
+tool_name: string | null, 
+/**
+ * Regex matched against the command of shell tools
+ */
+command_pattern: string | null, 
+/**
+ * Glob matched against the file paths the tool touches
+ */
+path_glob: string | null, path_scope: ApprovalPolicyPathScope, decision: ApprovalPolicyDecision, 
+/**
+ * Shown in the conversation when the rule decides a request
+ */
+reason: string, enabled: boolean, created_at: string, updated_at: string, };
+
+export enum ApprovalPolicyDecision { allow = "allow", deny = "deny", 
+/**
+ * Always ask the user, even if a later rule would decide automatically
+ */
+ask = "ask" }
+
+export enum ApprovalPolicyPathScope { any = "any", inside_worktree = "inside_worktree", outside_worktree = "outside_worktree" }
+
+export type CreateApprovalPolicyRule = { position: bigint | null, tool_name: string | null, command_pattern: string | null, path_glob: string | null, path_scope: ApprovalPolicyPathScope | null, decision: ApprovalPolicyDecision, reason: string | null, enabled: boolean | null, };
+
+export type UpdateApprovalPolicyRule = { position: bigint | null, tool_name: string | null, command_pattern: string | null, path_glob: string | null, path_scope: ApprovalPolicyPathScope | null, decision: ApprovalPolicyDecision | null, reason: string | null, enabled: boolean | null, };
+
+export type ApprovalRecord = { id: string, execution_process_id: string, tool_name: string, tool_input: JsonValue, tool_call_id: string, status: ApprovalRecordStatus, denial_reason: string | null, responder: ApprovalResponder | null, policy_rule_id: string | null, created_at: string, timeout_at: string, responded_at: string | null, 
+/**
+ * Time between the request and its response
+ */
+latency_ms: bigint | null, };
+
+export enum ApprovalRecordStatus { pending = "pending", approved = "approved", denied = "denied", timed_out = "timed_out" }
+
+export enum ApprovalResponder { user = "user", 
+/**
+ * An approval policy rule of the project
+ */
+policy = "policy", timeout = "timeout", 
+/**
+ * Closed by the server, e.g. because the executor exited before a response
+ */
+system = "system" }
 
 export type WorkspaceRepo = { id: string, workspace_id: string, repo_id: string, target_branch: string, created_at: Date, updated_at: Date, };
 
@@ -48,23 +134,97 @@ export type CreateTag = { tag_name: string, content: string, };
 
 export type UpdateTag = { tag_name: string | null, content: string | null, };
 
+export type McpToken = { id: string, name: string, 
+/**
+ * Workspace the client acts in; its context is served by `get_context`
+ */
+workspace_id: string | null, last_used_at: string | null, created_at: string, };
+
+export type CreateMcpToken = { name: string, workspace_id: string | null, };
+
+export type Secret = { id: string, project_id: string | null, repo_id: string | null, 
+/**
+ * Name of the environment variable the secret is injected as
+ */
+name: string, created_at: string, updated_at: string, };
+
 export type TaskStatus = "todo" | "inprogress" | "inreview" | "done" | "cancelled";
 
 export type TaskIntent = "code" | "jira" | "confluence";
 
-export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, intent: TaskIntent, parent_workspace_id: string | null, shared_task_id: string | null, history_deleted_at: string | null, created_at: string, updated_at: string, };
+export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, intent: TaskIntent, 
+/**
+ * Scheduling priority of the task's runs; higher priorities leave the execution queue first
+ */
+priority: bigint, parent_workspace_id: string | null, shared_task_id: string | null, history_deleted_at: string | null, created_at: string, updated_at: string, };
 
-export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, last_attempt_failed: boolean, executor: string, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, intent: TaskIntent, parent_workspace_id: string | null, shared_task_id: string | null, history_deleted_at: string | null, created_at: string, updated_at: string, };
+export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, 
+/**
+ * True when a coding agent run for this task is waiting in the execution queue
+ */
+has_queued_attempt: boolean, last_attempt_failed: boolean, executor: string, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, intent: TaskIntent, 
+/**
+ * Scheduling priority of the task's runs; higher priorities leave the execution queue first
+ */
+priority: bigint, parent_workspace_id: string | null, shared_task_id: string | null, history_deleted_at: string | null, created_at: string, updated_at: string, };
 
 export type TaskRelationships = { parent_task: Task | null, current_workspace: Workspace, children: Array<Task>, };
 
-export type CreateTask = { project_id: string, title: string, description: string | null, status: TaskStatus | null, intent: TaskIntent | null, parent_workspace_id: string | null, image_ids: Array<string> | null, shared_task_id: string | null, };
+export type CreateTask = { project_id: string, title: string, description: string | null, status: TaskStatus | null, intent: TaskIntent | null, priority: bigint | null, parent_workspace_id: string | null, image_ids: Array<string> | null, shared_task_id: string | null, };
 
-export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, intent: TaskIntent | null, parent_workspace_id: string | null, image_ids: Array<string> | null, };
+export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, intent: TaskIntent | null, priority: bigint | null, parent_workspace_id: string | null, image_ids: Array<string> | null, };
 
 export type TaskHistory = { id: string, task_id: string, event_type: TaskHistoryEventType, old_value: string | null, new_value: string | null, metadata: string | null, created_at: string, };
 
-export type TaskHistoryEventType = "status_changed" | "description_changed" | "title_changed" | "pr_body_updated" | "change_requested" | "other";
+export type TaskHistoryEventType = "status_changed" | "description_changed" | "title_changed" | "pr_body_updated" | "change_requested" | "plan_proposed" | "plan_edited" | "plan_approved" | "other";
+
+export type TaskPlan = { id: string, task_id: string, workspace_id: string, content: string, status: TaskPlanStatus, 
+/**
+ * Incremented by every new planning run and every edit
+ */
+revision: bigint, source_execution_process_id: string | null, execution_process_id: string | null, approved_at: string | null, created_at: string, updated_at: string, };
+
+export enum TaskPlanStatus { 
+/**
+ * Waiting for the user to review, edit or approve
+ */
+draft = "draft", approved = "approved" }
+
+export type TaskTranscript = { id: string, task_id: string, 
+/**
+ * Session the transcript was exported from, possibly in another installation
+ */
+source_session_id: string | null, title: string, transcript: JsonValue, created_at: string, };
+
+export type SearchHitKind = "task" | "turn" | "log_entry";
+
+export type SearchHit = { kind: SearchHitKind, task_id: string, task_title: string, project_id: string, workspace_id: string | null, execution_process_id: string | null, 
+/**
+ * Position of the entry in the process's normalized conversation
+ */
+entry_index: bigint | null, 
+/**
+ * Matching text with the matched terms wrapped in `<mark>` tags
+ */
+snippet: string, 
+/**
+ * BM25 score; lower is a better match
+ */
+rank: number, created_at: string, };
+
+export type LogStorageUsage = { 
+/**
+ * Uncompressed JSONL rows of running or not yet compressed processes
+ */
+raw_bytes: bigint, 
+/**
+ * Compressed chunks of finished processes
+ */
+compressed_bytes: bigint, 
+/**
+ * Size of the compressed chunks once decompressed
+ */
+compressed_raw_bytes: bigint, };
 
 export type DraftFollowUpData = { message: string, variant: string | null, };
 
@@ -94,12 +254,42 @@ export type ExecutionProcess = { id: string, session_id: string, run_reason: Exe
  */
 dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
 
-export enum ExecutionProcessStatus { running = "running", completed = "completed", failed = "failed", killed = "killed" }
+export enum ExecutionProcessStatus { 
+/**
+ * Waiting in the execution queue for a scheduler slot
+ */
+queued = "queued", running = "running", completed = "completed", failed = "failed", killed = "killed", 
+/**
+ * Stopped after exceeding its idle timeout or maximum runtime
+ */
+timedout = "timedout" }
 
 export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "reviewagent" | "devserver";
 
 export type ExecutionProcessRepoState = { id: string, execution_process_id: string, repo_id: string, before_head_commit: string | null, after_head_commit: string | null, merge_commit: string | null, created_at: Date, updated_at: Date, };
 
+export type ExecutionQueueEntry = { execution_process_id: string, project_id: string, executor: string, priority: bigint, created_at: string, };
+
+export type QueuedExecution = { session_id: string, workspace_id: string, task_id: string, task_title: string, 
+/**
+ * 1-based position in dispatch order, ignoring per-executor limits
+ */
+position: bigint, execution_process_id: string, project_id: string, executor: string, priority: bigint, created_at: string, };
+
+export type ExecutorFallback = { id: string, workspace_id: string, failed_execution_process_id: string, failed_session_id: string, failed_profile: ExecutorProfileId, fallback_session_id: string, fallback_profile: ExecutorProfileId, reason: FallbackReason, 
+/**
+ * Set when the fallback session's coding agent completed successfully
+ */
+succeeded_at: string | null, created_at: string, };
+
+export type AttemptComparison = { id: string, task_id: string, winner_workspace_id: string | null, judge_execution_process_id: string | null, created_at: string, updated_at: string, };
+
+export type AttemptComparisonCandidate = { comparison_id: string, workspace_id: string, executor_profile_id: ExecutorProfileId, 
+/**
+ * 0-based order in which the candidate was requested
+ */
+position: bigint, };
+
 export type Merge = { "type": "direct" } & DirectMerge | { "type": "pr" } & PrMerge;
 
 export type DirectMerge = { id: string, workspace_id: string, repo_id: string, merge_commit: string, target_branch_name: string, created_at: string, };
@@ -196,6 +386,24 @@ export type InitRepoRequest = { parent_path: string, folder_name: string, };
 
 export type TagSearchParams = { search: string | null, };
 
+export type CreateMcpTokenResponse = { token: McpToken, 
+/**
+ * The bearer token; it is not stored and can't be retrieved again
+ */
+secret: string, };
+
+export type CreateTerminalRequest = { workspace_id: string, cols: number, rows: number, 
+/**
+ * Record the terminal output, with secrets masked, to a log file
+ */
+record: boolean, };
+
+export type SecretScopeQuery = { project_id: string | null, repo_id: string | null, };
+
+export type CreateSecretRequest = { project_id: string | null, repo_id: string | null, name: string, value: string, };
+
+export type UpdateSecretRequest = { value: string, };
+
 export type TokenResponse = { access_token: string, expires_at: string | null, };
 
 export type UserSystemInfo = { config: Config, analytics_user_id: string, login_status: LoginStatus, environment: Environment, 
@@ -222,6 +430,94 @@ export type CurrentUserResponse = { user_id: string, };
 
 export type CreateFollowUpAttempt = { prompt: string, variant: string | null, retry_process_id: string | null, force_when_dirty: boolean | null, perform_git_reset: boolean | null, };
 
+export type SearchQuery = { 
+/**
+ * Free text; every term must match, as a word prefix
+ */
+q: string, project_id: string | null, kind: SearchHitKind | null, 
+/**
+ * Only return hits indexed at or after this time
+ */
+since: string | null, limit: bigint | null, };
+
+export type LogMaintenanceRequest = { 
+/**
+ * Rebuild the database afterwards so freed pages are returned to the filesystem
+ */
+vacuum: boolean, };
+
+export type LogMaintenanceReport = { 
+/**
+ * Finished processes whose raw logs were packed into compressed chunks
+ */
+compressed_processes: bigint, 
+/**
+ * Processes whose raw output was replaced by their normalized conversation under
+ * the retention policy
+ */
+expired_processes: bigint, 
+/**
+ * Log rows removed because their execution process no longer exists
+ */
+orphaned_rows: bigint, usage_before: LogStorageUsage, usage_after: LogStorageUsage, 
+/**
+ * Bytes of log storage freed by this pass
+ */
+reclaimed_bytes: bigint, 
+/**
+ * Size of the database file before and after; freed pages are only returned to
+ * the filesystem by a vacuum
+ */
+database_bytes_before: bigint, database_bytes_after: bigint, vacuumed: boolean, };
+
+export type WorkspaceDiskQuery = { 
+/**
+ * Measure the worktrees now instead of returning the last measurement
+ */
+refresh: boolean, };
+
+export type WorkspaceGcRequest = { 
+/**
+ * Also evict workspaces with uncommitted or unpushed changes
+ */
+force: boolean, };
+
+export type WorkspaceDiskUsage = { workspace_id: string, task_id: string, project_id: string, container_ref: string, 
+/**
+ * Size of the worktrees at the last measurement, 0 until measured
+ */
+bytes: bigint, measured_at: string | null, 
+/**
+ * Latest of the last access, the last execution process and the last update
+ */
+last_used_at: string, has_running_process: boolean, };
+
+export type WorkspaceDiskSummary = { total_bytes: bigint, total_budget_bytes: bigint | null, project_budget_bytes: bigint | null, 
+/**
+ * Least recently used first, which is the order they are evicted in
+ */
+workspaces: Array<WorkspaceDiskUsage>, };
+
+export type WorkspaceGcReport = { 
+/**
+ * Workspaces whose worktrees were removed; their branches are kept
+ */
+evicted: Array<string>, reclaimed_bytes: bigint, 
+/**
+ * Over-budget workspaces kept because they have uncommitted or unpushed changes
+ */
+skipped_unpushed: Array<string>, 
+/**
+ * Whether usage still exceeds a budget after this pass
+ */
+over_budget: boolean, };
+
+export type ForkPoint = "before" | "after";
+
+export type ForkSessionRequest = { prompt: string, point?: ForkPoint, variant: string | null, };
+
+export type ForkSessionResponse = { workspace: Workspace, session: Session, execution_process: ExecutionProcess, };
+
 export type ChangeTargetBranchRequest = { repo_id: string, new_target_branch: string, };
 
 export type ChangeTargetBranchResponse = { repo_id: string, new_target_branch: string, status: [number, number], };
@@ -230,6 +526,20 @@ export type MergeTaskAttemptRequest = { repo_id: string, };
 
 export type PushTaskAttemptRequest = { repo_id: string, };
 
+export type WorkspaceRepoDiff = { repo_id: string, repo_name: string, diffs: Array<Diff>, };
+
+export type WorkspaceRepoSparseCheckout = { repo_id: string, repo_name: string, 
+/**
+ * Cone-mode directories, or `null` when the whole repository is checked out
+ */
+dirs: Array<string> | null, };
+
+export type WidenSparseCheckoutRequest = { repo_id: string, 
+/**
+ * Directories to check out in addition to the current ones
+ */
+dirs: Array<string>, };
+
 export type RenameBranchRequest = { new_branch_name: string, };
 
 export type RenameBranchResponse = { branch: string, };
@@ -244,6 +554,19 @@ export type ShareTaskResponse = { shared_task_id: string, };
 
 export type CreateAndStartTaskRequest = { task: CreateTask, executor_profile_id: ExecutorProfileId, repos: Array<WorkspaceRepoInput>, };
 
+export type UpdateTaskPlanRequest = { content: string, };
+
+export type ApproveTaskPlanRequest = { 
+/**
+ * Final edits to apply before approving
+ */
+content: string | null, 
+/**
+ * Profile for the execution run. Defaults to the planning run's executor
+ * without its variant, e.g. Claude Code instead of Claude Code PLAN.
+ */
+executor_profile_id: ExecutorProfileId | null, };
+
 export type GenerateJiraTemplateRequest = { issue_type: string, task_description: string, };
 
 export type GenerateJiraTemplateResponse = { description: string, acceptance_criteria: string, additional_information: string, };
@@ -258,7 +581,19 @@ export type ImageResponse = { id: string, file_path: string, original_name: stri
 
 export type ImageMetadata = { exists: boolean, file_name: string | null, path: string | null, size_bytes: bigint | null, format: string | null, proxy_url: string | null, };
 
-export type CreateTaskAttemptBody = { task_id: string, executor_profile_id: ExecutorProfileId, repos: Array<WorkspaceRepoInput>, };
+export type CreateTaskAttemptBody = { task_id: string, executor_profile_id: ExecutorProfileId, repos: Array<WorkspaceRepoInput>, 
+/**
+ * Race these profiles against `executor_profile_id`, each in its own workspace,
+ * and group the attempts into a comparison
+ */
+additional_executor_profile_ids?: Array<ExecutorProfileId>, };
+
+export type CreateTaskAttemptResponse = { id: string, task_id: string, container_ref: string | null, branch: string, agent_working_dir: string | null, setup_completed_at: string | null, created_at: string, updated_at: string, comparison_id: string | null, 
+/**
+ * Workspaces of a best-of-N request in the order their profiles were requested;
+ * empty for a single attempt
+ */
+candidates: Array<Workspace>, };
 
 export type WorkspaceRepoInput = { repo_id: string, target_branch: string, };
 
@@ -266,6 +601,10 @@ export type RunAgentSetupRequest = { executor_profile_id: ExecutorProfileId, };
 
 export type RunAgentSetupResponse = Record<string, never>;
 
+export type PickComparisonWinnerRequest = { workspace_id: string, };
+
+export type JudgeComparisonRequest = { executor_profile_id: ExecutorProfileId, };
+
 export type GhCliSetupError = "BREW_MISSING" | "SETUP_HELPER_NOT_SUPPORTED" | { "OTHER": { message: string, } };
 
 export type RebaseTaskAttemptRequest = { repo_id: string, old_base_branch: string | null, new_base_branch: string | null, };
@@ -324,6 +663,34 @@ export type FlowActionStatus = "pending" | "inprogress" | "completed" | "failed"
 
 export type FlowSummary = { intent: FlowIntent, description: string, actions: Array<FlowAction>, };
 
+export type AttemptComparisonView = { comparison: AttemptComparison, candidates: Array<ComparisonCandidate>, judge: ComparisonJudge | null, };
+
+export type ComparisonCandidate = { workspace: Workspace, executor_profile_id: ExecutorProfileId, position: bigint, is_winner: boolean, 
+/**
+ * Status of the latest coding agent run
+ */
+agent_status: ExecutionProcessStatus | null, 
+/**
+ * Status of the latest cleanup script run, which is where projects run their checks
+ */
+check_status: ExecutionProcessStatus | null, check_exit_code: bigint | null, 
+/**
+ * Wall-clock time spent in coding agent runs
+ */
+duration_seconds: bigint | null, 
+/**
+ * Cost reported by the executor, for executors that report one
+ */
+cost_usd: number | null, files_changed: number, lines_added: number, lines_removed: number, repos: Array<ComparisonRepoStats>, };
+
+export type ComparisonRepoStats = { repo_id: string, repo_name: string, base_commit: string | null, files_changed: number, lines_added: number, lines_removed: number, uncommitted_count: number | null, untracked_count: number | null, };
+
+export type ComparisonJudge = { execution_process_id: string, status: ExecutionProcessStatus, 
+/**
+ * Final message of the judge agent, containing its ranking
+ */
+summary: string | null, };
+
 export type RepoBranchStatus = { repo_id: string, repo_name: string, commits_behind: number | null, commits_ahead: number | null, has_uncommitted_changes: boolean | null, head_oid: string | null, uncommitted_count: number | null, untracked_count: number | null, target_branch_name: string, remote_commits_behind: number | null, remote_commits_ahead: number | null, merges: Array<Merge>, 
 /**
  * True if a `git rebase` is currently in progress in this worktree
@@ -342,7 +709,7 @@ export type DirectoryEntry = { name: string, path: string, is_directory: boolean
 
 export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_path: string, };
 
-export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, jira: JiraConfig, slack: SlackConfig, confluence: ConfluenceConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, };
+export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, jira: JiraConfig, slack: SlackConfig, confluence: ConfluenceConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, scheduler: SchedulerConfig, approvals: ApprovalConfig, log_retention: LogRetentionConfig, dev_server: DevServerConfig, workspace_pool: WorkspacePoolConfig, workspace_disk: WorkspaceDiskConfig, };
 
 export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
 
@@ -362,6 +729,108 @@ export type SlackConfig = { enabled: boolean, bot_token: string | null, autorepl
 
 export type ConfluenceConfig = { enabled: boolean, api_token: string | null, base_url: string | null, email: string | null, space_key: string | null, parent_page_id: string | null, };
 
+export type SchedulerConfig = { 
+/**
+ * Maximum number of coding agents running at once across all projects.
+ * `None` disables the global limit.
+ */
+max_concurrent_executions: number | null, 
+/**
+ * Maximum number of concurrent runs per executor (e.g. `CLAUDE_CODE`).
+ */
+max_concurrent_per_executor: { [key in BaseCodingAgent]?: number }, };
+
+export type ApprovalConfig = { timeout_minutes: number, timeout_action: ApprovalTimeoutAction, 
+/**
+ * Repeat the approval notification at this interval while a request is pending.
+ * `None` disables reminders.
+ */
+reminder_interval_minutes: number | null, };
+
+export type LogRetentionConfig = { 
+/**
+ * Pack the raw logs of finished processes into compressed chunks.
+ */
+compress_completed: boolean, 
+/**
+ * Drop the raw stdout/stderr of processes that finished more than this many days ago.
+ * Their normalized conversation, turn summaries and search index entries are kept.
+ * `None` keeps raw logs forever.
+ */
+raw_log_retention_days: number | null, };
+
+export type DevServerConfig = { 
+/**
+ * First port handed out to dev servers
+ */
+port_range_start: number, 
+/**
+ * Last port handed out to dev servers
+ */
+port_range_end: number, 
+/**
+ * Extra environment variable that receives the port next to `PORT`, e.g. `VITE_PORT`
+ */
+port_env_var: string | null, readiness_check: DevServerReadinessCheck, 
+/**
+ * Path requested by the `http` readiness check
+ */
+health_check_path: string, 
+/**
+ * Regex matched against output lines by the `log_line` readiness check
+ */
+ready_log_pattern: string | null, 
+/**
+ * Report the dev server as unhealthy if it isn't ready after this many seconds
+ */
+ready_timeout_secs: number, 
+/**
+ * Restart dev servers that exit with a failure, with exponential backoff
+ */
+restart_on_crash: boolean, 
+/**
+ * Give up after this many consecutive restarts
+ */
+max_restarts: number, };
+
+export type DevServerReadinessCheck = "tcp" | "http" | "log_line";
+
+export type WorkspacePoolConfig = { 
+/**
+ * Ready workspaces kept per project. `0` disables the pool
+ */
+size_per_project: number, 
+/**
+ * Number of most recently active projects that get a pool
+ */
+max_projects: number, 
+/**
+ * How often pooled workspaces are checked against their target branches and refilled
+ */
+refresh_interval_secs: number, };
+
+export type WorkspaceDiskConfig = { 
+/**
+ * Budget for all workspaces together, in MB. `None` for no limit
+ */
+total_budget_mb: number | null, 
+/**
+ * Budget for the workspaces of each project, in MB. `None` for no limit
+ */
+project_budget_mb: number | null, 
+/**
+ * How often workspace disk usage is measured and the budgets enforced
+ */
+scan_interval_secs: number, };
+
+export type DevServerStatus = "starting" | "ready" | "unhealthy" | "restarting" | "crashed" | "stopped";
+
+export type DevServerInfo = { workspace_id: string, execution_process_id: string | null, port: number, url: string, status: DevServerStatus, 
+/**
+ * Restarts since the dev server was last started by hand
+ */
+restart_count: number, last_error: string | null, };
+
 export enum SoundFile { ABSTRACT_SOUND1 = "ABSTRACT_SOUND1", ABSTRACT_SOUND2 = "ABSTRACT_SOUND2", ABSTRACT_SOUND3 = "ABSTRACT_SOUND3", ABSTRACT_SOUND4 = "ABSTRACT_SOUND4", COW_MOOING = "COW_MOOING", PHONE_VIBRATION = "PHONE_VIBRATION", ROOSTER = "ROOSTER" }
 
 export type UiLanguage = "BROWSER" | "EN" | "ES" | "DE" | "IT";
@@ -372,21 +841,33 @@ export type GitBranch = { name: string, is_current: boolean, is_remote: boolean,
 
 export type SharedTaskDetails = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, };
 
-export type QueuedMessage = { 
+export type QueuedMessage = { id: string, session_id: string, 
 /**
- * The session this message is queued for
+ * Messages are sent in ascending position, one per completed turn
  */
-session_id: string, 
+position: bigint, message: string, variant: string | null, created_at: string, updated_at: string, };
+
+export type QueueStatus = { "status": "empty" } | { "status": "queued", messages: Array<QueuedMessage>, };
+
+export type TranscriptFormat = "markdown" | "html" | "json";
+
+export type Transcript = { version: number, title: string, task_id: string, workspace_id: string, session_id: string, branch: string, executor: string | null, exported_at: string, turns: Array<TranscriptTurn>, };
+
+export type TranscriptTurn = { execution_process_id: string, status: ExecutionProcessStatus, 
 /**
- * The follow-up data (message + variant)
+ * Prompt the turn was started with
  */
-data: DraftFollowUpData, 
+prompt: string | null, started_at: string, completed_at: string | null, entries: Array<NormalizedEntry>, approvals: Array<ApprovalRecord>, };
+
+export type TerminalSize = { cols: number, rows: number, };
+
+export type TerminalSession = { id: string, workspace_id: string, shell: string, cwd: string, 
 /**
- * Timestamp when the message was queued
+ * Terminal output, with secrets masked, is appended here while the session is recorded
  */
-queued_at: string, };
+log_path: string | null, created_at: string, };
 
-export type QueueStatus = { "status": "empty" } | { "status": "queued", message: QueuedMessage, };
+export type TerminalClientMessage = { "type": "input", data: string, } | { "type": "resize", cols: number, rows: number, };
 
 export type ConflictOp = "rebase" | "merge" | "cherry_pick" | "revert";
 
@@ -437,15 +918,19 @@ export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } |
 
 export type ExecutorConfigs = { executors: { [key in BaseCodingAgent]?: ExecutorConfig }, };
 
+export enum FallbackReason { auth_required = "auth_required", executable_not_found = "executable_not_found", rate_limited = "rate_limited" }
+
+export enum ApprovalTimeoutAction { deny = "deny", approve = "approve", interrupt = "interrupt" }
+
 export enum BaseAgentCapability { SESSION_FORK = "SESSION_FORK", SETUP_HELPER = "SETUP_HELPER" }
 
-export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, disable_api_key?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, disable_api_key?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, idle_timeout_minutes?: number | null, max_runtime_minutes?: number | null, fallback_profiles?: Array<ExecutorProfileId> | null, approval_timeout_minutes?: number | null, approval_timeout_action?: ApprovalTimeoutAction | null, };
 
-export type Gemini = { append_prompt: AppendPrompt, model?: string | null, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Gemini = { append_prompt: AppendPrompt, model?: string | null, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, idle_timeout_minutes?: number | null, max_runtime_minutes?: number | null, fallback_profiles?: Array<ExecutorProfileId> | null, approval_timeout_minutes?: number | null, approval_timeout_action?: ApprovalTimeoutAction | null, };
 
-export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, idle_timeout_minutes?: number | null, max_runtime_minutes?: number | null, fallback_profiles?: Array<ExecutorProfileId> | null, approval_timeout_minutes?: number | null, approval_timeout_action?: ApprovalTimeoutAction | null, };
 
-export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, ask_for_approval?: AskForApproval | null, oss?: boolean | null, model?: string | null, model_reasoning_effort?: ReasoningEffort | null, model_reasoning_summary?: ReasoningSummary | null, model_reasoning_summary_format?: ReasoningSummaryFormat | null, profile?: string | null, base_instructions?: string | null, include_apply_patch_tool?: boolean | null, model_provider?: string | null, compact_prompt?: string | null, developer_instructions?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, ask_for_approval?: AskForApproval | null, oss?: boolean | null, model?: string | null, model_reasoning_effort?: ReasoningEffort | null, model_reasoning_summary?: ReasoningSummary | null, model_reasoning_summary_format?: ReasoningSummaryFormat | null, profile?: string | null, base_instructions?: string | null, include_apply_patch_tool?: boolean | null, model_provider?: string | null, compact_prompt?: string | null, developer_instructions?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, idle_timeout_minutes?: number | null, max_runtime_minutes?: number | null, fallback_profiles?: Array<ExecutorProfileId> | null, approval_timeout_minutes?: number | null, approval_timeout_action?: ApprovalTimeoutAction | null, };
 
 export type SandboxMode = "auto" | "read-only" | "workspace-write" | "danger-full-access";
 
@@ -457,19 +942,19 @@ export type ReasoningSummary = "auto" | "concise" | "detailed" | "none";
 
 export type ReasoningSummaryFormat = "none" | "experimental";
 
-export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, idle_timeout_minutes?: number | null, max_runtime_minutes?: number | null, fallback_profiles?: Array<ExecutorProfileId> | null, approval_timeout_minutes?: number | null, approval_timeout_action?: ApprovalTimeoutAction | null, };
 
-export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, idle_timeout_minutes?: number | null, max_runtime_minutes?: number | null, fallback_profiles?: Array<ExecutorProfileId> | null, approval_timeout_minutes?: number | null, approval_timeout_action?: ApprovalTimeoutAction | null, };
 
 export type Opencode = { append_prompt: AppendPrompt, model?: string | null, mode?: string | null, 
 /**
  * Auto-approve agent actions
  */
-auto_approve: boolean, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+auto_approve: boolean, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, idle_timeout_minutes?: number | null, max_runtime_minutes?: number | null, fallback_profiles?: Array<ExecutorProfileId> | null, approval_timeout_minutes?: number | null, approval_timeout_action?: ApprovalTimeoutAction | null, };
 
-export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, idle_timeout_minutes?: number | null, max_runtime_minutes?: number | null, fallback_profiles?: Array<ExecutorProfileId> | null, approval_timeout_minutes?: number | null, approval_timeout_action?: ApprovalTimeoutAction | null, };
 
-export type Droid = { append_prompt: AppendPrompt, autonomy: Autonomy, model?: string | null, reasoning_effort?: DroidReasoningEffort | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+export type Droid = { append_prompt: AppendPrompt, autonomy: Autonomy, model?: string | null, reasoning_effort?: DroidReasoningEffort | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, idle_timeout_minutes?: number | null, max_runtime_minutes?: number | null, fallback_profiles?: Array<ExecutorProfileId> | null, approval_timeout_minutes?: number | null, approval_timeout_action?: ApprovalTimeoutAction | null, };
 
 export type Autonomy = "normal" | "low" | "medium" | "high" | "skip-permissions-unsafe";
 
@@ -532,7 +1017,7 @@ export type ActionType = { "action": "file_read", path: string, } | { "action":
 
 export type TodoItem = { content: string, status: string, priority: string | null, };
 
-export type NormalizedEntryError = { "type": "setup_required" } | { "type": "other" };
+export type NormalizedEntryError = { "type": "setup_required" } | { "type": "idle_timeout" } | { "type": "runtime_limit_exceeded" } | { "type": "other" };
 
 export type ToolResult = { type: ToolResultValueType, 
 /**